@@ -0,0 +1,315 @@
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use cpplumber::information_leak::ConfirmedLeakWithUniqueLocation;
+use cpplumber::manifest::{parse_manifest_file, ManifestProject};
+use cpplumber::reporting::{dump_confirmed_leaks, OutputSink, OutputSinkRegistry, ScanOutcome};
+use cpplumber::suppressions::parse_suppressions_file;
+use cpplumber::{ProjectSource, Scanner};
+
+use crate::cli::AggregateOptions;
+use crate::LeaksDetected;
+
+/// Results of the scan of one sub-project
+struct ProjectScan {
+    name: String,
+    /// Leaks found in each of the project's binaries, or the reason why the
+    /// project couldn't be scanned
+    result: Result<Vec<(PathBuf, BTreeSet<ConfirmedLeakWithUniqueLocation>)>>,
+}
+
+#[derive(Serialize)]
+struct JsonAggregateReport {
+    projects: Vec<JsonProjectReport>,
+}
+
+#[derive(Serialize)]
+struct JsonProjectReport {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    binaries: Vec<JsonBinaryReport>,
+}
+
+#[derive(Serialize)]
+struct JsonBinaryReport {
+    path: PathBuf,
+    report: serde_json::Value,
+}
+
+/// Scans all the sub-projects listed in a manifest and writes a combined
+/// report, sectioned per project. Projects that can't be scanned don't stop
+/// the others from being scanned, but fail the run.
+pub fn run_aggregate_scan(options: &AggregateOptions) -> Result<()> {
+    let output_sinks = OutputSinkRegistry::default();
+    let output_sink = output_sinks.get(&options.format)?;
+    let manifest = parse_manifest_file(&options.manifest)?;
+
+    let project_scans: Vec<ProjectScan> = manifest
+        .projects
+        .iter()
+        .map(|project| {
+            log::info!("Scanning project '{}'...", project.name);
+            let result = scan_project(project, options);
+            if let Err(ref e) = result {
+                log::error!("Failed to scan project '{}': {:#}", project.name, e);
+            }
+
+            ProjectScan {
+                name: project.name.clone(),
+                result,
+            }
+        })
+        .collect();
+
+    let mut stdout = std::io::stdout();
+    if options.format == "json" {
+        write_json_report(&mut stdout, &project_scans, output_sink, options.redact)?;
+    } else {
+        write_sectioned_report(&mut stdout, &project_scans, output_sink, options.redact)?;
+    }
+
+    let failed_projects = project_scans
+        .iter()
+        .filter(|project_scan| project_scan.result.is_err())
+        .count();
+    let leaks_found = project_scans.iter().any(|project_scan| {
+        matches!(project_scan.result, Ok(ref binaries) if binaries.iter().any(|(_, leaks)| !leaks.is_empty()))
+    });
+    if failed_projects > 0 {
+        Err(anyhow!(
+            "{} project(s) couldn't be scanned",
+            failed_projects
+        ))
+    } else if leaks_found {
+        Err(LeaksDetected("Leaks detected!".to_string()).into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Extracts the project's artifacts once and looks for them in all of its
+/// binaries concurrently
+fn scan_project(
+    project: &ManifestProject,
+    options: &AggregateOptions,
+) -> Result<Vec<(PathBuf, BTreeSet<ConfirmedLeakWithUniqueLocation>)>> {
+    for binary_file_path in &project.binaries {
+        check_binary_file_path(binary_file_path)?;
+    }
+
+    let mut scanner = Scanner::new(ProjectSource::CompilationDatabase(
+        project.compile_commands.clone(),
+    ))
+    .minimum_leak_size(options.minimum_leak_size.unwrap_or(4));
+    if let Some(ref suppressions_file_path) = project.suppressions {
+        scanner = scanner.suppressions(parse_suppressions_file(suppressions_file_path)?);
+    }
+
+    let (potential_leaks, _) = scanner.extract_artifacts()?;
+    scanner.find_leaks_in_binaries(&project.binaries, Arc::new(potential_leaks))
+}
+
+fn check_binary_file_path(binary_file_path: &Path) -> Result<()> {
+    if binary_file_path.is_file() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "'{}' is not a valid file path.",
+            binary_file_path.display()
+        ))
+    }
+}
+
+/// Writes one JSON document, embedding the report of each binary
+fn write_json_report<W: Write>(
+    writer: &mut W,
+    project_scans: &[ProjectScan],
+    output_sink: &dyn OutputSink,
+    redact: bool,
+) -> Result<()> {
+    let mut projects = vec![];
+    for project_scan in project_scans {
+        let (binaries, error) = match project_scan.result {
+            Ok(ref binaries) => (binaries.as_slice(), None),
+            Err(ref e) => (&[][..], Some(format!("{:#}", e))),
+        };
+        let binaries = binaries
+            .iter()
+            .map(|(path, confirmed_leaks)| {
+                let mut report = vec![];
+                dump_confirmed_leaks(
+                    &mut report,
+                    confirmed_leaks,
+                    output_sink,
+                    &ScanOutcome::default(),
+                    redact,
+                    None,
+                    false,
+                )?;
+
+                Ok(JsonBinaryReport {
+                    path: path.clone(),
+                    report: serde_json::from_slice(&report)?,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        projects.push(JsonProjectReport {
+            name: project_scan.name.clone(),
+            error,
+            binaries,
+        });
+    }
+
+    serde_json::to_writer(&mut *writer, &JsonAggregateReport { projects })?;
+    writeln!(writer)?;
+
+    Ok(())
+}
+
+/// Writes the report of each binary, under a header naming its project
+fn write_sectioned_report<W: Write>(
+    writer: &mut W,
+    project_scans: &[ProjectScan],
+    output_sink: &dyn OutputSink,
+    redact: bool,
+) -> Result<()> {
+    for project_scan in project_scans {
+        writeln!(writer, "=== Project '{}' ===", project_scan.name)?;
+        match project_scan.result {
+            Ok(ref binaries) => {
+                for (path, confirmed_leaks) in binaries {
+                    writeln!(
+                        writer,
+                        "--- {} ({} leak(s)) ---",
+                        path.display(),
+                        confirmed_leaks.len()
+                    )?;
+                    dump_confirmed_leaks(
+                        &mut *writer,
+                        confirmed_leaks,
+                        output_sink,
+                        &ScanOutcome::default(),
+                        redact,
+                        None,
+                        false,
+                    )?;
+                }
+            }
+            Err(ref e) => writeln!(writer, "Scan failed: {:#}", e)?,
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use cpplumber::information_leak::{
+        BinaryLocation, ConfirmedLeak, LeakLocation, LeakedDataType, SourceLocation,
+    };
+
+    use super::*;
+
+    fn project_scans() -> Vec<ProjectScan> {
+        let leak: ConfirmedLeakWithUniqueLocation = ConfirmedLeak {
+            data_type: LeakedDataType::StringLiteral,
+            data: Arc::new("secret".to_string()),
+            location: LeakLocation {
+                source: Arc::new(SourceLocation {
+                    file: PathBuf::from("engine.cc"),
+                    line: 3,
+                }),
+                binary: BinaryLocation {
+                    file: Arc::new(PathBuf::from("engine.dll")),
+                    offset: 16,
+                    region: None,
+                    virtual_address: None,
+                    architecture: None,
+                    debug_info: false,
+                },
+            },
+            size: 6,
+            partial_size: None,
+            severity: None,
+            confidence: None,
+            origin: None,
+            visibility: None,
+            infrastructure: None,
+            context: None,
+            pass: None,
+        }
+        .into();
+
+        vec![
+            ProjectScan {
+                name: "engine".to_string(),
+                result: Ok(vec![(
+                    PathBuf::from("engine.dll"),
+                    [leak].into_iter().collect(),
+                )]),
+            },
+            ProjectScan {
+                name: "launcher".to_string(),
+                result: Err(anyhow!("missing compilation database")),
+            },
+        ]
+    }
+
+    #[test]
+    fn write_json_aggregate_report() {
+        let output_sinks = OutputSinkRegistry::default();
+        let mut output = vec![];
+        write_json_report(
+            &mut output,
+            &project_scans(),
+            output_sinks.get("json").unwrap(),
+            false,
+        )
+        .expect("write_json_report failed");
+
+        let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(report["projects"][0]["name"], "engine");
+        assert_eq!(report["projects"][0]["binaries"][0]["path"], "engine.dll");
+        assert_eq!(
+            report["projects"][0]["binaries"][0]["report"]["leaks"][0]["data"],
+            "secret"
+        );
+        assert_eq!(report["projects"][1]["name"], "launcher");
+        assert_eq!(
+            report["projects"][1]["error"],
+            "missing compilation database"
+        );
+    }
+
+    #[test]
+    fn write_text_aggregate_report() {
+        let output_sinks = OutputSinkRegistry::default();
+        let mut output = vec![];
+        write_sectioned_report(
+            &mut output,
+            &project_scans(),
+            output_sinks.get("text").unwrap(),
+            false,
+        )
+        .expect("write_sectioned_report failed");
+
+        let output = String::from_utf8(output).unwrap();
+        let engine_section = output.find("=== Project 'engine' ===").unwrap();
+        let engine_binary = output.find("--- engine.dll (1 leak(s)) ---").unwrap();
+        let leak = output.find("\"secret\"").unwrap();
+        let launcher_section = output.find("=== Project 'launcher' ===").unwrap();
+        assert!(engine_section < engine_binary);
+        assert!(engine_binary < leak);
+        assert!(leak < launcher_section);
+        assert!(output.contains("Scan failed: missing compilation database"));
+    }
+}
@@ -0,0 +1,423 @@
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tempfile::NamedTempFile;
+
+use crate::compilation_database::CompileCommand;
+use crate::information_leak::PotentialLeak;
+
+const CACHE_FORMAT_VERSION: u32 = 4;
+
+/// Artifacts extracted from a single translation unit
+#[derive(Default, Serialize, Deserialize)]
+pub struct TranslationUnitArtifacts {
+    /// Artifacts kept after extraction
+    pub artifacts: Vec<PotentialLeak>,
+    /// Artifacts dropped because they are smaller than the minimum leak size
+    /// (kept so that statistics are the same with and without the cache)
+    pub too_small_artifacts: Vec<PotentialLeak>,
+}
+
+/// File a cache entry depends on, along with its content digest at the time
+/// the entry was created
+#[derive(Serialize, Deserialize)]
+struct FileDependency {
+    path: PathBuf,
+    sha256: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    format: u32,
+    dependencies: Vec<FileDependency>,
+    #[serde(flatten)]
+    artifacts: TranslationUnitArtifacts,
+}
+
+/// On-disk cache of the artifacts extracted from translation units, so that
+/// only changed files have to be parsed again on subsequent runs.
+///
+/// Entries are keyed by the digest of the source file, its compile arguments
+/// and the extraction settings. Each entry also records the digests of the
+/// files included by the translation unit, and is invalidated when one of them
+/// changes.
+pub struct AnalysisCache {
+    directory: PathBuf,
+    /// Set if translation units without an up-to-date entry mustn't be parsed
+    reuse_only: bool,
+}
+
+impl AnalysisCache {
+    /// Opens the cache stored in `directory`, creating it if needed
+    pub fn new<P: AsRef<Path>>(directory: P) -> Result<Self> {
+        let directory = directory.as_ref().to_path_buf();
+        fs::create_dir_all(&directory).with_context(|| {
+            format!("Failed to create cache directory '{}'", directory.display())
+        })?;
+
+        Ok(Self {
+            directory,
+            reuse_only: false,
+        })
+    }
+
+    /// Opens the existing cache stored in `directory`, without parsing
+    /// translation units that don't have an up-to-date entry: extraction
+    /// fails if some entries are missing or outdated
+    pub fn open_reuse_only<P: AsRef<Path>>(directory: P) -> Result<Self> {
+        let directory = directory.as_ref().to_path_buf();
+        if !directory.is_dir() {
+            return Err(anyhow!(
+                "Cache directory '{}' doesn't exist, populate it first",
+                directory.display()
+            ));
+        }
+
+        Ok(Self {
+            directory,
+            reuse_only: true,
+        })
+    }
+
+    /// Indicates if translation units without an up-to-date entry mustn't be
+    /// parsed
+    pub fn is_reuse_only(&self) -> bool {
+        self.reuse_only
+    }
+
+    /// Computes the key identifying the cache entry of the given translation
+    /// unit. `settings` must describe the extraction settings that affect the
+    /// extracted artifacts.
+    pub fn entry_key(&self, compile_cmd: &CompileCommand, settings: &str) -> Result<String> {
+        let source_file_data = fs::read(&compile_cmd.filename).with_context(|| {
+            format!(
+                "Failed to read source file '{}'",
+                compile_cmd.filename.display()
+            )
+        })?;
+
+        let mut hasher = Sha256::new();
+        hasher.update(compile_cmd.filename.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update(Sha256::digest(source_file_data));
+        for argument in compile_cmd.arguments.iter() {
+            hasher.update(argument.as_bytes());
+            hasher.update([0]);
+        }
+        hasher.update(settings.as_bytes());
+
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    /// Returns the artifacts stored for the given key, if the entry exists and
+    /// none of the files it depends on changed
+    pub fn load(&self, key: &str) -> Option<TranslationUnitArtifacts> {
+        let entry_path = self.entry_path(key);
+        let entry = match read_entry(&entry_path) {
+            Ok(entry) => entry,
+            Err(err) => {
+                if entry_path.exists() {
+                    log::debug!("Ignoring cache entry '{}': {}", entry_path.display(), err);
+                }
+                return None;
+            }
+        };
+        if entry.format != CACHE_FORMAT_VERSION {
+            return None;
+        }
+
+        let dependencies_unchanged = entry.dependencies.iter().all(|dependency| {
+            file_digest(&dependency.path)
+                .map(|digest| digest == dependency.sha256)
+                .unwrap_or(false)
+        });
+        if dependencies_unchanged {
+            Some(entry.artifacts)
+        } else {
+            None
+        }
+    }
+
+    /// Stores the artifacts extracted from a translation unit, which depends
+    /// on the files listed in `dependencies`
+    pub fn store(
+        &self,
+        key: &str,
+        dependencies: &[PathBuf],
+        artifacts: TranslationUnitArtifacts,
+    ) -> Result<TranslationUnitArtifacts> {
+        let dependencies = dependencies
+            .iter()
+            .map(|path| {
+                Ok(FileDependency {
+                    path: path.clone(),
+                    sha256: file_digest(path)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let entry = CacheEntry {
+            format: CACHE_FORMAT_VERSION,
+            dependencies,
+            artifacts,
+        };
+
+        // Write into a uniquely named temporary file first, so that concurrent
+        // runs never read a partially written entry, nor write into the same
+        // temporary file
+        let entry_path = self.entry_path(key);
+        let mut temporary_file = NamedTempFile::new_in(&self.directory)?;
+        {
+            let mut writer = BufWriter::new(temporary_file.as_file_mut());
+            serde_json::to_writer(&mut writer, &entry)?;
+            writer.flush()?;
+        }
+        temporary_file
+            .persist(&entry_path)
+            .with_context(|| format!("Failed to write cache entry '{}'", entry_path.display()))?;
+
+        Ok(entry.artifacts)
+    }
+
+    /// Returns the files the cache's entries depend on (i.e., translation units
+    /// and the files they include)
+    pub fn dependencies(&self) -> Vec<PathBuf> {
+        let entries = match fs::read_dir(&self.directory) {
+            Ok(entries) => entries,
+            Err(_) => return vec![],
+        };
+        let mut dependencies: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|entry_path| {
+                entry_path
+                    .extension()
+                    .is_some_and(|extension| extension == "json")
+            })
+            .filter_map(|entry_path| read_entry(&entry_path).ok())
+            .filter(|entry| entry.format == CACHE_FORMAT_VERSION)
+            .flat_map(|entry| entry.dependencies)
+            .map(|dependency| dependency.path)
+            .collect();
+        dependencies.sort();
+        dependencies.dedup();
+
+        dependencies
+    }
+
+    /// Returns the number of artifacts extracted from the given source file the
+    /// last time it was parsed, if known
+    pub fn previous_artifact_count(&self, source_file_path: &Path) -> Option<usize> {
+        fs::read_to_string(self.artifact_count_path(source_file_path))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    }
+
+    /// Records the number of artifacts extracted from the given source file,
+    /// used to prioritize files likely to contain leaks on subsequent runs
+    pub fn record_artifact_count(&self, source_file_path: &Path, count: usize) -> Result<()> {
+        Ok(fs::write(
+            self.artifact_count_path(source_file_path),
+            count.to_string(),
+        )?)
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.directory.join(format!("{}.json", key))
+    }
+
+    fn artifact_count_path(&self, source_file_path: &Path) -> PathBuf {
+        let path_digest = Sha256::digest(source_file_path.to_string_lossy().as_bytes());
+        self.directory.join(format!("{:x}.count", path_digest))
+    }
+}
+
+fn read_entry(entry_path: &Path) -> Result<CacheEntry> {
+    let reader = BufReader::new(File::open(entry_path)?);
+
+    Ok(serde_json::from_reader(reader)?)
+}
+
+fn file_digest(file_path: &Path) -> Result<String> {
+    Ok(format!("{:x}", Sha256::digest(fs::read(file_path)?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::information_leak::{LeakedDataType, SourceLocation};
+
+    use super::*;
+
+    fn compile_command(file_path: &Path) -> CompileCommand {
+        CompileCommand {
+            filename: file_path.to_path_buf(),
+            arguments: Arc::new(vec!["-DTEST".to_string()]),
+        }
+    }
+
+    fn artifacts(file_path: &Path) -> TranslationUnitArtifacts {
+        TranslationUnitArtifacts {
+            artifacts: vec![PotentialLeak {
+                data_type: LeakedDataType::StringLiteral,
+                data: Arc::new("\"secret\"".to_string()),
+                bytes: b"secret".to_vec(),
+                code_unit_size: 1,
+                declaration_metadata: Arc::new(SourceLocation {
+                    file: file_path.to_path_buf(),
+                    line: 1,
+                }),
+            }],
+            too_small_artifacts: vec![],
+        }
+    }
+
+    #[test]
+    fn entry_key_depends_on_content_and_settings() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let source_path = tmp_dir.path().join("main.c");
+        fs::write(&source_path, "int main() {}").unwrap();
+        let cache = AnalysisCache::new(tmp_dir.path().join("cache")).unwrap();
+        let compile_cmd = compile_command(&source_path);
+
+        let key = cache.entry_key(&compile_cmd, "settings").unwrap();
+        assert_eq!(key, cache.entry_key(&compile_cmd, "settings").unwrap());
+        assert_ne!(key, cache.entry_key(&compile_cmd, "other").unwrap());
+
+        fs::write(&source_path, "int main() { return 1; }").unwrap();
+        assert_ne!(key, cache.entry_key(&compile_cmd, "settings").unwrap());
+    }
+
+    #[test]
+    fn load_stored_entry() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let source_path = tmp_dir.path().join("main.c");
+        fs::write(&source_path, "int main() {}").unwrap();
+        let cache = AnalysisCache::new(tmp_dir.path().join("cache")).unwrap();
+
+        assert!(cache.load("key").is_none());
+        cache
+            .store(
+                "key",
+                std::slice::from_ref(&source_path),
+                artifacts(&source_path),
+            )
+            .unwrap();
+        let cached_artifacts = cache.load("key").expect("Missing cache entry");
+        assert_eq!(cached_artifacts.artifacts.len(), 1);
+        assert_eq!(cached_artifacts.artifacts[0].bytes, b"secret");
+    }
+
+    #[test]
+    fn store_entry_concurrently() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let source_path = tmp_dir.path().join("main.c");
+        fs::write(&source_path, "int main() {}").unwrap();
+        let cache_path = tmp_dir.path().join("cache");
+
+        // Separate runs sharing the same cache directory
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    AnalysisCache::new(&cache_path)
+                        .unwrap()
+                        .store(
+                            "key",
+                            std::slice::from_ref(&source_path),
+                            artifacts(&source_path),
+                        )
+                        .unwrap();
+                });
+            }
+        });
+
+        let cache = AnalysisCache::new(&cache_path).unwrap();
+        assert!(cache.load("key").is_some());
+        // No temporary file is left behind
+        assert_eq!(fs::read_dir(&cache_path).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn changed_dependency_invalidates_entry() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let source_path = tmp_dir.path().join("main.c");
+        let header_path = tmp_dir.path().join("header.h");
+        fs::write(&source_path, "#include \"header.h\"").unwrap();
+        fs::write(&header_path, "#define SECRET \"secret\"").unwrap();
+        let cache = AnalysisCache::new(tmp_dir.path().join("cache")).unwrap();
+
+        cache
+            .store(
+                "key",
+                &[source_path.clone(), header_path.clone()],
+                artifacts(&source_path),
+            )
+            .unwrap();
+        assert!(cache.load("key").is_some());
+
+        fs::write(&header_path, "#define SECRET \"other secret\"").unwrap();
+        assert!(cache.load("key").is_none());
+
+        fs::remove_file(&header_path).unwrap();
+        assert!(cache.load("key").is_none());
+    }
+
+    #[test]
+    fn list_dependencies() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let source_path = tmp_dir.path().join("main.c");
+        let header_path = tmp_dir.path().join("header.h");
+        fs::write(&source_path, "#include \"header.h\"").unwrap();
+        fs::write(&header_path, "").unwrap();
+        let cache = AnalysisCache::new(tmp_dir.path().join("cache")).unwrap();
+        assert!(cache.dependencies().is_empty());
+
+        cache
+            .store(
+                "key",
+                &[source_path.clone(), header_path.clone()],
+                artifacts(&source_path),
+            )
+            .unwrap();
+        cache
+            .store(
+                "other_key",
+                std::slice::from_ref(&header_path),
+                artifacts(&header_path),
+            )
+            .unwrap();
+        cache.record_artifact_count(&source_path, 1).unwrap();
+        assert_eq!(cache.dependencies(), vec![header_path, source_path]);
+    }
+
+    #[test]
+    fn artifact_count_history() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let cache = AnalysisCache::new(tmp_dir.path()).unwrap();
+        let source_path = Path::new("/src/main.c");
+
+        assert_eq!(cache.previous_artifact_count(source_path), None);
+        cache.record_artifact_count(source_path, 42).unwrap();
+        assert_eq!(cache.previous_artifact_count(source_path), Some(42));
+        assert_eq!(
+            cache.previous_artifact_count(Path::new("/src/other.c")),
+            None
+        );
+    }
+
+    #[test]
+    fn open_reuse_only_cache() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+
+        assert!(AnalysisCache::open_reuse_only(tmp_dir.path().join("missing")).is_err());
+        let cache = AnalysisCache::open_reuse_only(tmp_dir.path()).unwrap();
+        assert!(cache.is_reuse_only());
+        assert!(!AnalysisCache::new(tmp_dir.path()).unwrap().is_reuse_only());
+    }
+}
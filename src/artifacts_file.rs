@@ -0,0 +1,141 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::information_leak::PotentialLeak;
+
+const ARTIFACTS_FORMAT_VERSION: u32 = 1;
+
+/// Content of an artifacts file, used to transfer extracted artifacts between
+/// runs (e.g., when extraction is split across multiple machines).
+#[derive(Serialize, Deserialize)]
+struct ArtifactsFile {
+    format: u32,
+    artifacts: Vec<PotentialLeak>,
+}
+
+/// Borrowed version of `ArtifactsFile`, to avoid copying artifacts when
+/// serializing them
+#[derive(Serialize)]
+struct ArtifactsFileRef<'a> {
+    format: u32,
+    artifacts: &'a [PotentialLeak],
+}
+
+/// Writes the given artifacts into `output_file_path`
+pub fn export_artifacts(output_file_path: &Path, potential_leaks: &[PotentialLeak]) -> Result<()> {
+    let writer = BufWriter::new(File::create(output_file_path)?);
+
+    Ok(serde_json::to_writer(
+        writer,
+        &ArtifactsFileRef {
+            format: ARTIFACTS_FORMAT_VERSION,
+            artifacts: potential_leaks,
+        },
+    )?)
+}
+
+/// Reads artifacts previously exported with `export_artifacts`
+pub fn import_artifacts(input_file_path: &Path) -> Result<Vec<PotentialLeak>> {
+    let reader = BufReader::new(
+        File::open(input_file_path)
+            .with_context(|| format!("Failed to open '{}'", input_file_path.display()))?,
+    );
+    let artifacts_file: ArtifactsFile = serde_json::from_reader(reader)
+        .with_context(|| format!("Failed to parse '{}'", input_file_path.display()))?;
+    if artifacts_file.format != ARTIFACTS_FORMAT_VERSION {
+        return Err(anyhow!(
+            "Unsupported artifacts file format version: {}",
+            artifacts_file.format
+        ));
+    }
+
+    Ok(artifacts_file.artifacts)
+}
+
+/// Combines multiple artifacts files into one, removing duplicated artifacts
+pub fn merge_artifacts_files<P: AsRef<Path>>(
+    input_file_paths: &[P],
+    output_file_path: &Path,
+) -> Result<()> {
+    let mut potential_leaks = vec![];
+    for input_file_path in input_file_paths {
+        potential_leaks.extend(import_artifacts(input_file_path.as_ref())?);
+    }
+    // Shards may share headers, so the same artifacts can be found in
+    // multiple files
+    potential_leaks.sort_by(|a, b| {
+        (&a.declaration_metadata, &a.bytes).cmp(&(&b.declaration_metadata, &b.bytes))
+    });
+    potential_leaks
+        .dedup_by(|a, b| a.declaration_metadata == b.declaration_metadata && a.bytes == b.bytes);
+
+    export_artifacts(output_file_path, &potential_leaks)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, sync::Arc};
+
+    use crate::information_leak::{LeakedDataType, SourceLocation};
+
+    use super::*;
+
+    fn potential_leak(data: &str, line: u64) -> PotentialLeak {
+        PotentialLeak {
+            data_type: LeakedDataType::StringLiteral,
+            data: Arc::new(data.to_string()),
+            bytes: data.as_bytes().to_vec(),
+            code_unit_size: 1,
+            declaration_metadata: Arc::new(SourceLocation {
+                file: PathBuf::from("file.cc"),
+                line,
+            }),
+        }
+    }
+
+    #[test]
+    fn export_and_import_artifacts() {
+        let tmp_dir = tempfile::tempdir().expect("Failed to create directory");
+        let file_path = tmp_dir.path().join("artifacts.json");
+        export_artifacts(&file_path, &[potential_leak("value", 1)])
+            .expect("export_artifacts failed");
+
+        let potential_leaks = import_artifacts(&file_path).expect("import_artifacts failed");
+        assert_eq!(potential_leaks.len(), 1);
+        assert_eq!(*potential_leaks[0].data, "value");
+        assert_eq!(potential_leaks[0].bytes, b"value");
+        assert_eq!(potential_leaks[0].declaration_metadata.line, 1);
+    }
+
+    #[test]
+    fn merge_artifacts_files_deduplicates() {
+        let tmp_dir = tempfile::tempdir().expect("Failed to create directory");
+        let shard1_path = tmp_dir.path().join("shard1.json");
+        let shard2_path = tmp_dir.path().join("shard2.json");
+        let merged_path = tmp_dir.path().join("merged.json");
+        export_artifacts(
+            &shard1_path,
+            &[potential_leak("header", 1), potential_leak("value1", 2)],
+        )
+        .expect("export_artifacts failed");
+        export_artifacts(
+            &shard2_path,
+            &[potential_leak("header", 1), potential_leak("value2", 3)],
+        )
+        .expect("export_artifacts failed");
+
+        merge_artifacts_files(&[shard1_path, shard2_path], &merged_path)
+            .expect("merge_artifacts_files failed");
+
+        let data: Vec<String> = import_artifacts(&merged_path)
+            .expect("import_artifacts failed")
+            .into_iter()
+            .map(|leak| leak.data.to_string())
+            .collect();
+        assert_eq!(data, vec!["header", "value1", "value2"]);
+    }
+}
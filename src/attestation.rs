@@ -0,0 +1,238 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::ops::Deref;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+
+use cpplumber::information_leak::ConfirmedLeak;
+use cpplumber::path_mapping::PathNormalizer;
+use cpplumber::reporting::{display_leaked_data_type, to_hex_string, FileDigest};
+
+use crate::cli::CpplumberOptions;
+
+const PKG_VERSION: &str = env!("CARGO_PKG_VERSION");
+const ATTESTATION_FORMAT_VERSION: u32 = 1;
+const SIGNATURE_ALGORITHM: &str = "HMAC-SHA256";
+
+/// Document attesting that a leak scan ran on a given binary, with a given
+/// configuration, and summarizing what was found.
+#[derive(Serialize)]
+struct Attestation<'o> {
+    statement: AttestationStatement<'o>,
+    /// Signature of the serialized `statement`, if a key was provided
+    signature: Option<AttestationSignature>,
+}
+
+#[derive(Serialize)]
+struct AttestationStatement<'o> {
+    format: u32,
+    binary: FileDigest,
+    toolchain: Toolchain,
+    configuration: ScanConfiguration<'o>,
+    findings: FindingSummary,
+}
+
+#[derive(Serialize)]
+struct Toolchain {
+    cpplumber: String,
+    libclang: String,
+}
+
+#[derive(Serialize)]
+struct ScanConfiguration<'o> {
+    options: &'o CpplumberOptions,
+    config_file: Option<FileDigest>,
+    suppressions_file: Option<FileDigest>,
+}
+
+#[derive(Serialize)]
+struct FindingSummary {
+    /// Set if the scan has been interrupted before completion
+    partial: bool,
+    total: usize,
+    by_type: BTreeMap<String, usize>,
+}
+
+#[derive(Serialize)]
+struct AttestationSignature {
+    algorithm: String,
+    value: String,
+}
+
+/// Generates an attestation for the given scan results and writes it into
+/// `output_file_path`.
+pub fn write_attestation<SortedConfirmedLeak>(
+    output_file_path: &Path,
+    binary_file_path: &Path,
+    options: &CpplumberOptions,
+    confirmed_leaks: &BTreeSet<SortedConfirmedLeak>,
+    partial: bool,
+) -> Result<()>
+where
+    SortedConfirmedLeak: Deref<Target = ConfirmedLeak>,
+{
+    let signing_key = options
+        .attestation_key
+        .as_deref()
+        .map(read_signing_key)
+        .transpose()?;
+    let path_normalizer = PathNormalizer::new(options.path_prefix_map.clone());
+    let file_digest = |file_path: &Path| FileDigest::new(file_path, &path_normalizer);
+    let statement = AttestationStatement {
+        format: ATTESTATION_FORMAT_VERSION,
+        binary: file_digest(binary_file_path)?,
+        toolchain: Toolchain {
+            cpplumber: PKG_VERSION.into(),
+            libclang: clang::get_version(),
+        },
+        configuration: ScanConfiguration {
+            options,
+            config_file: options.config.as_deref().map(file_digest).transpose()?,
+            suppressions_file: options
+                .suppressions_list
+                .as_deref()
+                .map(file_digest)
+                .transpose()?,
+        },
+        findings: summarize_findings(confirmed_leaks, partial),
+    };
+    let attestation = sign_statement(statement, signing_key.as_deref())?;
+
+    Ok(fs::write(
+        output_file_path,
+        serde_json::to_vec_pretty(&attestation)?,
+    )?)
+}
+
+/// Reads the key used to sign attestations from the given file. Trailing
+/// whitespace (e.g., the newline editors add) isn't part of the key.
+fn read_signing_key(key_file_path: &Path) -> Result<Vec<u8>> {
+    let mut signing_key =
+        fs::read(key_file_path).with_context(|| "Failed to read attestation key")?;
+    let key_size = signing_key
+        .iter()
+        .rposition(|byte| !byte.is_ascii_whitespace())
+        .map_or(0, |position| position + 1);
+    signing_key.truncate(key_size);
+
+    Ok(signing_key)
+}
+
+fn sign_statement<'o>(
+    statement: AttestationStatement<'o>,
+    signing_key: Option<&[u8]>,
+) -> Result<Attestation<'o>> {
+    let signature = if let Some(signing_key) = signing_key {
+        // Sign the compact serialization of the statement
+        let mut mac = Hmac::<Sha256>::new_from_slice(signing_key)
+            .map_err(|_| anyhow!("Invalid attestation key"))?;
+        mac.update(&serde_json::to_vec(&statement)?);
+
+        Some(AttestationSignature {
+            algorithm: SIGNATURE_ALGORITHM.into(),
+            value: to_hex_string(&mac.finalize().into_bytes()),
+        })
+    } else {
+        None
+    };
+
+    Ok(Attestation {
+        statement,
+        signature,
+    })
+}
+
+fn summarize_findings<SortedConfirmedLeak>(
+    confirmed_leaks: &BTreeSet<SortedConfirmedLeak>,
+    partial: bool,
+) -> FindingSummary
+where
+    SortedConfirmedLeak: Deref<Target = ConfirmedLeak>,
+{
+    let by_type = confirmed_leaks
+        .iter()
+        .fold(BTreeMap::new(), |mut accum, leak| {
+            *accum
+                .entry(display_leaked_data_type(leak.data_type))
+                .or_insert(0) += 1;
+            accum
+        });
+
+    FindingSummary {
+        partial,
+        total: confirmed_leaks.len(),
+        by_type,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use structopt::StructOpt;
+
+    use super::*;
+
+    fn statement(options: &CpplumberOptions) -> AttestationStatement<'_> {
+        AttestationStatement {
+            format: ATTESTATION_FORMAT_VERSION,
+            binary: FileDigest {
+                path: "a.out".into(),
+                sha256: "00".into(),
+            },
+            toolchain: Toolchain {
+                cpplumber: PKG_VERSION.into(),
+                libclang: "clang version 14.0.0".into(),
+            },
+            configuration: ScanConfiguration {
+                options,
+                config_file: None,
+                suppressions_file: None,
+            },
+            findings: FindingSummary {
+                partial: false,
+                total: 0,
+                by_type: BTreeMap::new(),
+            },
+        }
+    }
+
+    fn options() -> CpplumberOptions {
+        CpplumberOptions::from_iter(["cpplumber", "--bin", "a.out"])
+    }
+
+    #[test]
+    fn sign_statement_without_key() {
+        let options = options();
+        let attestation = sign_statement(statement(&options), None).expect("sign_statement failed");
+        assert!(attestation.signature.is_none());
+    }
+
+    #[test]
+    fn sign_statement_with_key() {
+        let options = options();
+        let attestation = sign_statement(statement(&options), Some(b"secret key"))
+            .expect("sign_statement failed");
+        let signature = attestation.signature.expect("Missing signature");
+
+        // Verify the signature
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"secret key").unwrap();
+        mac.update(&serde_json::to_vec(&attestation.statement).unwrap());
+        assert_eq!(signature.algorithm, SIGNATURE_ALGORITHM);
+        assert_eq!(signature.value, to_hex_string(&mac.finalize().into_bytes()));
+    }
+
+    #[test]
+    fn read_signing_key_without_trailing_whitespace() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let key_file_path = tmp_dir.path().join("attestation.key");
+
+        fs::write(&key_file_path, "secret key\r\n").unwrap();
+        assert_eq!(read_signing_key(&key_file_path).unwrap(), b"secret key");
+        fs::write(&key_file_path, " secret key").unwrap();
+        assert_eq!(read_signing_key(&key_file_path).unwrap(), b" secret key");
+        assert!(read_signing_key(&tmp_dir.path().join("missing.key")).is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fs::File;
+use std::io::BufReader;
+use std::ops::Deref;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::information_leak::ConfirmedLeak;
+
+/// Returns an identifier of the given leak that's stable across runs and
+/// builds: the digest of its type, value and declaration file. Binary
+/// offsets and line numbers are left out, so that known leaks stay identified
+/// after unrelated code changes.
+pub fn leak_fingerprint(leak: &ConfirmedLeak) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{:?}", leak.data_type).as_bytes());
+    hasher.update([0]);
+    hasher.update(leak.data.as_bytes());
+    hasher.update([0]);
+    hasher.update(leak.location.source.file.to_string_lossy().as_bytes());
+
+    format!("{:x}", hasher.finalize())
+}
+
+#[derive(Deserialize)]
+struct BaselineReport {
+    leaks: Vec<BaselineLeak>,
+}
+
+#[derive(Deserialize)]
+struct BaselineLeak {
+    fingerprint: Option<String>,
+}
+
+/// Leaks known from a previous JSON report
+pub struct Baseline {
+    fingerprints: HashSet<String>,
+}
+
+impl Baseline {
+    /// Loads the leaks listed in the given JSON report
+    pub fn load(report_file_path: &Path) -> Result<Self> {
+        let report_file = File::open(report_file_path)
+            .with_context(|| format!("Failed to open baseline '{}'", report_file_path.display()))?;
+        let report: BaselineReport = serde_json::from_reader(BufReader::new(report_file))
+            .with_context(|| format!("Malformed baseline '{}'", report_file_path.display()))?;
+
+        Ok(Self {
+            fingerprints: report
+                .leaks
+                .into_iter()
+                .map(|leak| {
+                    leak.fingerprint.ok_or_else(|| {
+                        anyhow!(
+                            "Baseline '{}' lacks leak fingerprints, generate it again",
+                            report_file_path.display()
+                        )
+                    })
+                })
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// Indicates if the given leak is part of the baseline
+    pub fn contains(&self, leak: &ConfirmedLeak) -> bool {
+        self.fingerprints.contains(&leak_fingerprint(leak))
+    }
+}
+
+/// Removes the leaks that are part of the baseline
+pub fn filter_baseline_leaks<SortedConfirmedLeak>(
+    confirmed_leaks: BTreeSet<SortedConfirmedLeak>,
+    baseline: &Baseline,
+) -> BTreeSet<SortedConfirmedLeak>
+where
+    SortedConfirmedLeak: Deref<Target = ConfirmedLeak> + Ord,
+{
+    let leak_count = confirmed_leaks.len();
+    let new_leaks: BTreeSet<SortedConfirmedLeak> = confirmed_leaks
+        .into_iter()
+        .filter(|leak| !baseline.contains(leak))
+        .collect();
+    log::info!(
+        "{} leak(s) ignored, as part of the baseline",
+        leak_count - new_leaks.len()
+    );
+
+    new_leaks
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use crate::information_leak::{
+        BinaryLocation, ConfirmedLeakWithUniqueLocation, LeakedDataType, SourceLocation,
+    };
+    use crate::reporting::{dump_confirmed_leaks, OutputSinkRegistry, ScanOutcome};
+
+    use super::*;
+
+    fn confirmed_leak(data: &str, line: u64, offset: u64) -> ConfirmedLeakWithUniqueLocation {
+        ConfirmedLeak::builder(
+            LeakedDataType::StringLiteral,
+            Arc::new(data.to_string()),
+            Arc::new(SourceLocation {
+                file: PathBuf::from("file.cc"),
+                line,
+            }),
+            BinaryLocation::new(Arc::new(PathBuf::from("a.out")), offset),
+        )
+        .build()
+        .into()
+    }
+
+    #[test]
+    fn fingerprints_ignore_offsets_and_lines() {
+        let fingerprint = leak_fingerprint(&confirmed_leak("leak", 1, 16));
+        assert_eq!(
+            fingerprint,
+            leak_fingerprint(&confirmed_leak("leak", 5, 32))
+        );
+        assert_ne!(
+            fingerprint,
+            leak_fingerprint(&confirmed_leak("other", 1, 16))
+        );
+    }
+
+    #[test]
+    fn filter_leaks_present_in_baseline() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let baseline_path = tmp_dir.path().join("baseline.json");
+        // Redacted reports can be used as baselines too
+        dump_confirmed_leaks(
+            File::create(&baseline_path).unwrap(),
+            &[confirmed_leak("known", 1, 16)].into_iter().collect(),
+            OutputSinkRegistry::default().get("json").unwrap(),
+            &ScanOutcome::default(),
+            true,
+            None,
+            false,
+        )
+        .unwrap();
+        let baseline = Baseline::load(&baseline_path).expect("Baseline::load failed");
+
+        let new_leaks = filter_baseline_leaks(
+            [confirmed_leak("known", 2, 64), confirmed_leak("new", 3, 32)]
+                .into_iter()
+                .collect(),
+            &baseline,
+        );
+        let new_values: Vec<&str> = new_leaks.iter().map(|leak| leak.data.as_str()).collect();
+        assert_eq!(new_values, ["new"]);
+    }
+
+    #[test]
+    fn load_baseline_without_fingerprints() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let baseline_path = tmp_dir.path().join("baseline.json");
+        std::fs::write(&baseline_path, r#"{"leaks": [{"data": "leak"}]}"#).unwrap();
+
+        assert!(Baseline::load(&baseline_path).is_err());
+    }
+}
@@ -0,0 +1,119 @@
+//! Discovery of the binary files to scan in a directory (e.g., a build's
+//! output directory), optionally filtered with glob expressions.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use glob::{MatchOptions, Pattern};
+
+/// Returns the files contained in the given directory and its
+/// subdirectories, sorted by path.
+/// If glob expressions are given, only files matching one of them are
+/// returned. Expressions without separators (e.g., "*.dll") match file names,
+/// others (e.g., "bin/*.so") match paths relative to the directory.
+/// Note: Symbolic links to directories aren't followed, to avoid cycles.
+pub fn collect_binary_files(directory: &Path, globs: &[String]) -> Result<Vec<PathBuf>> {
+    let patterns = globs
+        .iter()
+        .map(|glob| {
+            Pattern::new(glob).map_err(|_| anyhow!("'{}' is not a valid glob expression", glob))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut binary_file_paths = vec![];
+    let mut pending_directories = vec![directory.to_path_buf()];
+    while let Some(current_directory) = pending_directories.pop() {
+        let entries = std::fs::read_dir(&current_directory)
+            .with_context(|| format!("Failed to list '{}'", current_directory.display()))?;
+        for entry in entries {
+            let entry = entry?;
+            let entry_path = entry.path();
+            if entry.file_type()?.is_dir() {
+                pending_directories.push(entry_path);
+            } else if entry_path.is_file() && matches_globs(directory, &entry_path, &patterns) {
+                binary_file_paths.push(entry_path);
+            }
+        }
+    }
+    binary_file_paths.sort();
+
+    Ok(binary_file_paths)
+}
+
+fn matches_globs(directory: &Path, file_path: &Path, patterns: &[Pattern]) -> bool {
+    if patterns.is_empty() {
+        return true;
+    }
+
+    let relative_path = file_path.strip_prefix(directory).unwrap_or(file_path);
+    patterns.iter().any(|pattern| {
+        if pattern.as_str().contains('/') {
+            pattern.matches_path_with(
+                relative_path,
+                MatchOptions {
+                    require_literal_separator: true,
+                    ..MatchOptions::new()
+                },
+            )
+        } else {
+            matches!(file_path.file_name(), Some(file_name) if pattern.matches(&file_name.to_string_lossy()))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect_file_names(directory: &Path, globs: &[&str]) -> Vec<String> {
+        let globs: Vec<String> = globs.iter().map(|glob| glob.to_string()).collect();
+        collect_binary_files(directory, &globs)
+            .expect("collect_binary_files failed")
+            .into_iter()
+            .map(|path| {
+                path.strip_prefix(directory)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn collect_binary_files_recursively() {
+        let directory = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(directory.path().join("bin/plugins")).unwrap();
+        for file_name in [
+            "app.exe",
+            "readme.txt",
+            "bin/core.dll",
+            "bin/plugins/extra.dll",
+        ] {
+            std::fs::write(directory.path().join(file_name), b"data").unwrap();
+        }
+
+        assert_eq!(
+            collect_file_names(directory.path(), &[]),
+            vec![
+                "app.exe",
+                "bin/core.dll",
+                "bin/plugins/extra.dll",
+                "readme.txt"
+            ]
+        );
+        assert_eq!(
+            collect_file_names(directory.path(), &["*.dll", "*.exe"]),
+            vec!["app.exe", "bin/core.dll", "bin/plugins/extra.dll"]
+        );
+        assert_eq!(
+            collect_file_names(directory.path(), &["bin/*.dll"]),
+            vec!["bin/core.dll"]
+        );
+    }
+
+    #[test]
+    fn collect_binary_files_invalid_glob() {
+        let directory = tempfile::tempdir().unwrap();
+        assert!(collect_binary_files(directory.path(), &["[".to_string()]).is_err());
+    }
+}
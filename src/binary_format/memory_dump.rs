@@ -0,0 +1,439 @@
+//! Regions of process memory dumps (ELF core dumps and Windows minidumps),
+//! based on the memory ranges they contain and on the modules mapped at these
+//! ranges, so that leaks that only materialize at runtime can be located.
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use object::elf::{FileHeader32, FileHeader64, NT_FILE, PT_LOAD, PT_NOTE};
+use object::read::elf::{FileHeader, ProgramHeader};
+use object::{Endian, Endianness, FileKind, Object, ObjectKind};
+
+use super::BinaryRegion;
+
+const MINIDUMP_SIGNATURE: &[u8] = b"MDMP";
+const MINIDUMP_HEADER_SIZE: usize = 32;
+const MINIDUMP_DIRECTORY_ENTRY_SIZE: usize = 12;
+const MINIDUMP_MODULE_SIZE: usize = 108;
+const MINIDUMP_MEMORY_DESCRIPTOR_SIZE: usize = 16;
+
+const MODULE_LIST_STREAM: u32 = 4;
+const MEMORY_LIST_STREAM: u32 = 5;
+const MEMORY64_LIST_STREAM: u32 = 9;
+
+/// Owner of the notes describing the files mapped into a dumped process
+const CORE_NOTE_NAME: &[u8] = b"CORE";
+
+/// Range of memory whose content is stored in a dump
+struct MemoryRange {
+    address: u64,
+    file_offset: u64,
+    size: u64,
+}
+
+/// Module (i.e., executable, library or mapped file) loaded in a dumped
+/// process
+struct Module {
+    path: String,
+    address_range: Range<u64>,
+}
+
+/// Returns `true` if the given data is an ELF core dump
+pub fn is_elf_core_dump(data: &[u8]) -> bool {
+    matches!(FileKind::parse(data), Ok(FileKind::Elf32 | FileKind::Elf64))
+        && matches!(object::File::parse(data), Ok(file) if file.kind() == ObjectKind::Core)
+}
+
+/// Returns `true` if the given data starts with the header of a minidump
+pub fn is_minidump(data: &[u8]) -> bool {
+    data.len() >= MINIDUMP_HEADER_SIZE && data.starts_with(MINIDUMP_SIGNATURE)
+}
+
+/// Parses an ELF core dump and returns its memory ranges as regions, along
+/// with the parts of them where files (e.g., shared libraries) are mapped
+pub fn parse_elf_core_regions(data: &[u8]) -> Result<Vec<BinaryRegion>> {
+    let (memory_ranges, modules) = match FileKind::parse(data)? {
+        FileKind::Elf32 => parse_elf_core::<FileHeader32<Endianness>>(data)?,
+        FileKind::Elf64 => parse_elf_core::<FileHeader64<Endianness>>(data)?,
+        _ => return Err(anyhow!("Not an ELF file")),
+    };
+
+    memory_regions(&memory_ranges, &modules)
+}
+
+fn parse_elf_core<Elf: FileHeader<Endian = Endianness>>(
+    data: &[u8],
+) -> Result<(Vec<MemoryRange>, Vec<Module>)> {
+    let header = Elf::parse(data)?;
+    let endian = header.endian()?;
+
+    let mut memory_ranges = vec![];
+    let mut modules = vec![];
+    for segment in header.program_headers(endian, data)? {
+        match segment.p_type(endian) {
+            PT_LOAD => memory_ranges.push(MemoryRange {
+                address: segment.p_vaddr(endian).into(),
+                file_offset: segment.p_offset(endian).into(),
+                size: segment.p_filesz(endian).into(),
+            }),
+            PT_NOTE => {
+                if let Some(mut notes) = segment.notes(endian, data)? {
+                    while let Some(note) = notes.next()? {
+                        if note.name() == CORE_NOTE_NAME && note.n_type(endian) == NT_FILE {
+                            modules.extend(parse_file_note(
+                                note.desc(),
+                                endian,
+                                header.is_type_64(),
+                            )?);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok((memory_ranges, modules))
+}
+
+/// Parses the content of a `NT_FILE` note, which lists the files mapped into
+/// the dumped process: the number of mappings and the page size, followed by
+/// the address range (and file offset) of each mapping, then by their paths
+fn parse_file_note(desc: &[u8], endian: Endianness, is_64: bool) -> Result<Vec<Module>> {
+    let word_size = if is_64 { 8 } else { 4 };
+    let read_word = |index: usize| -> Result<u64> {
+        let bytes = index
+            .checked_mul(word_size)
+            .and_then(|offset| desc.get(offset..offset.checked_add(word_size)?))
+            .ok_or_else(|| anyhow!("Truncated NT_FILE note"))?;
+        Ok(if is_64 {
+            endian.read_u64_bytes(bytes.try_into()?)
+        } else {
+            u64::from(endian.read_u32_bytes(bytes.try_into()?))
+        })
+    };
+
+    let mapping_count = read_word(0)? as usize;
+    let paths = mapping_count
+        .checked_mul(3)
+        .and_then(|word_count| word_count.checked_add(2))
+        .and_then(|word_count| word_count.checked_mul(word_size))
+        .and_then(|paths_offset| desc.get(paths_offset..))
+        .ok_or_else(|| anyhow!("Truncated NT_FILE note"))?
+        .split(|byte| *byte == 0);
+
+    (0..mapping_count)
+        .zip(paths)
+        .map(|(i, path)| {
+            let first_word = 2 + 3 * i;
+            Ok(Module {
+                path: String::from_utf8_lossy(path).into_owned(),
+                address_range: read_word(first_word)?..read_word(first_word + 1)?,
+            })
+        })
+        .collect()
+}
+
+/// Parses a minidump and returns its memory ranges as regions, along with the
+/// parts of them where modules are loaded
+pub fn parse_minidump_regions(data: &[u8]) -> Result<Vec<BinaryRegion>> {
+    let stream_count = read_u32(data, 8)? as usize;
+    let directory_offset = read_u32(data, 12)? as usize;
+
+    let mut memory_ranges = vec![];
+    let mut modules = vec![];
+    for i in 0..stream_count {
+        let entry_offset = directory_offset + i * MINIDUMP_DIRECTORY_ENTRY_SIZE;
+        let stream_type = read_u32(data, entry_offset)?;
+        let stream_offset = read_u32(data, entry_offset + 8)? as usize;
+        match stream_type {
+            MODULE_LIST_STREAM => modules.extend(parse_minidump_modules(data, stream_offset)?),
+            MEMORY_LIST_STREAM => {
+                let range_count = read_u32(data, stream_offset)? as usize;
+                for j in 0..range_count {
+                    let descriptor_offset = stream_offset + 4 + j * MINIDUMP_MEMORY_DESCRIPTOR_SIZE;
+                    memory_ranges.push(MemoryRange {
+                        address: read_u64(data, descriptor_offset)?,
+                        size: u64::from(read_u32(data, descriptor_offset + 8)?),
+                        file_offset: u64::from(read_u32(data, descriptor_offset + 12)?),
+                    });
+                }
+            }
+            MEMORY64_LIST_STREAM => {
+                // Note: The content of the ranges is stored contiguously
+                let range_count = read_u64(data, stream_offset)? as usize;
+                let mut file_offset = read_u64(data, stream_offset + 8)?;
+                for j in 0..range_count {
+                    let descriptor_offset =
+                        stream_offset + 16 + j * MINIDUMP_MEMORY_DESCRIPTOR_SIZE;
+                    let size = read_u64(data, descriptor_offset + 8)?;
+                    memory_ranges.push(MemoryRange {
+                        address: read_u64(data, descriptor_offset)?,
+                        file_offset,
+                        size,
+                    });
+                    file_offset = range_end(file_offset, size)?;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    memory_regions(&memory_ranges, &modules)
+}
+
+fn parse_minidump_modules(data: &[u8], stream_offset: usize) -> Result<Vec<Module>> {
+    let module_count = read_u32(data, stream_offset)? as usize;
+
+    (0..module_count)
+        .map(|i| {
+            let module_offset = stream_offset + 4 + i * MINIDUMP_MODULE_SIZE;
+            let base_address = read_u64(data, module_offset)?;
+            let size = u64::from(read_u32(data, module_offset + 8)?);
+            let name_offset = read_u32(data, module_offset + 20)? as usize;
+            // Names are stored as UTF-16LE strings, prefixed with their size
+            // in bytes
+            let name_size = read_u32(data, name_offset)? as usize;
+            let name = data
+                .get(name_offset + 4..name_offset + 4 + name_size)
+                .ok_or_else(|| anyhow!("Truncated minidump"))?;
+            let name: Vec<u16> = name
+                .chunks_exact(2)
+                .map(|code_unit| u16::from_le_bytes([code_unit[0], code_unit[1]]))
+                .collect();
+
+            Ok(Module {
+                path: String::from_utf16_lossy(&name),
+                address_range: base_address..range_end(base_address, size)?,
+            })
+        })
+        .collect()
+}
+
+/// Returns the memory ranges as regions, as well as the parts of the ranges
+/// covered by each module
+fn memory_regions(memory_ranges: &[MemoryRange], modules: &[Module]) -> Result<Vec<BinaryRegion>> {
+    let mut regions = vec![];
+    for memory_range in memory_ranges.iter().filter(|range| range.size > 0) {
+        let address_range =
+            memory_range.address..range_end(memory_range.address, memory_range.size)?;
+        regions.push(BinaryRegion {
+            name: Arc::new(format!(
+                "memory range 0x{:x}-0x{:x}",
+                address_range.start, address_range.end
+            )),
+            file_range: memory_range.file_offset
+                ..range_end(memory_range.file_offset, memory_range.size)?,
+            virtual_address: Some(address_range.start),
+            debug_info: false,
+        });
+
+        for module in modules {
+            let start = module.address_range.start.max(address_range.start);
+            let end = module.address_range.end.min(address_range.end);
+            if start >= end {
+                continue;
+            }
+            let file_offset = memory_range.file_offset + (start - address_range.start);
+            regions.push(BinaryRegion {
+                name: Arc::new(format!("module \"{}\"", module.path)),
+                file_range: file_offset..file_offset + (end - start),
+                virtual_address: Some(start),
+                debug_info: false,
+            });
+        }
+    }
+
+    Ok(regions)
+}
+
+/// Returns the end of the range of `size` bytes starting at `start`, checking
+/// for overflows since sizes come from the dump
+fn range_end(start: u64, size: u64) -> Result<u64> {
+    start
+        .checked_add(size)
+        .ok_or_else(|| anyhow!("Memory range is out of bounds"))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    let bytes = offset
+        .checked_add(4)
+        .and_then(|end| data.get(offset..end))
+        .ok_or_else(|| anyhow!("Truncated minidump"))?;
+    Ok(u32::from_le_bytes(bytes.try_into()?))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    let bytes = offset
+        .checked_add(8)
+        .and_then(|end| data.get(offset..end))
+        .ok_or_else(|| anyhow!("Truncated minidump"))?;
+    Ok(u64::from_le_bytes(bytes.try_into()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find_region<'r>(regions: &'r [BinaryRegion], name: &str) -> &'r BinaryRegion {
+        regions
+            .iter()
+            .find(|region| *region.name == name)
+            .expect("Missing region")
+    }
+
+    /// Builds a 64-bit ELF core dump containing a memory range at 0x3ff800,
+    /// in which "/usr/bin/app" is mapped from 0x400000
+    fn elf_core_dump() -> Vec<u8> {
+        const HEADER_SIZE: u64 = 64;
+        const PROGRAM_HEADER_SIZE: u64 = 56;
+
+        let mut desc = vec![];
+        for word in [1_u64, 0x1000, 0x400000, 0x401000, 0] {
+            desc.extend(word.to_le_bytes());
+        }
+        desc.extend(b"/usr/bin/app\0\0\0\0");
+        let mut note = vec![];
+        note.extend(5_u32.to_le_bytes());
+        note.extend((desc.len() as u32).to_le_bytes());
+        note.extend(NT_FILE.to_le_bytes());
+        note.extend(b"CORE\0\0\0\0");
+        note.extend(&desc);
+
+        let note_offset = HEADER_SIZE + 2 * PROGRAM_HEADER_SIZE;
+        let load_offset = note_offset + note.len() as u64;
+        let mut data = vec![0x7f, b'E', b'L', b'F', 2, 1, 1];
+        data.resize(16, 0);
+        data.extend(4_u16.to_le_bytes()); // ET_CORE
+        data.extend(62_u16.to_le_bytes()); // EM_X86_64
+        data.extend(1_u32.to_le_bytes());
+        data.extend(0_u64.to_le_bytes());
+        data.extend(HEADER_SIZE.to_le_bytes());
+        data.extend(0_u64.to_le_bytes());
+        data.extend(0_u32.to_le_bytes());
+        data.extend((HEADER_SIZE as u16).to_le_bytes());
+        data.extend((PROGRAM_HEADER_SIZE as u16).to_le_bytes());
+        data.extend(2_u16.to_le_bytes());
+        data.extend([0; 6]);
+        for (segment_type, offset, address, size) in [
+            (PT_NOTE, note_offset, 0_u64, note.len() as u64),
+            (PT_LOAD, load_offset, 0x3ff800, 0x1000),
+        ] {
+            data.extend(segment_type.to_le_bytes());
+            data.extend(0_u32.to_le_bytes());
+            data.extend(offset.to_le_bytes());
+            data.extend(address.to_le_bytes());
+            data.extend(0_u64.to_le_bytes());
+            data.extend(size.to_le_bytes());
+            data.extend(size.to_le_bytes());
+            data.extend(4_u64.to_le_bytes());
+        }
+        data.extend(note);
+        data.resize(data.len() + 0x1000, 0);
+
+        data
+    }
+
+    /// Builds a minidump containing a memory range at 0x10000, in which
+    /// "app.exe" is loaded from 0x10100
+    fn minidump() -> Vec<u8> {
+        let mut data = MINIDUMP_SIGNATURE.to_vec();
+        data.extend(0xa793_u32.to_le_bytes());
+        data.extend(2_u32.to_le_bytes());
+        data.extend(32_u32.to_le_bytes());
+        data.resize(MINIDUMP_HEADER_SIZE, 0);
+        // Stream directory
+        let module_list_offset = 32 + 2 * MINIDUMP_DIRECTORY_ENTRY_SIZE as u32;
+        let module_name_offset = module_list_offset + 4 + MINIDUMP_MODULE_SIZE as u32;
+        let memory_list_offset = module_name_offset + 4 + 14;
+        let memory_offset = memory_list_offset + 4 + MINIDUMP_MEMORY_DESCRIPTOR_SIZE as u32;
+        for (stream_type, offset) in [
+            (MODULE_LIST_STREAM, module_list_offset),
+            (MEMORY_LIST_STREAM, memory_list_offset),
+        ] {
+            data.extend(stream_type.to_le_bytes());
+            data.extend(0_u32.to_le_bytes());
+            data.extend(offset.to_le_bytes());
+        }
+        // Module list
+        data.extend(1_u32.to_le_bytes());
+        data.extend(0x10100_u64.to_le_bytes());
+        data.extend(0x1000_u32.to_le_bytes());
+        data.extend([0; 8]);
+        data.extend(module_name_offset.to_le_bytes());
+        data.resize(module_name_offset as usize, 0);
+        data.extend(14_u32.to_le_bytes());
+        data.extend("app.exe".encode_utf16().flat_map(u16::to_le_bytes));
+        // Memory list
+        data.extend(1_u32.to_le_bytes());
+        data.extend(0x10000_u64.to_le_bytes());
+        data.extend(0x200_u32.to_le_bytes());
+        data.extend(memory_offset.to_le_bytes());
+        data.resize(memory_offset as usize + 0x200, 0);
+
+        data
+    }
+
+    #[test]
+    fn parse_elf_core_dump_regions() {
+        let data = elf_core_dump();
+        assert!(is_elf_core_dump(&data));
+        assert!(!is_minidump(&data));
+
+        let regions = parse_elf_core_regions(&data).expect("parse_elf_core_regions failed");
+        assert_eq!(regions.len(), 2);
+        let memory_range = find_region(&regions, "memory range 0x3ff800-0x400800");
+        assert_eq!(memory_range.virtual_address, Some(0x3ff800));
+        let module = find_region(&regions, "module \"/usr/bin/app\"");
+        assert_eq!(
+            module.file_range,
+            memory_range.file_range.start + 0x800..memory_range.file_range.end
+        );
+        assert_eq!(module.virtual_address, Some(0x400000));
+    }
+
+    #[test]
+    fn parse_minidump_memory_regions() {
+        let data = minidump();
+        assert!(is_minidump(&data));
+        assert!(!is_elf_core_dump(&data));
+
+        let regions = parse_minidump_regions(&data).expect("parse_minidump_regions failed");
+        assert_eq!(regions.len(), 2);
+        let memory_range = find_region(&regions, "memory range 0x10000-0x10200");
+        assert_eq!(memory_range.virtual_address, Some(0x10000));
+        let module = find_region(&regions, "module \"app.exe\"");
+        assert_eq!(
+            module.file_range,
+            memory_range.file_range.start + 0x100..memory_range.file_range.end
+        );
+        assert_eq!(module.virtual_address, Some(0x10100));
+
+        // Truncated dumps are rejected
+        assert!(parse_minidump_regions(&data[..100]).is_err());
+    }
+
+    #[test]
+    fn parse_overflowing_memory_dumps() {
+        // NT_FILE note listing more mappings than can be addressed
+        let mut desc = vec![];
+        for word in [u64::MAX, 0x1000] {
+            desc.extend(word.to_le_bytes());
+        }
+        assert!(parse_file_note(&desc, Endianness::Little, true).is_err());
+
+        // Memory range ending past the end of the address space
+        let mut data = minidump();
+        let descriptor_offset = data.len() - 0x200 - MINIDUMP_MEMORY_DESCRIPTOR_SIZE;
+        data[descriptor_offset..descriptor_offset + 8]
+            .copy_from_slice(&(u64::MAX - 0x100).to_le_bytes());
+        assert!(parse_minidump_regions(&data).is_err());
+
+        // Module ending past the end of the address space
+        let mut data = minidump();
+        let module_offset = 32 + 2 * MINIDUMP_DIRECTORY_ENTRY_SIZE + 4;
+        data[module_offset..module_offset + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(parse_minidump_regions(&data).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+mod memory_dump;
+mod object_file;
+mod universal;
+mod wasm;
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+pub use universal::{parse_architecture_slices, ArchitectureSlice};
+
+/// Region of a binary file (e.g., a section or a data segment)
+pub struct BinaryRegion {
+    /// Human-readable description of the region
+    pub name: Arc<String>,
+    /// Range of the region in the binary file
+    pub file_range: Range<u64>,
+    /// Virtual address at which the region is loaded in memory, if known
+    pub virtual_address: Option<u64>,
+    /// Set if the region only contains debug information (e.g., DWARF
+    /// sections), which is removed when the binary is stripped
+    pub debug_info: bool,
+}
+
+/// Location of a file offset within a binary's layout
+pub struct RegionLocation {
+    /// Description of the innermost region containing the offset
+    pub region: Arc<String>,
+    /// Virtual address corresponding to the offset, if known
+    pub virtual_address: Option<u64>,
+    /// Set if the offset is part of the binary's debug information
+    pub debug_info: bool,
+}
+
+/// Layout of a binary file, used to give context to raw file offsets
+pub struct BinaryLayout {
+    regions: Vec<BinaryRegion>,
+}
+
+impl BinaryLayout {
+    /// Parses the layout of the given binary file. Returns `None` if the file
+    /// format isn't supported, in which case the file is treated as a raw blob.
+    pub fn parse(data: &[u8]) -> Result<Option<Self>> {
+        if wasm::is_wasm_module(data) {
+            return Ok(Some(Self {
+                regions: wasm::parse_regions(data)?,
+            }));
+        }
+        // Note: Memory dumps are scanned as a whole, but leaks are located in
+        // the memory ranges (and modules) they're found in
+        if memory_dump::is_minidump(data) {
+            return Ok(Some(Self {
+                regions: memory_dump::parse_minidump_regions(data)?,
+            }));
+        }
+        if memory_dump::is_elf_core_dump(data) {
+            return Ok(Some(Self {
+                regions: memory_dump::parse_elf_core_regions(data)?,
+            }));
+        }
+        match object::FileKind::parse(data) {
+            Ok(
+                object::FileKind::Elf32
+                | object::FileKind::Elf64
+                | object::FileKind::Pe32
+                | object::FileKind::Pe64
+                | object::FileKind::Coff
+                | object::FileKind::MachO32
+                | object::FileKind::MachO64,
+            ) => Ok(Some(Self {
+                regions: object_file::parse_regions(data)?,
+            })),
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the innermost region containing the given file offset
+    pub fn locate(&self, offset: u64) -> Option<RegionLocation> {
+        self.regions
+            .iter()
+            .filter(|region| region.file_range.contains(&offset))
+            .min_by_key(|region| region.file_range.end - region.file_range.start)
+            .map(|region| RegionLocation {
+                region: region.name.clone(),
+                virtual_address: region
+                    .virtual_address
+                    .map(|address| address + (offset - region.file_range.start)),
+                debug_info: region.debug_info,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(name: &str, file_range: Range<u64>, virtual_address: Option<u64>) -> BinaryRegion {
+        BinaryRegion {
+            name: Arc::new(name.to_string()),
+            file_range,
+            virtual_address,
+            debug_info: false,
+        }
+    }
+
+    #[test]
+    fn locate_innermost_region() {
+        let layout = BinaryLayout {
+            regions: vec![
+                region("section", 0x10..0x100, None),
+                region("segment", 0x20..0x40, Some(0x1000)),
+            ],
+        };
+
+        let location = layout.locate(0x18).expect("Offset not located");
+        assert_eq!(*location.region, "section");
+        assert_eq!(location.virtual_address, None);
+
+        let location = layout.locate(0x24).expect("Offset not located");
+        assert_eq!(*location.region, "segment");
+        assert_eq!(location.virtual_address, Some(0x1004));
+
+        assert!(layout.locate(0x100).is_none());
+    }
+}
@@ -0,0 +1,148 @@
+//! Regions of native object files (ELF, PE/COFF and Mach-O), based on their
+//! section headers, and on the debug directory of PE files.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use object::pe::{self, ImageDebugDirectory};
+use object::read::pe::{ImageNtHeaders, PeFile, PeFile32, PeFile64};
+use object::{FileKind, LittleEndian as LE, Object, ObjectKind, ObjectSection, SectionKind};
+
+/// Prefixes of the names of sections containing debug information (e.g.,
+/// DWARF sections, links to separate debug files)
+const DEBUG_SECTION_PREFIXES: [&str; 4] = [".debug", ".zdebug", ".gnu_debug", "__debug_"];
+
+use super::BinaryRegion;
+
+/// Parses an object file and returns its sections as regions, along with their
+/// virtual addresses for files that are loaded at a fixed address (i.e.,
+/// executables and shared libraries, not relocatable files)
+pub fn parse_regions(data: &[u8]) -> Result<Vec<BinaryRegion>> {
+    let object_file = object::File::parse(data)?;
+    let has_virtual_addresses = object_file.kind() != ObjectKind::Relocatable;
+
+    let mut regions: Vec<BinaryRegion> = object_file
+        .sections()
+        .filter_map(|section| {
+            let (file_offset, file_size) = section.file_range()?;
+            let section_name = section.name().unwrap_or_default();
+            // Sections that aren't loaded in memory have a null address
+            let virtual_address =
+                Some(section.address()).filter(|address| has_virtual_addresses && *address != 0);
+            let debug_info = section.kind() == SectionKind::Debug
+                || DEBUG_SECTION_PREFIXES
+                    .iter()
+                    .any(|prefix| section_name.starts_with(prefix));
+
+            Some(BinaryRegion {
+                name: Arc::new(format!("section \"{}\"", section_name)),
+                file_range: file_offset..file_offset + file_size,
+                virtual_address,
+                debug_info,
+            })
+        })
+        .collect();
+    // Debug data referenced by PE files (e.g., the PDB path) is stored in
+    // regular sections
+    match FileKind::parse(data)? {
+        FileKind::Pe32 => regions.extend(parse_debug_directory(&PeFile32::parse(data)?)),
+        FileKind::Pe64 => regions.extend(parse_debug_directory(&PeFile64::parse(data)?)),
+        _ => {}
+    }
+
+    Ok(regions)
+}
+
+/// Returns the data referenced by the entries of a PE file's debug directory
+/// as regions
+fn parse_debug_directory<Pe: ImageNtHeaders>(pe_file: &PeFile<Pe>) -> Vec<BinaryRegion> {
+    let debug_directory_data = match pe_file
+        .data_directory(pe::IMAGE_DIRECTORY_ENTRY_DEBUG)
+        .and_then(|directory| {
+            directory
+                .data(pe_file.data(), &pe_file.section_table())
+                .ok()
+        }) {
+        Some(data) => data,
+        None => return vec![],
+    };
+    let entry_count = debug_directory_data.len() / std::mem::size_of::<ImageDebugDirectory>();
+    let entries = match object::pod::slice_from_bytes::<ImageDebugDirectory>(
+        debug_directory_data,
+        entry_count,
+    ) {
+        Ok((entries, _)) => entries,
+        Err(_) => return vec![],
+    };
+
+    entries
+        .iter()
+        .filter(|entry| entry.pointer_to_raw_data.get(LE) != 0)
+        .map(|entry| {
+            let file_offset = u64::from(entry.pointer_to_raw_data.get(LE));
+            let name = match entry.typ.get(LE) {
+                pe::IMAGE_DEBUG_TYPE_CODEVIEW => "debug directory entry (CodeView)",
+                _ => "debug directory entry",
+            };
+            BinaryRegion {
+                name: Arc::new(name.to_string()),
+                file_range: file_offset..file_offset + u64::from(entry.size_of_data.get(LE)),
+                virtual_address: Some(u64::from(entry.address_of_raw_data.get(LE)))
+                    .filter(|address| *address != 0)
+                    .map(|address| address + pe_file.relative_address_base()),
+                debug_info: true,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    const FILE_LIST_PROJ_PATH: &str = "tests/data/main/file_list_proj";
+
+    fn read_test_binary(file_name: &str) -> Vec<u8> {
+        let binary_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join(FILE_LIST_PROJ_PATH)
+            .join(file_name);
+        std::fs::read(binary_path).expect("Failed to read test binary")
+    }
+
+    fn find_region<'r>(regions: &'r [BinaryRegion], name: &str) -> &'r BinaryRegion {
+        regions
+            .iter()
+            .find(|region| *region.name == name)
+            .expect("Missing region")
+    }
+
+    #[test]
+    fn parse_elf_regions() {
+        let regions = parse_regions(&read_test_binary("a.out")).expect("parse_regions failed");
+
+        let rodata = find_region(&regions, "section \".rodata\"");
+        assert!(rodata.file_range.start < rodata.file_range.end);
+        assert!(rodata.virtual_address.is_some());
+        // Not loaded in memory
+        let comment = find_region(&regions, "section \".comment\"");
+        assert!(comment.virtual_address.is_none());
+        assert!(!rodata.debug_info && !comment.debug_info);
+    }
+
+    #[test]
+    fn parse_pe_regions() {
+        let regions = parse_regions(&read_test_binary("a.exe")).expect("parse_regions failed");
+
+        // Virtual addresses include the image base
+        let rdata = find_region(&regions, "section \".rdata\"");
+        assert!(rdata.virtual_address.expect("Missing virtual address") > 0x1000);
+        assert!(!rdata.debug_info);
+        // Data referenced by the debug directory, stored in ".rdata"
+        let debug_entry = find_region(&regions, "debug directory entry");
+        assert_eq!(debug_entry.file_range, 0x1e958..0x1ec5c);
+        assert_eq!(debug_entry.virtual_address, Some(0x1_4001_fd58));
+        assert!(debug_entry.debug_info);
+    }
+}
@@ -0,0 +1,135 @@
+//! Slices of universal (fat) Mach-O binaries, which bundle one Mach-O file per
+//! architecture.
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use object::macho::{self, FatHeader};
+use object::read::macho::FatArch;
+use object::{BigEndian, FileKind};
+
+/// Java class files share the 32-bit fat magic, and store their version where
+/// universal binaries store their number of architectures. Class files'
+/// versions are greater than this.
+const MAX_ARCHITECTURE_COUNT: usize = 30;
+
+/// Mach-O file of a given architecture, within a universal binary
+pub struct ArchitectureSlice {
+    /// Name of the slice's architecture (e.g., "x86_64", "arm64")
+    pub architecture: Arc<String>,
+    /// Range of the slice in the universal binary
+    pub file_range: Range<usize>,
+}
+
+/// Returns the architecture slices of the given universal binary, or `None` if
+/// the data isn't a universal binary
+pub fn parse_architecture_slices(data: &[u8]) -> Result<Option<Vec<ArchitectureSlice>>> {
+    match FileKind::parse(data) {
+        Ok(FileKind::MachOFat32) => {
+            let architecture_count = FatHeader::parse(data)?.nfat_arch.get(BigEndian) as usize;
+            if architecture_count > MAX_ARCHITECTURE_COUNT {
+                return Ok(None);
+            }
+            collect_slices(data, FatHeader::parse_arch32(data)?).map(Some)
+        }
+        Ok(FileKind::MachOFat64) => collect_slices(data, FatHeader::parse_arch64(data)?).map(Some),
+        _ => Ok(None),
+    }
+}
+
+fn collect_slices<Arch: FatArch>(data: &[u8], arches: &[Arch]) -> Result<Vec<ArchitectureSlice>> {
+    arches
+        .iter()
+        .map(|arch| {
+            let (offset, size) = arch.file_range();
+            let start = usize::try_from(offset)?;
+            let end = start
+                .checked_add(usize::try_from(size)?)
+                .filter(|end| *end <= data.len())
+                .ok_or_else(|| anyhow!("Architecture slice is out of bounds"))?;
+
+            Ok(ArchitectureSlice {
+                architecture: Arc::new(architecture_name(arch.cputype(), arch.cpusubtype())),
+                file_range: start..end,
+            })
+        })
+        .collect()
+}
+
+/// Returns the name commonly used for the given Mach-O CPU type (e.g., by
+/// `lipo`)
+fn architecture_name(cputype: u32, cpusubtype: u32) -> String {
+    match cputype {
+        macho::CPU_TYPE_X86_64 => "x86_64".to_string(),
+        macho::CPU_TYPE_X86 => "i386".to_string(),
+        macho::CPU_TYPE_ARM64
+            if cpusubtype & !macho::CPU_SUBTYPE_MASK == macho::CPU_SUBTYPE_ARM64E =>
+        {
+            "arm64e".to_string()
+        }
+        macho::CPU_TYPE_ARM64 => "arm64".to_string(),
+        macho::CPU_TYPE_ARM => "arm".to_string(),
+        macho::CPU_TYPE_POWERPC64 => "ppc64".to_string(),
+        macho::CPU_TYPE_POWERPC => "ppc".to_string(),
+        _ => format!("cputype {}", cputype),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a 32-bit universal binary containing the given slices
+    fn universal_binary(slices: &[(u32, &[u8])]) -> Vec<u8> {
+        let mut data = vec![];
+        data.extend(macho::FAT_MAGIC.to_be_bytes());
+        data.extend((slices.len() as u32).to_be_bytes());
+        let mut offset = 8 + 20 * slices.len();
+        for (cputype, slice_data) in slices {
+            data.extend(cputype.to_be_bytes());
+            data.extend(0_u32.to_be_bytes());
+            data.extend((offset as u32).to_be_bytes());
+            data.extend((slice_data.len() as u32).to_be_bytes());
+            data.extend(0_u32.to_be_bytes());
+            offset += slice_data.len();
+        }
+        for (_, slice_data) in slices {
+            data.extend(*slice_data);
+        }
+
+        data
+    }
+
+    #[test]
+    fn parse_universal_binary_slices() {
+        let data = universal_binary(&[
+            (macho::CPU_TYPE_X86_64, b"intel".as_slice()),
+            (macho::CPU_TYPE_ARM64, b"apple".as_slice()),
+        ]);
+
+        let slices = parse_architecture_slices(&data)
+            .expect("parse_architecture_slices failed")
+            .expect("Universal binary not detected");
+        let slices: Vec<(&str, &[u8])> = slices
+            .iter()
+            .map(|slice| (slice.architecture.as_str(), &data[slice.file_range.clone()]))
+            .collect();
+        assert_eq!(
+            slices,
+            vec![
+                ("x86_64", b"intel".as_slice()),
+                ("arm64", b"apple".as_slice())
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_java_class_file() {
+        // Magic, then minor and major versions (Java 8)
+        let data = b"\xca\xfe\xba\xbe\x00\x00\x00\x34";
+        assert!(parse_architecture_slices(data)
+            .expect("parse_architecture_slices failed")
+            .is_none());
+    }
+}
@@ -0,0 +1,418 @@
+//! Minimal WebAssembly module parser, extracting the regions that matter when
+//! looking for leaks: data segments (with their linear memory addresses) and
+//! the entries of the "name" custom section. Custom sections containing debug
+//! information (i.e., "name" and DWARF sections) are flagged as such.
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+
+use super::BinaryRegion;
+
+const WASM_MAGIC: &[u8] = b"\0asm";
+const WASM_VERSION: u32 = 1;
+const HEADER_SIZE: usize = 8;
+
+const CUSTOM_SECTION_ID: u8 = 0;
+const DATA_SECTION_ID: u8 = 11;
+/// Subsection of the "name" custom section containing function names
+const FUNCTION_NAMES_SUBSECTION_ID: u8 = 1;
+
+/// Opcodes allowed in constant expressions (including the ones of the
+/// extended constant expressions proposal)
+const I32_CONST_OPCODE: u8 = 0x41;
+const I64_CONST_OPCODE: u8 = 0x42;
+const F32_CONST_OPCODE: u8 = 0x43;
+const F64_CONST_OPCODE: u8 = 0x44;
+const GLOBAL_GET_OPCODE: u8 = 0x23;
+const REF_NULL_OPCODE: u8 = 0xd0;
+const REF_FUNC_OPCODE: u8 = 0xd2;
+const ARITHMETIC_OPCODES: &[u8] = &[0x6a, 0x6b, 0x6c, 0x7c, 0x7d, 0x7e];
+const END_OPCODE: u8 = 0x0b;
+
+/// Returns `true` if the given data starts with the header of a WebAssembly
+/// module
+pub fn is_wasm_module(data: &[u8]) -> bool {
+    data.len() >= HEADER_SIZE
+        && data.starts_with(WASM_MAGIC)
+        && u32::from_le_bytes([data[4], data[5], data[6], data[7]]) == WASM_VERSION
+}
+
+/// Parses a WebAssembly module and returns its sections, data segments and
+/// function names as regions
+pub fn parse_regions(data: &[u8]) -> Result<Vec<BinaryRegion>> {
+    let mut regions = vec![];
+    let mut reader = Reader::new(data, HEADER_SIZE);
+    while !reader.is_empty() {
+        let section_id = reader.read_u8()?;
+        let section_size = reader.read_var_u32()? as usize;
+        let section_range = reader.skip(section_size)?;
+        let mut section_reader = Reader::new(&data[..section_range.end], section_range.start);
+
+        let (section_name, debug_info) = match section_id {
+            CUSTOM_SECTION_ID => {
+                let custom_section_name = section_reader.read_name()?;
+                if custom_section_name == "name" {
+                    regions.extend(parse_name_section(&mut section_reader)?);
+                }
+                (
+                    format!("custom section \"{}\"", custom_section_name),
+                    is_debug_section_name(&custom_section_name),
+                )
+            }
+            DATA_SECTION_ID => {
+                regions.extend(parse_data_section(&mut section_reader)?);
+                ("data section".to_string(), false)
+            }
+            section_id => (
+                format!("{} section", standard_section_name(section_id)),
+                false,
+            ),
+        };
+        regions.push(BinaryRegion {
+            name: Arc::new(section_name),
+            file_range: section_range.start as u64..section_range.end as u64,
+            virtual_address: None,
+            debug_info,
+        });
+    }
+
+    Ok(regions)
+}
+
+/// Returns `true` for custom sections that are removed when stripping a
+/// module (e.g., with `wasm-strip`)
+fn is_debug_section_name(name: &str) -> bool {
+    name == "name" || name.starts_with(".debug_") || name == "sourceMappingURL"
+}
+
+fn standard_section_name(section_id: u8) -> &'static str {
+    match section_id {
+        1 => "type",
+        2 => "import",
+        3 => "function",
+        4 => "table",
+        5 => "memory",
+        6 => "global",
+        7 => "export",
+        8 => "start",
+        9 => "element",
+        10 => "code",
+        12 => "data count",
+        13 => "tag",
+        _ => "unknown",
+    }
+}
+
+/// Returns the content of each data segment as a region, along with the
+/// address at which it's loaded in linear memory (for active segments)
+fn parse_data_section(reader: &mut Reader) -> Result<Vec<BinaryRegion>> {
+    let segment_count = reader.read_var_u32()?;
+    let mut regions = vec![];
+    for segment_index in 0..segment_count {
+        let virtual_address = match reader.read_var_u32()? {
+            // Active segment, in memory 0
+            0 => parse_constant_expression(reader)?,
+            // Passive segment
+            1 => None,
+            // Active segment, with an explicit memory index
+            2 => {
+                reader.read_var_u32()?;
+                parse_constant_expression(reader)?
+            }
+            flags => return Err(anyhow!("Unsupported data segment flags: {}", flags)),
+        };
+        let segment_size = reader.read_var_u32()? as usize;
+        let segment_range = reader.skip(segment_size)?;
+
+        regions.push(BinaryRegion {
+            name: Arc::new(format!("data segment {}", segment_index)),
+            file_range: segment_range.start as u64..segment_range.end as u64,
+            virtual_address,
+            debug_info: false,
+        });
+    }
+
+    Ok(regions)
+}
+
+/// Parses a constant expression and returns its value if it's a simple
+/// integer constant (e.g., not a global)
+fn parse_constant_expression(reader: &mut Reader) -> Result<Option<u64>> {
+    let mut values = vec![];
+    loop {
+        // Note: Immediates are decoded, as they may contain the end opcode
+        let value = match reader.read_u8()? {
+            END_OPCODE => break,
+            // Note: Negative 32-bit constants must not be sign-extended
+            I32_CONST_OPCODE => Some(reader.read_var_i64()? as u32 as u64),
+            I64_CONST_OPCODE => Some(reader.read_var_i64()? as u64),
+            F32_CONST_OPCODE => {
+                reader.skip(4)?;
+                None
+            }
+            F64_CONST_OPCODE => {
+                reader.skip(8)?;
+                None
+            }
+            GLOBAL_GET_OPCODE | REF_FUNC_OPCODE => {
+                reader.read_var_u32()?;
+                None
+            }
+            REF_NULL_OPCODE => {
+                reader.read_u8()?;
+                None
+            }
+            opcode if ARITHMETIC_OPCODES.contains(&opcode) => None,
+            opcode => {
+                return Err(anyhow!(
+                    "Unsupported opcode in constant expression: 0x{:02x}",
+                    opcode
+                ))
+            }
+        };
+        values.push(value);
+    }
+
+    Ok(match values[..] {
+        [value] => value,
+        _ => None,
+    })
+}
+
+/// Returns the function names of the "name" custom section as regions, so that
+/// leaks of function names can be correlated with the functions they name
+fn parse_name_section(reader: &mut Reader) -> Result<Vec<BinaryRegion>> {
+    let mut regions = vec![];
+    while !reader.is_empty() {
+        let subsection_id = reader.read_u8()?;
+        let subsection_size = reader.read_var_u32()? as usize;
+        let subsection_range = reader.skip(subsection_size)?;
+        if subsection_id != FUNCTION_NAMES_SUBSECTION_ID {
+            continue;
+        }
+
+        let mut subsection_reader =
+            Reader::new(&reader.data[..subsection_range.end], subsection_range.start);
+        let name_count = subsection_reader.read_var_u32()?;
+        for _ in 0..name_count {
+            let function_index = subsection_reader.read_var_u32()?;
+            let name_size = subsection_reader.read_var_u32()? as usize;
+            let name_range = subsection_reader.skip(name_size)?;
+            regions.push(BinaryRegion {
+                name: Arc::new(format!("name of function {}", function_index)),
+                file_range: name_range.start as u64..name_range.end as u64,
+                virtual_address: None,
+                debug_info: true,
+            });
+        }
+    }
+
+    Ok(regions)
+}
+
+/// Reads WebAssembly's primitive types, keeping track of file offsets
+struct Reader<'d> {
+    data: &'d [u8],
+    position: usize,
+}
+
+impl<'d> Reader<'d> {
+    fn new(data: &'d [u8], position: usize) -> Self {
+        Self { data, position }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.position >= self.data.len()
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        let byte = *self
+            .data
+            .get(self.position)
+            .ok_or_else(|| anyhow!("Unexpected end of WebAssembly module"))?;
+        self.position += 1;
+
+        Ok(byte)
+    }
+
+    /// Skips `size` bytes and returns their range
+    fn skip(&mut self, size: usize) -> Result<Range<usize>> {
+        let start = self.position;
+        let end = start
+            .checked_add(size)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| anyhow!("Unexpected end of WebAssembly module"))?;
+        self.position = end;
+
+        Ok(start..end)
+    }
+
+    /// Reads an unsigned LEB128 integer
+    fn read_var_u32(&mut self) -> Result<u32> {
+        let mut value: u64 = 0;
+        for shift in (0..35).step_by(7) {
+            let byte = self.read_u8()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return u32::try_from(value).map_err(|_| anyhow!("Invalid LEB128 integer"));
+            }
+        }
+
+        Err(anyhow!("Invalid LEB128 integer"))
+    }
+
+    /// Reads a signed LEB128 integer
+    fn read_var_i64(&mut self) -> Result<i64> {
+        let mut value: i64 = 0;
+        for shift in (0..70).step_by(7) {
+            let byte = self.read_u8()?;
+            value |= i64::from(byte & 0x7f).wrapping_shl(shift);
+            if byte & 0x80 == 0 {
+                // Sign-extend the value
+                if shift + 7 < 64 && byte & 0x40 != 0 {
+                    value |= -1 << (shift + 7);
+                }
+                return Ok(value);
+            }
+        }
+
+        Err(anyhow!("Invalid LEB128 integer"))
+    }
+
+    fn read_name(&mut self) -> Result<String> {
+        let size = self.read_var_u32()? as usize;
+        let range = self.skip(size)?;
+
+        Ok(String::from_utf8_lossy(&self.data[range]).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a section with the given id and content
+    fn section(id: u8, content: &[u8]) -> Vec<u8> {
+        let mut section = vec![id, content.len() as u8];
+        section.extend(content);
+        section
+    }
+
+    fn wasm_module() -> Vec<u8> {
+        let mut module = b"\0asm\x01\0\0\0".to_vec();
+        // Data section: an active segment at address 0x400 (encoded as a
+        // multi-byte LEB128) and a passive segment
+        let mut data_section = vec![2, 0, I32_CONST_OPCODE, 0x80, 0x08, END_OPCODE, 6];
+        data_section.extend(b"secret");
+        data_section.extend([1, 5]);
+        data_section.extend(b"hello");
+        module.extend(section(DATA_SECTION_ID, &data_section));
+        // "name" section, with the name of function 3
+        let mut function_names = vec![1, 3, 7];
+        function_names.extend(b"do_work");
+        let mut name_section = vec![4];
+        name_section.extend(b"name");
+        name_section.push(FUNCTION_NAMES_SUBSECTION_ID);
+        name_section.push(function_names.len() as u8);
+        name_section.extend(function_names);
+        module.extend(section(CUSTOM_SECTION_ID, &name_section));
+
+        module
+    }
+
+    #[test]
+    fn detect_wasm_module() {
+        assert!(is_wasm_module(&wasm_module()));
+        assert!(!is_wasm_module(b"\x7fELF\x02\x01\x01\0"));
+        assert!(!is_wasm_module(b"\0asm"));
+    }
+
+    #[test]
+    fn parse_wasm_regions() {
+        let module = wasm_module();
+        let regions = parse_regions(&module).expect("parse_regions failed");
+        let region_names: Vec<&str> = regions.iter().map(|region| region.name.as_str()).collect();
+        assert_eq!(
+            region_names,
+            [
+                "data segment 0",
+                "data segment 1",
+                "data section",
+                "name of function 3",
+                "custom section \"name\""
+            ]
+        );
+
+        // Active segment
+        let range = regions[0].file_range.start as usize..regions[0].file_range.end as usize;
+        assert_eq!(&module[range], b"secret");
+        assert_eq!(regions[0].virtual_address, Some(0x400));
+        // Passive segment
+        let range = regions[1].file_range.start as usize..regions[1].file_range.end as usize;
+        assert_eq!(&module[range], b"hello");
+        assert_eq!(regions[1].virtual_address, None);
+        // Function name
+        let range = regions[3].file_range.start as usize..regions[3].file_range.end as usize;
+        assert_eq!(&module[range], b"do_work");
+        // Only the "name" section is removed when stripping the module
+        let debug_info: Vec<bool> = regions.iter().map(|region| region.debug_info).collect();
+        assert_eq!(debug_info, [false, false, false, true, true]);
+    }
+
+    #[test]
+    fn parse_truncated_module() {
+        let module = wasm_module();
+        assert!(parse_regions(&module[..module.len() - 2]).is_err());
+    }
+
+    #[test]
+    fn parse_constant_expressions() {
+        let parse = |expression: &[u8]| {
+            let mut reader = Reader::new(expression, 0);
+            let value = parse_constant_expression(&mut reader);
+            (value.ok(), reader.is_empty())
+        };
+
+        // Immediates equal to the end opcode don't end the expression
+        assert_eq!(
+            parse(&[GLOBAL_GET_OPCODE, END_OPCODE, END_OPCODE]),
+            (Some(None), true)
+        );
+        assert_eq!(
+            parse(&[I32_CONST_OPCODE, END_OPCODE, END_OPCODE]),
+            (Some(Some(11)), true)
+        );
+        // 32-bit constants aren't sign-extended
+        assert_eq!(
+            parse(&[I32_CONST_OPCODE, 0x7f, END_OPCODE]),
+            (Some(Some(0xffff_ffff)), true)
+        );
+        assert_eq!(
+            parse(&[I64_CONST_OPCODE, 0x7f, END_OPCODE]),
+            (Some(Some(u64::MAX)), true)
+        );
+        // Extended constant expressions have no simple value
+        assert_eq!(
+            parse(&[
+                GLOBAL_GET_OPCODE,
+                0,
+                I32_CONST_OPCODE,
+                0x10,
+                0x6a,
+                END_OPCODE
+            ]),
+            (Some(None), true)
+        );
+        assert_eq!(parse(&[0x00, END_OPCODE]).0, None);
+    }
+
+    #[test]
+    fn read_signed_leb128() {
+        let mut reader = Reader::new(&[0x7f, 0x80, 0x7f, 0x3f], 0);
+        assert_eq!(reader.read_var_i64().unwrap(), -1);
+        assert_eq!(reader.read_var_i64().unwrap(), -128);
+        assert_eq!(reader.read_var_i64().unwrap(), 63);
+    }
+}
@@ -0,0 +1,67 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::Result;
+
+/// Exit code used when a scan is interrupted (i.e., 128 + SIGINT)
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+static CANCELLED: CancellationFlag = CancellationFlag::new();
+
+/// Records whether cancellation has been requested
+struct CancellationFlag(AtomicBool);
+
+impl CancellationFlag {
+    const fn new() -> Self {
+        Self(AtomicBool::new(false))
+    }
+
+    /// Requests cancellation, and returns `true` if it had already been
+    /// requested
+    fn request(&self) -> bool {
+        self.0.swap(true, Ordering::SeqCst)
+    }
+
+    fn is_requested(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Installs a handler for SIGINT/SIGTERM (Ctrl-C on Windows) that requests
+/// the current scan to stop gracefully. Receiving a second signal aborts the
+/// process immediately.
+pub fn install_cancellation_handler() -> Result<()> {
+    Ok(ctrlc::set_handler(|| {
+        if CANCELLED.request() {
+            std::process::exit(INTERRUPTED_EXIT_CODE);
+        }
+        log::warn!("Interrupted, stopping with partial results (interrupt again to abort)...");
+    })?)
+}
+
+/// Returns `true` if the scan has been interrupted and work should stop as
+/// soon as possible
+pub fn is_cancelled() -> bool {
+    CANCELLED.is_requested()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_cancellation() {
+        let flag = CancellationFlag::new();
+        assert!(!flag.is_requested());
+
+        // The first request stops the scan gracefully, the next ones abort it
+        assert!(!flag.request());
+        assert!(flag.is_requested());
+        assert!(flag.request());
+        assert!(flag.is_requested());
+    }
+
+    #[test]
+    fn not_cancelled_by_default() {
+        assert!(!is_cancelled());
+    }
+}
@@ -0,0 +1,324 @@
+//! Checkpoints of the extraction phase, so that an interrupted run over a large
+//! compilation database can resume where it stopped instead of parsing every
+//! translation unit again.
+
+use std::collections::BTreeSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::analysis_cache::TranslationUnitArtifacts;
+
+const CHECKPOINT_FORMAT_VERSION: u32 = 2;
+
+/// Number of translation units parsed between two checkpoints
+const CHECKPOINT_BATCH_SIZE: usize = 64;
+
+/// First line of a checkpoint file
+#[derive(Serialize, Deserialize)]
+struct CheckpointHeader {
+    format: u32,
+    /// Extraction settings the artifacts have been extracted with
+    settings: Option<String>,
+}
+
+/// Line of a checkpoint file following the header, holding the artifacts of a
+/// translation unit
+#[derive(Serialize, Deserialize)]
+struct CheckpointEntry {
+    file: PathBuf,
+    #[serde(flatten)]
+    artifacts: TranslationUnitArtifacts,
+}
+
+/// Translation units parsed so far and the artifacts extracted from them,
+/// periodically appended to a file (one line per translation unit), so that
+/// saving a checkpoint only writes what's been parsed since the last one
+pub struct Checkpoint {
+    path: PathBuf,
+    /// Extraction settings the artifacts have been extracted with
+    settings: Option<String>,
+    /// Translation units whose artifacts have been collected
+    parsed_files: BTreeSet<PathBuf>,
+    artifacts: TranslationUnitArtifacts,
+    /// Translation units recorded since the checkpoint was last saved
+    unsaved_entries: Vec<CheckpointEntry>,
+    /// Whether the checkpoint's file has been started with its header
+    file_started: bool,
+}
+
+impl Checkpoint {
+    /// Starts a new checkpoint, saved into `path` (replacing any previous
+    /// checkpoint)
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            settings: None,
+            parsed_files: BTreeSet::new(),
+            artifacts: TranslationUnitArtifacts::default(),
+            unsaved_entries: vec![],
+            file_started: false,
+        }
+    }
+
+    /// Loads the checkpoint saved into `path` by an interrupted run, so that
+    /// the translation units it lists aren't parsed again
+    pub fn resume<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::open(&path)
+            .with_context(|| format!("No checkpoint to resume from at '{}'", path.display()))?;
+        let invalid_checkpoint = || format!("Invalid checkpoint '{}'", path.display());
+
+        let mut reader = BufReader::new(file);
+        let mut line = vec![];
+        reader.read_until(b'\n', &mut line)?;
+        let header: CheckpointHeader =
+            serde_json::from_slice(&line).with_context(invalid_checkpoint)?;
+        if header.format != CHECKPOINT_FORMAT_VERSION {
+            return Err(anyhow!(
+                "Checkpoint '{}' has been written by an incompatible version",
+                path.display()
+            ));
+        }
+
+        let mut checkpoint = Self {
+            settings: header.settings,
+            file_started: true,
+            ..Self::new(&path)
+        };
+        let mut valid_size = line.len() as u64;
+        loop {
+            line.clear();
+            if reader.read_until(b'\n', &mut line)? == 0 {
+                break;
+            }
+            match serde_json::from_slice::<CheckpointEntry>(&line) {
+                Ok(entry) => {
+                    checkpoint.add_entry(&entry);
+                    valid_size += line.len() as u64;
+                }
+                // The previous run has been interrupted while saving
+                Err(_) if !line.ends_with(b"\n") => break,
+                Err(err) => return Err(err).with_context(invalid_checkpoint),
+            }
+        }
+        // Drop the entry that's been partially written, if any, so that new
+        // entries can be appended
+        OpenOptions::new()
+            .write(true)
+            .open(&path)?
+            .set_len(valid_size)?;
+
+        Ok(checkpoint)
+    }
+
+    /// Makes sure the checkpoint's artifacts have been extracted with the
+    /// given settings, which are recorded if the checkpoint is new
+    pub(crate) fn check_settings(&mut self, settings: &str) -> Result<()> {
+        match self.settings {
+            Some(ref checkpoint_settings) if checkpoint_settings != settings => Err(anyhow!(
+                "Checkpoint '{}' has been created with different extraction settings",
+                self.path.display()
+            )),
+            Some(_) => Ok(()),
+            None => {
+                self.settings = Some(settings.to_string());
+                Ok(())
+            }
+        }
+    }
+
+    /// Indicates if the artifacts of the given translation unit are part of
+    /// the checkpoint
+    pub fn is_parsed(&self, file_path: &Path) -> bool {
+        self.parsed_files.contains(file_path)
+    }
+
+    /// Returns the number of translation units whose artifacts are part of
+    /// the checkpoint
+    pub fn parsed_file_count(&self) -> usize {
+        self.parsed_files.len()
+    }
+
+    /// Returns the artifacts collected so far
+    pub fn artifacts(&self) -> &TranslationUnitArtifacts {
+        &self.artifacts
+    }
+
+    /// Adds the artifacts of a translation unit to the checkpoint, which is
+    /// saved once a batch of translation units has been recorded
+    pub(crate) fn record(
+        &mut self,
+        file_path: &Path,
+        artifacts: &TranslationUnitArtifacts,
+    ) -> Result<()> {
+        if self.is_parsed(file_path) {
+            return Ok(());
+        }
+        let entry = CheckpointEntry {
+            file: file_path.to_path_buf(),
+            artifacts: TranslationUnitArtifacts {
+                artifacts: artifacts.artifacts.clone(),
+                too_small_artifacts: artifacts.too_small_artifacts.clone(),
+            },
+        };
+        self.add_entry(&entry);
+
+        self.unsaved_entries.push(entry);
+        if self.unsaved_entries.len() >= CHECKPOINT_BATCH_SIZE {
+            self.save()?;
+        }
+
+        Ok(())
+    }
+
+    fn add_entry(&mut self, entry: &CheckpointEntry) {
+        if !self.parsed_files.insert(entry.file.clone()) {
+            return;
+        }
+        self.artifacts
+            .artifacts
+            .extend(entry.artifacts.artifacts.iter().cloned());
+        self.artifacts
+            .too_small_artifacts
+            .extend(entry.artifacts.too_small_artifacts.iter().cloned());
+    }
+
+    /// Appends the translation units recorded since the last save to the
+    /// checkpoint's file
+    pub fn save(&mut self) -> Result<()> {
+        let file = if self.file_started {
+            OpenOptions::new().append(true).open(&self.path)
+        } else {
+            File::create(&self.path)
+        }
+        .with_context(|| format!("Failed to save checkpoint '{}'", self.path.display()))?;
+
+        let mut writer = BufWriter::new(file);
+        if !self.file_started {
+            write_json_line(
+                &mut writer,
+                &CheckpointHeader {
+                    format: CHECKPOINT_FORMAT_VERSION,
+                    settings: self.settings.clone(),
+                },
+            )?;
+        }
+        for entry in &self.unsaved_entries {
+            write_json_line(&mut writer, entry)?;
+        }
+        writer.flush()?;
+        self.file_started = true;
+        self.unsaved_entries.clear();
+
+        Ok(())
+    }
+}
+
+fn write_json_line<W: Write, T: Serialize>(writer: &mut W, value: &T) -> Result<()> {
+    serde_json::to_writer(&mut *writer, value)?;
+    writer.write_all(b"\n")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::information_leak::{LeakedDataType, PotentialLeak, SourceLocation};
+
+    use super::*;
+
+    fn artifacts(value: &str) -> TranslationUnitArtifacts {
+        TranslationUnitArtifacts {
+            artifacts: vec![PotentialLeak {
+                data_type: LeakedDataType::StringLiteral,
+                data: Arc::new(value.to_string()),
+                bytes: value.as_bytes().to_vec(),
+                code_unit_size: 1,
+                declaration_metadata: Arc::new(SourceLocation {
+                    file: PathBuf::from("main.cc"),
+                    line: 1,
+                }),
+            }],
+            too_small_artifacts: vec![],
+        }
+    }
+
+    #[test]
+    fn resume_saved_checkpoint() {
+        let directory = tempfile::tempdir().unwrap();
+        let checkpoint_path = directory.path().join("checkpoint.json");
+
+        let mut checkpoint = Checkpoint::new(&checkpoint_path);
+        checkpoint.check_settings("settings").unwrap();
+        checkpoint
+            .record(Path::new("a.cc"), &artifacts("secret"))
+            .unwrap();
+        checkpoint.save().unwrap();
+
+        let mut checkpoint = Checkpoint::resume(&checkpoint_path).expect("resume failed");
+        assert!(checkpoint.is_parsed(Path::new("a.cc")));
+        assert!(!checkpoint.is_parsed(Path::new("b.cc")));
+        assert_eq!(checkpoint.parsed_file_count(), 1);
+        assert_eq!(*checkpoint.artifacts().artifacts[0].data, "secret");
+        assert!(checkpoint.check_settings("settings").is_ok());
+        assert!(checkpoint.check_settings("other settings").is_err());
+    }
+
+    #[test]
+    fn resume_checkpoint_saved_incrementally() {
+        let directory = tempfile::tempdir().unwrap();
+        let checkpoint_path = directory.path().join("checkpoint.json");
+
+        let mut checkpoint = Checkpoint::new(&checkpoint_path);
+        checkpoint.check_settings("settings").unwrap();
+        checkpoint
+            .record(Path::new("a.cc"), &artifacts("first"))
+            .unwrap();
+        checkpoint.save().unwrap();
+        checkpoint
+            .record(Path::new("b.cc"), &artifacts("second"))
+            .unwrap();
+        checkpoint.save().unwrap();
+        // Saves only append the translation units recorded since the last one
+        let content = std::fs::read_to_string(&checkpoint_path).unwrap();
+        assert_eq!(content.lines().count(), 3);
+
+        // Entries partially written by an interrupted save are dropped
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(&checkpoint_path)
+            .unwrap();
+        file.write_all(b"{\"file\":\"c.cc\",\"artif").unwrap();
+        let mut checkpoint = Checkpoint::resume(&checkpoint_path).expect("resume failed");
+        assert_eq!(checkpoint.parsed_file_count(), 2);
+        assert!(!checkpoint.is_parsed(Path::new("c.cc")));
+
+        checkpoint
+            .record(Path::new("c.cc"), &artifacts("third"))
+            .unwrap();
+        checkpoint.save().unwrap();
+        let checkpoint = Checkpoint::resume(&checkpoint_path).expect("resume failed");
+        assert_eq!(checkpoint.parsed_file_count(), 3);
+        assert_eq!(
+            checkpoint
+                .artifacts()
+                .artifacts
+                .iter()
+                .map(|artifact| artifact.data.as_str())
+                .collect::<Vec<_>>(),
+            vec!["first", "second", "third"]
+        );
+    }
+
+    #[test]
+    fn resume_missing_checkpoint() {
+        let directory = tempfile::tempdir().unwrap();
+        assert!(Checkpoint::resume(directory.path().join("checkpoint.json")).is_err());
+    }
+}
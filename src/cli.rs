@@ -1,49 +1,154 @@
 use std::path::PathBuf;
 
+use serde::{Serialize, Serializer};
 use structopt::StructOpt;
 
+use cpplumber::compilation_database::Shard;
+use cpplumber::diagnostics::LogFormat;
+use cpplumber::information_leak::WideCharMode;
+use cpplumber::path_mapping::PathPrefixMapping;
+use cpplumber::policy::FailOn;
+use cpplumber::reporting::{LeakGrouping, DEFAULT_OUTPUT_FORMAT};
+use cpplumber::severity::Severity;
+use cpplumber::target_profile::TargetProfile;
+
 const PKG_NAME: &str = env!("CARGO_PKG_NAME");
 
-#[derive(Debug, StructOpt)]
-#[structopt(name = PKG_NAME, about = "An information leak detector for C and C++ code bases")]
+#[derive(Debug, Serialize, StructOpt)]
+#[structopt(
+    name = PKG_NAME,
+    about = "An information leak detector for C and C++ code bases",
+    after_help = "If libclang fails to load or to parse source files, run `cpplumber doctor` \
+                  to diagnose the environment."
+)]
 pub struct CpplumberOptions {
-    /// Path to the output binary to scan for leaked information.
+    /// Path to the output binary to scan for leaked information (required
+    /// when no subcommand is used). If a directory is given, all the files it
+    /// contains are scanned recursively. Process memory dumps (minidumps and
+    /// ELF core dumps) can be given as well, to find leaks that only
+    /// materialize at runtime.
     #[structopt(parse(from_os_str), short, long = "bin")]
-    pub binary_file_path: PathBuf,
+    pub binary_file_path: Option<PathBuf>,
+
+    /// Only scan files matching the given glob expression (e.g., "*.dll") when
+    /// `--bin` is a directory. Expressions containing separators match paths
+    /// relative to the directory. Can be given multiple times.
+    #[structopt(long = "bin-glob", number_of_values = 1)]
+    pub binary_globs: Vec<String>,
+
+    /// Path to a previous version of the binary. Only artifacts that aren't
+    /// already present in this binary are reported.
+    #[structopt(parse(from_os_str), long = "reference-bin")]
+    pub reference_binary_file_path: Option<PathBuf>,
 
     /// Additional include directories.
-    /// Only used when project files aren't used.
+    /// Only used for source files that aren't part of the compilation
+    /// database.
     #[structopt(short = "I")]
     pub include_directories: Vec<String>,
 
     /// Additional preprocessor definitions.
-    /// Only used when project files aren't used.
+    /// Only used for source files that aren't part of the compilation
+    /// database.
     #[structopt(short = "D")]
     pub compile_definitions: Vec<String>,
 
-    /// Compilation database.
+    /// Only parse the given translation units (comma-separated), along with
+    /// the files they include. Source files are taken from the compilation
+    /// database if one is used.
+    #[structopt(parse(from_os_str), long, use_delimiter = true)]
+    pub entry_points: Vec<PathBuf>,
+
+    /// Only parse the source files matching the given glob expression (e.g.,
+    /// "src/net/*"). Expressions match absolute paths, as well as paths
+    /// relative to the current directory. Can be given multiple times.
+    #[structopt(long, number_of_values = 1)]
+    pub only_files: Vec<String>,
+
+    /// Don't parse the source files matching the given glob expression (e.g.,
+    /// "*_test.cc"). Can be given multiple times.
+    #[structopt(long, number_of_values = 1)]
+    pub exclude_files: Vec<String>,
+
+    /// Append the given argument to every compile command (e.g.,
+    /// "--extra-arg=-fparse-all-comments"). Can be given multiple times.
+    #[structopt(long = "extra-arg", number_of_values = 1, allow_hyphen_values = true)]
+    pub extra_args: Vec<String>,
+
+    /// Remove the arguments matching the given glob expression (e.g., "/MP"
+    /// or "-W*") from every compile command, such as flags libclang doesn't
+    /// understand. Can be given multiple times.
+    #[structopt(long = "remove-arg", number_of_values = 1, allow_hyphen_values = true)]
+    pub remove_args: Vec<String>,
+
+    /// Compilation database, or Visual Studio project (.vcxproj) or solution
+    /// (.sln).
     #[structopt(parse(from_os_str), short, long = "project")]
     pub project_file_path: Option<PathBuf>,
 
+    /// CMake build directory, whose compilation database is used as project
+    /// file. The build tree is reconfigured to export compile commands if
+    /// needed.
+    #[structopt(
+        parse(from_os_str),
+        long = "cmake-build-dir",
+        conflicts_with = "project-file-path"
+    )]
+    pub cmake_build_directory: Option<PathBuf>,
+
+    /// Path to a configuration file (e.g., containing custom artifact
+    /// definitions).
+    #[structopt(parse(from_os_str), short, long)]
+    pub config: Option<PathBuf>,
+
     /// Path to a file containing rules to prevent certain errors from being
     /// generated.
     #[structopt(parse(from_os_str), short, long)]
     pub suppressions_list: Option<PathBuf>,
 
-    /// Report leaked values only once, even when found in multiple locations.
+    /// Directory file patterns of the suppressions file can be relative to
+    /// (defaults to the `project_root` of the suppressions file, or to its
+    /// directory).
+    #[structopt(parse(from_os_str), long, requires = "suppressions-list")]
+    pub project_root: Option<PathBuf>,
+
+    /// Report leaked values only once, even when found in multiple locations,
+    /// along with the number of locations they were found at.
     #[structopt(long)]
     pub ignore_multiple_locations: bool,
 
+    /// Group leaks in text output by "source" file, by "binary" file or by
+    /// "value", with the number of leaks in each group.
+    #[structopt(long)]
+    pub group_by: Option<LeakGrouping>,
+
     /// Report leaks for data declared in system headers
     #[structopt(long)]
     pub report_system_headers: bool,
 
+    /// Treat headers located under the given directory (e.g., an SDK installed
+    /// inside the project, generated files) as system headers. Can be given
+    /// multiple times.
+    #[structopt(parse(from_os_str), long = "system-include-root", number_of_values = 1)]
+    pub system_include_roots: Vec<PathBuf>,
+
     /// Minimum required size in bytes, for a leak to be reported. Defaults to 4.
     /// Warning: Setting this to a lower value might greatly increase resource
     /// consumption and reports' sizes.
     #[structopt(short, long)]
     pub minimum_leak_size: Option<usize>,
 
+    /// Fail if fewer source files than this are left to parse (e.g., because
+    /// of a wrong compilation database or glob expression). Use 0 to disable.
+    /// Sharded projects are checked as a whole.
+    #[structopt(long, default_value = "1")]
+    pub min_files: usize,
+
+    /// Fail if fewer artifacts than this are left to look for (e.g., because
+    /// everything has been suppressed). Use 0 to disable. Ignored for shards.
+    #[structopt(long, default_value = "1")]
+    pub min_artifacts: usize,
+
     /// Ignore leaks of string literals.
     #[structopt(long)]
     pub ignore_string_literals: bool,
@@ -52,10 +157,537 @@ pub struct CpplumberOptions {
     #[structopt(long)]
     pub ignore_struct_names: bool,
 
-    /// Generate output as JSON.
-    #[structopt(short, long = "json")]
+    /// Ignore leaks of union names.
+    #[structopt(long)]
+    pub ignore_union_names: bool,
+
+    /// Ignore leaks of type names introduced with `typedef` or `using`.
+    #[structopt(long)]
+    pub ignore_typedef_names: bool,
+
+    /// Ignore leaks of enum and enumerator names.
+    #[structopt(long)]
+    pub ignore_enum_names: bool,
+
+    /// Report leaks of function and method names (e.g., through exported
+    /// symbols or RTTI).
+    #[structopt(long)]
+    pub include_function_names: bool,
+
+    /// Report leaks of struct and class field names (e.g., through
+    /// serialization frameworks or reflection macros).
+    #[structopt(long)]
+    pub include_field_names: bool,
+
+    /// Report leaks of comments' content (e.g., `TODO` notes, authors or
+    /// license headers) found in source files and the headers they include.
+    #[structopt(long)]
+    pub include_comments: bool,
+
+    /// Report leaks of the values of object-like macros defined as string
+    /// literals (e.g., `#define API_KEY "..."`), along with the usual string
+    /// literals.
+    #[structopt(long)]
+    pub include_macro_values: bool,
+
+    /// Extract artifacts with the given extractors only, instead of the ones
+    /// selected by the `--ignore-*` and `--include-*` options. Available
+    /// extractors: string_literals, macros, type_names, union_names,
+    /// typedef_names, enum_names, function_names, field_names, comments.
+    #[structopt(long, use_delimiter = true)]
+    pub extractors: Vec<String>,
+
+    /// Prune potential leaks that cannot be found in the printable strings of
+    /// the binary before looking for leaks. Speeds up scans when there are
+    /// many more artifacts than strings in the binary.
+    #[structopt(long)]
+    pub strings_prefilter: bool,
+
+    /// Only match wide and UTF-16/UTF-32 strings at offsets aligned on their
+    /// code unit size. By default, all patterns are matched at every byte
+    /// offset, which can find wide strings stored at unaligned offsets but
+    /// also yields more false positives and takes slightly longer.
+    #[structopt(long)]
+    pub aligned_wide_strings: bool,
+
+    /// Also report artifacts that only partially leaked (e.g., truncated
+    /// strings), as long as at least the given number of bytes is found.
+    #[structopt(long = "partial-leaks")]
+    pub minimum_partial_leak_size: Option<usize>,
+
+    /// Also report artifacts whose beginning or end is found in the binary
+    /// (e.g., strings folded or truncated by compilers), as long as at least
+    /// the given number of bytes is found. Implies `--partial-leaks`.
+    #[structopt(long, conflicts_with = "partial-leaks")]
+    pub match_substrings: Option<usize>,
+
+    /// Also look for leaks in the files contained in installers and archives
+    /// (e.g., NSIS installers, Electron asar archives). Leaks found in these
+    /// files are reported as "<container>!/<file>". Static libraries, ZIP and
+    /// tar archives and gzip-compressed files are always unpacked. Inno Setup
+    /// installers and bzip2-compressed NSIS installers aren't supported.
+    #[structopt(long)]
+    pub unpack_containers: bool,
+
+    /// Also look for UTF-16LE versions of narrow ASCII artifacts (e.g., strings
+    /// converted to wide strings or stored in PE resources).
+    #[structopt(long)]
+    pub utf16_variants: bool,
+
+    /// Also look for string artifacts in all the encodings they may be stored
+    /// with (UTF-8, UTF-16LE and UTF-32LE), whatever their prefix in the
+    /// source code.
+    #[structopt(long)]
+    pub all_encodings: bool,
+
+    /// Encode wide strings as compilers targeting the given platform do
+    /// ("windows": UTF-16LE, "unix": UTF-32LE). Defaults to the current
+    /// platform, which misses wide strings when scanning binaries built for
+    /// another platform.
+    #[structopt(long)]
+    pub wide_char_mode: Option<WideCharMode>,
+
+    /// Target the binary is built for ("windows-x64", "windows-arm64",
+    /// "linux-x64", "linux-arm64", "macos-x64" or "macos-arm64"). Sets the
+    /// wide char mode (unless `--wide-char-mode` is used) and the mangling
+    /// scheme of type names, and checks the binary's format and architecture.
+    #[structopt(long)]
+    pub profile: Option<TargetProfile>,
+
+    /// Detect files embedded with `#embed` or `INCBIN` and look for their
+    /// content.
+    #[structopt(long)]
+    pub detect_embedded_files: bool,
+
+    /// Detect COM GUIDs (e.g., `DEFINE_GUID`, `uuid` attributes) and interface
+    /// names, and look for both their string and binary representations.
+    #[structopt(long)]
+    pub detect_com_guids: bool,
+
+    /// Look for the paths of source files, which leak through `__FILE__`
+    /// expansions (e.g., in `assert` or logging macros).
+    #[structopt(long)]
+    pub detect_source_paths: bool,
+
+    /// Print additional details (e.g., list the artifacts dropped during the
+    /// extraction phase).
+    #[structopt(short, long)]
+    pub verbose: bool,
+
+    /// Generate output as JSON (shorthand for `--format json`).
+    #[structopt(short, long = "json", conflicts_with = "format")]
     pub json_output: bool,
 
-    /// List of source files to scan for (can be glob expressions).
+    /// Only report leaks whose severity is at least the given level (info,
+    /// low, medium, high or critical).
+    #[structopt(long)]
+    pub min_severity: Option<Severity>,
+
+    /// Only report leaks whose confidence score is at least the given value
+    /// (from 0 to 100). Scores account for the match's length, encoding, null
+    /// terminator and section, as well as how often the pattern is found.
+    #[structopt(long)]
+    pub min_confidence: Option<u8>,
+
+    /// Only look for artifacts whose value has a Shannon entropy of at least
+    /// the given number of bits per character (e.g., 3.5).
+    #[structopt(long, conflicts_with = "only-high-entropy")]
+    pub min_entropy: Option<f64>,
+
+    /// Only look for artifacts that look like embedded secrets (e.g., keys,
+    /// tokens): long values without whitespace and with a high entropy.
+    #[structopt(long)]
+    pub only_high_entropy: bool,
+
+    /// Tell leaks only found in debug information (e.g., DWARF sections, PDB
+    /// paths), which go away when stripping the binary, apart from leaks
+    /// visible at runtime.
+    #[structopt(long)]
+    pub debug_info: bool,
+
+    /// Only report the longest leak found at each range of bytes of the
+    /// binary, instead of every overlapping leak (e.g., for literals sharing
+    /// prefixes).
+    #[structopt(long)]
+    pub overlap_free: bool,
+
+    /// Don't report artifacts contained in other artifacts (e.g., "abc" in
+    /// "xabcx") where they're only found as part of the artifacts containing
+    /// them. Ignored by scan passes.
+    #[structopt(long)]
+    pub dedup_nested_artifacts: bool,
+
+    /// Replace leaked values in reports with a digest and their first and
+    /// last few characters (only the digest for short values), so that
+    /// reports can be shared safely.
+    #[structopt(long)]
+    pub redact: bool,
+
+    /// Only fail when leaks of at least the given severity (e.g., "high"), of
+    /// the given type (e.g., "string_literal"), from code of the given origin
+    /// ("first_party" or "third_party") or with at least the given confidence
+    /// score (e.g., "confidence:70") are found. Can be specified multiple
+    /// times. Ignored when the configuration file defines a policy.
+    #[structopt(long = "fail-on", number_of_values = 1)]
+    pub fail_on: Vec<FailOn>,
+
+    /// Report found leaks and policy violations as warnings, without failing.
+    #[structopt(long)]
+    pub warn_only: bool,
+
+    /// Exit code used when found leaks fail the run.
+    #[structopt(long, default_value = "1")]
+    pub error_exit_code: i32,
+
+    /// Truncate leaked values printed to stdout to the given number of
+    /// characters, so that scans don't spread secrets into build logs. Use
+    /// with `--report-file` to keep the full values.
+    #[structopt(long = "truncate-values")]
+    pub truncated_value_size: Option<usize>,
+
+    /// Only report leaks that aren't part of the given baseline (i.e., a JSON
+    /// report of a previous scan).
+    #[structopt(parse(from_os_str), long, conflicts_with = "write-baseline")]
+    pub baseline: Option<PathBuf>,
+
+    /// Write all the leaks found into the given baseline file (i.e., a JSON
+    /// report) instead of reporting them.
+    #[structopt(parse(from_os_str), long)]
+    pub write_baseline: Option<PathBuf>,
+
+    /// Write a suppressions file (YAML) suppressing the values of all the
+    /// leaks found, grouped by the files declaring them, instead of reporting
+    /// them. Meant to be reviewed and edited before use.
+    #[structopt(parse(from_os_str), long, conflicts_with = "write-baseline")]
+    pub generate_suppressions: Option<PathBuf>,
+
+    /// Write a JSON report into the given file showing, for each string
+    /// artifact, whether it's found in the binary as a narrow string, a
+    /// UTF-16LE string, both or neither (e.g., to check the effect of
+    /// `TEXT()` or `_T()` macros).
+    #[structopt(parse(from_os_str), long)]
+    pub encoding_coverage: Option<PathBuf>,
+
+    /// Write the byte patterns looked for (hex-encoded), along with their
+    /// encodings and the artifacts they come from, into the given JSON file
+    /// (e.g., to feed them into other scanners).
+    #[structopt(parse(from_os_str), long)]
+    pub emit_patterns: Option<PathBuf>,
+
+    /// Replace a path prefix with another one in reports (e.g.,
+    /// `--path-prefix-map /home/ci/project=.`), so that reports don't depend
+    /// on where the project is built. Can be specified multiple times.
+    #[structopt(long = "path-prefix-map", number_of_values = 1)]
+    pub path_prefix_map: Vec<PathPrefixMapping>,
+
+    /// Write progress events (files parsed, artifacts extracted, bytes
+    /// scanned, findings so far) as JSON lines into the given file (e.g., a
+    /// named pipe), or to stderr if "-" is given.
+    #[structopt(parse(from_os_str), long)]
+    pub progress_json: Option<PathBuf>,
+
+    /// Format of the log messages written to stderr ("text" or "json"). JSON
+    /// logs also include structured events (e.g., translation units parsed,
+    /// parse errors, binaries scanned, along with their durations).
+    #[structopt(long, default_value = "text")]
+    pub log_format: LogFormat,
+
+    /// Also write the full report as JSON into the given file.
+    #[structopt(parse(from_os_str), long)]
+    pub report_file: Option<PathBuf>,
+
+    /// Output format of the report ("text", "json", "yaml" or "toml").
+    /// Defaults to "text".
+    #[structopt(long)]
+    pub format: Option<String>,
+
+    /// Only extract artifacts from a subset of the source files, written as
+    /// "i/n" (e.g., "1/4" for the first of four shards).
+    #[structopt(long)]
+    #[serde(serialize_with = "serialize_shard")]
+    pub shard: Option<Shard>,
+
+    /// Write the extracted artifacts into the given file instead of looking
+    /// for leaks (no binary file is needed in this mode).
+    #[structopt(parse(from_os_str), long)]
+    pub export_artifacts: Option<PathBuf>,
+
+    /// Directory where the artifacts extracted from each translation unit are
+    /// cached, so that unchanged files aren't parsed again on subsequent runs.
+    #[structopt(parse(from_os_str), long = "cache-dir")]
+    pub cache_directory: Option<PathBuf>,
+
+    /// Stop parsing source files once the given number of minutes has
+    /// elapsed, and report the leaks found so far. Files most likely to
+    /// contain leaks (according to the cache, then modification times) are
+    /// parsed first.
+    #[structopt(long)]
+    pub time_budget: Option<u64>,
+
+    /// Parse each source file in a separate worker process, so that libclang
+    /// crashing on a file only skips this file instead of aborting the scan.
+    #[structopt(long)]
+    pub isolate_parsing: bool,
+
+    /// Give up parsing a source file after the given number of seconds, and
+    /// skip it. Implies `--isolate-parsing`.
+    #[structopt(long)]
+    pub parse_timeout: Option<u64>,
+
+    /// Skip the source files that fail to parse (e.g., because of a missing
+    /// header) instead of aborting the scan. Failures are summarized at the
+    /// end of the extraction.
+    #[structopt(long)]
+    pub keep_going: bool,
+
+    /// Only use the artifacts stored in the cache directory, without parsing
+    /// any source file. Fails if some source files don't have an up-to-date
+    /// cache entry.
+    #[structopt(long, requires = "cache-directory")]
+    pub reuse_cache_only: bool,
+
+    /// Periodically save the source files parsed so far and the artifacts
+    /// extracted from them into the given file, so that an interrupted run
+    /// can be resumed with `--resume`.
+    #[structopt(parse(from_os_str), long)]
+    pub checkpoint: Option<PathBuf>,
+
+    /// Resume the interrupted run whose progress has been saved into the
+    /// checkpoint file, instead of parsing every source file again.
+    #[structopt(long, requires = "checkpoint")]
+    pub resume: bool,
+
+    /// Don't extract artifacts from the project, only use the artifacts
+    /// given with `--import-artifacts` and the configuration file.
+    #[structopt(long, conflicts_with_all = &["reuse-cache-only", "skip-scan"])]
+    pub skip_extraction: bool,
+
+    /// Stop after extracting artifacts, without looking for leaks (e.g., to
+    /// populate the cache directory). No binary file is needed in this mode.
+    #[structopt(long)]
+    pub skip_scan: bool,
+
+    /// Additional artifacts files (previously exported with
+    /// `--export-artifacts` or merged with `merge-artifacts`) to use.
+    #[structopt(parse(from_os_str), long)]
+    pub import_artifacts: Vec<PathBuf>,
+
+    /// Files listing additional artifacts to look for (e.g., known API keys,
+    /// internal host names), one per line. Lines starting with `hex:` are byte
+    /// patterns, and lines starting with `#` are comments.
+    #[structopt(parse(from_os_str), long)]
+    pub extra_artifacts: Vec<PathBuf>,
+
+    /// Write an attestation of the scan (binary digest, toolchain versions,
+    /// configuration and summary of the findings) into the given file.
+    #[structopt(parse(from_os_str), long)]
+    pub attestation: Option<PathBuf>,
+
+    /// Path to a file containing the key used to sign attestations
+    /// (HMAC-SHA256). Trailing whitespace isn't part of the key.
+    #[structopt(parse(from_os_str), long, requires = "attestation")]
+    pub attestation_key: Option<PathBuf>,
+
+    /// When more leaks than this are found, only print the number of leaks per
+    /// source file in text output and write the full listing into a side
+    /// file. Use 0 to disable.
+    #[structopt(long, default_value = "1000")]
+    pub max_text_findings: usize,
+
+    /// File the full listing is written into when there are too many leaks
+    /// to print them (see `--max-text-findings`). Defaults to a file in the
+    /// temporary directory.
+    #[structopt(parse(from_os_str), long)]
+    pub findings_file: Option<PathBuf>,
+
+    /// Include the given number of bytes of binary content surrounding each
+    /// match in the report (as hex and printable characters). Ignored when
+    /// values are redacted or truncated.
+    #[structopt(long)]
+    pub context_bytes: Option<usize>,
+
+    /// Keep running after the scan, and scan again each time the project's
+    /// source files (including the headers they include) or binary change.
+    /// Only changed translation units are parsed again, and only the leaks
+    /// found or resolved since the previous scan are printed. Changed
+    /// suppressions and configuration are applied to the leaks already found.
+    #[structopt(
+        long,
+        conflicts_with_all = &[
+            "skip-scan",
+            "export-artifacts",
+            "attestation",
+            "write-baseline",
+            "generate-suppressions",
+        ]
+    )]
+    pub watch: bool,
+
+    /// List of source files to scan for (can be glob expressions). Parsed in
+    /// addition to the compilation database's files if one is used.
     pub source_path_globs: Vec<String>,
+
+    #[structopt(subcommand)]
+    #[serde(skip)]
+    pub command: Option<Command>,
+}
+
+impl CpplumberOptions {
+    /// Returns the minimum size of the partial leaks to report, if any, and
+    /// whether the leaks' suffixes are matched in addition to their prefixes
+    pub fn partial_matching(&self) -> (Option<usize>, bool) {
+        match self.match_substrings {
+            Some(minimum_size) => (Some(minimum_size), true),
+            None => (self.minimum_partial_leak_size, false),
+        }
+    }
+
+    /// Returns the name of the requested output format
+    pub fn output_format(&self) -> &str {
+        if self.json_output {
+            "json"
+        } else {
+            self.format.as_deref().unwrap_or(DEFAULT_OUTPUT_FORMAT)
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Command {
+    /// Look for arbitrary strings in a binary file (as narrow, UTF-16LE and
+    /// UTF-32LE strings), without needing any source code.
+    Grep(GrepOptions),
+    /// Show the byte patterns looked for when a given string literal is
+    /// extracted from the source code (e.g., to understand why a string isn't
+    /// matched).
+    Patterns(PatternsOptions),
+    /// Combine artifacts files exported by multiple shards into one.
+    MergeArtifacts(MergeArtifactsOptions),
+    /// Scan all the sub-projects listed in a manifest as a single job, with a
+    /// report sectioned per project.
+    Aggregate(AggregateOptions),
+    /// Check that libclang and the environment are usable (e.g., libclang
+    /// version, builtin headers, permissions on the binary to scan), and print
+    /// how to fix detected problems.
+    Doctor(DoctorOptions),
+    /// Scan two binaries (e.g., the previous and current releases) for the
+    /// artifacts extracted from the project, and report the leaks introduced,
+    /// removed and persisting between them.
+    Diff(DiffOptions),
+    /// Parse a single translation unit described on the standard input (used
+    /// by `--isolate-parsing`).
+    #[structopt(setting = structopt::clap::AppSettings::Hidden)]
+    ParseWorker,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct GrepOptions {
+    /// Path to the binary to scan.
+    #[structopt(parse(from_os_str), short, long = "bin")]
+    pub binary_file_path: PathBuf,
+
+    /// Only match wide strings at offsets aligned on their code unit size.
+    #[structopt(long)]
+    pub aligned_wide_strings: bool,
+
+    /// Also look for strings in the files contained in installers and archives
+    /// (e.g., NSIS installers, Electron asar archives). Strings found in these
+    /// files are reported as "<container>!/<file>". Static libraries, ZIP and
+    /// tar archives and gzip-compressed files are always unpacked. Inno Setup
+    /// installers and bzip2-compressed NSIS installers aren't supported.
+    #[structopt(long)]
+    pub unpack_containers: bool,
+
+    /// Strings to look for.
+    #[structopt(required = true)]
+    pub patterns: Vec<String>,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct PatternsOptions {
+    /// Also show UTF-16LE variants of narrow ASCII literals.
+    #[structopt(long)]
+    pub utf16_variants: bool,
+
+    /// Also show the variants of literals in the encodings they aren't
+    /// written with (UTF-8, UTF-16LE and UTF-32LE).
+    #[structopt(long)]
+    pub all_encodings: bool,
+
+    /// Encode wide strings as compilers targeting the given platform do
+    /// ("windows" or "unix"). Defaults to the current platform.
+    #[structopt(long)]
+    pub wide_char_mode: Option<WideCharMode>,
+
+    /// Encode wide strings as compilers targeting the given profile do (e.g.,
+    /// "windows-arm64"), unless `--wide-char-mode` is used.
+    #[structopt(long)]
+    pub profile: Option<TargetProfile>,
+
+    /// Also show the prefixes used to detect partial leaks of the given size.
+    #[structopt(long = "partial-leaks")]
+    pub minimum_partial_leak_size: Option<usize>,
+
+    /// String literals, as written in the source code (e.g., '"secret"' or
+    /// 'L"secret"'). Values without quotes are treated as ordinary string
+    /// literals.
+    #[structopt(required = true)]
+    pub literals: Vec<String>,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct DoctorOptions {
+    /// Path to a binary to scan, to check that it can be read.
+    #[structopt(parse(from_os_str), short, long = "bin")]
+    pub binary_file_path: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct DiffOptions {
+    /// Path to the old binary (or to a directory of binaries).
+    #[structopt(parse(from_os_str), long = "old")]
+    pub old_binary_path: PathBuf,
+
+    /// Path to the new binary (or to a directory of binaries).
+    #[structopt(parse(from_os_str), long = "new")]
+    pub new_binary_path: PathBuf,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct MergeArtifactsOptions {
+    /// Path of the merged artifacts file to write.
+    #[structopt(parse(from_os_str), short, long)]
+    pub output: PathBuf,
+
+    /// Artifacts files to merge.
+    #[structopt(parse(from_os_str), required = true)]
+    pub artifacts_files: Vec<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct AggregateOptions {
+    /// Path to the manifest listing the sub-projects to scan (YAML file).
+    #[structopt(parse(from_os_str))]
+    pub manifest: PathBuf,
+
+    /// Minimum required size in bytes, for a leak to be reported. Defaults to 4.
+    #[structopt(short, long)]
+    pub minimum_leak_size: Option<usize>,
+
+    /// Replace leaked values in reports with a digest and their first and
+    /// last few characters (only the digest for short values), so that
+    /// reports can be shared safely.
+    #[structopt(long)]
+    pub redact: bool,
+
+    /// Output format of the report ("text" or "json"). Defaults to "text".
+    #[structopt(long, default_value = DEFAULT_OUTPUT_FORMAT)]
+    pub format: String,
+}
+
+fn serialize_shard<S: Serializer>(shard: &Option<Shard>, serializer: S) -> Result<S::Ok, S::Error> {
+    match shard {
+        Some(shard) => serializer.serialize_some(&format!("{}/{}", shard.index, shard.count)),
+        None => serializer.serialize_none(),
+    }
 }
@@ -0,0 +1,343 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use rayon::prelude::*;
+use regex::Regex;
+
+use crate::compilation_database::{include_directories, CompileCommand, CompileCommands};
+use crate::information_leak::{encode_utf16_le, LeakedDataType, PotentialLeak, SourceLocation};
+
+/// Matches `DEFINE_GUID` invocations and captures their arguments
+const DEFINE_GUID_REGEX: &str = r"\bDEFINE_GUID\s*\(([^)]*)\)";
+
+/// Matches GUIDs declared with `uuid` attributes (C++ and IDL) and
+/// `MIDL_INTERFACE`. Captures the GUID and, for `MIDL_INTERFACE`, the name of
+/// the interface that follows it.
+/// Note: `__uuidof` expressions refer to GUIDs declared this way, which is why
+/// they aren't matched themselves.
+const UUID_REGEX: &str = r#"\b(?:uuid|DECLSPEC_UUID|MIDL_INTERFACE)\s*\(\s*"?\{?([0-9A-Fa-f]{8}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{12})\}?"?\s*\)(?:\s*(\w+))?"#;
+
+/// Matches local `#include` directives and captures the included path
+const INCLUDE_REGEX: &str = r#"(?m)^\s*#\s*include\s*"([^"]+)""#;
+
+/// Binary representation of a GUID, as laid out in memory on little-endian
+/// targets
+#[derive(Debug, PartialEq, Eq)]
+struct Guid([u8; 16]);
+
+impl Guid {
+    /// Parses GUIDs written as "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx"
+    fn parse(guid: &str) -> Result<Self> {
+        let digits: String = guid.chars().filter(|c| *c != '-').collect();
+        if digits.len() != 32 {
+            return Err(anyhow!("Invalid GUID '{}'", guid));
+        }
+        let data1 = u32::from_str_radix(&digits[0..8], 16)?;
+        let data2 = u16::from_str_radix(&digits[8..12], 16)?;
+        let data3 = u16::from_str_radix(&digits[12..16], 16)?;
+        let mut data4 = [0; 8];
+        for (i, byte) in data4.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&digits[16 + 2 * i..18 + 2 * i], 16)?;
+        }
+
+        Ok(Self::from_fields(data1, data2, data3, data4))
+    }
+
+    /// Parses the arguments of a `DEFINE_GUID` invocation (i.e., name,
+    /// followed by the 11 numbers the GUID is made of)
+    fn from_define_guid_arguments(arguments: &str) -> Result<Self> {
+        let numbers = arguments
+            .split(',')
+            .skip(1)
+            .map(parse_integer)
+            .collect::<Result<Vec<u64>>>()?;
+        if numbers.len() != 11 {
+            return Err(anyhow!("Invalid DEFINE_GUID arguments '{}'", arguments));
+        }
+        let mut data4 = [0; 8];
+        for (byte, number) in data4.iter_mut().zip(&numbers[3..]) {
+            *byte = u8::try_from(*number)?;
+        }
+
+        Ok(Self::from_fields(
+            u32::try_from(numbers[0])?,
+            u16::try_from(numbers[1])?,
+            u16::try_from(numbers[2])?,
+            data4,
+        ))
+    }
+
+    fn from_fields(data1: u32, data2: u16, data3: u16, data4: [u8; 8]) -> Self {
+        let mut bytes = [0; 16];
+        bytes[0..4].copy_from_slice(&data1.to_le_bytes());
+        bytes[4..6].copy_from_slice(&data2.to_le_bytes());
+        bytes[6..8].copy_from_slice(&data3.to_le_bytes());
+        bytes[8..16].copy_from_slice(&data4);
+
+        Self(bytes)
+    }
+
+    /// Returns the GUID's string representation, in lowercase
+    fn to_canonical_string(&self) -> String {
+        let b = &self.0;
+        format!(
+            "{:08x}-{:04x}-{:04x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+            u32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            u16::from_le_bytes([b[4], b[5]]),
+            u16::from_le_bytes([b[6], b[7]]),
+            b[8],
+            b[9],
+            b[10],
+            b[11],
+            b[12],
+            b[13],
+            b[14],
+            b[15]
+        )
+    }
+
+    /// Generates the potential leaks corresponding to the binary
+    /// representation of the GUID and to its string representations (lower
+    /// and uppercase, narrow and UTF-16LE)
+    fn to_potential_leaks(&self, location: &Arc<SourceLocation>) -> Vec<PotentialLeak> {
+        let data = Arc::new(self.to_canonical_string());
+        let upper_case = data.to_uppercase();
+
+        [
+            (self.0.to_vec(), 1),
+            (data.as_bytes().to_vec(), 1),
+            (upper_case.as_bytes().to_vec(), 1),
+            (encode_utf16_le(&data), 2),
+            (encode_utf16_le(&upper_case), 2),
+        ]
+        .into_iter()
+        .map(|(bytes, code_unit_size)| PotentialLeak {
+            data_type: LeakedDataType::Guid,
+            data: data.clone(),
+            bytes,
+            code_unit_size,
+            declaration_metadata: location.clone(),
+        })
+        .collect()
+    }
+}
+
+/// Parses C integer literals (e.g., "0x1F", "42", "0xcaL")
+fn parse_integer(literal: &str) -> Result<u64> {
+    let literal = literal.trim().trim_end_matches(['u', 'U', 'l', 'L']);
+    let value = if let Some(hex_digits) = literal
+        .strip_prefix("0x")
+        .or_else(|| literal.strip_prefix("0X"))
+    {
+        u64::from_str_radix(hex_digits, 16)?
+    } else {
+        literal.parse()?
+    };
+
+    Ok(value)
+}
+
+struct ComRegexes {
+    define_guid: Regex,
+    uuid: Regex,
+    include: Regex,
+}
+
+/// Looks for COM GUIDs (declared with `DEFINE_GUID`, `uuid` attributes or
+/// `MIDL_INTERFACE`) and COM interface names in the given translation units
+/// and the headers they include locally.
+pub fn extract_com_guids(compile_commands: &CompileCommands) -> Result<Vec<PotentialLeak>> {
+    let regexes = ComRegexes {
+        define_guid: Regex::new(DEFINE_GUID_REGEX)?,
+        uuid: Regex::new(UUID_REGEX)?,
+        include: Regex::new(INCLUDE_REGEX)?,
+    };
+
+    Ok(compile_commands
+        .par_iter()
+        .flat_map_iter(|compile_cmd| {
+            extract_com_guids_from_translation_unit(&regexes, compile_cmd).unwrap_or_else(|err| {
+                log::warn!(
+                    "Failed to look for COM GUIDs in '{}': {}",
+                    compile_cmd.filename.display(),
+                    err
+                );
+                vec![]
+            })
+        })
+        .collect())
+}
+
+fn extract_com_guids_from_translation_unit(
+    regexes: &ComRegexes,
+    compile_cmd: &CompileCommand,
+) -> Result<Vec<PotentialLeak>> {
+    let mut potential_leaks = vec![];
+    let mut visited_files = HashSet::new();
+    let mut files_to_visit = vec![compile_cmd.filename.canonicalize()?];
+    while let Some(file_path) = files_to_visit.pop() {
+        if !visited_files.insert(file_path.clone()) {
+            continue;
+        }
+        let source_content = fs::read_to_string(&file_path)?;
+        potential_leaks.extend(extract_com_guids_from_source(
+            regexes,
+            &file_path,
+            &source_content,
+        ));
+
+        // Follow local includes (e.g., MIDL-generated headers)
+        for captures in regexes.include.captures_iter(&source_content) {
+            if let Some(include_path) =
+                resolve_include_path(Path::new(&captures[1]), &file_path, compile_cmd)
+            {
+                files_to_visit.push(include_path);
+            }
+        }
+    }
+
+    Ok(potential_leaks)
+}
+
+fn extract_com_guids_from_source(
+    regexes: &ComRegexes,
+    file_path: &Path,
+    source_content: &str,
+) -> Vec<PotentialLeak> {
+    let location_at = |offset: usize| {
+        Arc::new(SourceLocation {
+            file: file_path.to_path_buf(),
+            line: source_content[..offset].matches('\n').count() as u64 + 1,
+        })
+    };
+
+    let mut potential_leaks = vec![];
+    for captures in regexes.define_guid.captures_iter(source_content) {
+        let arguments = &captures[1];
+        match Guid::from_define_guid_arguments(arguments) {
+            Ok(guid) => {
+                let guid_start = captures.get(0).expect("Match must exist").start();
+                potential_leaks.extend(guid.to_potential_leaks(&location_at(guid_start)))
+            }
+            Err(err) => log::debug!("Ignoring DEFINE_GUID in '{}': {}", file_path.display(), err),
+        }
+    }
+    for captures in regexes.uuid.captures_iter(source_content) {
+        let guid_match = captures.get(1).expect("GUID must be captured");
+        if let Ok(guid) = Guid::parse(guid_match.as_str()) {
+            potential_leaks.extend(guid.to_potential_leaks(&location_at(guid_match.start())));
+        }
+        if let Some(interface_name) = captures.get(2) {
+            potential_leaks.push(PotentialLeak {
+                data_type: LeakedDataType::ComInterfaceName,
+                data: Arc::new(interface_name.as_str().to_string()),
+                bytes: interface_name.as_str().as_bytes().to_vec(),
+                code_unit_size: 1,
+                declaration_metadata: location_at(interface_name.start()),
+            });
+        }
+    }
+
+    potential_leaks
+}
+
+/// Looks for the included file relatively to the including file first, and
+/// then in the include directories passed to the compiler.
+fn resolve_include_path(
+    include_path: &Path,
+    including_file_path: &Path,
+    compile_cmd: &CompileCommand,
+) -> Option<PathBuf> {
+    let including_directory = including_file_path.parent()?;
+    let include_directories = include_directories(&compile_cmd.arguments)
+        .into_iter()
+        .map(Path::new);
+
+    std::iter::once(including_directory)
+        .chain(include_directories)
+        .map(|directory| directory.join(include_path))
+        .find(|path| path.is_file())
+        .and_then(|path| path.canonicalize().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COM_GUIDS_PATH: &str = "tests/data/com_guids";
+
+    #[test]
+    fn parse_guids() {
+        let guid = Guid::parse("6B29FC40-CA47-1067-B31D-00DD010662DA").expect("parse failed");
+        assert_eq!(
+            guid.0,
+            [
+                0x40, 0xfc, 0x29, 0x6b, 0x47, 0xca, 0x67, 0x10, 0xb3, 0x1d, 0x00, 0xdd, 0x01, 0x06,
+                0x62, 0xda
+            ]
+        );
+        assert_eq!(
+            guid.to_canonical_string(),
+            "6b29fc40-ca47-1067-b31d-00dd010662da"
+        );
+        assert_eq!(
+            Guid::from_define_guid_arguments(
+                "CLSID_Internal, 0x6b29fc40, 0xca47, 0x1067, 0xb3, 0x1d, 0x00, 0xdd, 0x01, 0x06, 0x62, 0xdaL"
+            )
+            .expect("from_define_guid_arguments failed"),
+            guid
+        );
+        assert!(Guid::from_define_guid_arguments("name, 0x1, 0x2").is_err());
+    }
+
+    #[test]
+    fn extract_com_guids_and_interfaces() {
+        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(COM_GUIDS_PATH);
+        let compile_commands = vec![CompileCommand {
+            filename: root_dir_path.join("main.cpp"),
+            arguments: Arc::new(vec![]),
+        }];
+
+        let potential_leaks =
+            extract_com_guids(&compile_commands).expect("extract_com_guids failed");
+
+        let mut artifacts: Vec<(LeakedDataType, &str, u64)> = potential_leaks
+            .iter()
+            .map(|leak| {
+                (
+                    leak.data_type,
+                    leak.data.as_str(),
+                    leak.declaration_metadata.line,
+                )
+            })
+            .collect();
+        artifacts.dedup();
+        assert_eq!(
+            artifacts,
+            vec![
+                (
+                    LeakedDataType::Guid,
+                    "6b29fc40-ca47-1067-b31d-00dd010662da",
+                    4
+                ),
+                (
+                    LeakedDataType::Guid,
+                    "a1b2c3d4-0000-1111-2222-333344445555",
+                    3
+                ),
+                (LeakedDataType::ComInterfaceName, "IInternalService", 4),
+            ]
+        );
+        // Binary representation of the GUID
+        assert_eq!(
+            potential_leaks[0].bytes,
+            [
+                0x40, 0xfc, 0x29, 0x6b, 0x47, 0xca, 0x67, 0x10, 0xb3, 0x1d, 0x00, 0xdd, 0x01, 0x06,
+                0x62, 0xda
+            ]
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{anyhow, Context, Result};
+
+/// Name of the compilation database generated by CMake
+const COMPILE_COMMANDS_FILE_NAME: &str = "compile_commands.json";
+/// File identifying CMake build trees
+const CMAKE_CACHE_FILE_NAME: &str = "CMakeCache.txt";
+
+/// Returns the path of the compilation database of the given CMake build
+/// tree. If the build tree has been configured without exporting compile
+/// commands, it's reconfigured to generate the database.
+pub fn locate_cmake_compile_commands(build_directory: &Path) -> Result<PathBuf> {
+    locate_compile_commands_with(build_directory, "cmake")
+}
+
+fn locate_compile_commands_with(build_directory: &Path, cmake_program: &str) -> Result<PathBuf> {
+    let compile_commands_path = build_directory.join(COMPILE_COMMANDS_FILE_NAME);
+    if compile_commands_path.is_file() {
+        return Ok(compile_commands_path);
+    }
+    if !build_directory.join(CMAKE_CACHE_FILE_NAME).is_file() {
+        return Err(anyhow!(
+            "'{}' is not a CMake build directory (no {} found)",
+            build_directory.display(),
+            CMAKE_CACHE_FILE_NAME
+        ));
+    }
+
+    log::info!(
+        "Reconfiguring '{}' to export compile commands...",
+        build_directory.display()
+    );
+    let status = Command::new(cmake_program)
+        .arg("-DCMAKE_EXPORT_COMPILE_COMMANDS=ON")
+        .arg(build_directory)
+        .status()
+        .with_context(|| format!("Failed to run '{}'", cmake_program))?;
+    if !status.success() {
+        return Err(anyhow!(
+            "Failed to reconfigure '{}' ({})",
+            build_directory.display(),
+            status
+        ));
+    }
+    // Note: Some generators (e.g., Visual Studio) don't support exporting
+    // compile commands
+    if !compile_commands_path.is_file() {
+        return Err(anyhow!(
+            "CMake didn't generate '{}', the build tree's generator might not support it",
+            compile_commands_path.display()
+        ));
+    }
+
+    Ok(compile_commands_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locate_existing_compile_commands() {
+        let build_directory = tempfile::tempdir().unwrap();
+        let compile_commands_path = build_directory.path().join("compile_commands.json");
+        std::fs::write(&compile_commands_path, "[]").unwrap();
+
+        assert_eq!(
+            locate_cmake_compile_commands(build_directory.path()).unwrap(),
+            compile_commands_path
+        );
+    }
+
+    #[test]
+    fn locate_compile_commands_outside_build_tree() {
+        let directory = tempfile::tempdir().unwrap();
+
+        let err = locate_cmake_compile_commands(directory.path()).unwrap_err();
+        assert!(err.to_string().contains("is not a CMake build directory"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn locate_compile_commands_not_generated() {
+        let build_directory = tempfile::tempdir().unwrap();
+        std::fs::write(build_directory.path().join("CMakeCache.txt"), "").unwrap();
+
+        // Reconfiguring "succeeds" without generating the database
+        let err = locate_compile_commands_with(build_directory.path(), "true").unwrap_err();
+        assert!(err.to_string().starts_with("CMake didn't generate"));
+        // Reconfiguring fails
+        assert!(locate_compile_commands_with(build_directory.path(), "false").is_err());
+    }
+}
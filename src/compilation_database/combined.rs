@@ -0,0 +1,106 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use super::{CompilationDatabase, CompileCommand, CompileCommands};
+
+/// Merges the compile commands of multiple databases (e.g., a JSON compilation
+/// database and a list of generated source files that never appear in it).
+/// Files listed by several databases are only parsed once, with the arguments
+/// of the first database that lists them.
+pub struct CombinedDatabase {
+    databases: Vec<Box<dyn CompilationDatabase>>,
+}
+
+impl CombinedDatabase {
+    pub fn new(databases: Vec<Box<dyn CompilationDatabase>>) -> Self {
+        Self { databases }
+    }
+}
+
+impl CompilationDatabase for CombinedDatabase {
+    fn is_file_path_in_arguments(&self) -> bool {
+        // Commands are normalized so that they all contain their file path
+        true
+    }
+
+    fn get_all_compile_commands(&self) -> Result<CompileCommands> {
+        let mut known_files = HashSet::new();
+        let mut compile_commands = vec![];
+        for database in &self.databases {
+            let file_path_in_arguments = database.is_file_path_in_arguments();
+            for compile_cmd in database.get_all_compile_commands()? {
+                if !known_files.insert(compile_cmd.filename.clone()) {
+                    log::debug!(
+                        "'{}' is listed more than once, ignoring duplicate compile command",
+                        compile_cmd.filename.display()
+                    );
+                    continue;
+                }
+
+                if file_path_in_arguments {
+                    compile_commands.push(compile_cmd);
+                } else {
+                    let mut arguments = compile_cmd.arguments.as_ref().clone();
+                    arguments.push(compile_cmd.filename.display().to_string());
+                    compile_commands.push(CompileCommand {
+                        filename: compile_cmd.filename,
+                        arguments: Arc::new(arguments),
+                    });
+                }
+            }
+        }
+
+        Ok(compile_commands)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::super::FileListDatabase;
+    use super::*;
+
+    const FILE_LIST_PROJ_PATH: &str = "tests/data/main/file_list_proj";
+
+    #[test]
+    fn get_all_compile_commands_overlapping_files() {
+        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(FILE_LIST_PROJ_PATH);
+        let database = CombinedDatabase::new(vec![
+            Box::new(FileListDatabase::new(
+                &[root_dir_path.join("main.cc")],
+                vec!["-DFIRST".to_string()],
+            )),
+            Box::new(FileListDatabase::new(
+                &[
+                    root_dir_path.join("main.cc"),
+                    root_dir_path.join("header.h"),
+                ],
+                vec!["-DSECOND".to_string()],
+            )),
+        ]);
+        assert!(database.is_file_path_in_arguments());
+
+        let compile_commands = database
+            .get_all_compile_commands()
+            .expect("get_all_compile_commands failed");
+        assert_eq!(compile_commands.len(), 2);
+
+        // The first database wins
+        let main_path = root_dir_path.join("main.cc").canonicalize().unwrap();
+        assert_eq!(compile_commands[0].filename, main_path);
+        assert_eq!(
+            *compile_commands[0].arguments,
+            ["-DFIRST".to_string(), main_path.display().to_string()]
+        );
+
+        let header_path = root_dir_path.join("header.h").canonicalize().unwrap();
+        assert_eq!(compile_commands[1].filename, header_path);
+        assert_eq!(
+            *compile_commands[1].arguments,
+            ["-DSECOND".to_string(), header_path.display().to_string()]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::{fs, sync::Arc};
 
 use anyhow::{anyhow, Result};
@@ -6,6 +6,29 @@ use tempfile::TempDir;
 
 use super::{CompilationDatabase, CompileCommand, CompileCommands};
 
+/// Options that add include directories, either joined (e.g., `-Ifoo`) or
+/// separate (e.g., `-I foo`)
+const INCLUDE_DIRECTORY_OPTIONS: &[&str] = &["-I", "-isystem", "-iquote", "-idirafter"];
+/// Options that include a file, either joined or separate
+const INCLUDED_FILE_OPTIONS: &[&str] = &["-include", "-imacros"];
+/// Option adding include directories, for MSVC-style compiler drivers only
+/// (other drivers would mistake absolute paths for it)
+const MSVC_INCLUDE_DIRECTORY_OPTION: &str = "/I";
+
+/// Maximum nesting depth of response files, which guards against response
+/// files including themselves
+const MAX_RESPONSE_FILE_DEPTH: usize = 16;
+
+/// Quoting rules of response files, which depend on the compiler driver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResponseFileSyntax {
+    /// Backslashes escape any character (GCC, Clang)
+    Gnu,
+    /// Backslashes are only special before double quotes, so that Windows
+    /// paths are kept as is (MSVC, clang-cl)
+    Windows,
+}
+
 pub struct CompileCommandsDatabase {
     clang_db: clang::CompilationDatabase,
 }
@@ -38,15 +61,245 @@ fn convert_clang_compile_commands(clang_cmds: clang::CompileCommands) -> Result<
         .get_commands()
         .iter()
         .map(|cmd| {
+            let directory = cmd.get_directory();
+            let arguments = expand_response_files(cmd.get_arguments(), &directory);
             Ok(CompileCommand {
                 // Some file paths may not be canonical, so we have to force them to be
-                filename: cmd.get_filename().canonicalize()?,
-                arguments: Arc::new(cmd.get_arguments()),
+                filename: resolve_file_path(&cmd.get_filename(), &directory).canonicalize()?,
+                arguments: Arc::new(resolve_relative_paths(arguments, &directory)),
             })
         })
         .collect()
 }
 
+/// Resolves the path of a source file against the compile command's working
+/// directory. Falls back to the path as is if the resolved file doesn't exist
+/// (e.g., when the database has been generated on another machine).
+fn resolve_file_path(file_path: &Path, directory: &Path) -> PathBuf {
+    let resolved_path = directory.join(file_path);
+    if resolved_path.is_file() {
+        resolved_path
+    } else {
+        file_path.to_path_buf()
+    }
+}
+
+/// Makes relative include paths absolute by resolving them against the
+/// compile command's working directory, as we don't parse files from there
+fn resolve_relative_paths(arguments: Vec<String>, directory: &Path) -> Vec<String> {
+    let resolve_path = |path: &str| -> String {
+        if Path::new(path).is_relative() {
+            directory.join(path).display().to_string()
+        } else {
+            path.to_string()
+        }
+    };
+
+    let path_options = [
+        include_directory_options(&arguments),
+        INCLUDED_FILE_OPTIONS.to_vec(),
+    ]
+    .concat();
+    let mut resolved_arguments = Vec::with_capacity(arguments.len());
+    let mut arguments = arguments.into_iter();
+    while let Some(argument) = arguments.next() {
+        match split_path_option(&argument, &path_options) {
+            Some((_, "")) => {
+                // Separate form, the path is the next argument
+                resolved_arguments.push(argument);
+                if let Some(path) = arguments.next() {
+                    resolved_arguments.push(resolve_path(&path));
+                }
+            }
+            Some((option, path)) => {
+                resolved_arguments.push(format!("{}{}", option, resolve_path(path)))
+            }
+            None => resolved_arguments.push(argument),
+        }
+    }
+
+    resolved_arguments
+}
+
+/// Returns the include directories passed to the compiler invoked with
+/// `arguments`, in order, whether they're joined to their option (e.g.,
+/// `-Ifoo`) or separate (e.g., `-isystem foo`)
+pub fn include_directories(arguments: &[String]) -> Vec<&str> {
+    let options = include_directory_options(arguments);
+    let mut include_directories = vec![];
+    let mut arguments = arguments.iter();
+    while let Some(argument) = arguments.next() {
+        match split_path_option(argument, &options) {
+            Some((_, "")) => include_directories.extend(arguments.next().map(String::as_str)),
+            Some((_, path)) => include_directories.push(path),
+            None => {}
+        }
+    }
+
+    include_directories
+}
+
+/// Returns the options adding include directories understood by the
+/// compiler invoked with `arguments`
+fn include_directory_options(arguments: &[String]) -> Vec<&'static str> {
+    let mut options = INCLUDE_DIRECTORY_OPTIONS.to_vec();
+    if is_msvc_driver(arguments) {
+        options.push(MSVC_INCLUDE_DIRECTORY_OPTION);
+    }
+
+    options
+}
+
+/// Splits an argument starting with one of the given options into the option
+/// and the path joined to it, which is empty if the path is the next argument
+fn split_path_option<'a>(
+    argument: &'a str,
+    options: &[&'static str],
+) -> Option<(&'static str, &'a str)> {
+    options
+        .iter()
+        .find(|option| argument.starts_with(**option))
+        .map(|option| (*option, &argument[option.len()..]))
+}
+
+/// Replaces response file arguments (e.g., `@args.rsp`) with the arguments
+/// read from these files, as the parser doesn't expand them. Response files
+/// that cannot be read are left as is.
+fn expand_response_files(arguments: Vec<String>, directory: &Path) -> Vec<String> {
+    let syntax = response_file_syntax(&arguments);
+    let mut expanded_arguments = Vec::with_capacity(arguments.len());
+    expand_response_files_into(arguments, directory, syntax, 0, &mut expanded_arguments);
+
+    expanded_arguments
+}
+
+fn expand_response_files_into(
+    arguments: Vec<String>,
+    directory: &Path,
+    syntax: ResponseFileSyntax,
+    depth: usize,
+    expanded_arguments: &mut Vec<String>,
+) {
+    for argument in arguments {
+        let response_file_path = match argument.strip_prefix('@') {
+            Some(path) if !path.is_empty() => directory.join(path),
+            _ => {
+                expanded_arguments.push(argument);
+                continue;
+            }
+        };
+        if depth >= MAX_RESPONSE_FILE_DEPTH {
+            log::warn!(
+                "Response files nested too deeply, not expanding '{}'",
+                response_file_path.display()
+            );
+            expanded_arguments.push(argument);
+            continue;
+        }
+
+        match fs::read(&response_file_path) {
+            Ok(content) => {
+                // Note: Nested response files are relative to the response
+                // file including them
+                let response_file_directory = response_file_path
+                    .parent()
+                    .map(Path::to_path_buf)
+                    .unwrap_or_default();
+                expand_response_files_into(
+                    tokenize_response_file(&String::from_utf8_lossy(&content), syntax),
+                    &response_file_directory,
+                    syntax,
+                    depth + 1,
+                    expanded_arguments,
+                );
+            }
+            Err(err) => {
+                log::warn!(
+                    "Failed to read response file '{}', its arguments are ignored: {}",
+                    response_file_path.display(),
+                    err
+                );
+                expanded_arguments.push(argument);
+            }
+        }
+    }
+}
+
+/// Returns the quoting rules of the response files given to the compiler
+/// invoked with `arguments`
+fn response_file_syntax(arguments: &[String]) -> ResponseFileSyntax {
+    if is_msvc_driver(arguments) {
+        ResponseFileSyntax::Windows
+    } else {
+        ResponseFileSyntax::Gnu
+    }
+}
+
+/// Indicates if the compiler invoked with `arguments` is an MSVC-style
+/// compiler driver (i.e., `cl` or `clang-cl`)
+fn is_msvc_driver(arguments: &[String]) -> bool {
+    let compiler_name = arguments
+        .first()
+        .and_then(|compiler| Path::new(compiler).file_stem())
+        .map(|stem| stem.to_string_lossy().to_lowercase());
+
+    matches!(compiler_name.as_deref(), Some("cl" | "clang-cl"))
+        || arguments
+            .iter()
+            .any(|argument| argument == "--driver-mode=cl")
+}
+
+/// Splits the content of a response file into arguments, separated by
+/// whitespace and optionally quoted
+fn tokenize_response_file(content: &str, syntax: ResponseFileSyntax) -> Vec<String> {
+    // Strip the BOM written by some Windows tools
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+
+    let mut arguments = vec![];
+    let mut argument = String::new();
+    let mut in_argument = false;
+    let mut quote = None;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quote, syntax) {
+            ('\\', Some('\''), ResponseFileSyntax::Gnu) => argument.push(c),
+            ('\\', _, ResponseFileSyntax::Gnu) => {
+                if let Some(escaped) = chars.next() {
+                    // Escaped line breaks continue the argument
+                    if escaped != '\n' {
+                        argument.push(escaped);
+                    }
+                }
+                in_argument = true;
+            }
+            ('\\', _, ResponseFileSyntax::Windows) if chars.peek() == Some(&'"') => {
+                argument.push(chars.next().unwrap_or('"'));
+                in_argument = true;
+            }
+            ('"', None, _) | ('\'', None, ResponseFileSyntax::Gnu) => {
+                quote = Some(c);
+                in_argument = true;
+            }
+            (c, Some(quote_char), _) if c == quote_char => quote = None,
+            (c, None, _) if c.is_whitespace() => {
+                if in_argument {
+                    arguments.push(std::mem::take(&mut argument));
+                    in_argument = false;
+                }
+            }
+            (c, _, _) => {
+                argument.push(c);
+                in_argument = true;
+            }
+        }
+    }
+    if in_argument {
+        arguments.push(argument);
+    }
+
+    arguments
+}
+
 /// Move the database file with the name clang expects, into a temporary directory
 fn move_database_file_into_tmp_dir<P: AsRef<Path>>(db_file_path: P) -> Result<TempDir> {
     let tmp_directory = tempfile::tempdir()?;
@@ -91,6 +344,139 @@ mod tests {
         assert!(CompileCommandsDatabase::new(empty_db_path).is_err());
     }
 
+    #[test]
+    fn resolve_relative_paths_in_arguments() {
+        let arguments = [
+            "clang++",
+            "-Irelative",
+            "-I",
+            "relative",
+            "-I/absolute",
+            "-isystem",
+            "sys",
+            "-include",
+            "config.h",
+            "-DDIR=relative",
+            "file.cc",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+        assert_eq!(
+            resolve_relative_paths(arguments, Path::new("/build")),
+            vec![
+                "clang++",
+                "-I/build/relative",
+                "-I",
+                "/build/relative",
+                "-I/absolute",
+                "-isystem",
+                "/build/sys",
+                "-include",
+                "/build/config.h",
+                "-DDIR=relative",
+                "file.cc",
+            ]
+        );
+
+        let arguments = ["cl.exe", "/Iinclude", "/I", "sdk", "main.cc"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            resolve_relative_paths(arguments, Path::new("/build")),
+            vec!["cl.exe", "/I/build/include", "/I", "/build/sdk", "main.cc"]
+        );
+    }
+
+    #[test]
+    fn parse_include_directories() {
+        let arguments: Vec<String> = [
+            "clang++",
+            "-Ijoined",
+            "-I",
+            "separate",
+            "-isystem",
+            "sys",
+            "-iquotequote",
+            "-include",
+            "config.h",
+            "/Iunix_path.cc",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+        assert_eq!(
+            include_directories(&arguments),
+            vec!["joined", "separate", "sys", "quote"]
+        );
+
+        let arguments: Vec<String> = ["cl.exe", "/Imsvc", "/I", "separate", "-Idash", "main.cc"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            include_directories(&arguments),
+            vec!["msvc", "separate", "dash"]
+        );
+    }
+
+    #[test]
+    fn tokenize_response_files() {
+        assert_eq!(
+            tokenize_response_file(
+                "-Iinclude  -DNAME=\"a b\"\n'-DQUOTE=\"'\t-DESC=a\\ b\n\"\"",
+                ResponseFileSyntax::Gnu
+            ),
+            vec!["-Iinclude", "-DNAME=a b", "-DQUOTE=\"", "-DESC=a b", ""]
+        );
+        assert_eq!(
+            tokenize_response_file(
+                "\u{feff}/IC:\\include \"/DPATH=C:\\Program Files\" /DQUOTE=\\\"",
+                ResponseFileSyntax::Windows
+            ),
+            vec!["/IC:\\include", "/DPATH=C:\\Program Files", "/DQUOTE=\""]
+        );
+    }
+
+    #[test]
+    fn expand_response_files_in_arguments() {
+        let directory = tempfile::tempdir().unwrap();
+        fs::create_dir(directory.path().join("rsp")).unwrap();
+        fs::write(
+            directory.path().join("rsp/args.rsp"),
+            "-Iinclude \"-DNAME=a b\" @nested.rsp",
+        )
+        .unwrap();
+        fs::write(directory.path().join("rsp/nested.rsp"), "-DNESTED").unwrap();
+        // Response files including themselves
+        fs::write(directory.path().join("loop.rsp"), "@loop.rsp").unwrap();
+
+        let arguments = ["clang++", "@rsp/args.rsp", "-c", "@missing.rsp", "file.cc"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            expand_response_files(arguments, directory.path()),
+            vec![
+                "clang++",
+                "-Iinclude",
+                "-DNAME=a b",
+                "-DNESTED",
+                "-c",
+                "@missing.rsp",
+                "file.cc"
+            ]
+        );
+
+        let arguments = vec!["clang++".to_string(), "@loop.rsp".to_string()];
+        assert_eq!(
+            expand_response_files(arguments, directory.path()),
+            vec!["clang++", "@loop.rsp"]
+        );
+    }
+
     #[test]
     fn get_all_compile_commands() {
         let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(COMPILE_COMMANDS_PATH);
@@ -115,7 +501,7 @@ mod tests {
             vec![
                 "/usr/bin/clang++".to_string(),
                 "--driver-mode=g++".to_string(),
-                "-Irelative".to_string(),
+                "-I/home/user/cpplumber/relative".to_string(),
                 "-DSOMEDEF=With spaces, quotes.".to_string(),
                 "-c".to_string(),
                 "-o".to_string(),
@@ -136,7 +522,7 @@ mod tests {
             vec![
                 "/usr/bin/clang++".to_string(),
                 "--driver-mode=g++".to_string(),
-                "-Irelative".to_string(),
+                "-I/home/user/cpplumber/relative".to_string(),
                 "-DSOMEDEF=With spaces, quotes.".to_string(),
                 "-c".to_string(),
                 "-o".to_string(),
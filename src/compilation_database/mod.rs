@@ -1,17 +1,26 @@
+mod cmake;
+mod combined;
 mod compile_commands;
 mod file_list;
+mod targets;
+mod vcxproj;
 
-use glob::glob;
+use glob::{glob, Pattern};
 use std::{
     path::{Path, PathBuf},
+    str::FromStr,
     sync::Arc,
 };
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use rayon::prelude::*;
 
-pub use compile_commands::CompileCommandsDatabase;
+pub use cmake::locate_cmake_compile_commands;
+pub use combined::CombinedDatabase;
+pub use compile_commands::{include_directories, CompileCommandsDatabase};
 pub use file_list::FileListDatabase;
+pub use targets::{implied_target_names, is_target_binary, newer_source_file};
+pub use vcxproj::{is_visual_studio_project, VcxprojDatabase};
 
 pub enum ProjectConfiguration<'p> {
     CompilationDatabase {
@@ -22,6 +31,14 @@ pub enum ProjectConfiguration<'p> {
         include_directories: &'p [String],
         compile_definitions: &'p [String],
     },
+    /// Compilation database, along with source files that aren't part of it
+    /// (e.g., generated sources), compiled with the given arguments
+    Combined {
+        project_file_path: &'p Path,
+        source_path_globs: &'p [String],
+        include_directories: &'p [String],
+        compile_definitions: &'p [String],
+    },
 }
 
 #[derive(Debug)]
@@ -32,6 +49,174 @@ pub struct CompileCommand {
 
 pub type CompileCommands = Vec<CompileCommand>;
 
+/// Identifies a subset of compile commands, used to split the extraction
+/// phase across multiple jobs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    /// 1-based index of the shard
+    pub index: usize,
+    /// Total number of shards
+    pub count: usize,
+}
+
+impl FromStr for Shard {
+    type Err = anyhow::Error;
+
+    /// Parses shards written as "i/n" (e.g., "1/4")
+    fn from_str(s: &str) -> Result<Self> {
+        let (index, count) = s
+            .split_once('/')
+            .ok_or_else(|| anyhow!("Shards must be written as 'i/n'"))?;
+        let shard = Self {
+            index: index.trim().parse()?,
+            count: count.trim().parse()?,
+        };
+        if shard.index == 0 || shard.index > shard.count {
+            return Err(anyhow!("Shard index must be between 1 and {}", shard.count));
+        }
+
+        Ok(shard)
+    }
+}
+
+/// Deterministically selects the compile commands that belong to the given
+/// shard. Commands are sorted by file path beforehand so that every job
+/// agrees on the partitioning.
+pub fn select_shard(mut compile_commands: CompileCommands, shard: Shard) -> CompileCommands {
+    compile_commands.sort_by(|a, b| a.filename.cmp(&b.filename));
+    compile_commands
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, compile_cmd)| {
+            if i % shard.count == shard.index - 1 {
+                Some(compile_cmd)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Only keeps the compile commands of the files matching one of the
+/// `only_files` glob expressions (if any), and none of the `exclude_files`
+/// ones. Expressions match files' paths, either as written in the compilation
+/// database or relative to `base_directory`.
+pub fn filter_compile_commands_by_path(
+    compile_commands: CompileCommands,
+    only_files: &[String],
+    exclude_files: &[String],
+    base_directory: &Path,
+) -> Result<CompileCommands> {
+    let parse_patterns = |globs: &[String]| {
+        globs
+            .iter()
+            .map(|glob| {
+                Pattern::new(glob).map_err(|_| anyhow!("'{}' is not a valid glob expression", glob))
+            })
+            .collect::<Result<Vec<_>>>()
+    };
+    let only_patterns = parse_patterns(only_files)?;
+    let exclude_patterns = parse_patterns(exclude_files)?;
+
+    let matches_any = |patterns: &[Pattern], file_path: &Path| {
+        let relative_file_path = file_path.strip_prefix(base_directory).ok();
+        patterns.iter().any(|pattern| {
+            pattern.matches_path(file_path)
+                || matches!(relative_file_path, Some(relative) if pattern.matches_path(relative))
+        })
+    };
+
+    Ok(compile_commands
+        .into_iter()
+        .filter(|compile_cmd| {
+            (only_patterns.is_empty() || matches_any(&only_patterns, &compile_cmd.filename))
+                && !matches_any(&exclude_patterns, &compile_cmd.filename)
+        })
+        .collect())
+}
+
+/// Modifies the arguments of every compile command: arguments matching one of
+/// the `remove_args` glob expressions (e.g., "/MP", "-W*") are removed, then
+/// `extra_args` are appended (e.g., to pass flags libclang needs, or to strip
+/// flags it doesn't understand)
+pub fn adjust_compile_arguments(
+    compile_commands: CompileCommands,
+    extra_args: &[String],
+    remove_args: &[String],
+) -> Result<CompileCommands> {
+    let remove_patterns = remove_args
+        .iter()
+        .map(|glob| {
+            Pattern::new(glob).map_err(|_| anyhow!("'{}' is not a valid glob expression", glob))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(compile_commands
+        .into_iter()
+        .map(|compile_cmd| {
+            let arguments = compile_cmd
+                .arguments
+                .iter()
+                .filter(|argument| {
+                    !remove_patterns
+                        .iter()
+                        .any(|pattern| pattern.matches(argument))
+                })
+                .chain(extra_args)
+                .cloned()
+                .collect();
+
+            CompileCommand {
+                filename: compile_cmd.filename,
+                arguments: Arc::new(arguments),
+            }
+        })
+        .collect())
+}
+
+/// Only keeps the compile commands of the given translation units. Files they
+/// include are parsed along with them, so the scan covers everything reachable
+/// from these entry points.
+pub fn select_entry_points(
+    compile_commands: CompileCommands,
+    entry_points: &[PathBuf],
+) -> Result<CompileCommands> {
+    let entry_points = entry_points
+        .iter()
+        .map(|entry_point| {
+            entry_point
+                .canonicalize()
+                .map_err(|_| anyhow!("Entry point '{}' doesn't exist", entry_point.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let selected_commands: CompileCommands = compile_commands
+        .into_iter()
+        .filter(|compile_cmd| {
+            let file_path = compile_cmd
+                .filename
+                .canonicalize()
+                .unwrap_or_else(|_| compile_cmd.filename.clone());
+            entry_points.contains(&file_path)
+        })
+        .collect();
+
+    // Make sure every entry point is part of the project
+    for entry_point in &entry_points {
+        let is_selected = selected_commands.iter().any(|compile_cmd| {
+            compile_cmd.filename.canonicalize().ok().as_ref() == Some(entry_point)
+        });
+        if !is_selected {
+            return Err(anyhow!(
+                "Entry point '{}' isn't part of the project",
+                entry_point.display()
+            ));
+        }
+    }
+
+    Ok(selected_commands)
+}
+
 pub trait CompilationDatabase {
     /// Indicates if the file path can be found in the argument list.
     fn is_file_path_in_arguments(&self) -> bool;
@@ -44,54 +229,248 @@ pub fn generate_compilation_database(
 ) -> Result<Box<dyn CompilationDatabase>> {
     match project_config {
         ProjectConfiguration::CompilationDatabase { project_file_path } => {
-            // Parse compile commands from the JSON database
-            Ok(Box::new(CompileCommandsDatabase::new(project_file_path)?))
+            open_project_file(project_file_path)
         }
 
         ProjectConfiguration::Manual {
             source_path_globs,
             include_directories,
             compile_definitions,
-        } => {
-            // Otherwise, process glob expressions
-            let file_paths = source_path_globs
-                .par_iter()
-                .try_fold(
-                    Vec::new,
-                    |mut accum, glob_expression| -> Result<Vec<PathBuf>> {
-                        if let Ok(paths) = glob(glob_expression) {
-                            for path in paths {
-                                accum.push(path?);
-                            }
-                        } else {
-                            log::warn!(
-                                "'{}' is not a valid path or glob expression, ignoring it",
-                                glob_expression
-                            );
-                        }
-
-                        Ok(accum)
-                    },
-                )
-                .try_reduce(Vec::new, |mut accum, mut other| {
-                    accum.append(&mut other);
-                    Ok(accum)
-                })?;
-
-            // Generate `arguments` from the CLI arguments
-            let mut arguments = vec![];
-
-            // Add include directories to the list of arguments
-            for include_dir in include_directories.iter() {
-                arguments.push(format!("-I{}", include_dir));
-            }
-            // Add preprocessor defitions to the list of arguments
-            for compile_def in compile_definitions.iter() {
-                arguments.push(format!("-D{}", compile_def));
-            }
+        } => Ok(Box::new(generate_file_list_database(
+            source_path_globs,
+            include_directories,
+            compile_definitions,
+        )?)),
 
-            log::debug!("Using arguments: {:?}", arguments);
-            Ok(Box::new(FileListDatabase::new(&file_paths, arguments)))
-        }
+        ProjectConfiguration::Combined {
+            project_file_path,
+            source_path_globs,
+            include_directories,
+            compile_definitions,
+        } => Ok(Box::new(CombinedDatabase::new(vec![
+            // Files listed in the project keep their own arguments
+            open_project_file(project_file_path)?,
+            Box::new(generate_file_list_database(
+                source_path_globs,
+                include_directories,
+                compile_definitions,
+            )?),
+        ]))),
+    }
+}
+
+/// Parses compile commands from the JSON database, or from the Visual Studio
+/// project or solution
+fn open_project_file(project_file_path: &Path) -> Result<Box<dyn CompilationDatabase>> {
+    if is_visual_studio_project(project_file_path) {
+        Ok(Box::new(VcxprojDatabase::new(project_file_path)?))
+    } else {
+        Ok(Box::new(CompileCommandsDatabase::new(project_file_path)?))
+    }
+}
+
+/// Processes glob expressions and generates compile arguments from the given
+/// include directories and preprocessor definitions
+fn generate_file_list_database(
+    source_path_globs: &[String],
+    include_directories: &[String],
+    compile_definitions: &[String],
+) -> Result<FileListDatabase> {
+    let file_paths = source_path_globs
+        .par_iter()
+        .try_fold(
+            Vec::new,
+            |mut accum, glob_expression| -> Result<Vec<PathBuf>> {
+                if let Ok(paths) = glob(glob_expression) {
+                    for path in paths {
+                        accum.push(path?);
+                    }
+                } else {
+                    log::warn!(
+                        "'{}' is not a valid path or glob expression, ignoring it",
+                        glob_expression
+                    );
+                }
+
+                Ok(accum)
+            },
+        )
+        .try_reduce(Vec::new, |mut accum, mut other| {
+            accum.append(&mut other);
+            Ok(accum)
+        })?;
+
+    // Generate `arguments` from the CLI arguments
+    let mut arguments = vec![];
+
+    // Add include directories to the list of arguments
+    for include_dir in include_directories.iter() {
+        arguments.push(format!("-I{}", include_dir));
+    }
+    // Add preprocessor defitions to the list of arguments
+    for compile_def in compile_definitions.iter() {
+        arguments.push(format!("-D{}", compile_def));
+    }
+
+    log::debug!("Using arguments: {:?}", arguments);
+    Ok(FileListDatabase::new(&file_paths, arguments))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile_commands(file_names: &[&str]) -> CompileCommands {
+        file_names
+            .iter()
+            .map(|file_name| CompileCommand {
+                filename: PathBuf::from(file_name),
+                arguments: Arc::new(vec![]),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse_shard() {
+        assert_eq!(
+            "2/4".parse::<Shard>().expect("Failed to parse shard"),
+            Shard { index: 2, count: 4 }
+        );
+        assert!("0/4".parse::<Shard>().is_err());
+        assert!("5/4".parse::<Shard>().is_err());
+        assert!("2".parse::<Shard>().is_err());
+    }
+
+    #[test]
+    fn select_entry_points_in_project() {
+        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/main");
+        let file_list_proj = root_dir_path.join("file_list_proj");
+        let compile_commands = vec![
+            CompileCommand {
+                filename: file_list_proj.join("main.cc"),
+                arguments: Arc::new(vec![]),
+            },
+            CompileCommand {
+                filename: file_list_proj.join("header.h"),
+                arguments: Arc::new(vec![]),
+            },
+        ];
+
+        let selected_commands = select_entry_points(
+            compile_commands,
+            &[root_dir_path.join("file_list_proj/../file_list_proj/main.cc")],
+        )
+        .expect("select_entry_points failed");
+        assert_eq!(selected_commands.len(), 1);
+        assert_eq!(
+            selected_commands[0].filename,
+            file_list_proj.join("main.cc")
+        );
+
+        // Unknown entry points
+        assert!(select_entry_points(vec![], &[file_list_proj.join("main.cc")]).is_err());
+        assert!(select_entry_points(vec![], &[file_list_proj.join("missing.cc")]).is_err());
+    }
+
+    #[test]
+    fn filter_compile_commands_by_path_globs() {
+        let base_directory = PathBuf::from("/project");
+        let compile_commands = vec![
+            CompileCommand {
+                filename: base_directory.join("src/net/socket.cc"),
+                arguments: Arc::new(vec![]),
+            },
+            CompileCommand {
+                filename: base_directory.join("src/net/socket_test.cc"),
+                arguments: Arc::new(vec![]),
+            },
+            CompileCommand {
+                filename: base_directory.join("src/ui/window.cc"),
+                arguments: Arc::new(vec![]),
+            },
+        ];
+
+        let filtered_commands = filter_compile_commands_by_path(
+            compile_commands,
+            &["src/net/*".to_string()],
+            &["*_test.cc".to_string()],
+            &base_directory,
+        )
+        .expect("filter_compile_commands_by_path failed");
+        let file_names: Vec<&Path> = filtered_commands
+            .iter()
+            .map(|compile_cmd| compile_cmd.filename.as_path())
+            .collect();
+        assert_eq!(file_names, vec![base_directory.join("src/net/socket.cc")]);
+
+        assert!(
+            filter_compile_commands_by_path(vec![], &["[".to_string()], &[], &base_directory)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn adjust_compile_arguments_of_commands() {
+        let compile_commands = vec![CompileCommand {
+            filename: PathBuf::from("main.cc"),
+            arguments: Arc::new(
+                [
+                    "cl.exe",
+                    "/MP",
+                    "/W4",
+                    "-Wall",
+                    "-Wextra",
+                    "/Iinclude",
+                    "main.cc",
+                ]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            ),
+        }];
+
+        let adjusted_commands = adjust_compile_arguments(
+            compile_commands,
+            &["-fparse-all-comments".to_string()],
+            &["/MP".to_string(), "-W*".to_string()],
+        )
+        .expect("adjust_compile_arguments failed");
+        assert_eq!(
+            *adjusted_commands[0].arguments,
+            vec![
+                "cl.exe",
+                "/W4",
+                "/Iinclude",
+                "main.cc",
+                "-fparse-all-comments"
+            ]
+        );
+
+        assert!(adjust_compile_arguments(vec![], &[], &["[".to_string()]).is_err());
+    }
+
+    #[test]
+    fn select_shard_partitions_commands() {
+        let file_names = ["d.cc", "a.cc", "c.cc", "b.cc", "e.cc"];
+        let shards: Vec<Vec<PathBuf>> = (1..=2)
+            .map(|index| {
+                select_shard(compile_commands(&file_names), Shard { index, count: 2 })
+                    .into_iter()
+                    .map(|compile_cmd| compile_cmd.filename)
+                    .collect()
+            })
+            .collect();
+
+        assert_eq!(
+            shards,
+            vec![
+                vec![
+                    PathBuf::from("a.cc"),
+                    PathBuf::from("c.cc"),
+                    PathBuf::from("e.cc")
+                ],
+                vec![PathBuf::from("b.cc"), PathBuf::from("d.cc")],
+            ]
+        );
     }
 }
@@ -0,0 +1,170 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use super::CompileCommands;
+
+/// Extensions of object files, which aren't targets by themselves
+const OBJECT_FILE_EXTENSIONS: &[&str] = &["o", "obj"];
+
+/// Returns the names of the targets (i.e., executables and libraries) the
+/// compile commands build, as implied by their outputs: object files stored in
+/// a `<target>.dir` directory (as CMake does) or outputs that aren't object
+/// files (e.g., `cc main.c -o app`)
+pub fn implied_target_names(compile_commands: &CompileCommands) -> BTreeSet<String> {
+    compile_commands
+        .iter()
+        .flat_map(|compile_cmd| output_paths(&compile_cmd.arguments))
+        .filter_map(target_name)
+        .collect()
+}
+
+/// Indicates if the given binary corresponds to one of the targets
+pub fn is_target_binary(binary_file_path: &Path, target_names: &BTreeSet<String>) -> bool {
+    let binary_name = match binary_file_path.file_name() {
+        Some(file_name) => normalize_target_name(&file_name.to_string_lossy()),
+        None => return false,
+    };
+
+    target_names
+        .iter()
+        .any(|target_name| normalize_target_name(target_name) == binary_name)
+}
+
+/// Returns one of the source files modified after the binary was, if any,
+/// which indicates that the binary may be stale
+pub fn newer_source_file<'c>(
+    binary_file_path: &Path,
+    compile_commands: &'c CompileCommands,
+) -> Option<&'c Path> {
+    let binary_modified = fs::metadata(binary_file_path).ok()?.modified().ok()?;
+
+    compile_commands
+        .iter()
+        .map(|compile_cmd| compile_cmd.filename.as_path())
+        .find(|file_path| {
+            matches!(
+                fs::metadata(file_path).and_then(|metadata| metadata.modified()),
+                Ok(modified) if modified > binary_modified
+            )
+        })
+}
+
+/// Returns the outputs given in compile arguments (e.g., `-o main.o` or MSVC's
+/// `/Fomain.obj`)
+fn output_paths(arguments: &[String]) -> Vec<&str> {
+    let mut outputs = vec![];
+    let mut arguments = arguments.iter();
+    while let Some(argument) = arguments.next() {
+        if argument == "-o" {
+            // Separate form, the path is the next argument
+            outputs.extend(arguments.next().map(String::as_str));
+        } else if let Some(output) = ["-o", "/Fo", "-Fo", "/Fe", "-Fe"]
+            .iter()
+            .find_map(|option| argument.strip_prefix(option))
+        {
+            // Joined form
+            outputs.push(output);
+        }
+    }
+
+    outputs
+}
+
+/// Returns the name of the target an output belongs to, if it can be
+/// inferred
+fn target_name(output_path: &str) -> Option<String> {
+    let components: Vec<&str> = output_path
+        .split(['/', '\\'])
+        .filter(|component| !component.is_empty())
+        .collect();
+    if let Some(target_directory) = components
+        .iter()
+        .rev()
+        .find_map(|component| component.strip_suffix(".dir"))
+        .filter(|target_name| !target_name.is_empty())
+    {
+        return Some(target_directory.to_string());
+    }
+
+    let file_name = components.last()?;
+    let extension = file_name
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_ascii_lowercase());
+    if matches!(extension, Some(ref extension) if OBJECT_FILE_EXTENSIONS.contains(&extension.as_str()))
+    {
+        None
+    } else {
+        Some(file_name.to_string())
+    }
+}
+
+/// Normalizes target and binary names so that they can be compared (e.g.,
+/// target `foo` produces `libfoo.so.1` or `foo.dll`)
+fn normalize_target_name(name: &str) -> String {
+    let name = name.split('.').next().unwrap_or(name).to_lowercase();
+    match name.strip_prefix("lib") {
+        Some(stripped_name) if !stripped_name.is_empty() => stripped_name.to_string(),
+        _ => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use super::super::CompileCommand;
+    use super::*;
+
+    fn compile_command(arguments: &[&str]) -> CompileCommand {
+        CompileCommand {
+            filename: PathBuf::from("main.c"),
+            arguments: Arc::new(
+                arguments
+                    .iter()
+                    .map(|argument| argument.to_string())
+                    .collect(),
+            ),
+        }
+    }
+
+    #[test]
+    fn infer_target_names_from_outputs() {
+        let compile_commands = vec![
+            compile_command(&["cc", "-o", "CMakeFiles/engine.dir/src/main.c.o", "-c"]),
+            compile_command(&["cl.exe", "/Fotool.dir\\Release\\main.obj", "/c"]),
+            compile_command(&["cc", "main.c", "-olauncher"]),
+            // Object files built outside of target directories say nothing
+            compile_command(&["cc", "-c", "main.c", "-o", "main.o"]),
+            compile_command(&["cc", "-c", "main.c"]),
+        ];
+
+        let target_names = implied_target_names(&compile_commands);
+        assert_eq!(
+            target_names.iter().map(String::as_str).collect::<Vec<_>>(),
+            ["engine", "launcher", "tool"]
+        );
+    }
+
+    #[test]
+    fn match_binaries_against_targets() {
+        let target_names: BTreeSet<String> = ["engine".to_string(), "Tool".to_string()]
+            .into_iter()
+            .collect();
+
+        assert!(is_target_binary(
+            Path::new("build/libengine.so.1"),
+            &target_names
+        ));
+        assert!(is_target_binary(Path::new("build/engine"), &target_names));
+        assert!(is_target_binary(
+            Path::new("C:/build/tool.exe"),
+            &target_names
+        ));
+        assert!(!is_target_binary(
+            Path::new("build/other.exe"),
+            &target_names
+        ));
+    }
+}
@@ -0,0 +1,586 @@
+//! Visual Studio projects (`.vcxproj`) and solutions (`.sln`), so that
+//! Windows projects can be scanned without generating a compilation database
+//! first.
+//! Note: Only the subset of MSBuild needed to find the compiled files and
+//! their include directories and preprocessor definitions is supported.
+//! Settings are taken from the first "Release" configuration of each project
+//! (or from its first configuration if there's none), conditions can only depend
+//! on the configuration and the platform.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use roxmltree::{Document, Node};
+
+use super::{CompilationDatabase, CompileCommand, CompileCommands};
+
+/// Arguments needed to parse code written for MSVC
+const MSVC_COMPATIBILITY_ARGUMENTS: [&str; 2] = ["-fms-extensions", "-fms-compatibility"];
+
+pub struct VcxprojDatabase {
+    compile_commands: Vec<(PathBuf, Arc<Vec<String>>)>,
+}
+
+impl VcxprojDatabase {
+    /// Loads the given project, or all the C/C++ projects of the given
+    /// solution
+    pub fn new<P: AsRef<Path>>(project_file_path: P) -> Result<Self> {
+        let project_file_path = project_file_path.as_ref();
+        let mut compile_commands = vec![];
+        if is_solution_file(project_file_path) {
+            let solution_directory = parent_directory(project_file_path);
+            let solution = read_project_file(project_file_path)?;
+            for vcxproj_path in solution_projects(&solution) {
+                compile_commands.extend(parse_vcxproj(
+                    &solution_directory.join(vcxproj_path),
+                    Some(&solution_directory),
+                )?);
+            }
+        } else {
+            compile_commands = parse_vcxproj(project_file_path, None)?;
+        }
+
+        Ok(Self { compile_commands })
+    }
+}
+
+impl CompilationDatabase for VcxprojDatabase {
+    fn is_file_path_in_arguments(&self) -> bool {
+        false
+    }
+
+    fn get_all_compile_commands(&self) -> Result<CompileCommands> {
+        self.compile_commands
+            .iter()
+            .map(|(file_path, arguments)| {
+                Ok(CompileCommand {
+                    filename: file_path.canonicalize().with_context(|| {
+                        format!("Source file '{}' doesn't exist", file_path.display())
+                    })?,
+                    arguments: arguments.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Returns `true` if the given project file is a Visual Studio project or
+/// solution
+pub fn is_visual_studio_project(project_file_path: &Path) -> bool {
+    has_extension(project_file_path, "sln") || has_extension(project_file_path, "vcxproj")
+}
+
+fn is_solution_file(project_file_path: &Path) -> bool {
+    has_extension(project_file_path, "sln")
+}
+
+fn has_extension(file_path: &Path, expected_extension: &str) -> bool {
+    matches!(
+        file_path.extension(),
+        Some(extension) if extension.eq_ignore_ascii_case(expected_extension)
+    )
+}
+
+fn read_project_file(project_file_path: &Path) -> Result<String> {
+    std::fs::read_to_string(project_file_path)
+        .with_context(|| format!("Failed to read '{}'", project_file_path.display()))
+}
+
+fn parent_directory(file_path: &Path) -> PathBuf {
+    match file_path.parent() {
+        Some(directory) if !directory.as_os_str().is_empty() => directory.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// Returns the relative paths of the C/C++ projects referenced by a solution
+fn solution_projects(solution: &str) -> Vec<PathBuf> {
+    let project_regex =
+        Regex::new(r#"(?m)^Project\("[^"]*"\)\s*=\s*"[^"]*"\s*,\s*"([^"]+\.vcxproj)""#)
+            .expect("Invalid regex");
+    project_regex
+        .captures_iter(solution)
+        .map(|captures| windows_path(&captures[1]))
+        .collect()
+}
+
+/// Returns the source files compiled by the given project, along with their
+/// arguments
+fn parse_vcxproj(
+    vcxproj_path: &Path,
+    solution_directory: Option<&Path>,
+) -> Result<Vec<(PathBuf, Arc<Vec<String>>)>> {
+    let project_directory = parent_directory(vcxproj_path);
+    let project_text = read_project_file(vcxproj_path)?;
+    let project = Document::parse(&project_text)
+        .with_context(|| format!("Failed to parse '{}'", vcxproj_path.display()))?;
+    let macros = ProjectMacros {
+        project_directory: &project_directory,
+        solution_directory: solution_directory.unwrap_or(&project_directory),
+    };
+
+    let configuration = ProjectConfiguration::select(&project);
+    log::debug!(
+        "Using configuration '{}|{}' of '{}'",
+        configuration.configuration,
+        configuration.platform,
+        vcxproj_path.display()
+    );
+    let mut default_settings = CompilerSettings::default();
+    for compiler_settings in project_elements(&project, "ItemDefinitionGroup", "ClCompile")
+        .filter(|element| configuration.applies_to(element))
+    {
+        default_settings.apply(compiler_settings, &configuration);
+    }
+    let default_arguments = Arc::new(default_settings.arguments(&project_directory, &macros));
+    log::debug!(
+        "Using arguments for '{}': {:?}",
+        vcxproj_path.display(),
+        default_arguments
+    );
+
+    let mut compile_commands = vec![];
+    for source_item in project_elements(&project, "ItemGroup", "ClCompile")
+        .filter(|element| configuration.applies_to(element))
+    {
+        // Items may override the project's settings
+        let arguments = if source_item.children().any(|child| child.is_element()) {
+            let mut settings = default_settings.clone();
+            settings.apply(source_item, &configuration);
+            if settings.is_excluded_from_build() {
+                continue;
+            }
+            Arc::new(settings.arguments(&project_directory, &macros))
+        } else {
+            default_arguments.clone()
+        };
+
+        let file_paths = source_item.attribute("Include").unwrap_or_default();
+        compile_commands.extend(
+            file_paths
+                .split(';')
+                .map(str::trim)
+                .filter(|file_path| !file_path.is_empty())
+                .filter_map(|file_path| macros.expand(file_path))
+                .map(|file_path| {
+                    (
+                        project_directory.join(windows_path(&file_path)),
+                        arguments.clone(),
+                    )
+                }),
+        );
+    }
+    if compile_commands.is_empty() {
+        return Err(anyhow!(
+            "No source file found in '{}'",
+            vcxproj_path.display()
+        ));
+    }
+
+    Ok(compile_commands)
+}
+
+/// Returns the elements with the given name found in the project's groups
+/// with the given name (e.g., `ClCompile` items of `ItemGroup`s)
+fn project_elements<'d, 'i>(
+    project: &'d Document<'i>,
+    group_name: &'d str,
+    element_name: &'d str,
+) -> impl Iterator<Item = Node<'d, 'i>> + 'd {
+    let has_name = |node: &Node, name: &str| node.is_element() && node.tag_name().name() == name;
+    project
+        .root_element()
+        .children()
+        .filter(move |group| has_name(group, group_name))
+        .flat_map(move |group| {
+            group
+                .children()
+                .filter(move |element| has_name(element, element_name))
+        })
+}
+
+/// Build configuration (e.g., "Release|x64") whose settings are used
+struct ProjectConfiguration {
+    configuration: String,
+    platform: String,
+}
+
+impl ProjectConfiguration {
+    /// Selects the first "Release" configuration declared by the project, or
+    /// its first configuration if there's none
+    fn select(project: &Document) -> Self {
+        let configurations: Vec<Self> =
+            project_elements(project, "ItemGroup", "ProjectConfiguration")
+                .filter_map(|element| {
+                    let (configuration, platform) =
+                        element.attribute("Include")?.split_once('|')?;
+                    Some(Self {
+                        configuration: configuration.to_string(),
+                        platform: platform.to_string(),
+                    })
+                })
+                .collect();
+        let release_position = configurations
+            .iter()
+            .position(|configuration| configuration.configuration.eq_ignore_ascii_case("Release"));
+
+        configurations
+            .into_iter()
+            .nth(release_position.unwrap_or_default())
+            .unwrap_or_else(|| Self {
+                configuration: "Release".to_string(),
+                platform: "Win32".to_string(),
+            })
+    }
+
+    /// Returns `true` if the given element and its parent have no condition,
+    /// or conditions that hold for this configuration
+    fn applies_to(&self, element: &Node) -> bool {
+        let parent_condition = element
+            .parent_element()
+            .and_then(|parent| parent.attribute("Condition"));
+        self.evaluate(parent_condition) && self.evaluate(element.attribute("Condition"))
+    }
+
+    /// Evaluates an MSBuild condition. Only comparisons of strings, which can
+    /// reference `$(Configuration)` and `$(Platform)`, combined with `and` or
+    /// `or` are supported, other conditions are considered false.
+    fn evaluate(&self, condition: Option<&str>) -> bool {
+        let condition = match condition {
+            Some(condition) if !condition.trim().is_empty() => condition,
+            _ => return true,
+        };
+        let expanded_condition = condition
+            .replace("$(Configuration)", &self.configuration)
+            .replace("$(Platform)", &self.platform);
+        let or_regex = Regex::new(r"(?i)\s+or\s+").expect("Invalid regex");
+        let and_regex = Regex::new(r"(?i)\s+and\s+").expect("Invalid regex");
+
+        let result = or_regex
+            .split(&expanded_condition)
+            .map(|conjunction| {
+                and_regex
+                    .split(conjunction)
+                    .map(evaluate_comparison)
+                    .try_fold(true, |result, comparison| Some(result & comparison?))
+            })
+            .try_fold(false, |result, conjunction| Some(result | conjunction?));
+        result.unwrap_or_else(|| {
+            log::debug!("Ignoring unsupported condition '{}'", condition);
+            false
+        })
+    }
+}
+
+/// Evaluates a (case-insensitive) comparison of two strings, returns `None` if
+/// it isn't a supported comparison
+fn evaluate_comparison(comparison: &str) -> Option<bool> {
+    let comparison = comparison
+        .trim()
+        .trim_start_matches('(')
+        .trim_end_matches(')');
+    let (operands, equal) = match comparison.split_once("==") {
+        Some(operands) => (operands, true),
+        None => (comparison.split_once("!=")?, false),
+    };
+    let operand = |value: &str| {
+        let value = value.trim().trim_matches('\'');
+        (!value.contains("$(")).then(|| value.to_ascii_lowercase())
+    };
+
+    Some((operand(operands.0)? == operand(operands.1)?) == equal)
+}
+
+/// Compiler settings (i.e., `ClCompile` metadata), by name
+#[derive(Debug, Clone, Default)]
+struct CompilerSettings(HashMap<String, String>);
+
+impl CompilerSettings {
+    /// Applies the settings defined by the given `ClCompile` element, which
+    /// can reference the values they override (e.g.,
+    /// `%(PreprocessorDefinitions)`)
+    fn apply(&mut self, element: Node, configuration: &ProjectConfiguration) {
+        for setting in element
+            .children()
+            .filter(|child| child.is_element() && configuration.applies_to(child))
+        {
+            let name = setting.tag_name().name();
+            let inherited_value = self.0.get(name).cloned().unwrap_or_default();
+            let value = setting
+                .text()
+                .unwrap_or_default()
+                .trim()
+                .replace(&format!("%({})", name), &inherited_value);
+            self.0.insert(name.to_string(), value);
+        }
+    }
+
+    fn is_excluded_from_build(&self) -> bool {
+        matches!(
+            self.0.get("ExcludedFromBuild"),
+            Some(value) if value.eq_ignore_ascii_case("true")
+        )
+    }
+
+    /// Returns the clang arguments corresponding to the settings
+    fn arguments(&self, project_directory: &Path, macros: &ProjectMacros) -> Vec<String> {
+        let mut arguments: Vec<String> = MSVC_COMPATIBILITY_ARGUMENTS
+            .iter()
+            .map(|argument| argument.to_string())
+            .collect();
+        if let Some(language_standard) = self
+            .0
+            .get("LanguageStandard")
+            .and_then(|standard| language_standard_argument(standard))
+        {
+            arguments.push(language_standard);
+        }
+        for include_directory in self.list("AdditionalIncludeDirectories", macros) {
+            let include_directory = project_directory.join(windows_path(&include_directory));
+            arguments.push(format!("-I{}", include_directory.display()));
+        }
+        for compile_definition in self.list("PreprocessorDefinitions", macros) {
+            arguments.push(format!("-D{}", compile_definition));
+        }
+
+        arguments
+    }
+
+    /// Returns the items of a semicolon-separated list setting, without the
+    /// unresolved references to other settings and the items that cannot be
+    /// resolved
+    fn list(&self, name: &str, macros: &ProjectMacros) -> Vec<String> {
+        self.0
+            .get(name)
+            .map(|value| {
+                value
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty() && !item.starts_with("%("))
+                    .filter_map(|item| macros.expand(item))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Values of MSBuild macros that can be resolved without evaluating the
+/// whole project
+struct ProjectMacros<'p> {
+    project_directory: &'p Path,
+    solution_directory: &'p Path,
+}
+
+impl ProjectMacros<'_> {
+    /// Expands the known macros of the given value. Returns `None` if some
+    /// macros cannot be resolved.
+    fn expand(&self, value: &str) -> Option<String> {
+        let project_directory = format!("{}/", self.project_directory.display());
+        let solution_directory = format!("{}/", self.solution_directory.display());
+        let value = value
+            .replace("$(ProjectDir)", &project_directory)
+            .replace("$(MSBuildProjectDirectory)", &project_directory)
+            .replace("$(MSBuildThisFileDirectory)", &project_directory)
+            .replace("$(SolutionDir)", &solution_directory);
+        if value.contains("$(") {
+            log::debug!("Ignoring '{}' (unknown macro)", value);
+            None
+        } else {
+            Some(value)
+        }
+    }
+}
+
+/// Converts paths written with Windows separators for the host
+fn windows_path(path: &str) -> PathBuf {
+    PathBuf::from(path.replace('\\', "/"))
+}
+
+/// Converts MSVC's language standards (e.g., "stdcpp17") into clang's
+fn language_standard_argument(language_standard: &str) -> Option<String> {
+    match language_standard {
+        "stdcpplatest" => Some("-std=c++2b".to_string()),
+        _ => language_standard
+            .strip_prefix("stdcpp")
+            .map(|version| format!("-std=c++{}", version))
+            .or_else(|| {
+                language_standard
+                    .strip_prefix("stdc")
+                    .map(|version| format!("-std=c{}", version))
+            }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VCXPROJ_PATH: &str = "tests/data/vcxproj";
+
+    #[test]
+    fn parse_vcxproj_file() {
+        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(VCXPROJ_PATH);
+        let app_dir_path = root_dir_path.join("app");
+        let database =
+            VcxprojDatabase::new(app_dir_path.join("app.vcxproj")).expect("Failed to load project");
+        assert!(!database.is_file_path_in_arguments());
+
+        let compile_commands = database
+            .get_all_compile_commands()
+            .expect("get_all_compile_commands failed");
+        let file_paths: Vec<PathBuf> = compile_commands
+            .iter()
+            .map(|compile_cmd| compile_cmd.filename.clone())
+            .collect();
+        assert_eq!(
+            file_paths,
+            vec![
+                app_dir_path.join("src/main.cpp").canonicalize().unwrap(),
+                app_dir_path.join("src/config.cpp").canonicalize().unwrap(),
+            ]
+        );
+        // Settings of the release configuration
+        assert_eq!(
+            *compile_commands[0].arguments,
+            vec![
+                "-fms-extensions".to_string(),
+                "-fms-compatibility".to_string(),
+                "-std=c++17".to_string(),
+                format!("-I{}", app_dir_path.join("include").display()),
+                format!("-I{}/sdk/include", app_dir_path.display()),
+                "-DNDEBUG".to_string(),
+                "-DAPP_NAME=\"app\"".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_sln_file() {
+        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(VCXPROJ_PATH);
+        let database =
+            VcxprojDatabase::new(root_dir_path.join("app.sln")).expect("Failed to load solution");
+
+        let compile_commands = database
+            .get_all_compile_commands()
+            .expect("get_all_compile_commands failed");
+        assert_eq!(compile_commands.len(), 2);
+        assert_eq!(
+            compile_commands[0].filename,
+            root_dir_path
+                .join("app/src/main.cpp")
+                .canonicalize()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_vcxproj_with_conditional_settings() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let vcxproj_path = tmp_dir.path().join("lib.vcxproj");
+        std::fs::write(
+            &vcxproj_path,
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<Project xmlns="http://schemas.microsoft.com/developer/msbuild/2003">
+  <ItemGroup Label="ProjectConfigurations">
+    <ProjectConfiguration Include="Debug|Win32" />
+    <ProjectConfiguration Include="Release|Win32" />
+    <ProjectConfiguration Include="Release|x64" />
+  </ItemGroup>
+  <ItemDefinitionGroup>
+    <ClCompile>
+      <PreprocessorDefinitions>LIB_EXPORTS</PreprocessorDefinitions>
+    </ClCompile>
+  </ItemDefinitionGroup>
+  <ItemDefinitionGroup Condition="'$(Configuration)|$(Platform)'=='Debug|Win32'">
+    <ClCompile>
+      <PreprocessorDefinitions>_DEBUG;%(PreprocessorDefinitions)</PreprocessorDefinitions>
+    </ClCompile>
+  </ItemDefinitionGroup>
+  <ItemDefinitionGroup Condition=" '$(Configuration)' == 'release' And '$(Platform)' != 'x64' ">
+    <ClCompile>
+      <PreprocessorDefinitions>%(PreprocessorDefinitions);NDEBUG</PreprocessorDefinitions>
+      <AdditionalIncludeDirectories Condition="'$(Platform)'=='x64'">x64</AdditionalIncludeDirectories>
+      <AdditionalIncludeDirectories Condition="'$(Platform)'=='Win32'">x86</AdditionalIncludeDirectories>
+    </ClCompile>
+  </ItemDefinitionGroup>
+  <ItemGroup>
+    <ClCompile Include="lib.cpp;utils.cpp" />
+    <ClCompile Include="debug.cpp">
+      <ExcludedFromBuild Condition="'$(Configuration)'=='Release'">true</ExcludedFromBuild>
+    </ClCompile>
+    <ClCompile Include="legacy.c">
+      <PreprocessorDefinitions>LEGACY;%(PreprocessorDefinitions)</PreprocessorDefinitions>
+    </ClCompile>
+  </ItemGroup>
+  <ItemGroup Condition="'$(Configuration)'=='Debug'">
+    <ClCompile Include="tests.cpp" />
+  </ItemGroup>
+</Project>"#,
+        )
+        .unwrap();
+
+        let compile_commands = parse_vcxproj(&vcxproj_path, None).expect("parse_vcxproj failed");
+        let file_names: Vec<&str> = compile_commands
+            .iter()
+            .map(|(file_path, _)| file_path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(file_names, vec!["lib.cpp", "utils.cpp", "legacy.c"]);
+        // Settings of the "Release|Win32" configuration
+        let include_argument = format!("-I{}", tmp_dir.path().join("x86").display());
+        assert_eq!(
+            *compile_commands[0].1,
+            vec![
+                "-fms-extensions",
+                "-fms-compatibility",
+                &include_argument,
+                "-DLIB_EXPORTS",
+                "-DNDEBUG",
+            ]
+        );
+        assert_eq!(
+            compile_commands[2].1[3..],
+            ["-DLEGACY", "-DLIB_EXPORTS", "-DNDEBUG"]
+        );
+    }
+
+    #[test]
+    fn evaluate_conditions() {
+        let configuration = ProjectConfiguration {
+            configuration: "Release".to_string(),
+            platform: "x64".to_string(),
+        };
+        assert!(configuration.evaluate(None));
+        assert!(configuration.evaluate(Some("'$(Configuration)|$(Platform)'=='Release|x64'")));
+        assert!(configuration.evaluate(Some(
+            "'$(Platform)'=='Win32' or ('$(Configuration)'=='RELEASE')"
+        )));
+        assert!(!configuration.evaluate(Some("'$(Configuration)'!='Release'")));
+        assert!(!configuration.evaluate(Some("Exists('props.txt')")));
+        assert!(!configuration.evaluate(Some("'$(UseDebugLibraries)'=='true'")));
+    }
+
+    #[test]
+    fn detect_visual_studio_projects() {
+        assert!(is_visual_studio_project(Path::new("app.sln")));
+        assert!(is_visual_studio_project(Path::new("app/app.VCXPROJ")));
+        assert!(!is_visual_studio_project(Path::new(
+            "build/compile_commands.json"
+        )));
+    }
+
+    #[test]
+    fn convert_language_standards() {
+        assert_eq!(
+            language_standard_argument("stdcpp20"),
+            Some("-std=c++20".to_string())
+        );
+        assert_eq!(
+            language_standard_argument("stdc11"),
+            Some("-std=c11".to_string())
+        );
+        assert_eq!(language_standard_argument("Default"), None);
+    }
+}
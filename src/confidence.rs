@@ -0,0 +1,157 @@
+//! Scoring of matches, so that short coincidental matches (e.g., 4 bytes in
+//! code) don't carry the same weight as long strings found in read-only data.
+
+use std::collections::BTreeSet;
+use std::ops::Deref;
+
+use crate::information_leak::ConfirmedLeak;
+
+/// Highest confidence score
+pub const MAX_CONFIDENCE: u8 = 100;
+/// Score given to any match
+const BASE_SCORE: u8 = 10;
+/// Score given to matches of `LENGTH_SCORE_MAX_CHARS` characters or more
+const LENGTH_SCORE_MAX: usize = 50;
+const LENGTH_SCORE_MAX_CHARS: usize = 32;
+/// Bonus given to wide strings, whose interleaved null bytes rarely appear by
+/// chance
+const WIDE_ENCODING_BONUS: u8 = 10;
+/// Bonus given to matches followed by a null terminator
+const TERMINATOR_BONUS: u8 = 15;
+/// Bonus given to matches located in data sections
+const DATA_REGION_BONUS: u8 = 15;
+/// Penalty given to matches located in code sections
+const CODE_REGION_PENALTY: u8 = 15;
+/// Penalty given to partial matches
+const PARTIAL_MATCH_PENALTY: u8 = 10;
+/// Penalty given to patterns matched at more than `COMMON_PATTERN_MIN_MATCHES`
+/// offsets of the same binary, which are likely to be coincidental
+const COMMON_PATTERN_PENALTY: u8 = 20;
+const COMMON_PATTERN_MIN_MATCHES: usize = 4;
+
+/// Names of the regions containing data (e.g., constant strings)
+const DATA_REGION_NAMES: [&str; 6] = [
+    ".rodata",
+    ".rdata",
+    ".data",
+    "__cstring",
+    "__const",
+    "data segment",
+];
+/// Names of the regions containing code
+const CODE_REGION_NAMES: [&str; 3] = [".text", "__text", "code section"];
+
+/// Characteristics of a match, observed when scanning a binary
+pub struct MatchFeatures<'r> {
+    /// Number of bytes matched
+    pub matched_size: usize,
+    /// Size in bytes of the code units of the matched pattern
+    pub code_unit_size: usize,
+    /// Set if the match is followed by a null code unit
+    pub terminated: bool,
+    /// Set if only the beginning of the pattern matched
+    pub partial: bool,
+    /// Region of the binary containing the match, if known
+    pub region: Option<&'r str>,
+}
+
+/// Returns the confidence score (from 0 to `MAX_CONFIDENCE`) of a single
+/// match, not accounting for the number of times its pattern is matched
+pub fn match_confidence(features: &MatchFeatures) -> u8 {
+    let char_count = features.matched_size / features.code_unit_size.max(1);
+    let length_score =
+        char_count.min(LENGTH_SCORE_MAX_CHARS) * LENGTH_SCORE_MAX / LENGTH_SCORE_MAX_CHARS;
+
+    let mut score = BASE_SCORE + length_score as u8;
+    if features.code_unit_size > 1 {
+        score += WIDE_ENCODING_BONUS;
+    }
+    if features.terminated {
+        score += TERMINATOR_BONUS;
+    }
+    match features.region {
+        Some(region) if DATA_REGION_NAMES.iter().any(|name| region.contains(name)) => {
+            score += DATA_REGION_BONUS
+        }
+        Some(region) if CODE_REGION_NAMES.iter().any(|name| region.contains(name)) => {
+            score = score.saturating_sub(CODE_REGION_PENALTY)
+        }
+        _ => {}
+    }
+    if features.partial {
+        score = score.saturating_sub(PARTIAL_MATCH_PENALTY);
+    }
+
+    score.min(MAX_CONFIDENCE)
+}
+
+/// Lowers the confidence of a match whose pattern has been matched
+/// `match_count` times in the same binary
+pub fn adjust_confidence_to_match_count(confidence: u8, match_count: usize) -> u8 {
+    if match_count > COMMON_PATTERN_MIN_MATCHES {
+        confidence.saturating_sub(COMMON_PATTERN_PENALTY)
+    } else {
+        confidence
+    }
+}
+
+/// Removes the leaks whose confidence is below `min_confidence`
+pub fn filter_confirmed_leaks_by_confidence<SortedConfirmedLeak>(
+    confirmed_leaks: BTreeSet<SortedConfirmedLeak>,
+    min_confidence: u8,
+) -> BTreeSet<SortedConfirmedLeak>
+where
+    SortedConfirmedLeak: Deref<Target = ConfirmedLeak> + Ord,
+{
+    confirmed_leaks
+        .into_iter()
+        .filter(|leak| !matches!(leak.confidence, Some(confidence) if confidence < min_confidence))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn features(matched_size: usize, region: Option<&str>) -> MatchFeatures<'_> {
+        MatchFeatures {
+            matched_size,
+            code_unit_size: 1,
+            terminated: false,
+            partial: false,
+            region,
+        }
+    }
+
+    #[test]
+    fn score_match_length_and_region() {
+        // Short coincidental match in code
+        let short_match = match_confidence(&features(4, Some("section \".text\"")));
+        // Long string in read-only data
+        let long_match = match_confidence(&MatchFeatures {
+            terminated: true,
+            ..features(60, Some("section \".rodata\""))
+        });
+        assert_eq!(short_match, 1);
+        assert_eq!(long_match, 90);
+        assert_eq!(match_confidence(&features(16, None)), 35);
+
+        let wide_match = match_confidence(&MatchFeatures {
+            code_unit_size: 2,
+            ..features(32, None)
+        });
+        assert_eq!(wide_match, 45);
+        let partial_match = match_confidence(&MatchFeatures {
+            partial: true,
+            ..features(16, None)
+        });
+        assert_eq!(partial_match, 25);
+    }
+
+    #[test]
+    fn score_common_patterns() {
+        assert_eq!(adjust_confidence_to_match_count(50, 1), 50);
+        assert_eq!(adjust_confidence_to_match_count(50, 10), 30);
+        assert_eq!(adjust_confidence_to_match_count(10, 10), 0);
+    }
+}
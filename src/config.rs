@@ -0,0 +1,455 @@
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use glob::{glob, Pattern};
+use regex::Regex;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::information_leak::{LeakedDataType, PotentialLeak, SourceLocation};
+use crate::policy::Policy;
+use crate::provenance::ProvenanceConfiguration;
+use crate::scan_passes::ScanPass;
+use crate::severity::SeverityConfiguration;
+
+pub struct Configuration {
+    /// Path of the configuration file
+    pub file_path: PathBuf,
+    /// User-defined artifacts to look for in addition to the ones extracted
+    /// from the source code
+    pub artifacts: Vec<CustomArtifactRule>,
+    /// Resource files (as glob expressions, relative to the configuration
+    /// file) whose string values should be looked for
+    pub resources: Vec<String>,
+    /// Gates to evaluate against the confirmed leaks
+    pub policy: Option<Policy>,
+    /// Severity classification settings
+    pub severity: Option<SeverityConfiguration>,
+    /// Settings used to tell first-party code from third-party code
+    pub provenance: Option<ProvenanceConfiguration>,
+    /// Scan passes to run, in order
+    pub passes: Vec<ScanPass>,
+}
+
+impl Configuration {
+    /// Indicates if both configurations look for the same artifacts in the
+    /// same way, in which case leaks are only classified differently
+    pub fn has_same_scan_settings(&self, other: &Configuration) -> bool {
+        self.file_path == other.file_path
+            && self.artifacts == other.artifacts
+            && self.resources == other.resources
+            && self.passes == other.passes
+    }
+
+    /// Resolves a path written in the configuration file relative to the
+    /// file's directory, so that it doesn't depend on the current directory
+    fn resolve_path(&self, path: &Path) -> PathBuf {
+        match self.file_path.parent() {
+            Some(config_dir_path) => config_dir_path.join(path),
+            None => path.to_path_buf(),
+        }
+    }
+
+    /// Resolves a glob expression written in the configuration file relative
+    /// to the file's directory, the same way `resolve_path` does
+    pub fn resolve_glob_expression(&self, glob_expression: &str) -> String {
+        match self.file_path.parent() {
+            Some(config_dir_path)
+                if !config_dir_path.as_os_str().is_empty()
+                    && !Path::new(glob_expression).is_absolute() =>
+            {
+                // Note: The directory may contain characters with a special
+                // meaning in glob expressions
+                let config_dir_path = Pattern::escape(&config_dir_path.to_string_lossy());
+                Path::new(&config_dir_path)
+                    .join(glob_expression)
+                    .to_string_lossy()
+                    .into_owned()
+            }
+            _ => glob_expression.to_string(),
+        }
+    }
+}
+
+/// Describes how to generate custom artifacts
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum CustomArtifactRule {
+    /// Literal value
+    Value { value: String },
+    /// Regular expression matched against the content of the given files.
+    /// If the expression contains capture groups, only the first group is
+    /// used as an artifact.
+    Regex { regex: String, files: Vec<String> },
+    /// SHA-256 digest of the content of a file
+    FileHash { sha256_of: PathBuf },
+}
+
+#[derive(Deserialize)]
+struct ConfigurationYaml {
+    artifacts: Option<Vec<CustomArtifactRule>>,
+    resources: Option<Vec<String>>,
+    policy: Option<Policy>,
+    severity: Option<SeverityConfiguration>,
+    provenance: Option<ProvenanceConfiguration>,
+    passes: Option<Vec<ScanPass>>,
+}
+
+pub fn parse_configuration_file(config_file_path: &Path) -> Result<Configuration> {
+    // Read file
+    let mut config_data = vec![];
+    let mut config_file = File::open(config_file_path)?;
+    config_file.read_to_end(&mut config_data)?;
+
+    // Parse YAML content
+    let config_yaml: ConfigurationYaml = serde_yaml::from_slice(&config_data)?;
+
+    Ok(Configuration {
+        file_path: config_file_path.to_path_buf(),
+        artifacts: config_yaml.artifacts.unwrap_or_default(),
+        resources: config_yaml.resources.unwrap_or_default(),
+        policy: config_yaml.policy,
+        severity: config_yaml.severity,
+        provenance: config_yaml.provenance,
+        passes: config_yaml.passes.unwrap_or_default(),
+    })
+}
+
+/// Generates the potential leaks described by the configuration's custom
+/// artifact rules. Files are looked up relative to the configuration file.
+pub fn generate_custom_artifacts(config: &Configuration) -> Result<Vec<PotentialLeak>> {
+    let config_location = Arc::new(SourceLocation {
+        file: config.file_path.canonicalize()?,
+        line: 0,
+    });
+
+    config.artifacts.iter().try_fold(
+        Vec::new(),
+        |mut accum, rule| -> Result<Vec<PotentialLeak>> {
+            match rule {
+                CustomArtifactRule::Value { value } => {
+                    accum.push(custom_artifact(
+                        value.clone(),
+                        value.as_bytes().to_vec(),
+                        config_location.clone(),
+                    ));
+                }
+                CustomArtifactRule::Regex { regex, files } => {
+                    let regex = Regex::new(regex)
+                        .with_context(|| format!("Invalid regular expression '{}'", regex))?;
+                    for glob_expression in files {
+                        let paths = glob(&config.resolve_glob_expression(glob_expression))
+                            .map_err(|_| {
+                                anyhow!("'{}' is not a valid glob expression", glob_expression)
+                            })?;
+                        for path in paths {
+                            accum.extend(extract_artifacts_with_regex(&regex, &path?)?);
+                        }
+                    }
+                }
+                CustomArtifactRule::FileHash { sha256_of } => {
+                    let sha256_of = config.resolve_path(sha256_of);
+                    let file_data = fs::read(&sha256_of)
+                        .with_context(|| format!("Failed to read '{}'", sha256_of.display()))?;
+                    let digest = Sha256::digest(file_data);
+                    let hex_digest: String =
+                        digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+                    let file_location = Arc::new(SourceLocation {
+                        file: sha256_of.canonicalize()?,
+                        line: 0,
+                    });
+
+                    // The digest may be stored either as raw bytes or as text
+                    accum.push(custom_artifact(
+                        hex_digest.clone(),
+                        digest.to_vec(),
+                        file_location.clone(),
+                    ));
+                    accum.push(custom_artifact(
+                        hex_digest.clone(),
+                        hex_digest.into_bytes(),
+                        file_location,
+                    ));
+                }
+            }
+
+            Ok(accum)
+        },
+    )
+}
+
+/// Returns the artifacts matching `regex` in the given text file.
+fn extract_artifacts_with_regex(regex: &Regex, file_path: &Path) -> Result<Vec<PotentialLeak>> {
+    let file_content = fs::read_to_string(file_path)
+        .with_context(|| format!("Failed to read '{}'", file_path.display()))?;
+    let file_path = file_path.canonicalize()?;
+
+    Ok(regex
+        .captures_iter(&file_content)
+        .filter_map(|captures| {
+            // Use the first capture group if any, the whole match otherwise
+            let artifact = captures.get(1).or_else(|| captures.get(0))?;
+            let line = file_content[..artifact.start()].matches('\n').count() + 1;
+
+            Some(custom_artifact(
+                artifact.as_str().to_string(),
+                artifact.as_str().as_bytes().to_vec(),
+                Arc::new(SourceLocation {
+                    file: file_path.clone(),
+                    line: line as u64,
+                }),
+            ))
+        })
+        .collect())
+}
+
+fn custom_artifact(
+    data: String,
+    bytes: Vec<u8>,
+    declaration_metadata: Arc<SourceLocation>,
+) -> PotentialLeak {
+    PotentialLeak {
+        data_type: LeakedDataType::CustomArtifact,
+        data: Arc::new(data),
+        bytes,
+        code_unit_size: 1,
+        declaration_metadata,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::severity::{Severity, SeverityRuleConfiguration};
+
+    use super::*;
+
+    const CUSTOM_ARTIFACTS_PATH: &str = "tests/data/config/custom_artifacts.yml";
+    const RESOURCES_PATH: &str = "tests/data/config/resources.yml";
+    const POLICY_PATH: &str = "tests/data/config/policy.yml";
+    const SEVERITY_PATH: &str = "tests/data/config/severity.yml";
+    const PROVENANCE_PATH: &str = "tests/data/config/provenance.yml";
+    const PASSES_PATH: &str = "tests/data/config/passes.yml";
+
+    #[test]
+    fn parse_configuration_file_custom_artifacts() {
+        let file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(CUSTOM_ARTIFACTS_PATH);
+        let config = parse_configuration_file(&file_path).expect("Failed parsing config file");
+
+        assert_eq!(
+            config.artifacts,
+            vec![
+                CustomArtifactRule::Value {
+                    value: "internal.corp.example".to_string()
+                },
+                CustomArtifactRule::Regex {
+                    regex: r"FROM (\w+)".to_string(),
+                    files: vec!["*.sql".to_string()]
+                },
+                CustomArtifactRule::FileHash {
+                    sha256_of: PathBuf::from("query.sql")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_configuration_file_resources() {
+        let file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(RESOURCES_PATH);
+        let config = parse_configuration_file(&file_path).expect("Failed parsing config file");
+
+        assert!(config.artifacts.is_empty());
+        assert_eq!(
+            config.resources,
+            vec![
+                "../resources/*.json".to_string(),
+                "../resources/*.ini".to_string()
+            ]
+        );
+
+        // Resource files are looked up relative to the configuration file
+        let resource_file_count: usize = config
+            .resources
+            .iter()
+            .map(|glob_expression| {
+                glob(&config.resolve_glob_expression(glob_expression))
+                    .expect("Invalid glob expression")
+                    .count()
+            })
+            .sum();
+        assert_eq!(resource_file_count, 2);
+    }
+
+    #[test]
+    fn parse_configuration_file_policy() {
+        let file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(POLICY_PATH);
+        let config = parse_configuration_file(&file_path).expect("Failed parsing config file");
+
+        assert_eq!(
+            config.policy,
+            Some(Policy {
+                max_total: Some(25),
+                max_per_type: [(LeakedDataType::StringLiteral, 10)].into(),
+                forbidden_types: [LeakedDataType::CustomArtifact].into(),
+                max_per_severity: [(Severity::Critical, 0)].into(),
+                ignore_third_party: false,
+            })
+        );
+    }
+
+    #[test]
+    fn parse_configuration_file_severity() {
+        let file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(SEVERITY_PATH);
+        let config = parse_configuration_file(&file_path).expect("Failed parsing config file");
+
+        assert_eq!(
+            config.severity,
+            Some(SeverityConfiguration {
+                default: Some(Severity::Low),
+                third_party_max: None,
+                rules: vec![SeverityRuleConfiguration {
+                    name: "internal hosts".to_string(),
+                    regex: r"\.corp\.example$".to_string(),
+                    severity: Severity::Critical,
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn parse_configuration_file_provenance() {
+        let file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(PROVENANCE_PATH);
+        let config = parse_configuration_file(&file_path).expect("Failed parsing config file");
+
+        assert_eq!(
+            config.provenance,
+            Some(ProvenanceConfiguration {
+                third_party: Some(vec!["external/".to_string(), "*/generated/*".to_string()]),
+            })
+        );
+        assert_eq!(
+            config
+                .severity
+                .and_then(|severity| severity.third_party_max),
+            Some(Severity::Low)
+        );
+        assert!(config.policy.expect("Missing policy").ignore_third_party);
+    }
+
+    #[test]
+    fn parse_configuration_file_passes() {
+        let file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(PASSES_PATH);
+        let config = parse_configuration_file(&file_path).expect("Failed parsing config file");
+
+        assert_eq!(
+            config.passes,
+            vec![
+                ScanPass {
+                    name: "exact".to_string(),
+                    ..Default::default()
+                },
+                ScanPass {
+                    name: "wide".to_string(),
+                    utf16_variants: true,
+                    all_encodings: true,
+                    ..Default::default()
+                },
+                ScanPass {
+                    name: "partial".to_string(),
+                    partial_leaks: Some(8),
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn compare_scan_settings() {
+        let file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(SEVERITY_PATH);
+        let config = parse_configuration_file(&file_path).expect("Failed parsing config file");
+        let mut other_config =
+            parse_configuration_file(&file_path).expect("Failed parsing config file");
+        other_config.severity = None;
+        assert!(config.has_same_scan_settings(&other_config));
+
+        let passes_file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(PASSES_PATH);
+        other_config.passes = parse_configuration_file(&passes_file_path)
+            .expect("Failed parsing config file")
+            .passes;
+        assert!(!config.has_same_scan_settings(&other_config));
+    }
+
+    #[test]
+    fn generate_custom_artifacts_all_rules() {
+        let file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(CUSTOM_ARTIFACTS_PATH);
+        let config = parse_configuration_file(&file_path).expect("Failed parsing config file");
+        let potential_leaks =
+            generate_custom_artifacts(&config).expect("Failed to generate artifacts");
+
+        let data: Vec<&str> = potential_leaks
+            .iter()
+            .map(|leak| leak.data.as_str())
+            .collect();
+        assert_eq!(
+            data,
+            vec![
+                "internal.corp.example",
+                "secret_users",
+                "secret_sessions",
+                "9eb255c44e00c58be2983da2db67e71fc500449e56a6dae9e9099a821c983bc0",
+                "9eb255c44e00c58be2983da2db67e71fc500449e56a6dae9e9099a821c983bc0",
+            ]
+        );
+
+        // Regex matches are located in the matched file
+        assert_eq!(potential_leaks[2].declaration_metadata.line, 2);
+        // Digests are matched both as raw bytes and as text
+        assert_eq!(potential_leaks[3].bytes.len(), 32);
+        assert_eq!(potential_leaks[4].bytes.len(), 64);
+    }
+
+    #[test]
+    fn generate_custom_artifacts_relative_to_config_file() {
+        // The configuration file isn't in the current directory
+        let config_dir = tempfile::tempdir().expect("Failed to create directory");
+        fs::create_dir(config_dir.path().join("queries")).expect("Failed to create directory");
+        fs::write(
+            config_dir.path().join("queries/query.sql"),
+            "SELECT * FROM relative_table;\n",
+        )
+        .expect("Failed to write file");
+        let file_path = config_dir.path().join("cpplumber.yml");
+        fs::write(
+            &file_path,
+            r#"
+artifacts:
+  - regex: 'FROM (\w+)'
+    files:
+      - "queries/*.sql"
+  - sha256_of: "queries/query.sql"
+"#,
+        )
+        .expect("Failed to write file");
+        assert_ne!(
+            std::env::current_dir().unwrap().canonicalize().unwrap(),
+            config_dir.path().canonicalize().unwrap()
+        );
+
+        let config = parse_configuration_file(&file_path).expect("Failed parsing config file");
+        let potential_leaks =
+            generate_custom_artifacts(&config).expect("Failed to generate artifacts");
+
+        assert_eq!(potential_leaks.len(), 3);
+        assert_eq!(*potential_leaks[0].data, "relative_table");
+        assert_eq!(
+            potential_leaks[2].declaration_metadata.file,
+            config_dir
+                .path()
+                .join("queries/query.sql")
+                .canonicalize()
+                .unwrap()
+        );
+    }
+}
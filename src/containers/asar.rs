@@ -0,0 +1,163 @@
+//! Electron's asar archives, which contain the application's files stored as
+//! is, after a JSON header describing them.
+
+use std::borrow::Cow;
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use super::ContainedFile;
+
+/// Size of the fields preceding the JSON header
+const HEADER_PREFIX_SIZE: usize = 16;
+
+/// Returns `true` if the given data looks like an asar archive
+pub fn is_asar_archive(data: &[u8]) -> bool {
+    data.len() > HEADER_PREFIX_SIZE
+        && read_u32(data, 0) == Some(4)
+        && data[HEADER_PREFIX_SIZE..].starts_with(b"{\"files\":")
+}
+
+/// Returns the files stored in the archive
+pub fn unpack(data: &[u8]) -> Result<Vec<ContainedFile<'_>>> {
+    // The header is a "pickle" containing the JSON string, preceded by the
+    // pickle's size
+    let header_size = read_u32(data, 4).ok_or_else(invalid_archive)? as usize;
+    let json_size = read_u32(data, 12).ok_or_else(invalid_archive)? as usize;
+    let json_data = data
+        .get(HEADER_PREFIX_SIZE..HEADER_PREFIX_SIZE + json_size)
+        .ok_or_else(invalid_archive)?;
+    let header: Value = serde_json::from_slice(json_data)?;
+    // Files' offsets are relative to the end of the header
+    let data_offset = 8 + header_size;
+
+    let mut contained_files = vec![];
+    collect_files(data, data_offset, "", &header, &mut contained_files)?;
+
+    Ok(contained_files)
+}
+
+/// Walks the given directory entry recursively and collects the files it
+/// contains
+fn collect_files<'d>(
+    data: &'d [u8],
+    data_offset: usize,
+    directory_path: &str,
+    directory: &Value,
+    contained_files: &mut Vec<ContainedFile<'d>>,
+) -> Result<()> {
+    let entries = match directory.get("files").and_then(Value::as_object) {
+        Some(entries) => entries,
+        None => return Ok(()),
+    };
+    for (name, entry) in entries {
+        let path = if directory_path.is_empty() {
+            name.clone()
+        } else {
+            format!("{}/{}", directory_path, name)
+        };
+        if entry.get("files").is_some() {
+            collect_files(data, data_offset, &path, entry, contained_files)?;
+            continue;
+        }
+        // Unpacked files are stored next to the archive, and links have no
+        // content
+        let offset = match entry.get("offset").and_then(Value::as_str) {
+            Some(offset) if entry.get("unpacked") != Some(&Value::Bool(true)) => offset,
+            _ => continue,
+        };
+        let size = entry
+            .get("size")
+            .and_then(Value::as_u64)
+            .ok_or_else(invalid_archive)? as usize;
+        let start = data_offset + offset.parse::<usize>()?;
+        let range = start..start + size;
+        let file_data = data.get(range.clone()).ok_or_else(invalid_archive)?;
+
+        contained_files.push(ContainedFile {
+            name: path,
+            data: Cow::Borrowed(file_data),
+            source_range: Some(range),
+        });
+    }
+
+    Ok(())
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn invalid_archive() -> anyhow::Error {
+    anyhow!("Invalid asar archive")
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    /// Builds an asar archive containing the given files
+    pub fn asar_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut offset = 0;
+        let entries: serde_json::Map<String, Value> = files
+            .iter()
+            .map(|(name, content)| {
+                let entry = serde_json::json!({
+                    "size": content.len(),
+                    "offset": offset.to_string(),
+                });
+                offset += content.len();
+                (name.to_string(), entry)
+            })
+            .collect();
+        let json = serde_json::json!({
+            "files": {
+                "unpacked.node": {"size": 4, "unpacked": true},
+                "app": {"files": entries}
+            }
+        })
+        .to_string()
+        .into_bytes();
+        let padding = (4 - json.len() % 4) % 4;
+        let pickle_size = 8 + json.len() + padding;
+
+        let mut archive = vec![];
+        archive.extend(4u32.to_le_bytes());
+        archive.extend((pickle_size as u32).to_le_bytes());
+        archive.extend(((pickle_size - 4) as u32).to_le_bytes());
+        archive.extend((json.len() as u32).to_le_bytes());
+        archive.extend(json);
+        archive.extend(vec![0; padding]);
+        for (_, content) in files {
+            archive.extend(*content);
+        }
+
+        archive
+    }
+
+    #[test]
+    fn unpack_asar_archive() {
+        let archive = asar_archive(&[("main.js", b"secret"), ("index.html", b"<html>")]);
+        assert!(is_asar_archive(&archive));
+
+        let files = unpack(&archive).expect("unpack failed");
+        let files: Vec<(&str, &[u8])> = files
+            .iter()
+            .map(|file| (file.name.as_str(), file.data.as_ref()))
+            .collect();
+        assert_eq!(
+            files,
+            [
+                ("app/index.html", b"<html>".as_slice()),
+                ("app/main.js", b"secret".as_slice())
+            ]
+        );
+    }
+
+    #[test]
+    fn detect_asar_archive() {
+        assert!(!is_asar_archive(b"\x04\0\0\0not an asar archive"));
+        assert!(!is_asar_archive(b"MZ"));
+    }
+}
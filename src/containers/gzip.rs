@@ -0,0 +1,82 @@
+//! Gzip-compressed files (e.g., `.tar.gz` bundles), which contain a single
+//! compressed file.
+
+use std::borrow::Cow;
+use std::io::Read;
+
+use anyhow::{anyhow, Result};
+use flate2::read::MultiGzDecoder;
+
+use super::{ContainedFile, MAX_DECOMPRESSED_SIZE};
+
+const MAGIC: &[u8] = b"\x1f\x8b";
+/// Name given to the compressed file if its original name isn't stored
+const DEFAULT_FILE_NAME: &str = "data";
+
+/// Returns `true` if the given data looks like a gzip-compressed file
+pub fn is_gzip_file(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Returns the decompressed file, named after its original name if stored
+pub fn unpack(data: &[u8]) -> Result<Vec<ContainedFile<'_>>> {
+    let mut decoder = MultiGzDecoder::new(data);
+    let mut decompressed_data = vec![];
+    decoder
+        .by_ref()
+        .take(MAX_DECOMPRESSED_SIZE as u64 + 1)
+        .read_to_end(&mut decompressed_data)?;
+    if decompressed_data.len() > MAX_DECOMPRESSED_SIZE {
+        return Err(anyhow!("Decompressed data is too large"));
+    }
+    let name = decoder
+        .header()
+        .and_then(|header| header.filename())
+        .map(|file_name| String::from_utf8_lossy(file_name).into_owned())
+        .unwrap_or_else(|| DEFAULT_FILE_NAME.to_string());
+
+    Ok(vec![ContainedFile {
+        name,
+        data: Cow::Owned(decompressed_data),
+        source_range: None,
+    }])
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::io::Write;
+
+    use flate2::write::GzEncoder;
+    use flate2::{Compression, GzBuilder};
+
+    use super::*;
+
+    /// Compresses the given data, storing the original file name if given
+    pub fn gzip_file(file_name: Option<&str>, data: &[u8]) -> Vec<u8> {
+        let mut encoder = match file_name {
+            Some(file_name) => GzBuilder::new()
+                .filename(file_name)
+                .write(vec![], Compression::default()),
+            None => GzEncoder::new(vec![], Compression::default()),
+        };
+        encoder.write_all(data).unwrap();
+
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn unpack_gzip_file() {
+        let compressed_data = gzip_file(Some("app.exe"), b"secret");
+        assert!(is_gzip_file(&compressed_data));
+
+        let files = unpack(&compressed_data).expect("unpack failed");
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "app.exe");
+        assert_eq!(files[0].data.as_ref(), b"secret");
+        assert!(files[0].source_range.is_none());
+
+        let compressed_data = gzip_file(None, b"secret");
+        let files = unpack(&compressed_data).expect("unpack failed");
+        assert_eq!(files[0].name, DEFAULT_FILE_NAME);
+    }
+}
@@ -0,0 +1,33 @@
+//! Inno Setup installers, which store their setup data and files in
+//! proprietary compressed blocks. Their content can't be unpacked, so they're
+//! only detected to warn that their payload is scanned as is.
+
+/// Identifier found at the beginning of the installer's setup data (followed
+/// by the version of Inno Setup, e.g., "(5.5.7)")
+const SETUP_DATA_ID: &[u8] = b"Inno Setup Setup Data (";
+/// Signature of the zlib-compressed chunks containing the installed files
+const ZLIB_CHUNK_SIGNATURE: &[u8] = b"zlb\x1a";
+
+/// Returns `true` if the given data looks like an Inno Setup installer
+pub fn is_inno_setup_installer(data: &[u8]) -> bool {
+    contains(data, SETUP_DATA_ID) && contains(data, ZLIB_CHUNK_SIGNATURE)
+}
+
+fn contains(data: &[u8], pattern: &[u8]) -> bool {
+    data.windows(pattern.len()).any(|window| window == pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_inno_setup_installer() {
+        let installer = b"MZ\0\0Inno Setup Setup Data (5.5.7)\0\0\0\0zlb\x1a\x78\x9c";
+        assert!(is_inno_setup_installer(installer));
+        assert!(!is_inno_setup_installer(
+            b"MZ\0\0Inno Setup Setup Data (5.5.7)"
+        ));
+        assert!(!is_inno_setup_installer(b"MZ\0\0zlb\x1a\x78\x9c"));
+    }
+}
@@ -0,0 +1,112 @@
+//! Unpacking of the containers commonly used to ship applications (e.g.,
+//! installers), of archives and of static libraries, so that their payloads
+//! can be scanned and leaks attributed to the files they're found in.
+
+mod asar;
+mod gzip;
+mod inno_setup;
+mod nsis;
+mod static_library;
+mod tar;
+mod zip;
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use anyhow::Result;
+
+pub use inno_setup::is_inno_setup_installer;
+
+#[cfg(test)]
+pub(crate) use zip::tests::zip_archive;
+
+/// Maximum size of the files decompressed from containers, to avoid
+/// exhausting memory on decompression bombs
+const MAX_DECOMPRESSED_SIZE: usize = 1 << 30;
+
+/// File found inside of a container
+pub struct ContainedFile<'d> {
+    /// Path of the file inside of the container
+    pub name: String,
+    /// Content of the file
+    pub data: Cow<'d, [u8]>,
+    /// Range of the container's data where the file is stored as is, if
+    /// it's not compressed
+    pub source_range: Option<Range<usize>>,
+}
+
+/// Returns the files contained in the given data, or `None` if it isn't a
+/// supported container
+pub fn unpack_container(data: &[u8]) -> Result<Option<Vec<ContainedFile<'_>>>> {
+    if let Some(members) = unpack_archive(data)? {
+        return Ok(Some(members));
+    }
+    if asar::is_asar_archive(data) {
+        return asar::unpack(data).map(Some);
+    }
+    if let Some(first_header_offset) = nsis::find_first_header(data) {
+        return nsis::unpack(data, first_header_offset).map(Some);
+    }
+    Ok(None)
+}
+
+/// Returns the files contained in the given archive (i.e., static library,
+/// ZIP or tar archive, or gzip-compressed file), or `None` if the data isn't a
+/// supported archive. Unlike other containers, archives hold nothing but the
+/// files they contain.
+pub fn unpack_archive(data: &[u8]) -> Result<Option<Vec<ContainedFile<'_>>>> {
+    if static_library::is_static_library(data) {
+        static_library::unpack(data).map(Some)
+    } else if zip::is_zip_archive(data) {
+        zip::unpack(data).map(Some)
+    } else if tar::is_tar_archive(data) {
+        tar::unpack(data).map(Some)
+    } else if gzip::is_gzip_file(data) {
+        gzip::unpack(data).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpack_supported_containers() {
+        let archive = asar::tests::asar_archive(&[("main.js", b"secret")]);
+        let files = unpack_container(&archive)
+            .expect("unpack_container failed")
+            .expect("Container not detected");
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "app/main.js");
+
+        let library = static_library::tests::gnu_archive(&[("leak.o", b"secret")]);
+        let files = unpack_container(&library)
+            .expect("unpack_container failed")
+            .expect("Container not detected");
+        assert_eq!(files[0].name, "leak.o");
+
+        let archive = zip::tests::zip_archive(&[("bin/app.exe", b"secret", true)]);
+        let files = unpack_container(&archive)
+            .expect("unpack_container failed")
+            .expect("Container not detected");
+        assert_eq!(files[0].name, "bin/app.exe");
+
+        let archive = tar::tests::tar_archive(&[("bin/app", b"secret")]);
+        let files = unpack_archive(&archive)
+            .expect("unpack_archive failed")
+            .expect("Archive not detected");
+        assert_eq!(files[0].name, "bin/app");
+
+        let compressed_data = gzip::tests::gzip_file(Some("bundle.tar"), b"secret");
+        let files = unpack_archive(&compressed_data)
+            .expect("unpack_archive failed")
+            .expect("Archive not detected");
+        assert_eq!(files[0].name, "bundle.tar");
+
+        assert!(unpack_container(b"\x7fELF")
+            .expect("unpack_container failed")
+            .is_none());
+    }
+}
@@ -0,0 +1,707 @@
+//! Nullsoft Scriptable Install System (NSIS) installers, which append their
+//! data to the installer's executable stub.
+//! Data is either compressed block by block, or as a whole (i.e., solid
+//! compression), with zlib (raw deflate streams) or LZMA. NSIS' variant of
+//! bzip2 isn't supported.
+//! Data blocks are named after the files the installer's script extracts them
+//! to, when the script can be parsed.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+use anyhow::{anyhow, Result};
+use flate2::read::DeflateDecoder;
+use lzma_rs::decompress::{Options, UnpackedSize};
+
+use super::{ContainedFile, MAX_DECOMPRESSED_SIZE};
+
+/// The first header is always aligned on this boundary
+const FIRST_HEADER_ALIGNMENT: usize = 512;
+const FIRST_HEADER_SIZE: usize = 28;
+const FIRST_HEADER_SIGNATURE: &[u8] = b"\xef\xbe\xad\xdeNullsoftInst";
+/// Set in blocks' sizes when their content is compressed
+const COMPRESSED_BLOCK_FLAG: u32 = 0x8000_0000;
+/// Properties byte NSIS' LZMA streams start with (i.e., lc=3, lp=0, pb=2)
+const LZMA_PROPERTIES: u8 = 0x5d;
+/// Beginning of the first block of bzip2 streams, which NSIS stores without
+/// their stream header
+const BZIP2_BLOCK_MAGIC: &[u8] = b"1AY";
+/// Indices of the script's entries and strings in the header's table of
+/// blocks, which follows the header's flags
+const ENTRIES_BLOCK_INDEX: usize = 2;
+const STRINGS_BLOCK_INDEX: usize = 3;
+/// Entries are made of an opcode followed by 6 parameters
+const ENTRY_SIZE: usize = 28;
+/// Opcode of the instruction creating a directory (and setting the output
+/// directory when its second parameter is set, i.e., `SetOutPath`)
+const CREATE_DIRECTORY_OPCODE: u32 = 11;
+/// Opcode of the instruction extracting a data block to a file (i.e., `File`)
+const EXTRACT_FILE_OPCODE: u32 = 20;
+/// Names of the built-in variables following `$0`-`$9` and `$R0`-`$R9`
+const VARIABLE_NAMES: [&str; 12] = [
+    "CMDLINE",
+    "INSTDIR",
+    "OUTDIR",
+    "EXEDIR",
+    "LANGUAGE",
+    "TEMP",
+    "PLUGINSDIR",
+    "EXEPATH",
+    "EXEFILE",
+    "HWNDPARENT",
+    "_CLICK",
+    "_OUTDIR",
+];
+
+/// Compression methods of NSIS installers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompressionMethod {
+    Deflate,
+    Lzma,
+    Bzip2,
+}
+
+impl CompressionMethod {
+    /// Deduces the compression method of a stream from its first bytes, as
+    /// NSIS doesn't record it
+    fn detect(compressed_data: &[u8]) -> Self {
+        if compressed_data.first() == Some(&LZMA_PROPERTIES) {
+            Self::Lzma
+        } else if compressed_data.starts_with(BZIP2_BLOCK_MAGIC) {
+            Self::Bzip2
+        } else {
+            Self::Deflate
+        }
+    }
+}
+
+/// Returns the offset of the installer's first header, if the given data is an
+/// NSIS installer
+pub fn find_first_header(data: &[u8]) -> Option<usize> {
+    (0..data.len().saturating_sub(FIRST_HEADER_SIZE))
+        .step_by(FIRST_HEADER_ALIGNMENT)
+        .find(|offset| data[offset + 4..].starts_with(FIRST_HEADER_SIGNATURE))
+}
+
+/// Returns the data blocks of the installer (i.e., the files it installs),
+/// decompressed if needed
+pub fn unpack(data: &[u8], first_header_offset: usize) -> Result<Vec<ContainedFile<'_>>> {
+    let header_size = read_u32(data, first_header_offset + 20).unwrap_or_default();
+    let installer_size = read_u32(data, first_header_offset + 24).unwrap_or_default() as usize;
+    let installer_end = std::cmp::min(first_header_offset + installer_size, data.len());
+    let blocks_offset = first_header_offset + FIRST_HEADER_SIZE;
+
+    // Solid installers are compressed as a whole, their blocks can only be
+    // walked once decompressed. Otherwise, the first block is the header
+    // block, which only contains the installer's script
+    let (header, data_blocks_offset) =
+        match read_header_block(data, blocks_offset, installer_end, header_size as usize) {
+            Some(header_block) => header_block,
+            None => {
+                return unpack_solid(data.get(blocks_offset..installer_end).unwrap_or_default())
+            }
+        };
+    let file_names = installed_file_names(&header);
+
+    let mut contained_files = vec![];
+    let mut block_offset = data_blocks_offset;
+    while let Some(block_size) = read_u32(data, block_offset) {
+        let content_offset = block_offset + 4;
+        let content_size = (block_size & !COMPRESSED_BLOCK_FLAG) as usize;
+        let content_end = content_offset + content_size;
+        if content_end > installer_end {
+            // Trailing checksum or invalid block
+            break;
+        }
+
+        let name = file_names
+            .get(&(block_offset - data_blocks_offset))
+            .cloned()
+            .unwrap_or_else(|| format!("data block at 0x{:x}", block_offset));
+        let content = &data[content_offset..content_end];
+        if block_size & COMPRESSED_BLOCK_FLAG == 0 {
+            contained_files.push(ContainedFile {
+                name,
+                data: Cow::Borrowed(content),
+                source_range: Some(content_offset..content_end),
+            });
+        } else {
+            match decompress(content) {
+                Ok(decompressed_data) => contained_files.push(ContainedFile {
+                    name,
+                    data: Cow::Owned(decompressed_data),
+                    source_range: None,
+                }),
+                // Keep unpacking the other blocks
+                Err(err) => log::warn!("NSIS {} cannot be unpacked: {:#}", name, err),
+            }
+        }
+        block_offset = content_end;
+    }
+
+    Ok(contained_files)
+}
+
+/// Returns the content of the header block at the given offset, decompressed
+/// if needed, and the offset of the first data block following it. The block's
+/// content must be as large as the header's size the first header records.
+fn read_header_block(
+    data: &[u8],
+    block_offset: usize,
+    installer_end: usize,
+    header_size: usize,
+) -> Option<(Cow<'_, [u8]>, usize)> {
+    let block_size = read_u32(data, block_offset)?;
+    let content_offset = block_offset + 4;
+    let content_end = content_offset + (block_size & !COMPRESSED_BLOCK_FLAG) as usize;
+    if content_end > installer_end {
+        return None;
+    }
+    let content = &data[content_offset..content_end];
+    let header = if block_size & COMPRESSED_BLOCK_FLAG == 0 {
+        Cow::Borrowed(content)
+    } else {
+        // Only the header's decompressed size is recorded
+        Cow::Owned(decompress(content).ok()?)
+    };
+
+    (header.len() == header_size).then_some((header, content_end))
+}
+
+/// Returns the data blocks of a solid installer, given the data following its
+/// first header
+fn unpack_solid(compressed_data: &[u8]) -> Result<Vec<ContainedFile<'static>>> {
+    let data = match decompress(compressed_data) {
+        Ok(data) => data,
+        Err(err) => {
+            log::warn!("NSIS installer's solid data cannot be unpacked: {:#}", err);
+            return Ok(vec![]);
+        }
+    };
+
+    // Blocks are stored as is in the decompressed data, the first one being
+    // the header block
+    let mut contained_files = vec![];
+    let header_size = read_u32(&data, 0).unwrap_or_default() as usize;
+    let data_blocks_offset = header_size.saturating_add(4);
+    let file_names = data
+        .get(4..data_blocks_offset)
+        .map(installed_file_names)
+        .unwrap_or_default();
+    let mut block_offset = data_blocks_offset;
+    while let Some(block_size) = read_u32(&data, block_offset) {
+        let content_offset = block_offset + 4;
+        let content = match data.get(content_offset..content_offset + block_size as usize) {
+            Some(content) => content,
+            None => break,
+        };
+        contained_files.push(ContainedFile {
+            name: file_names
+                .get(&(block_offset - data_blocks_offset))
+                .cloned()
+                .unwrap_or_else(|| format!("solid data block at 0x{:x}", block_offset)),
+            data: Cow::Owned(content.to_vec()),
+            source_range: None,
+        });
+        block_offset = content_offset + content.len();
+    }
+
+    Ok(contained_files)
+}
+
+/// Walks the installer's script to map the offsets of data blocks (relative to
+/// the first data block) to the paths of the files they're extracted to.
+/// Control flow is ignored, the script's instructions are walked in order.
+fn installed_file_names(header: &[u8]) -> HashMap<usize, String> {
+    let mut file_names = HashMap::new();
+    // The header starts with flags followed by a table of (offset, count)
+    // pairs locating the script's blocks
+    let block = |index: usize| {
+        let entry_offset = 4 + index * 8;
+        Some((
+            read_u32(header, entry_offset)? as usize,
+            read_u32(header, entry_offset + 4)? as usize,
+        ))
+    };
+    let ((entries_offset, entry_count), (strings_offset, _)) =
+        match (block(ENTRIES_BLOCK_INDEX), block(STRINGS_BLOCK_INDEX)) {
+            (Some(entries), Some(strings)) => (entries, strings),
+            _ => return file_names,
+        };
+    let strings = header.get(strings_offset..).unwrap_or_default();
+    let encoding = StringEncoding::detect(strings);
+
+    // Files are extracted to the output directory unless their path is absolute
+    let mut output_directory = "$OUTDIR".to_string();
+    let entries = header
+        .get(entries_offset..)
+        .unwrap_or_default()
+        .chunks_exact(ENTRY_SIZE)
+        .take(entry_count);
+    for entry in entries {
+        let parameter = |index: usize| read_u32(entry, 4 + index * 4).unwrap_or_default();
+        match read_u32(entry, 0) {
+            Some(CREATE_DIRECTORY_OPCODE) if parameter(1) != 0 => {
+                if let Some(path) = encoding.read_string(strings, parameter(0)) {
+                    output_directory = path;
+                }
+            }
+            Some(EXTRACT_FILE_OPCODE) => {
+                let name = match encoding.read_string(strings, parameter(1)) {
+                    Some(name) => name,
+                    None => continue,
+                };
+                let path = if name.starts_with('$') || name.contains(':') {
+                    name
+                } else {
+                    format!("{}\\{}", output_directory, name)
+                };
+                // Identical files share the same data block
+                file_names.entry(parameter(2) as usize).or_insert(path);
+            }
+            _ => {}
+        }
+    }
+
+    file_names
+}
+
+/// Encodings of the script's strings, which embed special codes referencing
+/// variables, shell folders and language strings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StringEncoding {
+    /// Single-byte characters with NSIS 2's codes
+    Nsis2Ansi,
+    /// Single-byte characters with NSIS 3's codes
+    Nsis3Ansi,
+    /// UTF-16 characters with NSIS 3's codes (i.e., Unicode installers)
+    Nsis3Unicode,
+}
+
+/// Special codes, each followed by a parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StringCode {
+    /// The next character is a literal
+    Skip,
+    Variable,
+    ShellFolder,
+    LanguageString,
+}
+
+impl StringEncoding {
+    /// Deduces the encoding of the strings table, whose first string is
+    /// always empty
+    fn detect(strings: &[u8]) -> Self {
+        if strings.starts_with(&[0, 0]) {
+            Self::Nsis3Unicode
+        } else if strings.iter().any(|byte| (1..=4).contains(byte)) {
+            // Characters NSIS 3 uses as codes are escaped otherwise
+            Self::Nsis3Ansi
+        } else {
+            Self::Nsis2Ansi
+        }
+    }
+
+    fn code(self, unit: u16) -> Option<StringCode> {
+        let (lang, shell, variable, skip) = match self {
+            Self::Nsis2Ansi => (255, 254, 253, 252),
+            Self::Nsis3Ansi | Self::Nsis3Unicode => (1, 2, 3, 4),
+        };
+        match unit {
+            unit if unit == lang => Some(StringCode::LanguageString),
+            unit if unit == shell => Some(StringCode::ShellFolder),
+            unit if unit == variable => Some(StringCode::Variable),
+            unit if unit == skip => Some(StringCode::Skip),
+            _ => None,
+        }
+    }
+
+    /// Reads the string at the given offset (in characters) of the strings
+    /// table, rendering variables like NSIS scripts do
+    fn read_string(self, strings: &[u8], offset: u32) -> Option<String> {
+        // Negative offsets reference language strings
+        if offset > i32::MAX as u32 {
+            return None;
+        }
+        let unit_size = match self {
+            Self::Nsis2Ansi | Self::Nsis3Ansi => 1,
+            Self::Nsis3Unicode => 2,
+        };
+        let mut units = strings
+            .get(offset as usize * unit_size..)?
+            .chunks_exact(unit_size)
+            .map(|unit| match unit {
+                [byte] => u16::from(*byte),
+                _ => u16::from_le_bytes([unit[0], unit[1]]),
+            });
+        // Parameters take two bytes, in one or two characters
+        let parameter = |units: &mut dyn Iterator<Item = u16>| -> Option<[u8; 2]> {
+            if unit_size == 1 {
+                Some([units.next()? as u8, units.next()? as u8])
+            } else {
+                Some(units.next()?.to_le_bytes())
+            }
+        };
+
+        let mut string = vec![];
+        loop {
+            let unit = units.next()?;
+            if unit == 0 {
+                break;
+            }
+            let rendered = match self.code(unit) {
+                None => {
+                    string.push(unit);
+                    continue;
+                }
+                Some(StringCode::Skip) => {
+                    string.push(units.next()?);
+                    continue;
+                }
+                Some(StringCode::Variable) => {
+                    let [low, high] = parameter(&mut units)?;
+                    variable_name(usize::from(low & 0x7f) | usize::from(high & 0x7f) << 7)
+                }
+                Some(StringCode::ShellFolder) => {
+                    let [folder, _] = parameter(&mut units)?;
+                    format!("$SHELLFOLDER(0x{:02x})", folder)
+                }
+                Some(StringCode::LanguageString) => {
+                    let [low, high] = parameter(&mut units)?;
+                    let id = usize::from(low & 0x7f) | usize::from(high & 0x7f) << 7;
+                    format!("$(LSTR_{})", id)
+                }
+            };
+            string.extend(rendered.encode_utf16());
+        }
+
+        // Single-byte strings are decoded as Latin-1, as their code page is
+        // unknown
+        Some(String::from_utf16_lossy(&string))
+    }
+}
+
+/// Returns the name of the variable with the given index, as written in
+/// NSIS scripts
+fn variable_name(index: usize) -> String {
+    match index {
+        0..=9 => format!("${}", index),
+        10..=19 => format!("$R{}", index - 10),
+        _ => match VARIABLE_NAMES.get(index - 20) {
+            Some(name) => format!("${}", name),
+            None => format!("$_{}", index - 20 - VARIABLE_NAMES.len()),
+        },
+    }
+}
+
+/// Decompresses a stream compressed with one of the methods NSIS supports
+fn decompress(compressed_data: &[u8]) -> Result<Vec<u8>> {
+    let mut decompressed_data = vec![];
+    match CompressionMethod::detect(compressed_data) {
+        CompressionMethod::Deflate => {
+            DeflateDecoder::new(compressed_data)
+                .take(MAX_DECOMPRESSED_SIZE as u64 + 1)
+                .read_to_end(&mut decompressed_data)?;
+            if decompressed_data.len() > MAX_DECOMPRESSED_SIZE {
+                return Err(anyhow!("Decompressed data is too large"));
+            }
+        }
+        CompressionMethod::Lzma => {
+            // Streams only start with LZMA properties and the dictionary's
+            // size, the data's size being unknown
+            let options = Options {
+                unpacked_size: UnpackedSize::UseProvided(None),
+                ..Default::default()
+            };
+            let result = lzma_rs::lzma_decompress_with_options(
+                &mut &compressed_data[..],
+                &mut LimitedWriter(&mut decompressed_data),
+                &options,
+            );
+            // Streams may lack an end marker, what's been decompressed is
+            // still worth scanning
+            if let Err(err) = result {
+                if decompressed_data.is_empty() {
+                    return Err(anyhow!("Invalid LZMA stream: {}", err));
+                }
+                log::debug!("Incomplete NSIS LZMA stream: {}", err);
+            }
+        }
+        CompressionMethod::Bzip2 => {
+            return Err(anyhow!("bzip2 compression isn't supported"));
+        }
+    }
+
+    Ok(decompressed_data)
+}
+
+/// Writer failing once more than `MAX_DECOMPRESSED_SIZE` bytes are written
+struct LimitedWriter<'b>(&'b mut Vec<u8>);
+
+impl Write for LimitedWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.0.len() + buf.len() > MAX_DECOMPRESSED_SIZE {
+            return Err(io::Error::other("Decompressed data is too large"));
+        }
+        self.0.extend_from_slice(buf);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use flate2::write::DeflateEncoder;
+    use flate2::Compression;
+    use lzma_rs::compress;
+
+    use super::*;
+
+    const HEADER: &[u8] = b"script";
+
+    /// Compresses the given data like NSIS does with the given method
+    fn compress(data: &[u8], method: CompressionMethod) -> Vec<u8> {
+        match method {
+            CompressionMethod::Deflate => {
+                let mut encoder = DeflateEncoder::new(vec![], Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+            CompressionMethod::Lzma => {
+                let mut compressed_data = vec![];
+                lzma_rs::lzma_compress_with_options(
+                    &mut &data[..],
+                    &mut compressed_data,
+                    &compress::Options {
+                        unpacked_size: compress::UnpackedSize::SkipWritingToHeader,
+                    },
+                )
+                .unwrap();
+                compressed_data
+            }
+            CompressionMethod::Bzip2 => [BZIP2_BLOCK_MAGIC, data].concat(),
+        }
+    }
+
+    /// Builds an installer containing a stub, followed by the first header
+    /// (recording the given header's size) and the given data
+    fn nsis_installer(header: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut installer = vec![0; FIRST_HEADER_ALIGNMENT];
+        installer.extend(0u32.to_le_bytes());
+        installer.extend(FIRST_HEADER_SIGNATURE);
+        installer.extend((header.len() as u32).to_le_bytes());
+        installer.extend(((FIRST_HEADER_SIZE + data.len()) as u32).to_le_bytes());
+        installer.extend(data);
+
+        installer
+    }
+
+    /// Returns the given blocks, preceded by the given header block and with
+    /// the flags of compressed blocks set if `flag_compressed_blocks` is set
+    fn nsis_blocks(
+        header_block: (&[u8], bool),
+        blocks: &[(Vec<u8>, bool)],
+        flag_compressed_blocks: bool,
+    ) -> Vec<u8> {
+        let mut data = vec![];
+        let (header, header_compressed) = header_block;
+        for (content, compressed) in std::iter::once((header, &header_compressed)).chain(
+            blocks
+                .iter()
+                .map(|(content, compressed)| (&content[..], compressed)),
+        ) {
+            let mut size = content.len() as u32;
+            if *compressed && flag_compressed_blocks {
+                size |= COMPRESSED_BLOCK_FLAG;
+            }
+            data.extend(size.to_le_bytes());
+            data.extend(content);
+        }
+
+        data
+    }
+
+    /// Builds a header containing the given script entries and strings table
+    fn nsis_header(entries: &[[u32; 7]], strings: &[u8]) -> Vec<u8> {
+        let entries_offset = 4 + 8 * 8;
+        let strings_offset = entries_offset + entries.len() * ENTRY_SIZE;
+        let mut header = vec![0; entries_offset];
+        header[4 + ENTRIES_BLOCK_INDEX * 8..][..8].copy_from_slice(
+            &[
+                (entries_offset as u32).to_le_bytes(),
+                (entries.len() as u32).to_le_bytes(),
+            ]
+            .concat(),
+        );
+        header[4 + STRINGS_BLOCK_INDEX * 8..][..4]
+            .copy_from_slice(&(strings_offset as u32).to_le_bytes());
+        for entry in entries {
+            header.extend(entry.iter().flat_map(|value| value.to_le_bytes()));
+        }
+        header.extend(strings);
+
+        header
+    }
+
+    /// Header of a script extracting a file to `$INSTDIR\bin` and another
+    /// one to `$INSTDIR`, given the offset of the second file's block
+    fn nsis_script_header(second_block_offset: u32) -> Vec<u8> {
+        // `$INSTDIR` is encoded as NSIS 3's variable code followed by the
+        // variable's index
+        let strings = b"\0\x03\x95\x80\\bin\0app.exe\0\x03\x95\x80\\config.ini\0";
+        nsis_header(
+            &[
+                [CREATE_DIRECTORY_OPCODE, 1, 1, 0, 0, 0, 0],
+                [EXTRACT_FILE_OPCODE, 0, 9, 0, 0, 0, 0],
+                [EXTRACT_FILE_OPCODE, 0, 17, second_block_offset, 0, 0, 0],
+            ],
+            strings,
+        )
+    }
+
+    #[test]
+    fn unpack_uncompressed_blocks() {
+        let installer = nsis_installer(
+            HEADER,
+            &nsis_blocks((HEADER, false), &[(b"secret".to_vec(), false)], true),
+        );
+        let first_header_offset = find_first_header(&installer).expect("Not an NSIS installer");
+        assert_eq!(first_header_offset, FIRST_HEADER_ALIGNMENT);
+
+        let files = unpack(&installer, first_header_offset).expect("unpack failed");
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].data.as_ref(), b"secret");
+        assert_eq!(files[0].name, "data block at 0x226");
+        let source_range = files[0].source_range.clone().unwrap();
+        assert_eq!(&installer[source_range], b"secret");
+    }
+
+    #[test]
+    fn unpack_compressed_blocks() {
+        let installer = nsis_installer(
+            HEADER,
+            &nsis_blocks(
+                (HEADER, false),
+                &[
+                    (
+                        compress(b"deflate_secret", CompressionMethod::Deflate),
+                        true,
+                    ),
+                    (compress(b"lzma_secret", CompressionMethod::Lzma), true),
+                    (compress(b"bzip2_secret", CompressionMethod::Bzip2), true),
+                    (b"stored_secret".to_vec(), false),
+                ],
+                true,
+            ),
+        );
+
+        let files = unpack(&installer, FIRST_HEADER_ALIGNMENT).expect("unpack failed");
+        let contents: Vec<&[u8]> = files.iter().map(|file| file.data.as_ref()).collect();
+        // bzip2 blocks are skipped
+        assert_eq!(
+            contents,
+            vec![
+                b"deflate_secret".as_slice(),
+                b"lzma_secret",
+                b"stored_secret"
+            ]
+        );
+        assert!(files[0].source_range.is_none());
+    }
+
+    #[test]
+    fn unpack_solid_installer() {
+        let blocks = nsis_blocks(
+            (HEADER, false),
+            &[(b"secret".to_vec(), false), (b"other".to_vec(), false)],
+            false,
+        );
+        for method in [CompressionMethod::Deflate, CompressionMethod::Lzma] {
+            let installer = nsis_installer(HEADER, &compress(&blocks, method));
+
+            let files = unpack(&installer, FIRST_HEADER_ALIGNMENT).expect("unpack failed");
+            assert_eq!(files.len(), 2);
+            assert_eq!(files[0].data.as_ref(), b"secret");
+            assert_eq!(files[0].name, "solid data block at 0xa");
+            assert_eq!(files[1].data.as_ref(), b"other");
+        }
+
+        let installer = nsis_installer(HEADER, &compress(&blocks, CompressionMethod::Bzip2));
+        let files = unpack(&installer, FIRST_HEADER_ALIGNMENT).expect("unpack failed");
+        assert!(files.is_empty());
+    }
+
+    #[test]
+    fn name_blocks_after_installed_files() {
+        let blocks = [(b"secret".to_vec(), false), (b"other".to_vec(), false)];
+        let header = nsis_script_header(4 + blocks[0].0.len() as u32);
+        let expected_names = ["$INSTDIR\\bin\\app.exe", "$INSTDIR\\config.ini"];
+
+        // Compressed header block
+        let installer = nsis_installer(
+            &header,
+            &nsis_blocks(
+                (&compress(&header, CompressionMethod::Deflate), true),
+                &blocks,
+                true,
+            ),
+        );
+        let files = unpack(&installer, FIRST_HEADER_ALIGNMENT).expect("unpack failed");
+        let names: Vec<&str> = files.iter().map(|file| file.name.as_str()).collect();
+        assert_eq!(names, expected_names);
+        assert_eq!(files[0].data.as_ref(), b"secret");
+
+        // Solid installer
+        let installer = nsis_installer(
+            &header,
+            &compress(
+                &nsis_blocks((&header, false), &blocks, false),
+                CompressionMethod::Lzma,
+            ),
+        );
+        let files = unpack(&installer, FIRST_HEADER_ALIGNMENT).expect("unpack failed");
+        let names: Vec<&str> = files.iter().map(|file| file.name.as_str()).collect();
+        assert_eq!(names, expected_names);
+    }
+
+    #[test]
+    fn read_script_strings() {
+        // NSIS 3's Unicode strings
+        let strings: Vec<u8> = [0, 3, 0x8095, u16::from(b'\\'), 0xe9, 0, 0x4, 3, 0]
+            .iter()
+            .flat_map(|unit: &u16| unit.to_le_bytes())
+            .collect();
+        let encoding = StringEncoding::detect(&strings);
+        assert_eq!(encoding, StringEncoding::Nsis3Unicode);
+        assert_eq!(
+            encoding.read_string(&strings, 1).as_deref(),
+            Some("$INSTDIR\\é")
+        );
+        assert_eq!(encoding.read_string(&strings, 6).as_deref(), Some("\u{3}"));
+
+        // NSIS 2's single-byte strings
+        let strings = b"\0\xfd\x8a\x80\\\xfe\x1a\x1a\\app\xfc\xff\0";
+        let encoding = StringEncoding::detect(strings);
+        assert_eq!(encoding, StringEncoding::Nsis2Ansi);
+        assert_eq!(
+            encoding.read_string(strings, 1).as_deref(),
+            Some("$R0\\$SHELLFOLDER(0x1a)\\app\u{ff}")
+        );
+        assert_eq!(encoding.read_string(strings, u32::MAX), None);
+    }
+
+    #[test]
+    fn detect_nsis_installer() {
+        assert!(find_first_header(&[0; 2048]).is_none());
+    }
+}
@@ -0,0 +1,79 @@
+//! Static libraries (i.e., `.a` and `.lib` archives), which contain object
+//! files stored as is. Scanning them allows catching leaks before the final
+//! link.
+
+use std::borrow::Cow;
+
+use anyhow::Result;
+use object::read::archive::ArchiveFile;
+
+use super::ContainedFile;
+
+/// Returns `true` if the given data looks like a static library
+pub fn is_static_library(data: &[u8]) -> bool {
+    data.starts_with(&object::archive::MAGIC)
+}
+
+/// Returns the members of the library (usually, object files)
+pub fn unpack(data: &[u8]) -> Result<Vec<ContainedFile<'_>>> {
+    let archive = ArchiveFile::parse(data)?;
+
+    archive
+        .members()
+        .map(|member| {
+            let member = member?;
+            let (offset, size) = member.file_range();
+            let start = offset as usize;
+
+            Ok(ContainedFile {
+                name: String::from_utf8_lossy(member.name()).into_owned(),
+                data: Cow::Borrowed(member.data(data)?),
+                source_range: Some(start..start + size as usize),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// Builds a GNU archive containing the given files
+    pub fn gnu_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut archive = object::archive::MAGIC.to_vec();
+        for (name, data) in files {
+            let header = format!(
+                "{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n",
+                format!("{}/", name),
+                0,
+                0,
+                0,
+                644,
+                data.len()
+            );
+            archive.extend(header.as_bytes());
+            archive.extend(*data);
+            // Members are aligned on 2 bytes
+            if archive.len() % 2 == 1 {
+                archive.push(b'\n');
+            }
+        }
+
+        archive
+    }
+
+    #[test]
+    fn unpack_gnu_archive() {
+        let archive = gnu_archive(&[("first.o", b"secret"), ("second.o", b"other")]);
+        assert!(is_static_library(&archive));
+
+        let files = unpack(&archive).expect("unpack failed");
+        let names: Vec<&str> = files.iter().map(|file| file.name.as_str()).collect();
+        assert_eq!(names, ["first.o", "second.o"]);
+        for file in &files {
+            let range = file.source_range.clone().unwrap();
+            assert_eq!(&archive[range], file.data.as_ref());
+        }
+        assert_eq!(files[1].data.as_ref(), b"other");
+    }
+}
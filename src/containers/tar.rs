@@ -0,0 +1,156 @@
+//! Tar archives (e.g., release bundles), whose files are stored as is after
+//! 512-byte headers.
+
+use std::borrow::Cow;
+
+use anyhow::{anyhow, Result};
+
+use super::ContainedFile;
+
+const BLOCK_SIZE: usize = 512;
+const MAGIC_OFFSET: usize = 257;
+/// Both POSIX (`ustar\0`) and GNU (`ustar `) archives start with this magic
+const MAGIC: &[u8] = b"ustar";
+
+/// Returns `true` if the given data looks like a tar archive
+pub fn is_tar_archive(data: &[u8]) -> bool {
+    data.len() >= BLOCK_SIZE && data[MAGIC_OFFSET..].starts_with(MAGIC)
+}
+
+/// Returns the regular files stored in the archive
+pub fn unpack(data: &[u8]) -> Result<Vec<ContainedFile<'_>>> {
+    let mut contained_files = vec![];
+    // Name given by a GNU long name or a PAX extended header to the next file
+    let mut next_name = None;
+    let mut offset = 0;
+    while let Some(header) = data.get(offset..offset + BLOCK_SIZE) {
+        // The archive ends with empty blocks
+        if header.iter().all(|byte| *byte == 0) {
+            break;
+        }
+
+        let size = parse_octal(&header[124..136])
+            .ok_or_else(|| anyhow!("Invalid tar archive: malformed file size"))?;
+        let start = offset + BLOCK_SIZE;
+        let file_data = data
+            .get(start..start + size)
+            .ok_or_else(|| anyhow!("Invalid tar archive: truncated file"))?;
+        match header[156] {
+            // Regular files
+            b'0' | b'\0' | b'7' => {
+                let name = next_name.take().unwrap_or_else(|| header_name(header));
+                contained_files.push(ContainedFile {
+                    name,
+                    data: Cow::Borrowed(file_data),
+                    source_range: Some(start..start + size),
+                });
+            }
+            b'L' => next_name = Some(null_terminated_string(file_data)),
+            b'x' => next_name = pax_path(file_data).or(next_name),
+            // Directories, links, etc.
+            _ => next_name = None,
+        }
+
+        // Content is padded to a multiple of the block size
+        offset = start + size + padding_size(size);
+    }
+
+    Ok(contained_files)
+}
+
+/// Returns the size of the padding following content of the given size
+fn padding_size(size: usize) -> usize {
+    (BLOCK_SIZE - size % BLOCK_SIZE) % BLOCK_SIZE
+}
+
+/// Returns the name stored in the header, including the USTAR prefix
+fn header_name(header: &[u8]) -> String {
+    let name = null_terminated_string(&header[..100]);
+    let prefix = if header[MAGIC_OFFSET..].starts_with(b"ustar\0") {
+        null_terminated_string(&header[345..500])
+    } else {
+        String::new()
+    };
+
+    if prefix.is_empty() {
+        name
+    } else {
+        format!("{}/{}", prefix, name)
+    }
+}
+
+/// Returns the `path` record of a PAX extended header, if any. Records are
+/// written as "<length> <key>=<value>\n".
+fn pax_path(data: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(data).lines().find_map(|record| {
+        let (_, key_value) = record.split_once(' ')?;
+        key_value.strip_prefix("path=").map(str::to_string)
+    })
+}
+
+fn null_terminated_string(data: &[u8]) -> String {
+    let end = data
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..end]).into_owned()
+}
+
+/// Parses numeric fields, written as octal strings terminated by spaces or
+/// null bytes
+fn parse_octal(field: &[u8]) -> Option<usize> {
+    let digits = String::from_utf8_lossy(field);
+    let digits = digits.trim_matches(|c: char| c == ' ' || c == '\0');
+    if digits.is_empty() {
+        Some(0)
+    } else {
+        usize::from_str_radix(digits, 8).ok()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// Builds a USTAR archive containing the given files
+    pub fn tar_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut archive = vec![];
+        for (name, data) in files {
+            let mut header = vec![0; BLOCK_SIZE];
+            header[..name.len()].copy_from_slice(name.as_bytes());
+            header[124..135].copy_from_slice(format!("{:011o}", data.len()).as_bytes());
+            header[156] = b'0';
+            header[MAGIC_OFFSET..MAGIC_OFFSET + 8].copy_from_slice(b"ustar\x0000");
+            archive.extend(header);
+            archive.extend(*data);
+            archive.resize(archive.len() + padding_size(data.len()), 0);
+        }
+        archive.extend([0; 2 * BLOCK_SIZE]);
+
+        archive
+    }
+
+    #[test]
+    fn unpack_tar_archive() {
+        let archive = tar_archive(&[("bin/app", b"secret"), ("lib/libapp.so", b"other")]);
+        assert!(is_tar_archive(&archive));
+
+        let files = unpack(&archive).expect("unpack failed");
+        let names: Vec<&str> = files.iter().map(|file| file.name.as_str()).collect();
+        assert_eq!(names, ["bin/app", "lib/libapp.so"]);
+        for file in &files {
+            let range = file.source_range.clone().unwrap();
+            assert_eq!(&archive[range], file.data.as_ref());
+        }
+        assert_eq!(files[1].data.as_ref(), b"other");
+    }
+
+    #[test]
+    fn parse_pax_path() {
+        assert_eq!(
+            pax_path(b"30 mtime=1700000000.123456789\n27 path=very/long/file/name\n"),
+            Some("very/long/file/name".to_string())
+        );
+        assert_eq!(pax_path(b"20 uid=1000\n"), None);
+    }
+}
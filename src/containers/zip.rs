@@ -0,0 +1,223 @@
+//! ZIP archives (e.g., release bundles), whose files are either stored as is or
+//! compressed with Deflate.
+
+use std::borrow::Cow;
+use std::io::Read;
+
+use anyhow::{anyhow, Result};
+use flate2::read::DeflateDecoder;
+
+use super::{ContainedFile, MAX_DECOMPRESSED_SIZE};
+
+const LOCAL_HEADER_SIGNATURE: &[u8] = b"PK\x03\x04";
+const LOCAL_HEADER_SIZE: usize = 30;
+const CENTRAL_HEADER_SIGNATURE: &[u8] = b"PK\x01\x02";
+const CENTRAL_HEADER_SIZE: usize = 46;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: &[u8] = b"PK\x05\x06";
+const END_OF_CENTRAL_DIRECTORY_SIZE: usize = 22;
+/// The end of central directory record is followed by a comment of at most
+/// this size
+const MAX_COMMENT_SIZE: usize = 0xffff;
+
+const STORED_METHOD: u16 = 0;
+const DEFLATE_METHOD: u16 = 8;
+const ENCRYPTED_FLAG: u16 = 1;
+
+/// Returns `true` if the given data looks like a ZIP archive
+pub fn is_zip_archive(data: &[u8]) -> bool {
+    data.starts_with(LOCAL_HEADER_SIGNATURE)
+}
+
+/// Returns the files stored in the archive. Files that cannot be extracted
+/// (e.g., encrypted files) are skipped.
+pub fn unpack(data: &[u8]) -> Result<Vec<ContainedFile<'_>>> {
+    let end_offset = find_end_of_central_directory(data)
+        .ok_or_else(|| anyhow!("ZIP archive has no central directory"))?;
+    let entry_count = read_u16(data, end_offset + 10).ok_or_else(invalid_archive)?;
+    let mut offset = read_u32(data, end_offset + 16).ok_or_else(invalid_archive)? as usize;
+
+    let mut contained_files = vec![];
+    for _ in 0..entry_count {
+        if data.get(offset..offset + 4) != Some(CENTRAL_HEADER_SIGNATURE) {
+            return Err(invalid_archive());
+        }
+        let flags = read_u16(data, offset + 8).ok_or_else(invalid_archive)?;
+        let method = read_u16(data, offset + 10).ok_or_else(invalid_archive)?;
+        let compressed_size = read_u32(data, offset + 20).ok_or_else(invalid_archive)? as usize;
+        let size = read_u32(data, offset + 24).ok_or_else(invalid_archive)? as usize;
+        let name_size = read_u16(data, offset + 28).ok_or_else(invalid_archive)? as usize;
+        let extra_size = read_u16(data, offset + 30).ok_or_else(invalid_archive)? as usize;
+        let comment_size = read_u16(data, offset + 32).ok_or_else(invalid_archive)? as usize;
+        let local_header_offset = read_u32(data, offset + 42).ok_or_else(invalid_archive)? as usize;
+        let name_offset = offset + CENTRAL_HEADER_SIZE;
+        let name = data
+            .get(name_offset..name_offset + name_size)
+            .ok_or_else(invalid_archive)?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        offset = name_offset + name_size + extra_size + comment_size;
+
+        // Directories have no content
+        if name.ends_with('/') {
+            continue;
+        }
+        if flags & ENCRYPTED_FLAG != 0 {
+            log::warn!("'{}' is encrypted and cannot be unpacked", name);
+            continue;
+        }
+        if compressed_size == u32::MAX as usize || size == u32::MAX as usize {
+            log::warn!(
+                "'{}' is stored in ZIP64 format and cannot be unpacked",
+                name
+            );
+            continue;
+        }
+
+        // Local headers' variable fields may differ from the central ones
+        if data.get(local_header_offset..local_header_offset + 4) != Some(LOCAL_HEADER_SIGNATURE) {
+            return Err(invalid_archive());
+        }
+        let local_name_size =
+            read_u16(data, local_header_offset + 26).ok_or_else(invalid_archive)? as usize;
+        let local_extra_size =
+            read_u16(data, local_header_offset + 28).ok_or_else(invalid_archive)? as usize;
+        let start = local_header_offset + LOCAL_HEADER_SIZE + local_name_size + local_extra_size;
+        let file_data = data
+            .get(start..start + compressed_size)
+            .ok_or_else(invalid_archive)?;
+
+        match method {
+            STORED_METHOD => contained_files.push(ContainedFile {
+                name,
+                data: Cow::Borrowed(file_data),
+                source_range: Some(start..start + compressed_size),
+            }),
+            DEFLATE_METHOD if size > MAX_DECOMPRESSED_SIZE => {
+                log::warn!("'{}' is too large to be unpacked", name);
+            }
+            DEFLATE_METHOD => {
+                let mut decompressed_data = Vec::with_capacity(size);
+                DeflateDecoder::new(file_data)
+                    .take(size as u64)
+                    .read_to_end(&mut decompressed_data)?;
+                contained_files.push(ContainedFile {
+                    name,
+                    data: Cow::Owned(decompressed_data),
+                    source_range: None,
+                });
+            }
+            _ => log::warn!(
+                "'{}' is compressed with an unsupported method ({}) and cannot be unpacked",
+                name,
+                method
+            ),
+        }
+    }
+
+    Ok(contained_files)
+}
+
+/// Returns the offset of the end of central directory record
+fn find_end_of_central_directory(data: &[u8]) -> Option<usize> {
+    let last_offset = data.len().checked_sub(END_OF_CENTRAL_DIRECTORY_SIZE)?;
+    let first_offset = last_offset.saturating_sub(MAX_COMMENT_SIZE);
+
+    (first_offset..=last_offset)
+        .rev()
+        .find(|offset| data[*offset..].starts_with(END_OF_CENTRAL_DIRECTORY_SIGNATURE))
+}
+
+fn invalid_archive() -> anyhow::Error {
+    anyhow!("Invalid ZIP archive")
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::io::Write;
+
+    use flate2::write::DeflateEncoder;
+    use flate2::Compression;
+
+    use super::*;
+
+    /// Builds a ZIP archive containing the given files, compressed with
+    /// Deflate if requested
+    pub fn zip_archive(files: &[(&str, &[u8], bool)]) -> Vec<u8> {
+        let mut archive = vec![];
+        let mut central_directory = vec![];
+        for (name, data, compress) in files {
+            let (method, stored_data) = if *compress {
+                let mut encoder = DeflateEncoder::new(vec![], Compression::default());
+                encoder.write_all(data).unwrap();
+                (DEFLATE_METHOD, encoder.finish().unwrap())
+            } else {
+                (STORED_METHOD, data.to_vec())
+            };
+            let local_header_offset = archive.len() as u32;
+
+            // Note: CRCs aren't checked
+            archive.extend(LOCAL_HEADER_SIGNATURE);
+            archive.extend([20, 0, 0, 0]);
+            archive.extend(method.to_le_bytes());
+            archive.extend([0; 8]);
+            archive.extend((stored_data.len() as u32).to_le_bytes());
+            archive.extend((data.len() as u32).to_le_bytes());
+            archive.extend((name.len() as u16).to_le_bytes());
+            archive.extend([0, 0]);
+            archive.extend(name.as_bytes());
+            archive.extend(&stored_data);
+
+            central_directory.extend(CENTRAL_HEADER_SIGNATURE);
+            central_directory.extend([20, 0, 20, 0, 0, 0]);
+            central_directory.extend(method.to_le_bytes());
+            central_directory.extend([0; 8]);
+            central_directory.extend((stored_data.len() as u32).to_le_bytes());
+            central_directory.extend((data.len() as u32).to_le_bytes());
+            central_directory.extend((name.len() as u16).to_le_bytes());
+            central_directory.extend([0; 12]);
+            central_directory.extend(local_header_offset.to_le_bytes());
+            central_directory.extend(name.as_bytes());
+        }
+
+        let central_directory_offset = archive.len() as u32;
+        archive.extend(&central_directory);
+        archive.extend(END_OF_CENTRAL_DIRECTORY_SIGNATURE);
+        archive.extend([0; 4]);
+        archive.extend((files.len() as u16).to_le_bytes());
+        archive.extend((files.len() as u16).to_le_bytes());
+        archive.extend((central_directory.len() as u32).to_le_bytes());
+        archive.extend(central_directory_offset.to_le_bytes());
+        archive.extend([0, 0]);
+
+        archive
+    }
+
+    #[test]
+    fn unpack_zip_archive() {
+        let archive = zip_archive(&[
+            ("bin/app.exe", b"stored secret", false),
+            ("lib/app.dll", b"compressed secret", true),
+        ]);
+        assert!(is_zip_archive(&archive));
+
+        let files = unpack(&archive).expect("unpack failed");
+        let names: Vec<&str> = files.iter().map(|file| file.name.as_str()).collect();
+        assert_eq!(names, ["bin/app.exe", "lib/app.dll"]);
+
+        // Stored files are borrowed from the archive
+        let range = files[0].source_range.clone().unwrap();
+        assert_eq!(&archive[range], b"stored secret");
+        // Compressed files are inflated
+        assert!(files[1].source_range.is_none());
+        assert_eq!(files[1].data.as_ref(), b"compressed secret");
+    }
+}
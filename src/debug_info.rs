@@ -0,0 +1,131 @@
+//! Distinction between leaks visible at runtime and leaks only found in debug
+//! information (e.g., DWARF sections, PDB paths), which go away when binaries
+//! are stripped.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::information_leak::{ConfirmedLeak, SourceLocation};
+
+/// Where leaked data can be observed in a binary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    /// Found in loadable parts of the binary (e.g., read-only data)
+    Runtime,
+    /// Only found in the binary's debug information
+    DebugOnly,
+}
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Visibility::Runtime => "runtime",
+            Visibility::DebugOnly => "debug-only",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Assigns a visibility to each confirmed leak. Artifacts are debug-only in a
+/// binary if all of their matches in this binary are part of its debug
+/// information. Leaks found in binaries whose format isn't supported aren't
+/// classified.
+pub fn classify_leak_visibility<SortedConfirmedLeak>(
+    confirmed_leaks: BTreeSet<SortedConfirmedLeak>,
+) -> BTreeSet<SortedConfirmedLeak>
+where
+    SortedConfirmedLeak: Into<ConfirmedLeak> + From<ConfirmedLeak> + Ord,
+{
+    let confirmed_leaks: Vec<ConfirmedLeak> = confirmed_leaks.into_iter().map(Into::into).collect();
+    // Artifacts matched at least once outside of debug information, per binary
+    let runtime_artifacts: HashSet<(Arc<PathBuf>, Arc<SourceLocation>)> = confirmed_leaks
+        .iter()
+        .filter(|leak| !leak.location.binary.debug_info)
+        .map(|leak| {
+            (
+                leak.location.binary.file.clone(),
+                leak.location.source.clone(),
+            )
+        })
+        .collect();
+
+    confirmed_leaks
+        .into_iter()
+        .map(|mut leak| {
+            // Regions are only known for supported formats
+            if leak.location.binary.region.is_some() {
+                let key = (
+                    leak.location.binary.file.clone(),
+                    leak.location.source.clone(),
+                );
+                leak.visibility = Some(if runtime_artifacts.contains(&key) {
+                    Visibility::Runtime
+                } else {
+                    Visibility::DebugOnly
+                });
+            }
+            SortedConfirmedLeak::from(leak)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::information_leak::{
+        BinaryLocation, ConfirmedLeakWithUniqueLocation, LeakedDataType,
+    };
+
+    fn confirmed_leak(line: u64, offset: u64, region: Option<&str>) -> ConfirmedLeak {
+        let mut binary = BinaryLocation::new(Arc::new(PathBuf::from("a.out")), offset);
+        binary.region = region.map(|region| Arc::new(region.to_string()));
+        binary.debug_info = matches!(region, Some(region) if region.contains(".debug"));
+
+        ConfirmedLeak::builder(
+            LeakedDataType::StringLiteral,
+            Arc::new(format!("leak{}", line)),
+            Arc::new(SourceLocation {
+                file: PathBuf::from("main.cc"),
+                line,
+            }),
+            binary,
+        )
+        .size(4)
+        .build()
+    }
+
+    #[test]
+    fn classify_debug_only_leaks() {
+        let leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> = [
+            // Only in debug information
+            confirmed_leak(1, 0x100, Some("section \".debug_str\"")),
+            // Also in read-only data
+            confirmed_leak(2, 0x200, Some("section \".debug_str\"")),
+            confirmed_leak(2, 0x300, Some("section \".rodata\"")),
+            // Unknown format
+            confirmed_leak(3, 0x400, None),
+        ]
+        .into_iter()
+        .map(ConfirmedLeakWithUniqueLocation::from)
+        .collect();
+
+        let visibilities: Vec<(u64, Option<Visibility>)> = classify_leak_visibility(leaks)
+            .iter()
+            .map(|leak| (leak.location.source.line, leak.visibility))
+            .collect();
+        assert_eq!(
+            visibilities,
+            vec![
+                (1, Some(Visibility::DebugOnly)),
+                (2, Some(Visibility::Runtime)),
+                (2, Some(Visibility::Runtime)),
+                (3, None),
+            ]
+        );
+    }
+}
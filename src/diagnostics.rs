@@ -0,0 +1,195 @@
+//! Logging setup and structured diagnostics (e.g., how long each translation
+//! unit took to parse). With the JSON log format, every log record is written
+//! as a JSON line on stderr, so that build farms can track runs and spot slow
+//! or failing translation units automatically.
+
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{anyhow, Result};
+use log::Level;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Target of the log records carrying diagnostic events
+const DIAGNOSTICS_TARGET: &str = "cpplumber::diagnostics";
+
+static JSON_LOGS: AtomicBool = AtomicBool::new(false);
+
+/// Format of the log records written on stderr
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// Human-readable messages
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(anyhow!(
+                "Invalid log format '{}' (expected text or json)",
+                s
+            )),
+        }
+    }
+}
+
+/// Event worth tracking by tools monitoring runs
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum DiagnosticEvent<'a> {
+    /// Artifacts have been extracted from a translation unit, or loaded from
+    /// the cache
+    FileParsed {
+        file: &'a Path,
+        artifacts: usize,
+        duration_ms: u128,
+        cached: bool,
+    },
+    /// A translation unit couldn't be parsed
+    ParseError { file: &'a Path, error: String },
+    /// A binary file has been scanned
+    BinaryScanned {
+        file: &'a Path,
+        findings: usize,
+        duration_ms: u128,
+    },
+}
+
+impl fmt::Display for DiagnosticEvent<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagnosticEvent::FileParsed {
+                file,
+                artifacts,
+                duration_ms,
+                cached,
+            } => write!(
+                f,
+                "{} '{}' in {} ms ({} artifact(s))",
+                if *cached { "Loaded" } else { "Parsed" },
+                file.display(),
+                duration_ms,
+                artifacts
+            ),
+            DiagnosticEvent::ParseError { file, error } => {
+                write!(f, "Failed to parse '{}': {}", file.display(), error)
+            }
+            DiagnosticEvent::BinaryScanned {
+                file,
+                findings,
+                duration_ms,
+            } => write!(
+                f,
+                "Scanned '{}' in {} ms ({} finding(s))",
+                file.display(),
+                duration_ms,
+                findings
+            ),
+        }
+    }
+}
+
+/// Sets up the logger, which defaults to the 'info' level if `RUST_LOG` isn't
+/// set
+pub fn init_logger(log_format: LogFormat) {
+    let mut builder =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"));
+    if log_format == LogFormat::Json {
+        JSON_LOGS.store(true, Ordering::SeqCst);
+        builder.format(|buf, record| {
+            let line = json_log_line(
+                &buf.timestamp_millis().to_string(),
+                record.level(),
+                record.target(),
+                &record.args().to_string(),
+            );
+            writeln!(buf, "{}", line)
+        });
+    }
+    builder.init();
+}
+
+/// Logs the given event, as a JSON object with the JSON log format, or as a
+/// debug message otherwise
+pub fn emit_diagnostic(event: &DiagnosticEvent) {
+    if JSON_LOGS.load(Ordering::Relaxed) {
+        match serde_json::to_string(event) {
+            Ok(event) => log::info!(target: DIAGNOSTICS_TARGET, "{}", event),
+            Err(err) => log::debug!("Failed to serialize diagnostic event: {}", err),
+        }
+    } else {
+        log::debug!("{}", event);
+    }
+}
+
+/// Formats a log record as a JSON object. Diagnostic events' fields are
+/// inlined, instead of being given as a message.
+fn json_log_line(timestamp: &str, level: Level, target: &str, message: &str) -> String {
+    let mut line = Map::new();
+    line.insert("timestamp".to_string(), timestamp.into());
+    line.insert("level".to_string(), level.as_str().to_lowercase().into());
+    match serde_json::from_str::<Value>(message) {
+        Ok(Value::Object(event)) if target == DIAGNOSTICS_TARGET => line.extend(event),
+        _ => {
+            line.insert("target".to_string(), target.into());
+            line.insert("message".to_string(), message.into());
+        }
+    }
+
+    Value::Object(line).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_log_line_message() {
+        let line: Value = serde_json::from_str(&json_log_line(
+            "2023-01-01T00:00:00.000Z",
+            Level::Warn,
+            "cpplumber",
+            "Scan interrupted",
+        ))
+        .unwrap();
+        assert_eq!(line["timestamp"], "2023-01-01T00:00:00.000Z");
+        assert_eq!(line["level"], "warn");
+        assert_eq!(line["target"], "cpplumber");
+        assert_eq!(line["message"], "Scan interrupted");
+    }
+
+    #[test]
+    fn json_log_line_diagnostic_event() {
+        let event = serde_json::to_string(&DiagnosticEvent::FileParsed {
+            file: Path::new("main.cc"),
+            artifacts: 3,
+            duration_ms: 42,
+            cached: false,
+        })
+        .unwrap();
+        let line: Value = serde_json::from_str(&json_log_line(
+            "2023-01-01T00:00:00.000Z",
+            Level::Info,
+            DIAGNOSTICS_TARGET,
+            &event,
+        ))
+        .unwrap();
+        assert_eq!(line["level"], "info");
+        assert_eq!(line["event"], "file_parsed");
+        assert_eq!(line["file"], "main.cc");
+        assert_eq!(line["artifacts"], 3);
+        assert_eq!(line["duration_ms"], 42);
+        assert!(line.get("message").is_none());
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use cpplumber::binary_files::collect_binary_files;
+use cpplumber::information_leak::{ConfirmedLeakWithUniqueLocation, PotentialLeak};
+use cpplumber::leak_diff::{diff_confirmed_leaks, LeakDiff};
+use cpplumber::path_mapping::{normalize_confirmed_leak_paths, PathNormalizer};
+use cpplumber::reporting::{dump_confirmed_leaks, OutputSink, OutputSinkRegistry, ScanOutcome};
+use cpplumber::LeakMatcher;
+use rayon::prelude::*;
+
+use crate::cli::{CpplumberOptions, DiffOptions};
+
+#[derive(Serialize)]
+struct JsonDiffReport {
+    introduced: serde_json::Value,
+    removed: serde_json::Value,
+    persisting: serde_json::Value,
+}
+
+/// Looks for the given artifacts in the old and new binaries, and writes the
+/// leaks introduced, removed and persisting between them
+pub fn run_binary_diff(
+    options: &CpplumberOptions,
+    diff_options: &DiffOptions,
+    potential_leaks: Vec<PotentialLeak>,
+) -> Result<()> {
+    let output_sinks = OutputSinkRegistry::default();
+    let output_format = options.output_format();
+    if output_format != "text" && output_format != "json" {
+        return Err(anyhow!(
+            "Diffs can only be written as text or JSON, not as '{}'",
+            output_format
+        ));
+    }
+    let output_sink = output_sinks.get(output_format)?;
+
+    // Note: Both binaries are scanned concurrently, with the same patterns
+    let (minimum_partial_leak_size, match_suffixes) = options.partial_matching();
+    let matcher = LeakMatcher::new(
+        Arc::new(potential_leaks),
+        options.aligned_wide_strings,
+        minimum_partial_leak_size,
+        match_suffixes,
+    )?;
+    let (old_leaks, new_leaks) = rayon::join(
+        || scan_binary(&diff_options.old_binary_path, options, &matcher),
+        || scan_binary(&diff_options.new_binary_path, options, &matcher),
+    );
+    let diff = diff_confirmed_leaks(old_leaks?, new_leaks?);
+    log::info!(
+        "{} leak(s) introduced, {} removed, {} persisting",
+        diff.introduced.len(),
+        diff.removed.len(),
+        diff.persisting.len()
+    );
+
+    let mut stdout = std::io::stdout();
+    if output_format == "json" {
+        write_json_diff(&mut stdout, &diff, output_sink, options)
+    } else {
+        write_sectioned_diff(&mut stdout, &diff, output_sink, options)
+    }
+}
+
+/// Looks for the matcher's artifacts in a binary, or in the binaries
+/// contained in a directory
+fn scan_binary(
+    binary_path: &Path,
+    options: &CpplumberOptions,
+    matcher: &LeakMatcher,
+) -> Result<BTreeSet<ConfirmedLeakWithUniqueLocation>> {
+    let binary_file_paths = if binary_path.is_dir() {
+        collect_binary_files(binary_path, &options.binary_globs)?
+    } else if binary_path.is_file() {
+        vec![binary_path.to_path_buf()]
+    } else {
+        return Err(anyhow!(
+            "'{}' is not a valid file path.",
+            binary_path.display()
+        ));
+    };
+
+    log::info!("Looking for leaks in '{}'...", binary_path.display());
+    let leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> = binary_file_paths
+        .par_iter()
+        .map(|binary_file_path| {
+            matcher.find_leaks_in_file(binary_file_path, options.unpack_containers)
+        })
+        .try_reduce(BTreeSet::new, |mut accum, other| {
+            accum.extend(other);
+            Ok(accum)
+        })?;
+
+    Ok(normalize_confirmed_leak_paths(
+        leaks,
+        &PathNormalizer::new(options.path_prefix_map.clone()),
+    ))
+}
+
+fn diff_sections(
+    diff: &LeakDiff,
+) -> [(&'static str, &BTreeSet<ConfirmedLeakWithUniqueLocation>); 3] {
+    [
+        ("Introduced", &diff.introduced),
+        ("Removed", &diff.removed),
+        ("Persisting", &diff.persisting),
+    ]
+}
+
+fn dump_section<W: Write>(
+    writer: W,
+    leaks: &BTreeSet<ConfirmedLeakWithUniqueLocation>,
+    output_sink: &dyn OutputSink,
+    options: &CpplumberOptions,
+) -> Result<()> {
+    dump_confirmed_leaks(
+        writer,
+        leaks,
+        output_sink,
+        &ScanOutcome::default(),
+        options.redact,
+        options.truncated_value_size,
+        options.ignore_multiple_locations,
+    )
+}
+
+fn write_json_diff<W: Write>(
+    writer: &mut W,
+    diff: &LeakDiff,
+    output_sink: &dyn OutputSink,
+    options: &CpplumberOptions,
+) -> Result<()> {
+    let json_report =
+        |leaks: &BTreeSet<ConfirmedLeakWithUniqueLocation>| -> Result<serde_json::Value> {
+            let mut report = vec![];
+            dump_section(&mut report, leaks, output_sink, options)?;
+            Ok(serde_json::from_slice(&report)?)
+        };
+    let report = JsonDiffReport {
+        introduced: json_report(&diff.introduced)?,
+        removed: json_report(&diff.removed)?,
+        persisting: json_report(&diff.persisting)?,
+    };
+
+    serde_json::to_writer(&mut *writer, &report)?;
+    writeln!(writer)?;
+
+    Ok(())
+}
+
+fn write_sectioned_diff<W: Write>(
+    writer: &mut W,
+    diff: &LeakDiff,
+    output_sink: &dyn OutputSink,
+    options: &CpplumberOptions,
+) -> Result<()> {
+    for (name, leaks) in diff_sections(diff) {
+        writeln!(writer, "=== {} ({} leak(s)) ===", name, leaks.len())?;
+        dump_section(&mut *writer, leaks, output_sink, options)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use cpplumber::information_leak::{
+        BinaryLocation, ConfirmedLeak, LeakedDataType, SourceLocation,
+    };
+    use structopt::StructOpt;
+
+    use super::*;
+
+    fn confirmed_leaks(binary: &str, data: &str) -> BTreeSet<ConfirmedLeakWithUniqueLocation> {
+        [ConfirmedLeak::builder(
+            LeakedDataType::StringLiteral,
+            Arc::new(data.to_string()),
+            Arc::new(SourceLocation {
+                file: PathBuf::from("main.cc"),
+                line: 1,
+            }),
+            BinaryLocation::new(Arc::new(PathBuf::from(binary)), 16),
+        )
+        .build()
+        .into()]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn write_diff_sections() {
+        let options = CpplumberOptions::from_iter(["cpplumber"]);
+        let diff = diff_confirmed_leaks(
+            confirmed_leaks("v1.bin", "fixed"),
+            confirmed_leaks("v2.bin", "new"),
+        );
+        let output_sinks = OutputSinkRegistry::default();
+
+        let mut output = vec![];
+        write_sectioned_diff(
+            &mut output,
+            &diff,
+            output_sinks.get("text").unwrap(),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "=== Introduced (1 leak(s)) ===\n\
+             \"new\" (string literal) leaked at offset 0x10 in \"v2.bin\" [declared at main.cc:1]\n\
+             === Removed (1 leak(s)) ===\n\
+             \"fixed\" (string literal) leaked at offset 0x10 in \"v1.bin\" [declared at main.cc:1]\n\
+             === Persisting (0 leak(s)) ===\n"
+        );
+
+        let mut output = vec![];
+        write_json_diff(
+            &mut output,
+            &diff,
+            output_sinks.get("json").unwrap(),
+            &options,
+        )
+        .unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(report["introduced"]["leaks"][0]["data"], "new");
+        assert_eq!(report["removed"]["leaks"][0]["data"], "fixed");
+        assert_eq!(report["persisting"]["leaks"], serde_json::json!([]));
+    }
+}
@@ -0,0 +1,228 @@
+use std::fs::File;
+use std::io::{ErrorKind, Read, Write};
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use clang::diagnostic::Severity;
+use clang::{Clang, Index, Unsaved};
+
+use cpplumber::binary_format::BinaryLayout;
+
+use crate::cli::DoctorOptions;
+
+/// Name of the in-memory source files parsed by the checks
+const SAMPLE_SOURCE_FILE_NAME: &str = "cpplumber_doctor.cc";
+/// Translation unit without any dependency
+const TRIVIAL_SOURCE: &str = "struct Sample { int value; };\nint main() { return 0; }\n";
+/// Translation unit including headers that are provided by clang itself (i.e.,
+/// located in its resource directory), not by the system
+const BUILTIN_HEADERS_SOURCE: &str = "#include <stddef.h>\n#include <stdarg.h>\n";
+
+const INCOMPLETE_INSTALLATION_FIX: &str = "check that libclang's installation is complete \
+     (e.g., reinstall the libclang package of your distribution)";
+
+/// Outcome of a single environment check
+enum CheckOutcome {
+    Passed(String),
+    Failed { problem: String, fix: String },
+}
+
+struct Check {
+    name: &'static str,
+    outcome: CheckOutcome,
+}
+
+/// Checks that cpplumber's environment (e.g., libclang, permissions on the
+/// binary to scan) is usable and prints how to fix what isn't
+pub fn run_doctor(options: &DoctorOptions) -> Result<()> {
+    let mut checks = check_libclang();
+    if let Some(ref binary_file_path) = options.binary_file_path {
+        checks.push(Check {
+            name: "binary file",
+            outcome: check_binary_file(binary_file_path),
+        });
+    }
+
+    let failed_checks = write_report(&mut std::io::stdout(), &checks)?;
+    if failed_checks > 0 {
+        Err(anyhow!("{} check(s) failed", failed_checks))
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks that libclang can be loaded and can parse source files
+fn check_libclang() -> Vec<Check> {
+    let clang = match Clang::new() {
+        Ok(clang) => clang,
+        Err(err) => {
+            return vec![Check {
+                name: "libclang",
+                outcome: CheckOutcome::Failed {
+                    problem: err,
+                    fix: "make sure no other libclang instance is active in this process"
+                        .to_string(),
+                },
+            }]
+        }
+    };
+    let index = Index::new(&clang, false, false);
+
+    vec![
+        Check {
+            name: "libclang",
+            outcome: CheckOutcome::Passed(clang::get_version()),
+        },
+        Check {
+            name: "parsing",
+            outcome: match parse_errors(&index, TRIVIAL_SOURCE) {
+                Ok(errors) if errors.is_empty() => {
+                    CheckOutcome::Passed("trivial translation unit parsed".to_string())
+                }
+                Ok(errors) => CheckOutcome::Failed {
+                    problem: errors.join("; "),
+                    fix: INCOMPLETE_INSTALLATION_FIX.to_string(),
+                },
+                Err(err) => CheckOutcome::Failed {
+                    problem: err.to_string(),
+                    fix: INCOMPLETE_INSTALLATION_FIX.to_string(),
+                },
+            },
+        },
+        Check {
+            name: "resource directory",
+            outcome: match parse_errors(&index, BUILTIN_HEADERS_SOURCE) {
+                Ok(errors) if errors.is_empty() => {
+                    CheckOutcome::Passed("builtin headers found".to_string())
+                }
+                Ok(errors) => CheckOutcome::Failed {
+                    problem: errors.join("; "),
+                    fix: "install the clang package matching libclang's version, or add \
+                          `-resource-dir <dir>` to the compile arguments (see `clang \
+                          -print-resource-dir`)"
+                        .to_string(),
+                },
+                Err(err) => CheckOutcome::Failed {
+                    problem: err.to_string(),
+                    fix: INCOMPLETE_INSTALLATION_FIX.to_string(),
+                },
+            },
+        },
+    ]
+}
+
+/// Parses the given source code and returns the errors reported by libclang
+fn parse_errors(index: &Index, source: &str) -> Result<Vec<String>> {
+    let translation_unit = index
+        .parser(SAMPLE_SOURCE_FILE_NAME)
+        .unsaved(&[Unsaved::new(SAMPLE_SOURCE_FILE_NAME, source)])
+        .parse()
+        .map_err(|err| anyhow!("Failed to parse sample source file: {}", err))?;
+
+    Ok(translation_unit
+        .get_diagnostics()
+        .into_iter()
+        .filter(|diagnostic| matches!(diagnostic.get_severity(), Severity::Error | Severity::Fatal))
+        .map(|diagnostic| diagnostic.get_text())
+        .collect())
+}
+
+/// Checks that the given binary file can be read entirely, and whether its
+/// format is supported
+fn check_binary_file(binary_file_path: &Path) -> CheckOutcome {
+    let mut data = vec![];
+    let read_result = File::open(binary_file_path).and_then(|mut file| file.read_to_end(&mut data));
+    if let Err(err) = read_result {
+        let fix = match err.kind() {
+            ErrorKind::NotFound => "check the path of the binary file",
+            ErrorKind::PermissionDenied => {
+                "grant read permissions on the binary file to the current user"
+            }
+            _ => "check that the binary file is a regular, readable file",
+        };
+        return CheckOutcome::Failed {
+            problem: format!("failed to read '{}': {}", binary_file_path.display(), err),
+            fix: fix.to_string(),
+        };
+    }
+
+    let format = match BinaryLayout::parse(&data) {
+        Ok(Some(_)) => "format supported",
+        Ok(None) => "unknown format, scanned as a raw blob",
+        Err(_) => "malformed headers, scanned as a raw blob",
+    };
+    CheckOutcome::Passed(format!("{} bytes readable, {}", data.len(), format))
+}
+
+/// Writes the outcome of each check and returns the number of failed checks
+fn write_report<W: Write>(writer: &mut W, checks: &[Check]) -> Result<usize> {
+    let mut failed_checks = 0;
+    for check in checks {
+        match check.outcome {
+            CheckOutcome::Passed(ref details) => {
+                writeln!(writer, "[ok]   {}: {}", check.name, details)?
+            }
+            CheckOutcome::Failed {
+                ref problem,
+                ref fix,
+            } => {
+                failed_checks += 1;
+                writeln!(writer, "[FAIL] {}: {}", check.name, problem)?;
+                writeln!(writer, "       fix: {}", fix)?;
+            }
+        }
+    }
+
+    Ok(failed_checks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_readable_binary_file() {
+        match check_binary_file(Path::new("tests/data/templates/templates.cc")) {
+            CheckOutcome::Passed(details) => {
+                assert!(details.ends_with("unknown format, scanned as a raw blob"))
+            }
+            CheckOutcome::Failed { problem, .. } => panic!("Check failed: {}", problem),
+        }
+
+        match check_binary_file(Path::new("tests/data/missing.exe")) {
+            CheckOutcome::Failed { fix, .. } => {
+                assert_eq!(fix, "check the path of the binary file")
+            }
+            CheckOutcome::Passed(_) => panic!("Check passed"),
+        }
+    }
+
+    #[test]
+    fn report_failed_checks() {
+        let checks = [
+            Check {
+                name: "libclang",
+                outcome: CheckOutcome::Passed("clang version 14.0.0".to_string()),
+            },
+            Check {
+                name: "resource directory",
+                outcome: CheckOutcome::Failed {
+                    problem: "'stddef.h' file not found".to_string(),
+                    fix: "install clang".to_string(),
+                },
+            },
+        ];
+
+        let mut output = vec![];
+        let failed_checks = write_report(&mut output, &checks).expect("write_report failed");
+        assert_eq!(failed_checks, 1);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            concat!(
+                "[ok]   libclang: clang version 14.0.0\n",
+                "[FAIL] resource directory: 'stddef.h' file not found\n",
+                "       fix: install clang\n",
+            )
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Result;
+use rayon::prelude::*;
+use regex::Regex;
+
+use crate::compilation_database::{include_directories, CompileCommand, CompileCommands};
+use crate::information_leak::{LeakedDataType, PotentialLeak, SourceLocation};
+
+/// Maximum number of bytes of an embedded file to look for in the binary
+const EMBEDDED_FILE_PREFIX_SIZE: u64 = 256;
+
+/// Matches C23 `#embed` directives as well as `INCBIN` macro invocations (from
+/// `incbin.h`) and `.incbin` assembler directives. Each alternative captures
+/// the path of the embedded file in its own group.
+const EMBED_DIRECTIVES_REGEX: &str = r#"(?m)(?:^\s*#\s*embed\s*[<"]([^>"]+)[>"]|\bINCBIN\s*\([^)]*?"([^"]+)"\s*\)|\.incbin\s+"([^"]+)")"#;
+
+/// Looks for files embedded into the binary by the given translation units and
+/// returns the beginning of their content as potential leaks.
+pub fn extract_embedded_files(compile_commands: &CompileCommands) -> Result<Vec<PotentialLeak>> {
+    let regex = Regex::new(EMBED_DIRECTIVES_REGEX)?;
+
+    Ok(compile_commands
+        .par_iter()
+        .flat_map_iter(|compile_cmd| {
+            extract_embedded_files_from_source_file(&regex, compile_cmd).unwrap_or_else(|err| {
+                log::warn!(
+                    "Failed to look for embedded files in '{}': {}",
+                    compile_cmd.filename.display(),
+                    err
+                );
+                vec![]
+            })
+        })
+        .collect())
+}
+
+fn extract_embedded_files_from_source_file(
+    regex: &Regex,
+    compile_cmd: &CompileCommand,
+) -> Result<Vec<PotentialLeak>> {
+    let source_content = fs::read_to_string(&compile_cmd.filename)?;
+
+    let mut potential_leaks = vec![];
+    for captures in regex.captures_iter(&source_content) {
+        let embedded_path = if let Some(path) = captures.iter().skip(1).flatten().next() {
+            path
+        } else {
+            continue;
+        };
+        let line = source_content[..embedded_path.start()]
+            .matches('\n')
+            .count()
+            + 1;
+
+        if let Some(embedded_file_path) =
+            resolve_embedded_file_path(Path::new(embedded_path.as_str()), compile_cmd)
+        {
+            let mut bytes = vec![];
+            File::open(&embedded_file_path)?
+                .take(EMBEDDED_FILE_PREFIX_SIZE)
+                .read_to_end(&mut bytes)?;
+
+            potential_leaks.push(PotentialLeak {
+                data_type: LeakedDataType::EmbeddedFile,
+                data: Arc::new(embedded_file_path.display().to_string()),
+                bytes,
+                code_unit_size: 1,
+                declaration_metadata: Arc::new(SourceLocation {
+                    file: compile_cmd.filename.clone(),
+                    line: line as u64,
+                }),
+            });
+        } else {
+            log::warn!(
+                "Failed to locate embedded file '{}' (referenced in '{}')",
+                embedded_path.as_str(),
+                compile_cmd.filename.display()
+            );
+        }
+    }
+
+    Ok(potential_leaks)
+}
+
+/// Looks for the embedded file relatively to the source file first, and then
+/// in the include directories passed to the compiler.
+fn resolve_embedded_file_path(
+    embedded_path: &Path,
+    compile_cmd: &CompileCommand,
+) -> Option<PathBuf> {
+    let source_directory = compile_cmd.filename.parent()?;
+    let include_directories = include_directories(&compile_cmd.arguments)
+        .into_iter()
+        .map(Path::new);
+
+    std::iter::once(source_directory)
+        .chain(include_directories)
+        .map(|directory| directory.join(embedded_path))
+        .find(|path| path.is_file())
+        .and_then(|path| path.canonicalize().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMBEDDED_FILES_PATH: &str = "tests/data/embedded_files";
+
+    #[test]
+    fn extract_embedded_files_directives() {
+        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(EMBEDDED_FILES_PATH);
+        let compile_commands = vec![CompileCommand {
+            filename: root_dir_path.join("main.c"),
+            // Note: Include directories may be separate from their option
+            arguments: Arc::new(vec![
+                "-I".to_string(),
+                root_dir_path.join("include").display().to_string(),
+            ]),
+        }];
+
+        let potential_leaks =
+            extract_embedded_files(&compile_commands).expect("extract_embedded_files failed");
+
+        let embedded_files: Vec<(PathBuf, u64)> = potential_leaks
+            .iter()
+            .map(|leak| {
+                (
+                    PathBuf::from(leak.data.as_str()),
+                    leak.declaration_metadata.line,
+                )
+            })
+            .collect();
+        assert_eq!(
+            embedded_files,
+            vec![
+                (root_dir_path.join("blob.bin").canonicalize().unwrap(), 2),
+                (
+                    root_dir_path
+                        .join("include/icon.bin")
+                        .canonicalize()
+                        .unwrap(),
+                    5
+                ),
+                (root_dir_path.join("blob.bin").canonicalize().unwrap(), 8),
+            ]
+        );
+        assert_eq!(potential_leaks[0].bytes, b"embedded blob content\n");
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::{BTreeSet, HashSet};
+use std::sync::Arc;
+
+use aho_corasick::AhoCorasick;
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::information_leak::{LeakedDataType, PotentialLeak, SourceLocation};
+
+/// Encoding a string artifact is declared with in the source code (e.g.,
+/// `"..."` vs `L"..."`, or whatever `TEXT()` and `_T()` expand to)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeclaredEncoding {
+    Narrow,
+    Wide,
+}
+
+/// Encodings a string artifact has been found with in the binary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EncodingCoverage {
+    Narrow,
+    Wide,
+    Both,
+    Neither,
+}
+
+/// Coverage of a single string artifact
+#[derive(Debug, Serialize)]
+pub struct ArtifactEncodingCoverage {
+    pub value: Arc<String>,
+    pub declaration: Arc<SourceLocation>,
+    pub declared_encoding: DeclaredEncoding,
+    pub found_as: EncodingCoverage,
+}
+
+/// Number of string artifacts per coverage
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct EncodingCoverageSummary {
+    pub narrow: usize,
+    pub wide: usize,
+    pub both: usize,
+    pub neither: usize,
+    /// Artifacts only found with the encoding they haven't been declared
+    /// with (e.g., narrow literals converted to wide strings at runtime)
+    pub mismatched: usize,
+}
+
+/// Shows, for each string artifact, whether it's present in the binary as a
+/// narrow string, a UTF-16LE string, both or neither
+#[derive(Debug, Serialize)]
+pub struct EncodingCoverageReport {
+    pub summary: EncodingCoverageSummary,
+    pub artifacts: Vec<ArtifactEncodingCoverage>,
+}
+
+/// Looks for both the narrow and UTF-16LE representations of the string
+/// artifacts in `bin_data`. Only artifacts made of ASCII characters can be
+/// represented both ways, others are ignored.
+pub fn analyze_encoding_coverage(
+    bin_data: &[u8],
+    potential_leaks: &[PotentialLeak],
+) -> Result<EncodingCoverageReport> {
+    // Note: UTF-16LE variants of narrow artifacts (see `add_utf16_variants`)
+    // are deduplicated, as they share their value and declaration with the
+    // original artifact
+    let mut seen_artifacts = BTreeSet::new();
+    let artifacts: Vec<(&PotentialLeak, DeclaredEncoding, Vec<u8>)> = potential_leaks
+        .iter()
+        .filter(|leak| {
+            matches!(
+                leak.data_type,
+                LeakedDataType::StringLiteral | LeakedDataType::MacroValue
+            )
+        })
+        .filter_map(|leak| {
+            let (declared_encoding, narrow_bytes) = narrow_representation(leak)?;
+            Some((leak, declared_encoding, narrow_bytes))
+        })
+        .filter(|(leak, _, narrow_bytes)| {
+            seen_artifacts.insert((narrow_bytes.clone(), leak.declaration_metadata.clone()))
+        })
+        .collect();
+
+    // Patterns `2 * i` and `2 * i + 1` are respectively the narrow and wide
+    // representations of the i-th artifact
+    let patterns = artifacts.iter().flat_map(|(_, _, narrow_bytes)| {
+        [
+            narrow_bytes.clone(),
+            narrow_bytes.iter().flat_map(|byte| [*byte, 0]).collect(),
+        ]
+    });
+    let automaton = AhoCorasick::new(patterns)?;
+    let found_patterns: HashSet<usize> = automaton
+        .find_overlapping_iter(bin_data)
+        .map(|pattern_match| pattern_match.pattern().as_usize())
+        .collect();
+
+    let mut summary = EncodingCoverageSummary::default();
+    let artifacts = artifacts
+        .into_iter()
+        .enumerate()
+        .map(|(i, (leak, declared_encoding, _))| {
+            let found_as = match (
+                found_patterns.contains(&(2 * i)),
+                found_patterns.contains(&(2 * i + 1)),
+            ) {
+                (true, true) => EncodingCoverage::Both,
+                (true, false) => EncodingCoverage::Narrow,
+                (false, true) => EncodingCoverage::Wide,
+                (false, false) => EncodingCoverage::Neither,
+            };
+            match found_as {
+                EncodingCoverage::Narrow => summary.narrow += 1,
+                EncodingCoverage::Wide => summary.wide += 1,
+                EncodingCoverage::Both => summary.both += 1,
+                EncodingCoverage::Neither => summary.neither += 1,
+            }
+            if matches!(
+                (declared_encoding, found_as),
+                (DeclaredEncoding::Narrow, EncodingCoverage::Wide)
+                    | (DeclaredEncoding::Wide, EncodingCoverage::Narrow)
+            ) {
+                summary.mismatched += 1;
+            }
+
+            ArtifactEncodingCoverage {
+                value: leak.data.clone(),
+                declaration: leak.declaration_metadata.clone(),
+                declared_encoding,
+                found_as,
+            }
+        })
+        .collect();
+
+    Ok(EncodingCoverageReport { summary, artifacts })
+}
+
+/// Returns the declared encoding of the artifact along with its narrow
+/// representation, if it only contains ASCII characters
+fn narrow_representation(leak: &PotentialLeak) -> Option<(DeclaredEncoding, Vec<u8>)> {
+    let (declared_encoding, narrow_bytes) = match leak.code_unit_size {
+        1 => (DeclaredEncoding::Narrow, leak.bytes.clone()),
+        2 => {
+            if leak
+                .bytes
+                .chunks(2)
+                .any(|unit| unit.len() != 2 || unit[1] != 0)
+            {
+                return None;
+            }
+            (
+                DeclaredEncoding::Wide,
+                leak.bytes.iter().step_by(2).copied().collect(),
+            )
+        }
+        _ => return None,
+    };
+
+    if narrow_bytes.is_empty() || !narrow_bytes.is_ascii() {
+        None
+    } else {
+        Some((declared_encoding, narrow_bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::add_utf16_variants;
+
+    use super::*;
+
+    fn string_literal(literal: &str, line: u64) -> PotentialLeak {
+        PotentialLeak::from_string_literal(
+            literal,
+            SourceLocation {
+                file: PathBuf::from("main.cpp"),
+                line,
+            },
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn analyze_narrow_and_wide_coverage() {
+        let potential_leaks = add_utf16_variants(vec![
+            string_literal("\"narrow\"", 1),
+            string_literal("u\"wide\"", 2),
+            string_literal("\"converted\"", 3),
+            string_literal("\"both\"", 4),
+            string_literal("\"absent\"", 5),
+            string_literal("u8\"café\"", 6),
+        ]);
+        let mut bin_data = b"\0narrow\0both\0".to_vec();
+        for value in ["wide", "converted", "both"] {
+            bin_data.extend(value.bytes().flat_map(|byte| [byte, 0]));
+            bin_data.extend([0, 0]);
+        }
+
+        let report = analyze_encoding_coverage(&bin_data, &potential_leaks)
+            .expect("analyze_encoding_coverage failed");
+
+        let coverage: Vec<(&str, DeclaredEncoding, EncodingCoverage)> = report
+            .artifacts
+            .iter()
+            .map(|artifact| {
+                (
+                    artifact.value.as_str(),
+                    artifact.declared_encoding,
+                    artifact.found_as,
+                )
+            })
+            .collect();
+        assert_eq!(
+            coverage,
+            [
+                ("narrow", DeclaredEncoding::Narrow, EncodingCoverage::Narrow),
+                ("wide", DeclaredEncoding::Wide, EncodingCoverage::Wide),
+                (
+                    "converted",
+                    DeclaredEncoding::Narrow,
+                    EncodingCoverage::Wide
+                ),
+                ("both", DeclaredEncoding::Narrow, EncodingCoverage::Both),
+                (
+                    "absent",
+                    DeclaredEncoding::Narrow,
+                    EncodingCoverage::Neither
+                ),
+            ]
+        );
+        assert_eq!(
+            report.summary,
+            EncodingCoverageSummary {
+                narrow: 1,
+                wide: 2,
+                both: 1,
+                neither: 1,
+                mismatched: 1,
+            }
+        );
+    }
+}
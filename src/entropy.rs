@@ -0,0 +1,112 @@
+//! Filtering of artifacts based on the Shannon entropy of their values, to
+//! focus on values that look random (e.g., keys, tokens) rather than on all
+//! literals.
+
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+
+use crate::information_leak::PotentialLeak;
+use crate::statistics::{DropReason, ExtractionStatistics};
+
+/// Minimum entropy (in bits per character) of values kept in high entropy mode
+const HIGH_ENTROPY_MIN_ENTROPY: f64 = 3.5;
+/// Minimum number of characters of values kept in high entropy mode. Shorter
+/// values cannot reach a high entropy and are rarely secrets.
+const HIGH_ENTROPY_MIN_LENGTH: usize = 16;
+
+/// Returns the Shannon entropy of the given value, in bits per character
+pub fn shannon_entropy(value: &str) -> f64 {
+    let mut char_counts: HashMap<char, usize> = HashMap::new();
+    for c in value.chars() {
+        *char_counts.entry(c).or_default() += 1;
+    }
+    let char_count = char_counts.values().sum::<usize>() as f64;
+
+    char_counts
+        .values()
+        .map(|&count| {
+            let probability = count as f64 / char_count;
+            -probability * probability.log2()
+        })
+        .sum()
+}
+
+/// Criteria values must meet to be kept
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntropyFilter {
+    min_entropy: f64,
+    min_length: usize,
+    allow_whitespace: bool,
+}
+
+impl EntropyFilter {
+    /// Keeps values whose entropy is at least `min_entropy` bits per character
+    pub fn new(min_entropy: f64) -> Self {
+        Self {
+            min_entropy,
+            min_length: 0,
+            allow_whitespace: true,
+        }
+    }
+
+    /// Only keeps values that look like embedded secrets: long, random-looking
+    /// values without whitespace
+    pub fn high_entropy() -> Self {
+        Self {
+            min_entropy: HIGH_ENTROPY_MIN_ENTROPY,
+            min_length: HIGH_ENTROPY_MIN_LENGTH,
+            allow_whitespace: false,
+        }
+    }
+
+    /// Returns `true` if the given value meets the filter's criteria
+    pub fn is_kept(&self, value: &str) -> bool {
+        value.chars().count() >= self.min_length
+            && (self.allow_whitespace || !value.contains(char::is_whitespace))
+            && shannon_entropy(value) >= self.min_entropy
+    }
+}
+
+/// Removes the artifacts whose values don't meet the given filter's criteria
+pub fn filter_low_entropy_artifacts(
+    potential_leaks: Vec<PotentialLeak>,
+    filter: &EntropyFilter,
+    statistics: &mut ExtractionStatistics,
+) -> Vec<PotentialLeak> {
+    let (kept_leaks, dropped_leaks): (Vec<_>, Vec<_>) = potential_leaks
+        .into_par_iter()
+        .partition(|leak| filter.is_kept(&leak.data));
+    statistics.record_all(DropReason::LowEntropy, &dropped_leaks);
+
+    kept_leaks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compute_shannon_entropy() {
+        assert_eq!(shannon_entropy(""), 0.0);
+        assert_eq!(shannon_entropy("aaaa"), 0.0);
+        assert_eq!(shannon_entropy("abab"), 1.0);
+        assert_eq!(shannon_entropy("0123456789abcdef"), 4.0);
+    }
+
+    #[test]
+    fn filter_values() {
+        let filter = EntropyFilter::new(2.0);
+        assert!(filter.is_kept("abcd"));
+        assert!(!filter.is_kept("aaab"));
+
+        let filter = EntropyFilter::high_entropy();
+        assert!(filter.is_kept("AKIAIOSFODNN7EXAMPLEwJalrXUtnFEMI"));
+        // Too short
+        assert!(!filter.is_kept("x7Gp2qLz"));
+        // Plain text
+        assert!(!filter.is_kept("Failed to open the configuration file"));
+        // Low entropy
+        assert!(!filter.is_kept("================"));
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+
+use cpplumber::find_leaks_in_binary_file;
+use cpplumber::information_leak::{
+    encode_utf16_le, encode_utf32_le, ConfirmedLeak, ConfirmedLeakWithUniqueLocation,
+    LeakedDataType, PotentialLeak, SourceLocation,
+};
+use cpplumber::reporting::display_binary_context;
+
+use crate::cli::GrepOptions;
+
+/// Looks for user-provided strings in a binary file, without needing any
+/// source code. Succeeds if at least one of the patterns was found, like
+/// `grep` does.
+pub fn grep_binary_file(options: &GrepOptions) -> Result<()> {
+    if !options.binary_file_path.is_file() {
+        return Err(anyhow!(
+            "'{}' is not a valid file path.",
+            options.binary_file_path.display()
+        ));
+    }
+
+    let potential_leaks = options
+        .patterns
+        .iter()
+        .flat_map(|pattern| generate_pattern_variants(pattern))
+        .collect::<Vec<_>>();
+    let matches: BTreeSet<ConfirmedLeakWithUniqueLocation> = find_leaks_in_binary_file(
+        &options.binary_file_path,
+        potential_leaks,
+        options.aligned_wide_strings,
+        None,
+        false,
+        options.unpack_containers,
+    )?;
+
+    for found in &matches {
+        println!("{}", display_match(found));
+    }
+
+    if matches.is_empty() {
+        Err(anyhow!("No match found"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Describes where a pattern has been found, including the section and
+/// virtual address it's located at for supported file formats
+fn display_match(found: &ConfirmedLeak) -> String {
+    format!(
+        "\"{}\" found at offset 0x{:x}{} in \"{}\"",
+        found.data,
+        found.location.binary.offset,
+        display_binary_context(&found.location.binary),
+        found.location.binary.file.display(),
+    )
+}
+
+/// Generates the narrow (UTF-8), UTF-16LE and UTF-32LE variants of the given
+/// pattern.
+fn generate_pattern_variants(pattern: &str) -> Vec<PotentialLeak> {
+    let data = Arc::new(pattern.to_string());
+    let declaration_metadata = Arc::new(SourceLocation {
+        file: PathBuf::default(),
+        line: 0,
+    });
+
+    [
+        (pattern.as_bytes().to_vec(), 1),
+        (encode_utf16_le(pattern), 2),
+        (encode_utf32_le(pattern), 4),
+    ]
+    .into_iter()
+    .map(|(bytes, code_unit_size)| PotentialLeak {
+        data_type: LeakedDataType::UserProvided,
+        data: data.clone(),
+        bytes,
+        code_unit_size,
+        declaration_metadata: declaration_metadata.clone(),
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use cpplumber::information_leak::BinaryLocation;
+
+    use super::*;
+
+    #[test]
+    fn generate_pattern_variants_encodings() {
+        let variants = generate_pattern_variants("key");
+
+        assert_eq!(variants.len(), 3);
+        assert_eq!(variants[0].bytes, b"key");
+        assert_eq!(variants[1].bytes, b"k\0e\0y\0");
+        assert_eq!(variants[2].bytes, b"k\0\0\0e\0\0\0y\0\0\0");
+    }
+
+    #[test]
+    fn display_match_with_binary_context() {
+        let mut binary_location = BinaryLocation::new(Arc::new(PathBuf::from("a.out")), 0x2010);
+        binary_location.region = Some(Arc::new("section \".rodata\"".to_string()));
+        binary_location.virtual_address = Some(0x402010);
+        let found = ConfirmedLeak::builder(
+            LeakedDataType::UserProvided,
+            Arc::new("key".to_string()),
+            Arc::new(SourceLocation {
+                file: PathBuf::default(),
+                line: 0,
+            }),
+            binary_location,
+        )
+        .build();
+
+        assert_eq!(
+            display_match(&found),
+            "\"key\" found at offset 0x2010 (section \".rodata\", address 0x402010) in \"a.out\""
+        );
+    }
+}
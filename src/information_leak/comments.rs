@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use clang::source::{File, SourceRange};
+use clang::{token::TokenKind, Entity, EntityKind};
+
+use super::{LeakedDataType, PotentialLeak, SourceLocation};
+use crate::system_headers::SystemHeaderFilter;
+
+/// Tag introducing the author of a file or declaration in documentation
+/// comments
+const AUTHOR_TAG: &str = "@author";
+
+/// Extracts the text of the comments found in the files of a translation
+/// unit, line by line and without comment delimiters. Files included from
+/// system headers are skipped if a `system_header_filter` is given. Authors
+/// (`@author`) and email addresses are extracted on their own as well, as they
+/// may leak separately (e.g., through version information).
+/// Note: Included files are only known if the translation unit has been
+/// parsed with a detailed preprocessing record.
+pub fn comments(
+    translation_unit: Entity,
+    system_header_filter: Option<&SystemHeaderFilter>,
+) -> Result<Vec<PotentialLeak>> {
+    let main_file_range = translation_unit
+        .get_range()
+        .ok_or_else(|| anyhow!("Failed to get translation unit's range"))?;
+
+    let mut potential_leaks = comments_in_range(main_file_range)?;
+    for file in included_files(translation_unit, system_header_filter) {
+        let file_size = file.get_contents().unwrap_or_default().len() as u32;
+        potential_leaks.extend(comments_in_range(SourceRange::new(
+            file.get_offset_location(0),
+            file.get_offset_location(file_size),
+        ))?);
+    }
+
+    Ok(potential_leaks)
+}
+
+/// Returns the files included by a translation unit, directly or not, ordered
+/// by path. System headers are left out if a `system_header_filter` is given.
+fn included_files<'tu>(
+    translation_unit: Entity<'tu>,
+    system_header_filter: Option<&SystemHeaderFilter>,
+) -> impl Iterator<Item = File<'tu>> {
+    let included_files: BTreeMap<PathBuf, File> = translation_unit
+        .get_children()
+        .into_iter()
+        .filter(|entity| entity.get_kind() == EntityKind::InclusionDirective)
+        .filter_map(|entity| entity.get_file())
+        .filter(|file| match system_header_filter {
+            Some(filter) => {
+                !file.get_location(1, 1).is_in_system_header()
+                    && !filter.is_under_system_include_root(&file.get_path())
+            }
+            None => true,
+        })
+        .map(|file| (file.get_path(), file))
+        .collect();
+
+    included_files.into_values()
+}
+
+/// Extracts the text of the comments found in the given range
+fn comments_in_range(range: SourceRange) -> Result<Vec<PotentialLeak>> {
+    let mut potential_leaks = vec![];
+    for token in range
+        .tokenize()
+        .into_iter()
+        .filter(|token| token.get_kind() == TokenKind::Comment)
+    {
+        let location = token.get_location().get_file_location();
+        let file_location = location
+            .file
+            .ok_or_else(|| anyhow!("Failed to get token's file location"))?
+            .get_path()
+            .canonicalize()?;
+
+        for (i, line) in token.get_spelling().lines().enumerate() {
+            let declaration_metadata = Arc::new(SourceLocation {
+                file: file_location.clone(),
+                line: (location.line as usize + i) as u64,
+            });
+            potential_leaks.extend(comment_line_artifacts(line).into_iter().map(|text| {
+                PotentialLeak {
+                    data_type: LeakedDataType::Comment,
+                    bytes: text.as_bytes().to_vec(),
+                    code_unit_size: 1,
+                    data: Arc::new(text),
+                    declaration_metadata: declaration_metadata.clone(),
+                }
+            }));
+        }
+    }
+
+    Ok(potential_leaks)
+}
+
+/// Returns the artifacts found in a line of comment: its text, then the author
+/// it names and the email addresses it contains, if any
+fn comment_line_artifacts(line: &str) -> Vec<String> {
+    let text = strip_comment_delimiters(line);
+    if text.is_empty() {
+        return vec![];
+    }
+
+    let mut artifacts = vec![text.to_string()];
+    if let Some(author) = text
+        .find(AUTHOR_TAG)
+        .map(|tag_offset| &text[tag_offset + AUTHOR_TAG.len()..])
+    {
+        // Email addresses are extracted separately
+        let author = author.split('<').next().unwrap_or_default().trim();
+        artifacts.push(author.to_string());
+    }
+    artifacts.extend(
+        text.split_whitespace()
+            .map(|word| {
+                word.trim_matches(|c: char| {
+                    matches!(
+                        c,
+                        '<' | '>' | '(' | ')' | '[' | ']' | ',' | ';' | '"' | '\''
+                    )
+                })
+            })
+            .filter(|word| is_email_address(word))
+            .map(str::to_string),
+    );
+    artifacts.retain(|artifact| !artifact.is_empty());
+    artifacts.dedup();
+
+    artifacts
+}
+
+/// Removes the delimiters and decorations (e.g., leading `*` in block
+/// comments) surrounding the text of a line of comment
+fn strip_comment_delimiters(line: &str) -> &str {
+    let line = line.trim().trim_start_matches(['/', '*', '!']).trim_end();
+    match line.strip_suffix("*/") {
+        Some(line) => line.trim_end_matches('*').trim(),
+        None => line.trim(),
+    }
+}
+
+fn is_email_address(word: &str) -> bool {
+    match word.split_once('@') {
+        Some((local_part, domain)) => {
+            !local_part.is_empty()
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comment_line_artifacts_delimiters() {
+        assert_eq!(
+            comment_line_artifacts("// TODO: remove the debug backdoor"),
+            vec!["TODO: remove the debug backdoor"]
+        );
+        assert_eq!(
+            comment_line_artifacts("/** Secret sauce */"),
+            vec!["Secret sauce"]
+        );
+        assert_eq!(
+            comment_line_artifacts(" * see http://intranet.example/ "),
+            vec!["see http://intranet.example/"]
+        );
+        assert!(comment_line_artifacts("/*****/").is_empty());
+        assert!(comment_line_artifacts("//").is_empty());
+    }
+
+    #[test]
+    fn comment_line_artifacts_authors_and_emails() {
+        assert_eq!(
+            comment_line_artifacts(" * @author Alex Doe <alex.doe@corp.example>"),
+            vec![
+                "@author Alex Doe <alex.doe@corp.example>",
+                "Alex Doe",
+                "alex.doe@corp.example"
+            ]
+        );
+        assert_eq!(
+            comment_line_artifacts("// Contact: (support@corp.example), not @mentions"),
+            vec![
+                "Contact: (support@corp.example), not @mentions",
+                "support@corp.example"
+            ]
+        );
+    }
+}
@@ -1,12 +1,17 @@
 use std::{ops::Deref, sync::Arc};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use super::{LeakLocation, LeakedDataType};
+use super::{BinaryLocation, LeakLocation, LeakedDataType, SourceLocation};
+use crate::debug_info::Visibility;
+use crate::infrastructure::InfrastructureKind;
+use crate::match_context::MatchContext;
+use crate::provenance::Origin;
+use crate::severity::Severity;
 
 /// Struct containing information on a piece of data that has leaked into a
 /// binary file.
-#[derive(Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ConfirmedLeak {
     /// Type of data leaked
     pub data_type: LeakedDataType,
@@ -15,92 +20,166 @@ pub struct ConfirmedLeak {
     /// Information on where the leaked data is declared in the source code as
     /// well as found in in the target binary
     pub location: LeakLocation,
+    /// Number of bytes matched in the binary.
+    /// Note: Not part of reports, leaks loaded from reports have a size of 0.
+    #[serde(skip)]
+    pub size: usize,
+    /// Number of bytes found in the binary, if the data only partially leaked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partial_size: Option<usize>,
+    /// Severity assigned by the classifier, if classified
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+    /// Confidence score of the match (from 0 to 100), based on the match's
+    /// length, encoding and context, if scored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<u8>,
+    /// Origin of the code declaring the leaked data, if classified
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin: Option<Origin>,
+    /// Whether the leaked data is visible at runtime or only found in debug
+    /// information, if classified
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<Visibility>,
+    /// Kind of infrastructure (e.g., registry paths) the leaked string
+    /// reveals, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub infrastructure: Option<InfrastructureKind>,
+    /// Binary content surrounding the match, if requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<Arc<MatchContext>>,
+    /// Name of the scan pass that found the leak, in multi-pass scans
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pass: Option<Arc<String>>,
 }
 
-impl From<ConfirmedLeakWithUniqueLocation> for ConfirmedLeak {
-    fn from(leak: ConfirmedLeakWithUniqueLocation) -> Self {
-        leak.0
+impl ConfirmedLeak {
+    /// Returns a builder for a leak of `data`, declared at `source` and found
+    /// at `binary`
+    pub fn builder(
+        data_type: LeakedDataType,
+        data: Arc<String>,
+        source: Arc<SourceLocation>,
+        binary: BinaryLocation,
+    ) -> ConfirmedLeakBuilder {
+        ConfirmedLeakBuilder {
+            leak: ConfirmedLeak {
+                data_type,
+                size: data.len(),
+                data,
+                location: LeakLocation { source, binary },
+                partial_size: None,
+                severity: None,
+                confidence: None,
+                origin: None,
+                visibility: None,
+                infrastructure: None,
+                context: None,
+                pass: None,
+            },
+        }
     }
 }
 
-impl From<ConfirmedLeakWithUniqueValue> for ConfirmedLeak {
-    fn from(leak: ConfirmedLeakWithUniqueValue) -> Self {
-        leak.0
-    }
+/// Builds `ConfirmedLeak`s, for tools that generate or enrich reports
+pub struct ConfirmedLeakBuilder {
+    leak: ConfirmedLeak,
 }
 
-/// Wrapper struct used to deduplicate `ConfirmedLeak`s in `BTreeSet`s based on
-/// the value of the `location` field.
-#[derive(Serialize)]
-pub struct ConfirmedLeakWithUniqueLocation(ConfirmedLeak);
+impl ConfirmedLeakBuilder {
+    /// Sets the number of bytes matched in the binary (defaults to the size
+    /// of the leaked data)
+    pub fn size(mut self, size: usize) -> Self {
+        self.leak.size = size;
+        self
+    }
 
-impl From<ConfirmedLeak> for ConfirmedLeakWithUniqueLocation {
-    fn from(leak: ConfirmedLeak) -> Self {
-        ConfirmedLeakWithUniqueLocation(leak)
+    pub fn partial_size(mut self, partial_size: usize) -> Self {
+        self.leak.partial_size = Some(partial_size);
+        self
     }
-}
 
-impl Deref for ConfirmedLeakWithUniqueLocation {
-    type Target = ConfirmedLeak;
-    fn deref(&self) -> &ConfirmedLeak {
-        &self.0
+    pub fn severity(mut self, severity: Severity) -> Self {
+        self.leak.severity = Some(severity);
+        self
     }
-}
 
-impl PartialEq for ConfirmedLeakWithUniqueLocation {
-    fn eq(&self, other: &Self) -> bool {
-        self.0.location == other.0.location
+    /// Sets the confidence score (from 0 to 100)
+    pub fn confidence(mut self, confidence: u8) -> Self {
+        self.leak.confidence = Some(confidence.min(100));
+        self
     }
-}
 
-impl Eq for ConfirmedLeakWithUniqueLocation {}
+    pub fn origin(mut self, origin: Origin) -> Self {
+        self.leak.origin = Some(origin);
+        self
+    }
 
-impl PartialOrd for ConfirmedLeakWithUniqueLocation {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.0.location.partial_cmp(&other.0.location)
+    pub fn visibility(mut self, visibility: Visibility) -> Self {
+        self.leak.visibility = Some(visibility);
+        self
+    }
+
+    pub fn infrastructure(mut self, infrastructure: InfrastructureKind) -> Self {
+        self.leak.infrastructure = Some(infrastructure);
+        self
+    }
+
+    pub fn context(mut self, context: MatchContext) -> Self {
+        self.leak.context = Some(Arc::new(context));
+        self
+    }
+
+    pub fn pass(mut self, pass: Arc<String>) -> Self {
+        self.leak.pass = Some(pass);
+        self
+    }
+
+    pub fn build(self) -> ConfirmedLeak {
+        self.leak
     }
 }
 
-impl Ord for ConfirmedLeakWithUniqueLocation {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.0.location.cmp(&other.0.location)
+impl From<ConfirmedLeakWithUniqueLocation> for ConfirmedLeak {
+    fn from(leak: ConfirmedLeakWithUniqueLocation) -> Self {
+        leak.0
     }
 }
 
 /// Wrapper struct used to deduplicate `ConfirmedLeak`s in `BTreeSet`s based on
-/// the value of the `leak_information` field.
-#[derive(Serialize)]
-pub struct ConfirmedLeakWithUniqueValue(ConfirmedLeak);
+/// the value of the `location` field.
+#[derive(Clone, Serialize)]
+pub struct ConfirmedLeakWithUniqueLocation(ConfirmedLeak);
 
-impl From<ConfirmedLeak> for ConfirmedLeakWithUniqueValue {
+impl From<ConfirmedLeak> for ConfirmedLeakWithUniqueLocation {
     fn from(leak: ConfirmedLeak) -> Self {
-        ConfirmedLeakWithUniqueValue(leak)
+        ConfirmedLeakWithUniqueLocation(leak)
     }
 }
 
-impl Deref for ConfirmedLeakWithUniqueValue {
+impl Deref for ConfirmedLeakWithUniqueLocation {
     type Target = ConfirmedLeak;
     fn deref(&self) -> &ConfirmedLeak {
         &self.0
     }
 }
 
-impl PartialEq for ConfirmedLeakWithUniqueValue {
+impl PartialEq for ConfirmedLeakWithUniqueLocation {
     fn eq(&self, other: &Self) -> bool {
-        self.0.data == other.0.data
+        self.0.location == other.0.location
     }
 }
 
-impl Eq for ConfirmedLeakWithUniqueValue {}
+impl Eq for ConfirmedLeakWithUniqueLocation {}
 
-impl PartialOrd for ConfirmedLeakWithUniqueValue {
+impl PartialOrd for ConfirmedLeakWithUniqueLocation {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.0.data.partial_cmp(&other.0.data)
+        Some(self.cmp(other))
     }
 }
 
-impl Ord for ConfirmedLeakWithUniqueValue {
+impl Ord for ConfirmedLeakWithUniqueLocation {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.0.data.cmp(&other.0.data)
+        self.0.location.cmp(&other.0.location)
     }
 }
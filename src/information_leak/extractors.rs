@@ -0,0 +1,424 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use clang::{Entity, EntityKind};
+
+use super::comments::comments;
+use super::macro_expansion::MacroExpander;
+use super::potential_leak::{
+    attribute_string_literals, macro_definition_string_literal, record_names, string_literal,
+    template_instantiation_names, union_name, PotentialLeak, WideCharMode,
+};
+use crate::system_headers::SystemHeaderFilter;
+
+/// Kinds of declarations through which class template instantiations are
+/// looked for
+const TEMPLATE_INSTANTIATION_USERS: [EntityKind; 5] = [
+    EntityKind::VarDecl,
+    EntityKind::FieldDecl,
+    EntityKind::TypedefDecl,
+    EntityKind::TypeAliasDecl,
+    EntityKind::BaseSpecifier,
+];
+
+/// Kinds of attributes, whose arguments may contain string literals (e.g.,
+/// `[[nodiscard("...")]]`). Attributes without a dedicated kind (e.g.,
+/// `[[deprecated("...")]]`) are exposed as `UnexposedAttr`.
+const ATTRIBUTE_KINDS: [EntityKind; 42] = [
+    EntityKind::UnexposedAttr,
+    EntityKind::IbActionAttr,
+    EntityKind::IbOutletAttr,
+    EntityKind::IbOutletCollectionAttr,
+    EntityKind::FinalAttr,
+    EntityKind::OverrideAttr,
+    EntityKind::AnnotateAttr,
+    EntityKind::AsmLabelAttr,
+    EntityKind::PackedAttr,
+    EntityKind::PureAttr,
+    EntityKind::ConstAttr,
+    EntityKind::NoDuplicateAttr,
+    EntityKind::CudaConstantAttr,
+    EntityKind::CudaDeviceAttr,
+    EntityKind::CudaGlobalAttr,
+    EntityKind::CudaHostAttr,
+    EntityKind::CudaSharedAttr,
+    EntityKind::VisibilityAttr,
+    EntityKind::DllExport,
+    EntityKind::DllImport,
+    EntityKind::NSReturnsRetained,
+    EntityKind::NSReturnsNotRetained,
+    EntityKind::NSReturnsAutoreleased,
+    EntityKind::NSConsumesSelf,
+    EntityKind::NSConsumed,
+    EntityKind::ObjCException,
+    EntityKind::ObjCNSObject,
+    EntityKind::ObjCIndependentClass,
+    EntityKind::ObjCPreciseLifetime,
+    EntityKind::ObjCReturnsInnerPointer,
+    EntityKind::ObjCRequiresSuper,
+    EntityKind::ObjCRootClass,
+    EntityKind::ObjCSubclassingRestricted,
+    EntityKind::ObjCExplicitProtocolImpl,
+    EntityKind::ObjCDesignatedInitializer,
+    EntityKind::ObjCRuntimeVisible,
+    EntityKind::ObjCBoxable,
+    EntityKind::FlagEnum,
+    EntityKind::ConvergentAttr,
+    EntityKind::WarnUnusedAttr,
+    EntityKind::WarnUnusedResultAttr,
+    EntityKind::AlignedAttr,
+];
+
+/// State shared by the extractors while processing the entities of a
+/// translation unit
+pub struct ExtractionContext<'a> {
+    /// Encoding of wide strings, or the current platform's convention if not
+    /// specified
+    pub wide_char_mode: Option<WideCharMode>,
+    /// Filter of the entities declared in system headers, if they're ignored
+    pub system_header_filter: Option<&'a SystemHeaderFilter>,
+    /// Macros defined so far in the translation unit
+    pub macro_expander: MacroExpander,
+}
+
+impl<'a> ExtractionContext<'a> {
+    pub fn new(
+        wide_char_mode: Option<WideCharMode>,
+        system_header_filter: Option<&'a SystemHeaderFilter>,
+    ) -> Self {
+        Self {
+            wide_char_mode,
+            system_header_filter,
+            macro_expander: MacroExpander::default(),
+        }
+    }
+}
+
+/// Extracts a category of artifacts (e.g., string literals) from the entities
+/// of a translation unit
+pub trait ArtifactExtractor: Send + Sync {
+    /// Kinds of entities this extractor extracts artifacts from
+    fn entity_kinds(&self) -> &[EntityKind];
+
+    /// Extracts the artifacts declared by `entity`, whose kind is one of
+    /// `entity_kinds`. Entities are given in the order they appear in the
+    /// translation unit.
+    fn extract(
+        &self,
+        entity: Entity,
+        context: &mut ExtractionContext,
+    ) -> Result<Vec<PotentialLeak>>;
+}
+
+/// String literals, including attributes' arguments
+struct StringLiteralExtractor {
+    entity_kinds: Vec<EntityKind>,
+}
+
+impl Default for StringLiteralExtractor {
+    fn default() -> Self {
+        let mut entity_kinds = vec![EntityKind::StringLiteral];
+        // Attributes' arguments aren't exposed as string literals
+        entity_kinds.extend(ATTRIBUTE_KINDS);
+
+        Self { entity_kinds }
+    }
+}
+
+impl ArtifactExtractor for StringLiteralExtractor {
+    fn entity_kinds(&self) -> &[EntityKind] {
+        &self.entity_kinds
+    }
+
+    fn extract(
+        &self,
+        entity: Entity,
+        context: &mut ExtractionContext,
+    ) -> Result<Vec<PotentialLeak>> {
+        match entity.get_kind() {
+            EntityKind::StringLiteral => {
+                string_literal(entity, context.wide_char_mode).map(|leak| vec![leak])
+            }
+            _ => attribute_string_literals(entity, context.wide_char_mode),
+        }
+    }
+}
+
+/// String constants defined as macros
+struct MacroExtractor;
+
+impl ArtifactExtractor for MacroExtractor {
+    fn entity_kinds(&self) -> &[EntityKind] {
+        &[EntityKind::MacroDefinition]
+    }
+
+    fn extract(
+        &self,
+        entity: Entity,
+        context: &mut ExtractionContext,
+    ) -> Result<Vec<PotentialLeak>> {
+        macro_definition_string_literal(entity, &mut context.macro_expander, context.wide_char_mode)
+    }
+}
+
+/// Struct, class and class template names, along with the names of the class
+/// template instantiations in use
+struct TypeNameExtractor {
+    entity_kinds: Vec<EntityKind>,
+}
+
+impl Default for TypeNameExtractor {
+    fn default() -> Self {
+        let mut entity_kinds = vec![
+            EntityKind::StructDecl,
+            EntityKind::ClassDecl,
+            EntityKind::ClassTemplate,
+        ];
+        // Declarations whose type may be a class template instantiation
+        entity_kinds.extend(TEMPLATE_INSTANTIATION_USERS);
+
+        Self { entity_kinds }
+    }
+}
+
+impl ArtifactExtractor for TypeNameExtractor {
+    fn entity_kinds(&self) -> &[EntityKind] {
+        &self.entity_kinds
+    }
+
+    fn extract(
+        &self,
+        entity: Entity,
+        context: &mut ExtractionContext,
+    ) -> Result<Vec<PotentialLeak>> {
+        match entity.get_kind() {
+            EntityKind::StructDecl | EntityKind::ClassDecl => record_names(entity),
+            entity_kind if TEMPLATE_INSTANTIATION_USERS.contains(&entity_kind) => {
+                template_instantiation_names(entity, context.system_header_filter)
+            }
+            _ => entity.try_into().map(|leak| vec![leak]),
+        }
+    }
+}
+
+/// Names of unions
+struct UnionNameExtractor;
+
+impl ArtifactExtractor for UnionNameExtractor {
+    fn entity_kinds(&self) -> &[EntityKind] {
+        &[EntityKind::UnionDecl]
+    }
+
+    fn extract(&self, entity: Entity, _: &mut ExtractionContext) -> Result<Vec<PotentialLeak>> {
+        union_name(entity)
+    }
+}
+
+/// Extractor of the entities that map directly to a single artifact (e.g.,
+/// enum names)
+struct DeclarationNameExtractor {
+    entity_kinds: &'static [EntityKind],
+}
+
+impl ArtifactExtractor for DeclarationNameExtractor {
+    fn entity_kinds(&self) -> &[EntityKind] {
+        self.entity_kinds
+    }
+
+    fn extract(&self, entity: Entity, _: &mut ExtractionContext) -> Result<Vec<PotentialLeak>> {
+        entity.try_into().map(|leak| vec![leak])
+    }
+}
+
+/// Text of comments, which requires tokenizing the translation unit's main
+/// file
+struct CommentExtractor;
+
+impl ArtifactExtractor for CommentExtractor {
+    fn entity_kinds(&self) -> &[EntityKind] {
+        &[EntityKind::TranslationUnit]
+    }
+
+    fn extract(
+        &self,
+        entity: Entity,
+        context: &mut ExtractionContext,
+    ) -> Result<Vec<PotentialLeak>> {
+        comments(entity, context.system_header_filter)
+    }
+}
+
+/// Set of extractors, selectable by name
+pub struct ArtifactExtractorRegistry {
+    extractors: BTreeMap<String, Box<dyn ArtifactExtractor>>,
+}
+
+impl ArtifactExtractorRegistry {
+    /// Creates a registry without any extractor
+    pub fn new() -> Self {
+        Self {
+            extractors: BTreeMap::new(),
+        }
+    }
+
+    /// Registers an extractor under the given name, replacing any extractor
+    /// previously registered with it
+    pub fn register(&mut self, name: &str, extractor: Box<dyn ArtifactExtractor>) {
+        self.extractors.insert(name.to_string(), extractor);
+    }
+
+    /// Returns the extractor registered under the given name
+    pub fn get(&self, name: &str) -> Result<&dyn ArtifactExtractor> {
+        self.extractors
+            .get(name)
+            .map(|extractor| extractor.as_ref())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unknown extractor '{}' (available extractors: {})",
+                    name,
+                    self.names().join(", ")
+                )
+            })
+    }
+
+    /// Returns the extractors registered under the given names
+    pub fn select<S: AsRef<str>>(&self, names: &[S]) -> Result<Vec<&dyn ArtifactExtractor>> {
+        names.iter().map(|name| self.get(name.as_ref())).collect()
+    }
+
+    /// Returns the names of the registered extractors
+    pub fn names(&self) -> Vec<String> {
+        self.extractors.keys().cloned().collect()
+    }
+}
+
+impl Default for ArtifactExtractorRegistry {
+    /// Creates a registry containing the built-in extractors
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register(
+            "string_literals",
+            Box::new(StringLiteralExtractor::default()),
+        );
+        registry.register("macros", Box::new(MacroExtractor));
+        registry.register("type_names", Box::new(TypeNameExtractor::default()));
+        registry.register("union_names", Box::new(UnionNameExtractor));
+        registry.register(
+            "typedef_names",
+            Box::new(DeclarationNameExtractor {
+                entity_kinds: &[EntityKind::TypedefDecl, EntityKind::TypeAliasDecl],
+            }),
+        );
+        registry.register(
+            "enum_names",
+            Box::new(DeclarationNameExtractor {
+                entity_kinds: &[EntityKind::EnumDecl, EntityKind::EnumConstantDecl],
+            }),
+        );
+        registry.register(
+            "function_names",
+            Box::new(DeclarationNameExtractor {
+                entity_kinds: &[EntityKind::FunctionDecl, EntityKind::Method],
+            }),
+        );
+        registry.register(
+            "field_names",
+            Box::new(DeclarationNameExtractor {
+                entity_kinds: &[EntityKind::FieldDecl],
+            }),
+        );
+        registry.register("comments", Box::new(CommentExtractor));
+
+        registry
+    }
+}
+
+/// Categories of artifacts to extract, as enabled or disabled by the
+/// `ignore_*` and `include_*` options. The default selection corresponds to
+/// the command-line tool's defaults.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExtractorSelection {
+    pub ignore_string_literals: bool,
+    pub ignore_struct_names: bool,
+    pub ignore_union_names: bool,
+    pub ignore_typedef_names: bool,
+    pub ignore_enum_names: bool,
+    pub include_function_names: bool,
+    pub include_field_names: bool,
+    pub include_comments: bool,
+    pub include_macro_values: bool,
+}
+
+impl ExtractorSelection {
+    /// Returns the names of the built-in extractors enabled by the selection
+    pub fn extractor_names(&self) -> Vec<String> {
+        [
+            ("string_literals", !self.ignore_string_literals),
+            ("macros", self.include_macro_values),
+            ("type_names", !self.ignore_struct_names),
+            ("union_names", !self.ignore_union_names),
+            ("typedef_names", !self.ignore_typedef_names),
+            ("enum_names", !self.ignore_enum_names),
+            ("function_names", self.include_function_names),
+            ("field_names", self.include_field_names),
+            ("comments", self.include_comments),
+        ]
+        .into_iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| name.to_string())
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extractor_names_from_selection() {
+        assert_eq!(
+            ExtractorSelection::default().extractor_names(),
+            vec![
+                "string_literals",
+                "type_names",
+                "union_names",
+                "typedef_names",
+                "enum_names"
+            ]
+        );
+        assert_eq!(
+            ExtractorSelection {
+                ignore_string_literals: true,
+                ignore_struct_names: true,
+                ignore_union_names: true,
+                ignore_typedef_names: true,
+                ignore_enum_names: true,
+                include_function_names: true,
+                include_field_names: true,
+                include_comments: true,
+                include_macro_values: true,
+            }
+            .extractor_names(),
+            vec!["macros", "function_names", "field_names", "comments"]
+        );
+    }
+
+    #[test]
+    fn select_extractors() {
+        let registry = ArtifactExtractorRegistry::default();
+        let extractors = registry
+            .select(&["macros", "field_names"])
+            .expect("select failed");
+        assert_eq!(extractors.len(), 2);
+        assert_eq!(
+            extractors[0].entity_kinds(),
+            [EntityKind::MacroDefinition].as_slice()
+        );
+        assert_eq!(
+            extractors[1].entity_kinds(),
+            [EntityKind::FieldDecl].as_slice()
+        );
+
+        assert!(registry.select(&["string_literals", "docs"]).is_err());
+    }
+}
@@ -1,22 +1,53 @@
 use std::{hash::Hash, path::PathBuf, sync::Arc};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 /// Struct containing the source and binary locations of leaked data
-#[derive(Serialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct LeakLocation {
     pub source: Arc<SourceLocation>,
     pub binary: BinaryLocation,
 }
 
-#[derive(Debug, Serialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct SourceLocation {
     pub file: PathBuf,
     pub line: u64,
 }
 
-#[derive(Serialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct BinaryLocation {
     pub file: Arc<PathBuf>,
     pub offset: u64,
+    /// Region of the binary containing the leaked data (e.g., a WebAssembly
+    /// data segment), for supported file formats
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<Arc<String>>,
+    /// Virtual address of the leaked data (e.g., to cross-reference it in a
+    /// disassembler), if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub virtual_address: Option<u64>,
+    /// Architecture of the slice of a universal (fat) binary containing the
+    /// leaked data, if the binary contains multiple architectures
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub architecture: Option<Arc<String>>,
+    /// Set if the leaked data is part of the binary's debug information
+    /// (e.g., DWARF sections), for supported file formats.
+    /// Note: Not part of reports, see `ConfirmedLeak::visibility` instead.
+    #[serde(skip)]
+    pub debug_info: bool,
+}
+
+impl BinaryLocation {
+    /// Creates the location of data found at `offset` in the given file
+    pub fn new(file: Arc<PathBuf>, offset: u64) -> Self {
+        Self {
+            file,
+            offset,
+            region: None,
+            virtual_address: None,
+            architecture: None,
+            debug_info: false,
+        }
+    }
 }
@@ -0,0 +1,356 @@
+//! Minimal evaluation of macro definitions, used to compute the string
+//! literals macros expand to when they are built from other macros (e.g.,
+//! `#define GREETING "Hello, " NAME`) or from stringification (e.g.,
+//! `#define VERSION_STRING "v" STRINGIFY(VERSION)`).
+
+use std::collections::HashMap;
+
+use clang::{token::TokenKind, Entity};
+
+/// Maximum number of nested macros expanded in stringified arguments
+const MAX_EXPANSION_DEPTH: usize = 8;
+
+/// Token of a macro definition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MacroToken {
+    pub spelling: String,
+    pub kind: MacroTokenKind,
+    /// Set if the token is preceded by whitespace in the source code
+    pub preceded_by_space: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MacroTokenKind {
+    /// Keyword or identifier
+    Identifier,
+    Literal,
+    Punctuation,
+}
+
+impl MacroToken {
+    /// Returns the tokens of the given macro definition (including its name)
+    pub fn from_macro_definition(entity: Entity) -> Vec<Self> {
+        let tokens = match entity.get_range() {
+            Some(range) => range.tokenize(),
+            None => return vec![],
+        };
+
+        let mut previous_end_offset = None;
+        tokens
+            .into_iter()
+            .filter_map(|token| {
+                let kind = match token.get_kind() {
+                    TokenKind::Identifier | TokenKind::Keyword => MacroTokenKind::Identifier,
+                    TokenKind::Literal => MacroTokenKind::Literal,
+                    TokenKind::Punctuation => MacroTokenKind::Punctuation,
+                    TokenKind::Comment => return None,
+                };
+                let range = token.get_range();
+                let start_offset = range.get_start().get_file_location().offset;
+                let preceded_by_space =
+                    matches!(previous_end_offset, Some(end_offset) if end_offset < start_offset);
+                previous_end_offset = Some(range.get_end().get_file_location().offset);
+
+                Some(Self {
+                    spelling: token.get_spelling(),
+                    kind,
+                    preceded_by_space,
+                })
+            })
+            .collect()
+    }
+}
+
+/// What is known about a macro defined earlier in the translation unit
+enum MacroValue {
+    /// Object-like macro expanding to string literals
+    StringLiterals(Vec<String>),
+    /// Other object-like macro (e.g., a version number), kept so that it can
+    /// be stringified
+    Tokens(String),
+    /// Function-like macro stringifying its argument (e.g.,
+    /// `#define STR(x) #x`), possibly after expanding it (e.g.,
+    /// `#define XSTR(x) STR(x)`)
+    Stringification { expand_argument: bool },
+}
+
+/// Keeps track of the macros defined in a translation unit, in order to
+/// evaluate the string literals macros expand to
+#[derive(Default)]
+pub struct MacroExpander {
+    macros: HashMap<String, MacroValue>,
+}
+
+impl MacroExpander {
+    /// Records a macro definition, given its tokens (starting with its name),
+    /// and returns the string literals it expands to, if it only expands to
+    /// string literals. Adjacent string literals are returned separately, as
+    /// they're concatenated by the compiler.
+    pub fn define(&mut self, tokens: &[MacroToken]) -> Option<Vec<String>> {
+        let (name, tokens) = tokens.split_first()?;
+        let is_function_like = matches!(tokens.first(), Some(token) if token.spelling == "(" && !token.preceded_by_space);
+        if is_function_like {
+            if let Some(value) = self.parse_function_like_macro(tokens) {
+                self.macros.insert(name.spelling.clone(), value);
+            } else {
+                self.macros.remove(&name.spelling);
+            }
+            return None;
+        }
+
+        match self.expand_string_literals(tokens) {
+            Some(string_literals) => {
+                self.macros.insert(
+                    name.spelling.clone(),
+                    MacroValue::StringLiterals(string_literals.clone()),
+                );
+                Some(string_literals)
+            }
+            None => {
+                self.macros.insert(
+                    name.spelling.clone(),
+                    MacroValue::Tokens(join_tokens(tokens)),
+                );
+                None
+            }
+        }
+    }
+
+    /// Recognizes stringification macros (i.e., `(x) #x` and `(x) STR(x)`
+    /// where `STR` is a stringification macro)
+    fn parse_function_like_macro(&self, tokens: &[MacroToken]) -> Option<MacroValue> {
+        let spellings: Vec<&str> = tokens.iter().map(|token| token.spelling.as_str()).collect();
+        match spellings.as_slice() {
+            ["(", parameter, ")", "#", argument] if parameter == argument => {
+                Some(MacroValue::Stringification {
+                    expand_argument: false,
+                })
+            }
+            ["(", parameter, ")", inner_macro, "(", argument, ")"]
+                if parameter == argument && self.is_stringification(inner_macro) =>
+            {
+                Some(MacroValue::Stringification {
+                    expand_argument: true,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn is_stringification(&self, name: &str) -> bool {
+        matches!(
+            self.macros.get(name),
+            Some(MacroValue::Stringification { .. })
+        )
+    }
+
+    /// Returns the string literals the given replacement tokens expand to, if
+    /// they only contain string literals, string macros and invocations of
+    /// stringification macros
+    fn expand_string_literals(&self, tokens: &[MacroToken]) -> Option<Vec<String>> {
+        let mut string_literals = vec![];
+        let mut remaining_tokens = tokens;
+        while let Some((token, rest)) = remaining_tokens.split_first() {
+            remaining_tokens = rest;
+            match (token.kind, self.macros.get(&token.spelling)) {
+                (MacroTokenKind::Literal, _) if is_string_literal(&token.spelling) => {
+                    string_literals.push(token.spelling.clone())
+                }
+                (
+                    MacroTokenKind::Identifier,
+                    Some(MacroValue::StringLiterals(macro_string_literals)),
+                ) => string_literals.extend(macro_string_literals.iter().cloned()),
+                (
+                    MacroTokenKind::Identifier,
+                    Some(MacroValue::Stringification { expand_argument }),
+                ) => {
+                    let (argument, rest) = split_invocation_argument(remaining_tokens)?;
+                    remaining_tokens = rest;
+                    let argument = if *expand_argument {
+                        self.expand_argument(argument, 0)
+                    } else {
+                        join_tokens(argument)
+                    };
+                    string_literals.push(stringify(&argument));
+                }
+                _ => return None,
+            }
+        }
+
+        if string_literals.is_empty() {
+            None
+        } else {
+            Some(string_literals)
+        }
+    }
+
+    /// Expands the object-like macros used as the argument of a
+    /// stringification macro
+    fn expand_argument(&self, argument: &[MacroToken], depth: usize) -> String {
+        match argument {
+            [token] if depth < MAX_EXPANSION_DEPTH => match self.macros.get(&token.spelling) {
+                Some(MacroValue::Tokens(value)) => {
+                    // Expand macros defined as other macros
+                    let value_token = MacroToken {
+                        spelling: value.clone(),
+                        kind: MacroTokenKind::Identifier,
+                        preceded_by_space: false,
+                    };
+                    self.expand_argument(&[value_token], depth + 1)
+                }
+                Some(MacroValue::StringLiterals(string_literals)) => string_literals.join(" "),
+                _ => token.spelling.clone(),
+            },
+            _ => join_tokens(argument),
+        }
+    }
+}
+
+/// Splits `( argument ) rest` into `argument` and `rest`
+fn split_invocation_argument(tokens: &[MacroToken]) -> Option<(&[MacroToken], &[MacroToken])> {
+    if tokens.first()?.spelling != "(" {
+        return None;
+    }
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        match token.spelling.as_str() {
+            "(" => depth += 1,
+            ")" => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((&tokens[1..i], &tokens[i + 1..]));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Joins tokens the way the preprocessor does when stringifying them: tokens
+/// separated by whitespace are separated by a single space
+fn join_tokens(tokens: &[MacroToken]) -> String {
+    tokens
+        .iter()
+        .enumerate()
+        .fold(String::new(), |mut accum, (i, token)| {
+            if i > 0 && token.preceded_by_space {
+                accum.push(' ');
+            }
+            accum.push_str(&token.spelling);
+            accum
+        })
+}
+
+/// Applies the `#` operator to the given text
+fn stringify(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn is_string_literal(spelling: &str) -> bool {
+    // Note: Character literals aren't string literals
+    spelling.ends_with('"')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tokenizes a simplified macro definition (e.g., `NAME "value"`), where
+    /// tokens are separated by spaces unless they're punctuation
+    fn tokens(definition: &str) -> Vec<MacroToken> {
+        let mut tokens: Vec<MacroToken> = vec![];
+        let mut preceded_by_space = false;
+        let mut chars = definition.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == ' ' {
+                preceded_by_space = true;
+                continue;
+            }
+            let (spelling, kind) = if c == '"' {
+                let mut spelling = String::from(c);
+                for c in chars.by_ref() {
+                    spelling.push(c);
+                    if c == '"' {
+                        break;
+                    }
+                }
+                (spelling, MacroTokenKind::Literal)
+            } else if c.is_alphanumeric() || c == '_' {
+                let mut spelling = String::from(c);
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                    spelling.push(c);
+                }
+                let kind = if c.is_ascii_digit() {
+                    MacroTokenKind::Literal
+                } else {
+                    MacroTokenKind::Identifier
+                };
+                (spelling, kind)
+            } else {
+                (c.to_string(), MacroTokenKind::Punctuation)
+            };
+            tokens.push(MacroToken {
+                spelling,
+                kind,
+                preceded_by_space,
+            });
+            preceded_by_space = false;
+        }
+
+        tokens
+    }
+
+    fn define(expander: &mut MacroExpander, definition: &str) -> Option<Vec<String>> {
+        expander.define(&tokens(definition))
+    }
+
+    #[test]
+    fn expand_string_macros() {
+        let mut expander = MacroExpander::default();
+
+        assert_eq!(
+            define(&mut expander, r#"NAME "Bob""#),
+            Some(vec![r#""Bob""#.to_string()])
+        );
+        assert_eq!(
+            define(&mut expander, r#"GREETING "Hello, " NAME "!""#),
+            Some(vec![
+                r#""Hello, ""#.to_string(),
+                r#""Bob""#.to_string(),
+                r#""!""#.to_string()
+            ])
+        );
+        assert_eq!(define(&mut expander, "VALUE 42"), None);
+        assert_eq!(define(&mut expander, r#"MIXED "value" VALUE"#), None);
+        assert_eq!(define(&mut expander, r#"UNKNOWN "value" OTHER"#), None);
+    }
+
+    #[test]
+    fn expand_stringification() {
+        let mut expander = MacroExpander::default();
+
+        assert_eq!(define(&mut expander, "STR(x) #x"), None);
+        assert_eq!(define(&mut expander, "XSTR(x) STR(x)"), None);
+        assert_eq!(define(&mut expander, "MAJOR 1"), None);
+        assert_eq!(define(&mut expander, "VERSION MAJOR"), None);
+
+        assert_eq!(
+            define(&mut expander, r#"NAME STR(VERSION)"#),
+            Some(vec![r#""VERSION""#.to_string()])
+        );
+        assert_eq!(
+            define(&mut expander, r#"VERSION_STRING "v" XSTR(VERSION)"#),
+            Some(vec![r#""v""#.to_string(), r#""1""#.to_string()])
+        );
+        assert_eq!(
+            define(&mut expander, r#"QUOTED STR(a "b" c)"#),
+            Some(vec![r#""a \"b\" c""#.to_string()])
+        );
+        // Not a stringification, as the parameter list isn't attached to
+        // the name
+        assert_eq!(define(&mut expander, "NOT_STR (x) #x"), None);
+        assert_eq!(define(&mut expander, "INVALID NOT_STR(x)"), None);
+    }
+}
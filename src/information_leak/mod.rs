@@ -1,20 +1,91 @@
+mod comments;
 mod confirmed_leak;
+mod extractors;
 mod leak_location;
+mod macro_expansion;
 mod potential_leak;
 
+pub use comments::comments;
 pub use confirmed_leak::*;
+pub use extractors::*;
 pub use leak_location::*;
+pub use macro_expansion::MacroExpander;
 pub use potential_leak::*;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-/// Describes the kind of data that's leaked
-#[derive(Debug, Serialize, Clone, Copy)]
+/// Describes the kind of data that's leaked.
+/// Can be deserialized from snake_case names (e.g., in configuration files) as
+/// well as from the names used in reports.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all(deserialize = "snake_case"))]
 pub enum LeakedDataType {
     /// Data comes from a string literal
+    #[serde(alias = "StringLiteral")]
     StringLiteral,
+    /// Data comes from a macro defined as a string literal
+    #[serde(alias = "MacroValue")]
+    MacroValue,
     /// Data represents the name of a C/C++ struct
+    #[serde(alias = "StructName")]
     StructName,
     /// Data represents the name of a C++ class
+    #[serde(alias = "ClassName")]
     ClassName,
+    /// Data represents the name of a C++ class template or of one of its
+    /// instantiations (e.g., `MyTemplate<int>`)
+    #[serde(alias = "TemplateName")]
+    TemplateName,
+    /// Data represents the name of a C/C++ union
+    #[serde(alias = "UnionName")]
+    UnionName,
+    /// Data represents the name given to a type with `typedef` or `using`
+    #[serde(alias = "TypedefName")]
+    TypedefName,
+    /// Data represents the name of a C/C++ enum
+    #[serde(alias = "EnumName")]
+    EnumName,
+    /// Data represents the name of a C/C++ enumerator
+    #[serde(alias = "EnumeratorName")]
+    EnumeratorName,
+    /// Data represents the name of a C/C++ function
+    #[serde(alias = "FunctionName")]
+    FunctionName,
+    /// Data represents the name of a C++ method
+    #[serde(alias = "MethodName")]
+    MethodName,
+    /// Data represents the name of a C/C++ struct or class field
+    #[serde(alias = "FieldName")]
+    FieldName,
+    /// Data comes from a custom artifact rule defined by the user
+    #[serde(alias = "CustomArtifact")]
+    CustomArtifact,
+    /// Data comes from a resource file (e.g., JSON, YAML or INI file)
+    #[serde(alias = "ResourceString")]
+    ResourceString,
+    /// Data represents the content of a file embedded into the binary (e.g.,
+    /// with `#embed` or `INCBIN`)
+    #[serde(alias = "EmbeddedFile")]
+    EmbeddedFile,
+    /// Data comes from a file generated from a serialization schema (e.g.,
+    /// Protobuf descriptors, message and field names)
+    #[serde(alias = "SerializationSchema")]
+    SerializationSchema,
+    /// Data represents a COM GUID (in its string or binary form)
+    #[serde(alias = "Guid")]
+    Guid,
+    /// Data represents the name of a COM interface
+    #[serde(alias = "ComInterfaceName")]
+    ComInterfaceName,
+    /// Data represents the path of a source file (e.g., expanded from
+    /// `__FILE__` in assertions or logging macros)
+    #[serde(alias = "SourcePath")]
+    SourcePath,
+    /// Data has been provided by the user
+    #[serde(alias = "UserProvided")]
+    UserProvided,
+    /// Data comes from a comment (e.g., `TODO` notes, authors or license
+    /// headers)
+    #[serde(alias = "Comment")]
+    Comment,
 }
@@ -1,14 +1,17 @@
-use std::{borrow::Cow, hash::Hash, sync::Arc};
+use std::{borrow::Cow, hash::Hash, str::FromStr, sync::Arc};
 
 use anyhow::{anyhow, Result};
-use clang::{Entity, EntityKind};
+use clang::{token::TokenKind, Entity, EntityKind};
+use serde::{Deserialize, Serialize};
 use widestring::{encode_utf16, encode_utf32};
 
+use super::macro_expansion::{MacroExpander, MacroToken};
 use super::{LeakedDataType, SourceLocation};
+use crate::system_headers::SystemHeaderFilter;
 
 /// Struct containing information on a piece of data from the source code, which
 /// may leak into a binary file.
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PotentialLeak {
     /// Type of data leaked
     pub data_type: LeakedDataType,
@@ -17,6 +20,9 @@ pub struct PotentialLeak {
     /// Byte pattern to match (i.e., leaked information, as represented in the
     /// binary file)
     pub bytes: Vec<u8>,
+    /// Size in bytes of the code units used to encode `bytes` (e.g., 2 for
+    /// UTF-16LE strings)
+    pub code_unit_size: usize,
     /// Information on where the leaked data is declared in the source code
     pub declaration_metadata: Arc<SourceLocation>,
 }
@@ -35,34 +41,98 @@ impl TryFrom<Entity<'_>> for PotentialLeak {
             .get_path();
 
         match entity.get_kind() {
-            EntityKind::StringLiteral => {
-                let leaked_information = entity
-                    .get_display_name()
-                    .ok_or_else(|| anyhow!("Failed to get entity's display name"))?;
-                let (_, string_content) = parse_string_literal(&leaked_information)?;
+            EntityKind::StringLiteral => string_literal(entity, None),
+            entity_kind @ (EntityKind::StructDecl
+            | EntityKind::ClassDecl
+            | EntityKind::EnumDecl
+            | EntityKind::EnumConstantDecl) => {
+                // Convert `EntityKind` to `LeakedDataType`
+                let data_type = match entity_kind {
+                    EntityKind::StructDecl => LeakedDataType::StructName,
+                    EntityKind::ClassDecl => LeakedDataType::ClassName,
+                    EntityKind::EnumDecl => LeakedDataType::EnumName,
+                    EntityKind::EnumConstantDecl => LeakedDataType::EnumeratorName,
+                    _ => unreachable!("This entity kind should not be matched"),
+                };
+                let leaked_information = entity.get_display_name().unwrap_or_default();
+
+                Ok(Self {
+                    data_type,
+                    bytes: leaked_information.as_bytes().to_vec(),
+                    code_unit_size: 1,
+                    data: Arc::new(leaked_information),
+                    declaration_metadata: Arc::new(SourceLocation {
+                        file: file_location.canonicalize()?,
+                        line: location.line as u64,
+                    }),
+                })
+            }
+            EntityKind::ClassTemplate => {
+                // Note: Display names include template parameters, which
+                // are replaced by arguments in instantiations' names
+                let leaked_information = entity.get_name().unwrap_or_default();
 
                 Ok(Self {
-                    data_type: LeakedDataType::StringLiteral,
-                    data: Arc::new(string_content.to_owned()),
-                    bytes: string_literal_to_bytes(&leaked_information, None)?,
+                    data_type: LeakedDataType::TemplateName,
+                    bytes: leaked_information.as_bytes().to_vec(),
+                    code_unit_size: 1,
+                    data: Arc::new(leaked_information),
                     declaration_metadata: Arc::new(SourceLocation {
                         file: file_location.canonicalize()?,
                         line: location.line as u64,
                     }),
                 })
             }
-            entity_kind @ (EntityKind::StructDecl | EntityKind::ClassDecl) => {
+            entity_kind @ (EntityKind::UnionDecl
+            | EntityKind::TypedefDecl
+            | EntityKind::TypeAliasDecl) => {
                 // Convert `EntityKind` to `LeakedDataType`
                 let data_type = match entity_kind {
-                    EntityKind::StructDecl => LeakedDataType::StructName,
-                    EntityKind::ClassDecl => LeakedDataType::ClassName,
+                    EntityKind::UnionDecl => LeakedDataType::UnionName,
+                    _ => LeakedDataType::TypedefName,
+                };
+                let leaked_information = entity.get_name().unwrap_or_default();
+
+                Ok(Self {
+                    data_type,
+                    bytes: leaked_information.as_bytes().to_vec(),
+                    code_unit_size: 1,
+                    data: Arc::new(leaked_information),
+                    declaration_metadata: Arc::new(SourceLocation {
+                        file: file_location.canonicalize()?,
+                        line: location.line as u64,
+                    }),
+                })
+            }
+            entity_kind @ (EntityKind::FunctionDecl | EntityKind::Method) => {
+                // Convert `EntityKind` to `LeakedDataType`
+                let data_type = match entity_kind {
+                    EntityKind::FunctionDecl => LeakedDataType::FunctionName,
+                    EntityKind::Method => LeakedDataType::MethodName,
                     _ => unreachable!("This entity kind should not be matched"),
                 };
-                let leaked_information = entity.get_display_name().unwrap_or_default();
+                // Note: Display names include parameter types, which aren't
+                // part of the symbol names we're looking for
+                let leaked_information = entity.get_name().unwrap_or_default();
 
                 Ok(Self {
                     data_type,
                     bytes: leaked_information.as_bytes().to_vec(),
+                    code_unit_size: 1,
+                    data: Arc::new(leaked_information),
+                    declaration_metadata: Arc::new(SourceLocation {
+                        file: file_location.canonicalize()?,
+                        line: location.line as u64,
+                    }),
+                })
+            }
+            EntityKind::FieldDecl => {
+                let leaked_information = entity.get_name().unwrap_or_default();
+
+                Ok(Self {
+                    data_type: LeakedDataType::FieldName,
+                    bytes: leaked_information.as_bytes().to_vec(),
+                    code_unit_size: 1,
                     data: Arc::new(leaked_information),
                     declaration_metadata: Arc::new(SourceLocation {
                         file: file_location.canonicalize()?,
@@ -75,6 +145,380 @@ impl TryFrom<Entity<'_>> for PotentialLeak {
     }
 }
 
+impl PotentialLeak {
+    /// Creates a `PotentialLeak` from a string literal, as written in the
+    /// source code. Wide strings are encoded according to `wide_char_mode`, or
+    /// to the current platform's convention if not specified.
+    pub fn from_string_literal(
+        string_literal: &str,
+        location: SourceLocation,
+        wide_char_mode: Option<WideCharMode>,
+    ) -> Result<Self> {
+        let (string_encoding, string_content) = parse_string_literal(string_literal)?;
+
+        Ok(Self {
+            data_type: LeakedDataType::StringLiteral,
+            data: Arc::new(string_content.to_owned()),
+            bytes: string_literal_to_bytes(string_literal, wide_char_mode)?,
+            code_unit_size: code_unit_size(&string_encoding, wide_char_mode),
+            declaration_metadata: Arc::new(location),
+        })
+    }
+
+    /// Returns a UTF-16LE variant of the potential leak, if its byte pattern
+    /// is made of ASCII characters only
+    pub fn utf16_variant(&self) -> Option<Self> {
+        if self.code_unit_size != 1 || !self.bytes.is_ascii() {
+            return None;
+        }
+
+        Some(Self {
+            data_type: self.data_type,
+            data: self.data.clone(),
+            bytes: self.bytes.iter().flat_map(|byte| [*byte, 0]).collect(),
+            code_unit_size: 2,
+            declaration_metadata: self.declaration_metadata.clone(),
+        })
+    }
+
+    /// Returns variants of a string potential leak in the encodings it isn't
+    /// represented with (i.e., UTF-8, UTF-16LE and UTF-32LE), so that strings
+    /// are found whatever the encoding the compiler or the code picked
+    pub fn encoding_variants(&self) -> Vec<Self> {
+        if !matches!(
+            self.data_type,
+            LeakedDataType::StringLiteral | LeakedDataType::MacroValue
+        ) {
+            return vec![];
+        }
+        let string = match decode_string(&self.bytes, self.code_unit_size) {
+            Some(string) => string,
+            None => return vec![],
+        };
+
+        [
+            (1, string.as_bytes().to_vec()),
+            (2, encode_utf16_le(&string)),
+            (4, encode_utf32_le(&string)),
+        ]
+        .into_iter()
+        .filter(|(code_unit_size, _)| *code_unit_size != self.code_unit_size)
+        .map(|(code_unit_size, bytes)| Self {
+            data_type: self.data_type,
+            data: self.data.clone(),
+            bytes,
+            code_unit_size,
+            declaration_metadata: self.declaration_metadata.clone(),
+        })
+        .collect()
+    }
+
+    /// Returns the mangling scheme of the RTTI type name this potential leak
+    /// looks for, if it's the mangled form of a type name
+    pub fn mangling_scheme(&self) -> Option<ManglingScheme> {
+        if !matches!(
+            self.data_type,
+            LeakedDataType::StructName | LeakedDataType::ClassName | LeakedDataType::TemplateName
+        ) || self.bytes == self.data.as_bytes()
+        {
+            return None;
+        }
+
+        if self.bytes.starts_with(b".?A") {
+            Some(ManglingScheme::Msvc)
+        } else if self.bytes.starts_with(b"N") && self.bytes.ends_with(b"E") {
+            Some(ManglingScheme::Itanium)
+        } else {
+            None
+        }
+    }
+}
+
+/// Extracts a string literal, whose wide strings are encoded according to
+/// `wide_char_mode` (or to the current platform's convention if not specified)
+pub fn string_literal(
+    entity: Entity,
+    wide_char_mode: Option<WideCharMode>,
+) -> Result<PotentialLeak> {
+    let location = entity
+        .get_location()
+        .ok_or_else(|| anyhow!("Failed to get entity's location"))?
+        .get_file_location();
+    let file_location = location
+        .file
+        .ok_or_else(|| anyhow!("Failed to get entity's file location"))?
+        .get_path();
+    let leaked_information = entity
+        .get_display_name()
+        .ok_or_else(|| anyhow!("Failed to get entity's display name"))?;
+
+    PotentialLeak::from_string_literal(
+        &leaked_information,
+        SourceLocation {
+            file: file_location.canonicalize()?,
+            line: location.line as u64,
+        },
+        wide_char_mode,
+    )
+}
+
+/// Extracts the string literals passed as arguments to an attribute (e.g.,
+/// `[[deprecated("use Foo instead")]]` or `__declspec(uuid("..."))`). libclang
+/// doesn't expose these as `StringLiteral` entities, so attributes' tokens are
+/// parsed instead.
+pub fn attribute_string_literals(
+    entity: Entity,
+    wide_char_mode: Option<WideCharMode>,
+) -> Result<Vec<PotentialLeak>> {
+    let range = entity
+        .get_range()
+        .ok_or_else(|| anyhow!("Failed to get entity's range"))?;
+
+    range
+        .tokenize()
+        .into_iter()
+        .filter(|token| token.get_kind() == TokenKind::Literal)
+        .filter_map(|token| {
+            let spelling = token.get_spelling();
+            // Ignore other kinds of literals (e.g., integers)
+            parse_string_literal(&spelling).ok()?;
+            let location = token.get_location().get_file_location();
+            Some((spelling, location))
+        })
+        .map(|(spelling, location)| {
+            let file_location = location
+                .file
+                .ok_or_else(|| anyhow!("Failed to get token's file location"))?
+                .get_path();
+
+            PotentialLeak::from_string_literal(
+                &spelling,
+                SourceLocation {
+                    file: file_location.canonicalize()?,
+                    line: location.line as u64,
+                },
+                wide_char_mode,
+            )
+        })
+        .collect()
+}
+
+/// Extracts the value of an object-like macro whose replacement text is made of
+/// string literals (e.g., `#define API_KEY "..."`), possibly built from other
+/// macros or stringification. Other macros are ignored.
+/// Macro definitions must be processed in order with the same `expander`, so
+/// that macros can refer to previously defined ones.
+pub fn macro_definition_string_literal(
+    entity: Entity,
+    expander: &mut MacroExpander,
+    wide_char_mode: Option<WideCharMode>,
+) -> Result<Vec<PotentialLeak>> {
+    if entity.is_builtin_macro() {
+        return Ok(vec![]);
+    }
+    let string_literals = match expander.define(&MacroToken::from_macro_definition(entity)) {
+        Some(string_literals) => string_literals,
+        None => return Ok(vec![]),
+    };
+    let location = entity
+        .get_location()
+        .ok_or_else(|| anyhow!("Failed to get entity's location"))?
+        .get_file_location();
+    let file_location = match location.file {
+        Some(file) => file.get_path(),
+        // Predefined macros and macros defined on the command line aren't
+        // declared in source files
+        None => return Ok(vec![]),
+    };
+
+    // Adjacent string literals are concatenated
+    let mut potential_leak = PotentialLeak::from_string_literal(
+        &string_literals[0],
+        SourceLocation {
+            file: file_location.canonicalize()?,
+            line: location.line as u64,
+        },
+        wide_char_mode,
+    )?;
+    for string_literal in &string_literals[1..] {
+        let (_, string_content) = parse_string_literal(string_literal)?;
+        Arc::make_mut(&mut potential_leak.data).push_str(string_content);
+        potential_leak
+            .bytes
+            .extend(string_literal_to_bytes(string_literal, wide_char_mode)?);
+    }
+    potential_leak.data_type = LeakedDataType::MacroValue;
+
+    Ok(vec![potential_leak])
+}
+
+/// Extracts the name of a struct or class, along with its fully-qualified name
+/// (e.g., `ns::Outer::Inner`) and the mangled forms found in RTTI type names,
+/// if the type is declared in a namespace or another type
+pub fn record_names(entity: Entity) -> Result<Vec<PotentialLeak>> {
+    let mut potential_leak = PotentialLeak::try_from(entity)?;
+    // Explicit specializations are named after their template's arguments
+    // (e.g., `MyTemplate<int>`)
+    if entity.get_template().is_some() {
+        potential_leak.data_type = LeakedDataType::TemplateName;
+    }
+    let scopes = match enclosing_scopes(entity) {
+        Some(scopes) if !scopes.is_empty() => scopes,
+        _ => return Ok(vec![potential_leak]),
+    };
+
+    let name = potential_leak.data.as_str();
+    let qualified_name = Arc::new(format!("{}::{}", scopes.join("::"), name));
+    let is_class = potential_leak.data_type == LeakedDataType::ClassName;
+    let qualified_patterns = [
+        qualified_name.as_bytes().to_vec(),
+        itanium_type_name(&scopes, name).into_bytes(),
+        msvc_type_descriptor_name(&scopes, name, is_class).into_bytes(),
+    ];
+    let mut potential_leaks = vec![potential_leak.clone()];
+    potential_leaks.extend(qualified_patterns.into_iter().map(|bytes| PotentialLeak {
+        data: qualified_name.clone(),
+        bytes,
+        ..potential_leak.clone()
+    }));
+
+    Ok(potential_leaks)
+}
+
+/// Extracts the name of a union, unless it's anonymous (e.g., a union declared
+/// as a member of a struct)
+pub fn union_name(entity: Entity) -> Result<Vec<PotentialLeak>> {
+    if !has_identifier_name(entity) {
+        return Ok(vec![]);
+    }
+
+    PotentialLeak::try_from(entity).map(|leak| vec![leak])
+}
+
+/// Extracts the names of the class template instantiation used by the given
+/// declaration (e.g., `ns::MyTemplate<int>` and `MyTemplate<int>` for
+/// `ns::MyTemplate<int>* value;`), as they appear in RTTI and debug
+/// information. Instantiations of templates declared in system headers are
+/// ignored if requested.
+pub fn template_instantiation_names(
+    entity: Entity,
+    system_header_filter: Option<&SystemHeaderFilter>,
+) -> Result<Vec<PotentialLeak>> {
+    let mut instantiation_type = match entity.get_type() {
+        Some(entity_type) => entity_type.get_canonical_type(),
+        None => return Ok(vec![]),
+    };
+    // Look through pointers and references
+    while let Some(pointee_type) = instantiation_type.get_pointee_type() {
+        instantiation_type = pointee_type.get_canonical_type();
+    }
+    if instantiation_type.get_template_argument_types().is_none() {
+        return Ok(vec![]);
+    }
+    let declaration = match instantiation_type.get_declaration() {
+        Some(declaration) if !matches!(system_header_filter, Some(filter) if filter.is_in_system_header(&declaration)) => {
+            declaration
+        }
+        _ => return Ok(vec![]),
+    };
+
+    let location = declaration
+        .get_location()
+        .ok_or_else(|| anyhow!("Failed to get declaration's location"))?
+        .get_file_location();
+    let declaration_metadata = Arc::new(SourceLocation {
+        file: location
+            .file
+            .ok_or_else(|| anyhow!("Failed to get declaration's file location"))?
+            .get_path()
+            .canonicalize()?,
+        line: location.line as u64,
+    });
+
+    // Canonical types are fully qualified
+    let mut names = vec![instantiation_type.get_display_name()];
+    names.extend(declaration.get_display_name());
+    names.dedup();
+
+    Ok(names
+        .into_iter()
+        .filter(|name| !name.is_empty())
+        .map(|name| PotentialLeak {
+            data_type: LeakedDataType::TemplateName,
+            bytes: name.as_bytes().to_vec(),
+            code_unit_size: 1,
+            data: Arc::new(name),
+            declaration_metadata: declaration_metadata.clone(),
+        })
+        .collect())
+}
+
+/// Returns the names of the namespaces and types enclosing the given entity,
+/// outermost first, or `None` if one of them can't be named (e.g., anonymous
+/// namespaces, templates or function-local types)
+fn enclosing_scopes(entity: Entity) -> Option<Vec<String>> {
+    // Templates have no simple mangled form
+    if entity.get_display_name() != entity.get_name() || !has_identifier_name(entity) {
+        return None;
+    }
+
+    let mut scopes = vec![];
+    let mut parent = entity.get_semantic_parent()?;
+    loop {
+        match parent.get_kind() {
+            EntityKind::TranslationUnit => break,
+            // `extern "C"` and `extern "C++"` blocks don't introduce scopes
+            EntityKind::LinkageSpec => {}
+            EntityKind::Namespace | EntityKind::StructDecl | EntityKind::ClassDecl
+                if has_identifier_name(parent) =>
+            {
+                scopes.push(parent.get_name()?);
+            }
+            _ => return None,
+        }
+        parent = parent.get_semantic_parent()?;
+    }
+    scopes.reverse();
+
+    Some(scopes)
+}
+
+/// Indicates if the given entity is named by an identifier (recent versions of
+/// libclang name anonymous entities after their location)
+fn has_identifier_name(entity: Entity) -> bool {
+    !entity.is_anonymous()
+        && matches!(entity.get_name(), Some(name) if !name.is_empty()
+            && name.chars().all(|c| c.is_alphanumeric() || c == '_'))
+}
+
+/// Returns the name of a type, as stored in its `std::type_info` by compilers
+/// following the Itanium C++ ABI (e.g., `N2ns5Outer5InnerE`)
+fn itanium_type_name(scopes: &[String], name: &str) -> String {
+    let mut type_name = String::from("N");
+    for component in scopes.iter().map(String::as_str).chain([name]) {
+        type_name.push_str(&format!("{}{}", component.len(), component));
+    }
+    type_name.push('E');
+
+    type_name
+}
+
+/// Returns the decorated name of a type, as stored in MSVC's RTTI type
+/// descriptors (e.g., `.?AVInner@Outer@ns@@`)
+fn msvc_type_descriptor_name(scopes: &[String], name: &str, is_class: bool) -> String {
+    let mut type_name = String::from(if is_class { ".?AV" } else { ".?AU" });
+    for component in [name]
+        .into_iter()
+        .chain(scopes.iter().rev().map(String::as_str))
+    {
+        type_name.push_str(component);
+        type_name.push('@');
+    }
+    type_name.push('@');
+
+    type_name
+}
+
 impl PartialEq for PotentialLeak {
     fn eq(&self, other: &Self) -> bool {
         self.data == other.data
@@ -90,6 +534,8 @@ impl Hash for PotentialLeak {
 }
 
 /// Kind of wide chars to use when encoding wide strings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum WideCharMode {
     /// Wide strings are encoded as UTF-16LE
     Windows,
@@ -97,6 +543,31 @@ pub enum WideCharMode {
     Unix,
 }
 
+impl FromStr for WideCharMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "windows" => Ok(WideCharMode::Windows),
+            "unix" => Ok(WideCharMode::Unix),
+            _ => Err(anyhow!(
+                "Invalid wide char mode '{}' (expected 'windows' or 'unix')",
+                s
+            )),
+        }
+    }
+}
+
+/// Name mangling scheme of a C++ ABI, which determines how type names appear in
+/// RTTI data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManglingScheme {
+    /// Itanium C++ ABI (e.g., GCC, Clang on Linux and macOS)
+    Itanium,
+    /// Microsoft C++ ABI (e.g., MSVC, clang-cl)
+    Msvc,
+}
+
 /// Describes the string encoding specified for a string literal
 enum StringLiteralEncoding {
     /// No encoding specified (i.e., typical "*" string)
@@ -111,20 +582,42 @@ enum StringLiteralEncoding {
     Utf32,
 }
 
+/// Returns the size in bytes of the code units used to encode string literals
+/// with the given encoding.
+fn code_unit_size(
+    string_encoding: &StringLiteralEncoding,
+    wide_char_mode: Option<WideCharMode>,
+) -> usize {
+    match string_encoding {
+        StringLiteralEncoding::Unspecified | StringLiteralEncoding::Utf8 => 1,
+        StringLiteralEncoding::Utf16 => 2,
+        StringLiteralEncoding::Utf32 => 4,
+        StringLiteralEncoding::Wide => {
+            match wide_char_mode.unwrap_or_else(default_wide_char_mode) {
+                WideCharMode::Windows => 2,
+                WideCharMode::Unix => 4,
+            }
+        }
+    }
+}
+
+/// Returns the wide char mode used by default on the current platform.
+fn default_wide_char_mode() -> WideCharMode {
+    if cfg!(windows) {
+        WideCharMode::Windows
+    } else {
+        WideCharMode::Unix
+    }
+}
+
 /// We have to reimplement this ourselves since the `clang` crate doesn't
 /// provide an easy way to get byte representations of `StringLiteral` entities.
 fn string_literal_to_bytes(
     string_literal: &str,
     wide_char_mode: Option<WideCharMode>,
 ) -> Result<Vec<u8>> {
-    let wide_char_mode = wide_char_mode.unwrap_or({
-        // Pick the sensible default if not specified
-        if cfg!(windows) {
-            WideCharMode::Windows
-        } else {
-            WideCharMode::Unix
-        }
-    });
+    // Pick the sensible default if not specified
+    let wide_char_mode = wide_char_mode.unwrap_or_else(default_wide_char_mode);
 
     let (string_encoding, string_content) = parse_string_literal(string_literal)?;
     match string_encoding {
@@ -136,33 +629,13 @@ fn string_literal_to_bytes(
 
         // Wide
         StringLiteralEncoding::Wide => {
+            let string_content = process_escape_sequences(string_content)
+                .ok_or_else(|| anyhow!("Failed to process escape sequences"))?;
             match wide_char_mode {
-                WideCharMode::Windows => {
-                    // Encode as UTF-16LE on Windows
-                    Ok(encode_utf16(
-                        process_escape_sequences(string_content)
-                            .ok_or_else(|| anyhow!("Failed to process escape sequences"))?
-                            .chars(),
-                    )
-                    .map(u16::to_le_bytes)
-                    .fold(Vec::new(), |mut acc: Vec<u8>, e| {
-                        acc.extend(e);
-                        acc
-                    }))
-                }
-                WideCharMode::Unix => {
-                    // Encode as UTF-32LE on Unix platforms
-                    Ok(encode_utf32(
-                        process_escape_sequences(string_content)
-                            .ok_or_else(|| anyhow!("Failed to process escape sequences"))?
-                            .chars(),
-                    )
-                    .map(u32::to_le_bytes)
-                    .fold(Vec::new(), |mut acc: Vec<u8>, e| {
-                        acc.extend(e);
-                        acc
-                    }))
-                }
+                // Encode as UTF-16LE on Windows
+                WideCharMode::Windows => Ok(encode_utf16_le(&string_content)),
+                // Encode as UTF-32LE on Unix platforms
+                WideCharMode::Unix => Ok(encode_utf32_le(&string_content)),
             }
         }
 
@@ -173,29 +646,67 @@ fn string_literal_to_bytes(
             .to_owned()),
 
         // UTF-16LE
-        StringLiteralEncoding::Utf16 => Ok(encode_utf16(
-            process_escape_sequences(string_content)
-                .ok_or_else(|| anyhow!("Failed to process escape sequences"))?
-                .chars(),
-        )
+        StringLiteralEncoding::Utf16 => Ok(encode_utf16_le(
+            &process_escape_sequences(string_content)
+                .ok_or_else(|| anyhow!("Failed to process escape sequences"))?,
+        )),
+
+        // UTF-32LE
+        StringLiteralEncoding::Utf32 => Ok(encode_utf32_le(
+            &process_escape_sequences(string_content)
+                .ok_or_else(|| anyhow!("Failed to process escape sequences"))?,
+        )),
+    }
+}
+
+/// Decodes a string encoded with code units of the given size (i.e., UTF-8,
+/// UTF-16LE or UTF-32LE)
+fn decode_string(bytes: &[u8], code_unit_size: usize) -> Option<String> {
+    match code_unit_size {
+        1 => return String::from_utf8(bytes.to_vec()).ok(),
+        2 | 4 => {}
+        _ => return None,
+    }
+    let code_units = bytes.chunks_exact(code_unit_size);
+    // Truncated code unit
+    if !code_units.remainder().is_empty() {
+        return None;
+    }
+
+    match code_unit_size {
+        2 => {
+            let code_units: Vec<u16> = code_units
+                .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+                .collect();
+            String::from_utf16(&code_units).ok()
+        }
+        4 => code_units
+            .map(|chunk| {
+                char::from_u32(u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            })
+            .collect(),
+        _ => unreachable!("Unsupported code unit size"),
+    }
+}
+
+/// Encodes the given string as UTF-16LE
+pub fn encode_utf16_le(string: &str) -> Vec<u8> {
+    encode_utf16(string.chars())
         .map(u16::to_le_bytes)
         .fold(Vec::new(), |mut acc: Vec<u8>, e| {
             acc.extend(e);
             acc
-        })),
+        })
+}
 
-        // UTF-32LE
-        StringLiteralEncoding::Utf32 => Ok(encode_utf32(
-            process_escape_sequences(string_content)
-                .ok_or_else(|| anyhow!("Failed to process escape sequences"))?
-                .chars(),
-        )
+/// Encodes the given string as UTF-32LE
+pub fn encode_utf32_le(string: &str) -> Vec<u8> {
+    encode_utf32(string.chars())
         .map(u32::to_le_bytes)
         .fold(Vec::new(), |mut acc: Vec<u8>, e| {
             acc.extend(e);
             acc
-        })),
-    }
+        })
 }
 
 /// Takes in a string literal (e.g., "str", L"str") and returns the specified
@@ -254,10 +765,10 @@ fn parse_string_literal(string_literal: &str) -> Result<(StringLiteralEncoding,
     }
 }
 
-fn process_escape_sequences(string: &str) -> Option<Cow<str>> {
+fn process_escape_sequences(string: &str) -> Option<Cow<'_, str>> {
     let mut owned: Option<String> = None;
     let mut skip_until: usize = 0;
-    for (position, char) in string.chars().enumerate() {
+    for (position, char) in string.char_indices() {
         if position < skip_until {
             continue;
         }
@@ -267,10 +778,10 @@ fn process_escape_sequences(string: &str) -> Option<Cow<str>> {
                 owned = Some(string[..position].to_owned());
             }
             let b = owned.as_mut()?;
-            let mut escape_char_it = string.chars();
-            let first_char = escape_char_it.nth(position + 1);
+            let mut escape_char_it = string[position..].chars();
+            let first_char = escape_char_it.nth(1);
             if let Some(first_char) = first_char {
-                skip_until = position + 2;
+                skip_until = position + 1 + first_char.len_utf8();
                 match first_char {
                     // Simple escape sequences
                     'a' => b.push('\x07'),
@@ -323,6 +834,95 @@ fn process_escape_sequences(string: &str) -> Option<Cow<str>> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn mangled_type_names() {
+        let scopes = ["ns".to_string(), "Outer".to_string()];
+
+        assert_eq!(itanium_type_name(&scopes, "Inner"), "N2ns5Outer5InnerE");
+        assert_eq!(
+            msvc_type_descriptor_name(&scopes, "Inner", true),
+            ".?AVInner@Outer@ns@@"
+        );
+        assert_eq!(
+            msvc_type_descriptor_name(&scopes, "Inner", false),
+            ".?AUInner@Outer@ns@@"
+        );
+    }
+
+    #[test]
+    fn mangling_scheme_of_type_names() {
+        let type_name = |bytes: &[u8]| PotentialLeak {
+            data_type: LeakedDataType::ClassName,
+            data: Arc::new("ns::Inner".to_string()),
+            bytes: bytes.to_vec(),
+            code_unit_size: 1,
+            declaration_metadata: Arc::new(SourceLocation {
+                file: "file.cc".into(),
+                line: 1,
+            }),
+        };
+
+        assert_eq!(type_name(b"ns::Inner").mangling_scheme(), None);
+        assert_eq!(
+            type_name(b"N2ns5InnerE").mangling_scheme(),
+            Some(ManglingScheme::Itanium)
+        );
+        assert_eq!(
+            type_name(b".?AVInner@ns@@").mangling_scheme(),
+            Some(ManglingScheme::Msvc)
+        );
+    }
+
+    #[test]
+    fn utf16_variants() {
+        let location = SourceLocation {
+            file: "file.cc".into(),
+            line: 1,
+        };
+        let narrow_leak = PotentialLeak::from_string_literal("\"hello\"", location, None)
+            .expect("from_string_literal failed");
+        let wide_leak = narrow_leak.utf16_variant().expect("utf16_variant failed");
+        assert_eq!(wide_leak.bytes, b"h\0e\0l\0l\0o\0");
+        assert_eq!(wide_leak.code_unit_size, 2);
+        assert_eq!(wide_leak.data, narrow_leak.data);
+
+        // Only narrow ASCII patterns have a variant
+        assert!(wide_leak.utf16_variant().is_none());
+        let non_ascii_leak = PotentialLeak {
+            bytes: vec![0xde, 0xad],
+            ..narrow_leak
+        };
+        assert!(non_ascii_leak.utf16_variant().is_none());
+    }
+
+    #[test]
+    fn encoding_variants() {
+        let location = SourceLocation {
+            file: "file.cc".into(),
+            line: 1,
+        };
+        let wide_leak = PotentialLeak::from_string_literal(
+            "L\"h\u{e9}\"",
+            location,
+            Some(WideCharMode::Windows),
+        )
+        .expect("from_string_literal failed");
+        let variants = wide_leak.encoding_variants();
+        assert_eq!(variants.len(), 2);
+        assert_eq!(variants[0].bytes, "h\u{e9}".as_bytes());
+        assert_eq!(variants[0].code_unit_size, 1);
+        assert_eq!(variants[1].bytes, b"h\0\0\0\xe9\0\0\0");
+        assert_eq!(variants[1].code_unit_size, 4);
+        assert_eq!(variants[1].data, wide_leak.data);
+
+        // Only strings have variants
+        let struct_name_leak = PotentialLeak {
+            data_type: LeakedDataType::StructName,
+            ..wide_leak
+        };
+        assert!(struct_name_leak.encoding_variants().is_empty());
+    }
+
     #[test]
     fn string_literal_to_bytes_empty_string() {
         // We consider empty string literals an error, as they should at least
@@ -401,6 +1001,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn code_unit_size_per_encoding() {
+        assert_eq!(code_unit_size(&StringLiteralEncoding::Unspecified, None), 1);
+        assert_eq!(code_unit_size(&StringLiteralEncoding::Utf8, None), 1);
+        assert_eq!(code_unit_size(&StringLiteralEncoding::Utf16, None), 2);
+        assert_eq!(code_unit_size(&StringLiteralEncoding::Utf32, None), 4);
+        assert_eq!(
+            code_unit_size(&StringLiteralEncoding::Wide, Some(WideCharMode::Windows)),
+            2
+        );
+        assert_eq!(
+            code_unit_size(&StringLiteralEncoding::Wide, Some(WideCharMode::Unix)),
+            4
+        );
+    }
+
     #[test]
     fn process_escape_sequences_no_escape_sequence() {
         assert_eq!(
@@ -429,4 +1045,22 @@ mod tests {
             "\x00\x01\x02\x03\x04\x05\x06\x07\x08\x40"
         );
     }
+
+    #[test]
+    fn process_escape_sequences_non_ascii() {
+        // Escape sequences are located by byte position, which differs from
+        // character position after non-ASCII characters
+        assert_eq!(
+            process_escape_sequences(r"é\tà\101").expect("Failed to escape string"),
+            "é\tàA"
+        );
+        assert_eq!(
+            process_escape_sequences(r"\é\\").expect("Failed to escape string"),
+            "é\\"
+        );
+        assert_eq!(
+            process_escape_sequences("日本語").expect("Failed to escape string"),
+            "日本語"
+        );
+    }
 }
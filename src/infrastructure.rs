@@ -0,0 +1,169 @@
+//! Tagging of leaked strings that reveal the infrastructure a binary relies on
+//! (e.g., registry keys or environment variables), which security reviewers
+//! usually want to triage first.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::information_leak::{ConfirmedLeak, LeakedDataType};
+
+/// Matches registry paths, given with their root key's full or abbreviated
+/// name (e.g., `HKEY_LOCAL_MACHINE\...`, `HKCU\...`)
+const REGISTRY_PATH_REGEX: &str = r"(?i)\bHKEY_[A-Z_]+|\b(?:HKLM|HKCU|HKCR|HKU|HKCC)\\";
+
+/// Matches references to environment variables (e.g., `%APPDATA%`,
+/// `${HOME}`, `$LD_LIBRARY_PATH`) and assignments (e.g., `PATH=...`)
+const ENVIRONMENT_VARIABLE_REGEX: &str = r"%[A-Za-z_][A-Za-z0-9_()]*%|\$\{[A-Za-z_][A-Za-z0-9_]*\}|\$[A-Z_][A-Z0-9_]+|^[A-Z_][A-Z0-9_]*=";
+
+/// Environment variables whose names alone reveal how a program can be
+/// influenced (e.g., library injection)
+const WELL_KNOWN_ENVIRONMENT_VARIABLES: [&str; 10] = [
+    "LD_PRELOAD",
+    "LD_LIBRARY_PATH",
+    "DYLD_INSERT_LIBRARIES",
+    "DYLD_LIBRARY_PATH",
+    "PATH",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "USERPROFILE",
+    "PROGRAMDATA",
+    "TMPDIR",
+];
+
+/// Kind of infrastructure a leaked string reveals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InfrastructureKind {
+    /// Windows registry key or value path
+    RegistryPath,
+    /// Name of, reference to or assignment of an environment variable
+    EnvironmentVariable,
+}
+
+impl fmt::Display for InfrastructureKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            InfrastructureKind::RegistryPath => "registry path",
+            InfrastructureKind::EnvironmentVariable => "environment variable",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Tells which leaked strings reveal infrastructure, based on heuristics
+pub struct InfrastructureClassifier {
+    registry_path_regex: Regex,
+    environment_variable_regex: Regex,
+}
+
+impl InfrastructureClassifier {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            registry_path_regex: Regex::new(REGISTRY_PATH_REGEX)?,
+            environment_variable_regex: Regex::new(ENVIRONMENT_VARIABLE_REGEX)?,
+        })
+    }
+
+    /// Returns the kind of infrastructure the given string reveals, if any
+    pub fn classify(&self, value: &str) -> Option<InfrastructureKind> {
+        if self.registry_path_regex.is_match(value) {
+            Some(InfrastructureKind::RegistryPath)
+        } else if self.environment_variable_regex.is_match(value)
+            || WELL_KNOWN_ENVIRONMENT_VARIABLES
+                .iter()
+                .any(|name| value == *name || value.contains(&format!("{}=", name)))
+        {
+            Some(InfrastructureKind::EnvironmentVariable)
+        } else {
+            None
+        }
+    }
+}
+
+/// Tags the confirmed string leaks that reveal infrastructure
+pub fn classify_confirmed_leak_infrastructure<SortedConfirmedLeak>(
+    confirmed_leaks: BTreeSet<SortedConfirmedLeak>,
+    classifier: &InfrastructureClassifier,
+) -> BTreeSet<SortedConfirmedLeak>
+where
+    SortedConfirmedLeak: Into<ConfirmedLeak> + From<ConfirmedLeak> + Ord,
+{
+    confirmed_leaks
+        .into_iter()
+        .map(Into::into)
+        .map(|mut leak: ConfirmedLeak| {
+            if is_string_data(leak.data_type) {
+                leak.infrastructure = classifier.classify(&leak.data);
+            }
+            SortedConfirmedLeak::from(leak)
+        })
+        .collect()
+}
+
+/// Indicates if the given type of data is free-form text (as opposed to
+/// identifiers, for instance)
+fn is_string_data(data_type: LeakedDataType) -> bool {
+    matches!(
+        data_type,
+        LeakedDataType::StringLiteral
+            | LeakedDataType::MacroValue
+            | LeakedDataType::ResourceString
+            | LeakedDataType::CustomArtifact
+            | LeakedDataType::UserProvided
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_registry_paths() {
+        let classifier = InfrastructureClassifier::new().unwrap();
+        for value in [
+            r"HKEY_LOCAL_MACHINE\SOFTWARE\Acme",
+            r"Software\Acme under HKEY_CURRENT_USER",
+            r"hklm\System\CurrentControlSet",
+            r"HKCU\Software\Acme",
+        ] {
+            assert_eq!(
+                classifier.classify(value),
+                Some(InfrastructureKind::RegistryPath),
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn classify_environment_variables() {
+        let classifier = InfrastructureClassifier::new().unwrap();
+        for value in [
+            r"%APPDATA%\Acme\config.ini",
+            "${HOME}/.acme",
+            "$ACME_TOKEN",
+            "PATH=/opt/acme/bin",
+            "LD_PRELOAD",
+            "export LD_PRELOAD=/tmp/hook.so",
+        ] {
+            assert_eq!(
+                classifier.classify(value),
+                Some(InfrastructureKind::EnvironmentVariable),
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn classify_other_strings() {
+        let classifier = InfrastructureClassifier::new().unwrap();
+        for value in ["Hello, world!", "100% sure", "price: $5", "a=b", "PATHS"] {
+            assert_eq!(classifier.classify(value), None, "{}", value);
+        }
+    }
+}
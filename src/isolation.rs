@@ -0,0 +1,261 @@
+//! Parsing of translation units in worker subprocesses, so that libclang
+//! crashing on a pathological file doesn't take the whole scan down.
+
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context, Result};
+use clang::{Clang, Index};
+use serde::{Deserialize, Serialize};
+
+use crate::analysis_cache::TranslationUnitArtifacts;
+use crate::compilation_database::CompileCommand;
+use crate::information_leak::ArtifactExtractorRegistry;
+use crate::{extract_artifacts_from_translation_unit, ExtractionOptions};
+
+/// Command starting a parsing worker, i.e., a process that calls
+/// `serve_parse_request` with its standard input and output
+#[derive(Debug, Clone)]
+pub struct WorkerCommand {
+    pub program: PathBuf,
+    pub arguments: Vec<String>,
+    /// Time after which a worker is killed, if set
+    pub timeout: Option<Duration>,
+}
+
+/// Interval at which workers with a timeout are checked for completion
+const WORKER_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Translation unit a worker must parse, along with extraction settings
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ParseRequest {
+    pub filename: PathBuf,
+    pub arguments: Vec<String>,
+    pub options: ExtractionOptions,
+    /// Indicates if the files the translation unit depends on must be listed
+    pub collect_dependencies: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum ParseResponse {
+    Parsed {
+        artifacts: TranslationUnitArtifacts,
+        dependencies: Vec<PathBuf>,
+    },
+    /// The translation unit couldn't be parsed (e.g., missing file), which
+    /// isn't a crash
+    Failed { error: String },
+}
+
+/// Result of the parsing of a translation unit by a worker
+pub(crate) enum WorkerOutcome {
+    Parsed {
+        artifacts: TranslationUnitArtifacts,
+        dependencies: Vec<PathBuf>,
+    },
+    /// The worker died without answering, with the given exit status
+    Crashed(String),
+    /// The worker has been killed as it didn't answer before its timeout
+    TimedOut(Duration),
+}
+
+/// Reads a parse request from `input`, parses the requested translation unit
+/// and writes the extracted artifacts into `output`
+pub fn serve_parse_request<R: Read, W: Write>(input: R, output: W) -> Result<()> {
+    let request: ParseRequest =
+        serde_json::from_reader(input).with_context(|| "Malformed parse request")?;
+    let response = match parse_request(&request) {
+        Ok((artifacts, dependencies)) => ParseResponse::Parsed {
+            artifacts,
+            dependencies,
+        },
+        Err(err) => ParseResponse::Failed {
+            error: format!("{:#}", err),
+        },
+    };
+    serde_json::to_writer(output, &response)?;
+
+    Ok(())
+}
+
+fn parse_request(request: &ParseRequest) -> Result<(TranslationUnitArtifacts, Vec<PathBuf>)> {
+    let clang = Clang::new().map_err(|e| anyhow!(e))?;
+    let index = Index::new(&clang, false, false);
+    let extractor_registry = ArtifactExtractorRegistry::default();
+    let compile_cmd = CompileCommand {
+        filename: request.filename.clone(),
+        arguments: request.arguments.clone().into(),
+    };
+
+    extract_artifacts_from_translation_unit(
+        &index,
+        &compile_cmd,
+        &extractor_registry.select(&request.options.extractor_names)?,
+        &request.options,
+        request.collect_dependencies,
+    )
+}
+
+/// Starts a worker and waits for it to parse the requested translation unit
+/// (and kills it if it takes longer than its timeout).
+/// Parsing errors reported by the worker are returned as errors.
+pub(crate) fn parse_in_worker(
+    worker: &WorkerCommand,
+    request: &ParseRequest,
+) -> Result<WorkerOutcome> {
+    let mut child = Command::new(&worker.program)
+        .args(&worker.arguments)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .with_context(|| {
+            format!(
+                "Failed to start parsing worker '{}'",
+                worker.program.display()
+            )
+        })?;
+
+    // Note: Closing the worker's input once the request has been written
+    // signals the end of the request
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Err(err) = serde_json::to_writer(&mut stdin, request) {
+            // The worker died early, which is detected below
+            log::debug!("Failed to send parse request: {}", err);
+        }
+    }
+    // Note: The response is read concurrently, so that the worker doesn't
+    // block on a full pipe while being waited for
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow!("Worker's output isn't piped"))?;
+    let response_reader = std::thread::spawn(move || -> std::io::Result<Vec<u8>> {
+        let mut response = vec![];
+        stdout.read_to_end(&mut response)?;
+        Ok(response)
+    });
+    let status = match worker.timeout {
+        Some(timeout) => match wait_with_timeout(&mut child, timeout)? {
+            Some(status) => status,
+            None => {
+                child.kill()?;
+                child.wait()?;
+                return Ok(WorkerOutcome::TimedOut(timeout));
+            }
+        },
+        None => child.wait()?,
+    };
+    let response = response_reader
+        .join()
+        .map_err(|_| anyhow!("Failed to read worker's response"))??;
+
+    match serde_json::from_slice(&response) {
+        Ok(ParseResponse::Parsed {
+            artifacts,
+            dependencies,
+        }) if status.success() => Ok(WorkerOutcome::Parsed {
+            artifacts,
+            dependencies,
+        }),
+        Ok(ParseResponse::Failed { error }) => Err(anyhow!(error)),
+        _ => Ok(WorkerOutcome::Crashed(status.to_string())),
+    }
+}
+
+/// Waits for the given process to exit, for at most `timeout`. Returns `None`
+/// if the process is still running.
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> Result<Option<ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+        std::thread::sleep(WORKER_POLL_INTERVAL);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn parse_request() -> ParseRequest {
+        ParseRequest {
+            filename: PathBuf::from("main.cc"),
+            arguments: vec![],
+            options: ExtractionOptions {
+                extractor_names: vec!["string_literals".to_string()],
+                ..Default::default()
+            },
+            collect_dependencies: false,
+        }
+    }
+
+    /// Worker running the given shell script
+    fn shell_worker(script: &str) -> WorkerCommand {
+        WorkerCommand {
+            program: PathBuf::from("sh"),
+            arguments: vec!["-c".to_string(), script.to_string()],
+            timeout: None,
+        }
+    }
+
+    #[test]
+    fn parse_in_worker_parsed() {
+        let worker = shell_worker(
+            r#"cat > /dev/null; echo '{"status": "parsed", "artifacts": {"artifacts": [], "too_small_artifacts": []}, "dependencies": ["main.cc"]}'"#,
+        );
+
+        match parse_in_worker(&worker, &parse_request()).expect("parse_in_worker failed") {
+            WorkerOutcome::Parsed {
+                artifacts,
+                dependencies,
+            } => {
+                assert!(artifacts.artifacts.is_empty());
+                assert_eq!(dependencies, [PathBuf::from("main.cc")]);
+            }
+            _ => panic!("Worker reported as crashed"),
+        }
+    }
+
+    #[test]
+    fn parse_in_worker_failed() {
+        let worker = shell_worker(
+            r#"cat > /dev/null; echo '{"status": "failed", "error": "missing file"}'"#,
+        );
+
+        let err = parse_in_worker(&worker, &parse_request())
+            .err()
+            .expect("Failure not reported");
+        assert_eq!(err.to_string(), "missing file");
+    }
+
+    #[test]
+    fn parse_in_worker_crashed() {
+        let worker = shell_worker("kill -SEGV $$");
+
+        assert!(matches!(
+            parse_in_worker(&worker, &parse_request()),
+            Ok(WorkerOutcome::Crashed(_))
+        ));
+    }
+
+    #[test]
+    fn parse_in_worker_timed_out() {
+        let mut worker = shell_worker("cat > /dev/null; sleep 10");
+        worker.timeout = Some(Duration::from_millis(100));
+
+        let parse_start = Instant::now();
+        assert!(matches!(
+            parse_in_worker(&worker, &parse_request()),
+            Ok(WorkerOutcome::TimedOut(_))
+        ));
+        assert!(parse_start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,107 @@
+//! Comparison of the leaks found in two binaries (e.g., two releases of the
+//! same program), so that reviews can focus on what changed.
+
+use std::collections::{BTreeSet, HashSet};
+
+use crate::baseline::leak_fingerprint;
+use crate::information_leak::{ConfirmedLeak, ConfirmedLeakWithUniqueLocation};
+
+/// Leaks of two binaries, split by whether they are found in the old binary,
+/// the new one or both.
+/// Leaks are identified by their fingerprint (see `leak_fingerprint`), so
+/// that leaks moved to other offsets or lines are considered persisting.
+#[derive(Default)]
+pub struct LeakDiff {
+    /// Leaks only found in the new binary
+    pub introduced: BTreeSet<ConfirmedLeakWithUniqueLocation>,
+    /// Leaks only found in the old binary
+    pub removed: BTreeSet<ConfirmedLeakWithUniqueLocation>,
+    /// Leaks found in both binaries (with their locations in the new binary)
+    pub persisting: BTreeSet<ConfirmedLeakWithUniqueLocation>,
+}
+
+/// Compares the leaks found in an old binary with the leaks found in a new one
+pub fn diff_confirmed_leaks<SortedConfirmedLeak>(
+    old_leaks: BTreeSet<SortedConfirmedLeak>,
+    new_leaks: BTreeSet<SortedConfirmedLeak>,
+) -> LeakDiff
+where
+    SortedConfirmedLeak: Into<ConfirmedLeak>,
+{
+    let old_leaks: Vec<ConfirmedLeak> = old_leaks.into_iter().map(Into::into).collect();
+    let new_leaks: Vec<ConfirmedLeak> = new_leaks.into_iter().map(Into::into).collect();
+    let old_fingerprints: HashSet<String> = old_leaks.iter().map(leak_fingerprint).collect();
+    let new_fingerprints: HashSet<String> = new_leaks.iter().map(leak_fingerprint).collect();
+
+    let mut diff = LeakDiff::default();
+    for leak in new_leaks {
+        if old_fingerprints.contains(&leak_fingerprint(&leak)) {
+            diff.persisting.insert(leak.into());
+        } else {
+            diff.introduced.insert(leak.into());
+        }
+    }
+    diff.removed = old_leaks
+        .into_iter()
+        .filter(|leak| !new_fingerprints.contains(&leak_fingerprint(leak)))
+        .map(Into::into)
+        .collect();
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use crate::information_leak::{BinaryLocation, LeakedDataType, SourceLocation};
+
+    use super::*;
+
+    fn confirmed_leaks(
+        binary: &str,
+        leaks: &[(&str, u64)],
+    ) -> BTreeSet<ConfirmedLeakWithUniqueLocation> {
+        leaks
+            .iter()
+            .map(|(data, offset)| {
+                ConfirmedLeak::builder(
+                    LeakedDataType::StringLiteral,
+                    Arc::new(data.to_string()),
+                    Arc::new(SourceLocation {
+                        file: PathBuf::from("main.cc"),
+                        line: *offset,
+                    }),
+                    BinaryLocation::new(Arc::new(PathBuf::from(binary)), *offset),
+                )
+                .build()
+                .into()
+            })
+            .collect()
+    }
+
+    fn values(leaks: &BTreeSet<ConfirmedLeakWithUniqueLocation>) -> Vec<&str> {
+        leaks.iter().map(|leak| leak.data.as_str()).collect()
+    }
+
+    #[test]
+    fn diff_leaks_between_binaries() {
+        let diff = diff_confirmed_leaks(
+            confirmed_leaks("v1.bin", &[("kept", 16), ("fixed", 32)]),
+            // Persisting leaks may have moved
+            confirmed_leaks("v2.bin", &[("kept", 48), ("new", 64)]),
+        );
+
+        assert_eq!(values(&diff.introduced), vec!["new"]);
+        assert_eq!(values(&diff.removed), vec!["fixed"]);
+        assert_eq!(values(&diff.persisting), vec!["kept"]);
+        // Persisting leaks are located in the new binary
+        let persisting_leak = diff.persisting.iter().next().unwrap();
+        assert_eq!(
+            persisting_leak.location.binary.file.as_path(),
+            PathBuf::from("v2.bin")
+        );
+        assert_eq!(persisting_leak.location.binary.offset, 48);
+    }
+}
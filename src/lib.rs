@@ -0,0 +1,2422 @@
+//! Cpplumber is an information leak detector for C and C++ code bases. It
+//! extracts artifacts (e.g., string literals, struct and class names) from
+//! source files and looks for them in binary files.
+//!
+//! The analysis can be embedded into other tools through [`Scanner`]:
+//!
+//! ```no_run
+//! use std::path::{Path, PathBuf};
+//!
+//! use cpplumber::{ProjectSource, Scanner};
+//!
+//! let leaks = Scanner::new(ProjectSource::CompilationDatabase(PathBuf::from(
+//!     "compile_commands.json",
+//! )))
+//! .minimum_leak_size(8)
+//! .scan(Path::new("a.out"))?;
+//! for leak in &leaks {
+//!     println!("{} leaked at offset {}", leak.data, leak.location.binary.offset);
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+// Note: Modules are public so that the command-line tool can use them, but
+// only the items re-exported below are part of the library's API
+#[doc(hidden)]
+pub mod analysis_cache;
+#[doc(hidden)]
+pub mod artifacts_file;
+#[doc(hidden)]
+pub mod baseline;
+#[doc(hidden)]
+pub mod binary_files;
+#[doc(hidden)]
+pub mod binary_format;
+#[doc(hidden)]
+pub mod cancellation;
+#[doc(hidden)]
+pub mod checkpoint;
+#[doc(hidden)]
+pub mod com_guids;
+#[doc(hidden)]
+pub mod compilation_database;
+#[doc(hidden)]
+pub mod confidence;
+#[doc(hidden)]
+pub mod config;
+#[doc(hidden)]
+pub mod containers;
+#[doc(hidden)]
+pub mod debug_info;
+#[doc(hidden)]
+pub mod diagnostics;
+#[doc(hidden)]
+pub mod embedded_files;
+#[doc(hidden)]
+pub mod encoding_coverage;
+#[doc(hidden)]
+pub mod entropy;
+#[doc(hidden)]
+pub mod extra_artifacts;
+#[doc(hidden)]
+pub mod information_leak;
+#[doc(hidden)]
+pub mod infrastructure;
+#[doc(hidden)]
+pub mod isolation;
+#[doc(hidden)]
+pub mod leak_diff;
+#[doc(hidden)]
+pub mod manifest;
+#[doc(hidden)]
+pub mod match_context;
+#[doc(hidden)]
+pub mod nested_artifacts;
+#[doc(hidden)]
+pub mod overlaps;
+#[doc(hidden)]
+pub mod path_mapping;
+#[doc(hidden)]
+pub mod pattern_set;
+#[doc(hidden)]
+pub mod policy;
+#[doc(hidden)]
+pub mod progress;
+#[doc(hidden)]
+pub mod provenance;
+#[doc(hidden)]
+pub mod reporting;
+#[doc(hidden)]
+pub mod resources;
+#[doc(hidden)]
+pub mod scan_passes;
+mod scanner;
+#[doc(hidden)]
+pub mod serialization_schemas;
+#[doc(hidden)]
+pub mod severity;
+#[doc(hidden)]
+pub mod source_paths;
+#[doc(hidden)]
+pub mod statistics;
+#[doc(hidden)]
+pub mod strings_prefilter;
+#[doc(hidden)]
+pub mod suppressions;
+#[doc(hidden)]
+pub mod system_headers;
+#[doc(hidden)]
+pub mod target_profile;
+
+use std::{
+    cmp::Reverse,
+    collections::{BTreeSet, HashSet},
+    fs::File,
+    io::Read,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use aho_corasick::{AhoCorasick, Input};
+use anyhow::{anyhow, Context, Result};
+use clang::{Clang, Entity, EntityKind, Index};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use analysis_cache::{AnalysisCache, TranslationUnitArtifacts};
+use binary_format::{parse_architecture_slices, ArchitectureSlice, BinaryLayout};
+use cancellation::is_cancelled;
+use checkpoint::Checkpoint;
+use compilation_database::{CompileCommand, CompileCommands};
+use confidence::{adjust_confidence_to_match_count, match_confidence, MatchFeatures};
+use containers::{is_inno_setup_installer, unpack_archive, unpack_container};
+use diagnostics::{emit_diagnostic, DiagnosticEvent};
+use information_leak::{
+    ArtifactExtractor, ArtifactExtractorRegistry, ExtractionContext, ExtractorSelection,
+    WideCharMode,
+};
+use isolation::{parse_in_worker, ParseRequest, WorkerCommand, WorkerOutcome};
+use nested_artifacts::NestedArtifacts;
+use progress::{emit_progress_event, progress_events_enabled, ProgressEvent};
+use serialization_schemas::classify_serialization_schema_artifact;
+use statistics::{DropReason, ExtractionStatistics};
+use suppressions::Suppressions;
+use system_headers::SystemHeaderFilter;
+
+pub use information_leak::{
+    BinaryLocation, ConfirmedLeak, ConfirmedLeakBuilder, ConfirmedLeakWithUniqueLocation,
+    LeakLocation, LeakedDataType, PotentialLeak, SourceLocation,
+};
+pub use reporting::{
+    dump_confirmed_leaks, JsonSink, LeakGrouping, OutputSink, OutputSinkRegistry, Report,
+    ReportMetadata, ScanOutcome, TextSink, TomlSink, YamlSink,
+};
+pub use scanner::{ProjectSource, Scanner};
+
+/// Gathers the entities of the given kinds, ignoring entities declared in
+/// system headers if a filter is given
+fn gather_entities_by_kind<'tu>(
+    root_entity: Entity<'tu>,
+    entity_kind_filter: &[EntityKind],
+    system_header_filter: Option<&SystemHeaderFilter>,
+) -> Vec<Entity<'tu>> {
+    gather_entities_by_kind_rec(root_entity, entity_kind_filter, system_header_filter)
+}
+
+fn gather_entities_by_kind_rec<'tu>(
+    root_entity: Entity<'tu>,
+    entity_kind_filter: &[EntityKind],
+    system_header_filter: Option<&SystemHeaderFilter>,
+) -> Vec<Entity<'tu>> {
+    let mut entities = vec![];
+
+    let root_entity_kind = root_entity.get_kind();
+    // Check the if entity's kind is one we're looking for
+    if entity_kind_filter
+        .iter()
+        .any(|elem| elem == &root_entity_kind)
+    {
+        entities.push(root_entity);
+    }
+
+    for child in root_entity.get_children() {
+        // Ignore entity if requested
+        if matches!(system_header_filter, Some(filter) if filter.is_in_system_header(&child)) {
+            continue;
+        }
+
+        let entities_sub =
+            gather_entities_by_kind_rec(child, entity_kind_filter, system_header_filter);
+        entities.extend(entities_sub);
+    }
+
+    entities
+}
+
+/// Removes the compile commands of suppressed source files
+pub fn filter_suppressed_files(
+    compile_cmds: CompileCommands,
+    suppressions: &Option<Suppressions>,
+) -> CompileCommands {
+    if let Some(suppressions) = suppressions {
+        compile_cmds
+            .into_par_iter()
+            .filter(|compile_cmd| !suppressions.is_file_suppressed(&compile_cmd.filename))
+            .collect()
+    } else {
+        compile_cmds
+    }
+}
+
+/// Settings of the extraction of artifacts from translation units. Defaults
+/// correspond to the command-line tool's defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractionOptions {
+    /// Indicates if source files' paths are part of their compile arguments,
+    /// in which case they aren't given to libclang separately
+    pub use_file_path_from_arguments: bool,
+    /// Ignores the entities declared in system headers, as well as in the
+    /// files located under `system_include_roots`
+    pub ignore_system_headers: bool,
+    pub system_include_roots: Vec<PathBuf>,
+    /// Names of the built-in extractors to extract artifacts with
+    pub extractor_names: Vec<String>,
+    pub minimum_leak_size: usize,
+    /// Encoding of wide strings, or the current platform's convention if not
+    /// specified
+    pub wide_char_mode: Option<WideCharMode>,
+    /// Skips the translation units that fail to parse instead of failing
+    #[serde(skip)]
+    pub keep_going: bool,
+    /// Time after which remaining translation units are skipped
+    #[serde(skip)]
+    pub deadline: Option<Instant>,
+}
+
+impl Default for ExtractionOptions {
+    fn default() -> Self {
+        Self {
+            use_file_path_from_arguments: false,
+            ignore_system_headers: true,
+            system_include_roots: vec![],
+            extractor_names: ExtractorSelection::default().extractor_names(),
+            minimum_leak_size: 4,
+            wide_char_mode: None,
+            keep_going: false,
+            deadline: None,
+        }
+    }
+}
+
+/// Parses the given translation units and extracts the artifacts that may
+/// leak into binaries. Translation units already present in `cache` aren't
+/// parsed again.
+/// If a checkpoint is given, translation units it lists aren't parsed again,
+/// and the artifacts extracted from other ones are recorded into it.
+/// Translation units whose parsing crashes or times out (in worker processes)
+/// are skipped, as are the ones that fail to parse if `keep_going` is set.
+/// Skipped translation units are recorded into `statistics`.
+pub fn extract_artifacts_from_source_files(
+    compile_commands: CompileCommands,
+    options: &ExtractionOptions,
+    cache: Option<&AnalysisCache>,
+    mut checkpoint: Option<&mut Checkpoint>,
+    parsing_worker: Option<&WorkerCommand>,
+    statistics: &mut ExtractionStatistics,
+) -> Result<Vec<PotentialLeak>> {
+    // Prepare the clang index
+    let clang = Clang::new().map_err(|e| anyhow!(e))?;
+    let index = Index::new(&clang, false, false);
+
+    // Setup extractors
+    let extractor_registry = ArtifactExtractorRegistry::default();
+    let extractors = extractor_registry.select(&options.extractor_names)?;
+    // Settings that affect the extracted artifacts, used to key cache entries
+    let cache_settings = format!(
+        "{:?}/{}/{:?}/{}/{:?}",
+        options.extractor_names,
+        options.ignore_system_headers,
+        options.system_include_roots,
+        options.minimum_leak_size,
+        options.wide_char_mode
+    );
+
+    // Translation units without an up-to-date cache entry, when the cache
+    // must be reused as is
+    let mut uncached_files = vec![];
+    // Start from the artifacts of the translation units parsed by an
+    // interrupted run, if any
+    let mut restored_artifacts = vec![];
+    if let Some(ref mut checkpoint) = checkpoint {
+        checkpoint.check_settings(&cache_settings)?;
+        if checkpoint.parsed_file_count() > 0 {
+            log::info!(
+                "Resuming from checkpoint ({} source file(s) already parsed)",
+                checkpoint.parsed_file_count()
+            );
+        }
+        statistics.record_all(
+            DropReason::TooSmall,
+            &checkpoint.artifacts().too_small_artifacts,
+        );
+        restored_artifacts.extend(checkpoint.artifacts().artifacts.iter().cloned());
+    }
+    let file_count = compile_commands.len();
+    emit_progress_event(&ProgressEvent::ExtractionStarted { files: file_count });
+    let potential_leaks = compile_commands
+        .into_iter()
+        .enumerate()
+        // Populate indexes by parsing source files in parallel
+        .try_fold(
+            restored_artifacts,
+            |mut accum, (i, compile_cmd)| -> Result<Vec<PotentialLeak>> {
+                let report_progress = |artifacts: usize| {
+                    emit_progress_event(&ProgressEvent::FileParsed {
+                        file: &compile_cmd.filename,
+                        files_parsed: i + 1,
+                        files: file_count,
+                        artifacts,
+                    })
+                };
+                let parse_start = Instant::now();
+                // Stop parsing files if interrupted, but keep what has been
+                // extracted so far
+                if is_cancelled() {
+                    return Ok(accum);
+                }
+                // Skip remaining files once the time budget is exhausted
+                if matches!(options.deadline, Some(deadline) if Instant::now() >= deadline) {
+                    statistics.record_skipped_translation_unit();
+                    return Ok(accum);
+                }
+                // Artifacts of checkpointed files have already been collected
+                if matches!(checkpoint, Some(ref checkpoint) if checkpoint.is_parsed(&compile_cmd.filename))
+                {
+                    report_progress(accum.len());
+                    return Ok(accum);
+                }
+
+                let cache_key = if let Some(cache) = cache {
+                    let cache_key = cache.entry_key(&compile_cmd, &cache_settings)?;
+                    if let Some(cached_artifacts) = cache.load(&cache_key) {
+                        log::debug!(
+                            "Using cached artifacts for '{}'",
+                            compile_cmd.filename.display()
+                        );
+                        statistics.record_all(
+                            DropReason::TooSmall,
+                            &cached_artifacts.too_small_artifacts,
+                        );
+                        emit_diagnostic(&DiagnosticEvent::FileParsed {
+                            file: &compile_cmd.filename,
+                            artifacts: cached_artifacts.artifacts.len(),
+                            duration_ms: parse_start.elapsed().as_millis(),
+                            cached: true,
+                        });
+                        if let Some(ref mut checkpoint) = checkpoint {
+                            checkpoint.record(&compile_cmd.filename, &cached_artifacts)?;
+                        }
+                        accum.extend(cached_artifacts.artifacts);
+                        report_progress(accum.len());
+                        return Ok(accum);
+                    }
+                    if cache.is_reuse_only() {
+                        uncached_files.push(compile_cmd.filename.clone());
+                        return Ok(accum);
+                    }
+                    Some(cache_key)
+                } else {
+                    None
+                };
+
+                let parse_result = if let Some(parsing_worker) = parsing_worker {
+                    let request = ParseRequest {
+                        filename: compile_cmd.filename.clone(),
+                        arguments: compile_cmd.arguments.as_ref().clone(),
+                        options: options.clone(),
+                        collect_dependencies: cache_key.is_some(),
+                    };
+                    match parse_in_worker(parsing_worker, &request) {
+                        Ok(WorkerOutcome::Parsed {
+                            artifacts,
+                            dependencies,
+                        }) => Ok((artifacts, dependencies)),
+                        // Keep scanning other files
+                        Ok(WorkerOutcome::Crashed(exit_status)) => {
+                            skip_failed_translation_unit(
+                                &compile_cmd.filename,
+                                format!("crashed ({})", exit_status),
+                                statistics,
+                            );
+                            report_progress(accum.len());
+                            return Ok(accum);
+                        }
+                        Ok(WorkerOutcome::TimedOut(timeout)) => {
+                            skip_failed_translation_unit(
+                                &compile_cmd.filename,
+                                format!("timed out after {} s", timeout.as_secs()),
+                                statistics,
+                            );
+                            report_progress(accum.len());
+                            return Ok(accum);
+                        }
+                        Err(err) => Err(err),
+                    }
+                } else {
+                    extract_artifacts_from_translation_unit(
+                        &index,
+                        &compile_cmd,
+                        &extractors,
+                        options,
+                        cache_key.is_some(),
+                    )
+                };
+                let (mut tu_artifacts, dependencies) = match parse_result {
+                    Ok(parse_result) => parse_result,
+                    Err(err) if options.keep_going => {
+                        skip_failed_translation_unit(
+                            &compile_cmd.filename,
+                            format!("{:#}", err),
+                            statistics,
+                        );
+                        report_progress(accum.len());
+                        return Ok(accum);
+                    }
+                    Err(err) => {
+                        emit_diagnostic(&DiagnosticEvent::ParseError {
+                            file: &compile_cmd.filename,
+                            error: format!("{:#}", err),
+                        });
+                        return Err(err);
+                    }
+                };
+
+                if let (Some(cache), Some(cache_key)) = (cache, cache_key) {
+                    cache.record_artifact_count(
+                        &compile_cmd.filename,
+                        tu_artifacts.artifacts.len(),
+                    )?;
+                    tu_artifacts = cache
+                        .store(&cache_key, &dependencies, tu_artifacts)
+                        .with_context(|| {
+                            format!(
+                                "Failed to cache artifacts of '{}'",
+                                compile_cmd.filename.display()
+                            )
+                        })?;
+                }
+                statistics.record_all(DropReason::TooSmall, &tu_artifacts.too_small_artifacts);
+                emit_diagnostic(&DiagnosticEvent::FileParsed {
+                    file: &compile_cmd.filename,
+                    artifacts: tu_artifacts.artifacts.len(),
+                    duration_ms: parse_start.elapsed().as_millis(),
+                    cached: false,
+                });
+                if let Some(ref mut checkpoint) = checkpoint {
+                    checkpoint.record(&compile_cmd.filename, &tu_artifacts)?;
+                }
+                accum.extend(tu_artifacts.artifacts);
+                report_progress(accum.len());
+
+                Ok(accum)
+            },
+        )?;
+
+    // Save the translation units parsed since the last checkpoint, notably
+    // when interrupted
+    if let Some(checkpoint) = checkpoint {
+        checkpoint.save()?;
+    }
+
+    if let Some(uncached_file) = uncached_files.first() {
+        return Err(anyhow!(
+            "{} source file(s) have no up-to-date entry in the analysis cache (e.g., '{}')",
+            uncached_files.len(),
+            uncached_file.display()
+        ));
+    }
+
+    Ok(potential_leaks)
+}
+
+/// Reports that a translation unit couldn't be parsed for the given reason, and
+/// that it's skipped
+fn skip_failed_translation_unit(
+    file_path: &Path,
+    reason: String,
+    statistics: &mut ExtractionStatistics,
+) {
+    log::warn!(
+        "Failed to parse '{}' ({}), skipping it",
+        file_path.display(),
+        reason
+    );
+    emit_diagnostic(&DiagnosticEvent::ParseError {
+        file: file_path,
+        error: reason.clone(),
+    });
+    statistics.record_failed_translation_unit(file_path.to_path_buf(), reason);
+}
+
+/// Parses a single translation unit and extracts artifacts from it with the
+/// given extractors (rather than the ones named in `options`), along with the
+/// list of files it depends on if `collect_dependencies` is set
+pub(crate) fn extract_artifacts_from_translation_unit(
+    index: &Index,
+    compile_cmd: &CompileCommand,
+    extractors: &[&dyn ArtifactExtractor],
+    options: &ExtractionOptions,
+    collect_dependencies: bool,
+) -> Result<(TranslationUnitArtifacts, Vec<PathBuf>)> {
+    // Note: For some reason, having the file path in `arguments` when
+    // passing the file path explicitly to libclang make the parser fail.
+    // So we explicitely avoid doing so.
+    let file_path = if options.use_file_path_from_arguments {
+        PathBuf::default()
+    } else {
+        compile_cmd.filename.clone()
+    };
+    let entity_kind_filter: Vec<EntityKind> = extractors
+        .iter()
+        .flat_map(|extractor| extractor.entity_kinds().iter().copied())
+        .collect();
+    let translation_unit = index
+        .parser(&file_path)
+        .arguments(&compile_cmd.arguments)
+        // Needed to visit macro definitions, as well as inclusion
+        // directives (to know which files cache entries depend on, and which
+        // files to read comments from)
+        .detailed_preprocessing_record(
+            entity_kind_filter.contains(&EntityKind::MacroDefinition)
+                || entity_kind_filter.contains(&EntityKind::TranslationUnit)
+                || collect_dependencies,
+        )
+        .parse()
+        .with_context(|| format!("Failed to parse source file '{}'", file_path.display()))?;
+
+    // Gather entities
+    let system_header_filter = if options.ignore_system_headers {
+        Some(SystemHeaderFilter::new(&options.system_include_roots))
+    } else {
+        None
+    };
+    let entities = gather_entities_by_kind(
+        translation_unit.get_entity(),
+        &entity_kind_filter,
+        system_header_filter.as_ref(),
+    );
+
+    let mut tu_artifacts = TranslationUnitArtifacts::default();
+    let mut context = ExtractionContext::new(options.wide_char_mode, system_header_filter.as_ref());
+    for entity in entities {
+        let entity_kind = entity.get_kind();
+        // An entity may be of interest to several extractors (e.g., fields'
+        // names and types)
+        for extractor in extractors
+            .iter()
+            .filter(|extractor| extractor.entity_kinds().contains(&entity_kind))
+        {
+            match extractor.extract(entity, &mut context) {
+                Ok(potential_leaks) => {
+                    for mut potential_leak in potential_leaks {
+                        classify_serialization_schema_artifact(&mut potential_leak);
+                        if potential_leak.bytes.len() >= options.minimum_leak_size {
+                            tu_artifacts.artifacts.push(potential_leak);
+                        } else {
+                            // Value is too small, ignore it
+                            tu_artifacts.too_small_artifacts.push(potential_leak);
+                        }
+                    }
+                }
+                Err(err) => {
+                    // Log failure and discard element
+                    log::warn!("Failed to process entity '{:?}': {}", entity, err);
+                }
+            }
+        }
+    }
+
+    let dependencies = if collect_dependencies {
+        translation_unit_dependencies(&compile_cmd.filename, translation_unit.get_entity())
+    } else {
+        vec![]
+    };
+
+    Ok((tu_artifacts, dependencies))
+}
+
+/// Sorts compile commands so that the files most likely to contain leaks are
+/// parsed first: files from which the most artifacts have been extracted
+/// previously (according to `cache`), then the most recently modified files.
+pub fn prioritize_compile_commands(
+    compile_commands: CompileCommands,
+    cache: Option<&AnalysisCache>,
+) -> CompileCommands {
+    let mut prioritized_commands: Vec<_> = compile_commands
+        .into_iter()
+        .map(|compile_cmd| {
+            let previous_artifact_count = cache
+                .and_then(|cache| cache.previous_artifact_count(&compile_cmd.filename))
+                .unwrap_or(0);
+            let modification_time = std::fs::metadata(&compile_cmd.filename)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            (
+                Reverse((previous_artifact_count, modification_time)),
+                compile_cmd,
+            )
+        })
+        .collect();
+    // Note: Stable sort, to keep the original order of equivalent files
+    prioritized_commands.sort_by_key(|(priority, _)| *priority);
+
+    prioritized_commands
+        .into_iter()
+        .map(|(_, compile_cmd)| compile_cmd)
+        .collect()
+}
+
+/// Returns the source file of a translation unit, followed by all the files it
+/// includes
+fn translation_unit_dependencies(source_file_path: &Path, root_entity: Entity) -> Vec<PathBuf> {
+    let included_files = root_entity
+        .get_children()
+        .into_iter()
+        .filter(|entity| entity.get_kind() == EntityKind::InclusionDirective)
+        .filter_map(|entity| entity.get_file())
+        .map(|file| file.get_path())
+        .collect::<BTreeSet<_>>();
+
+    std::iter::once(source_file_path.to_path_buf())
+        .chain(included_files)
+        .collect()
+}
+
+/// Removes artifacts extracted multiple times (e.g., from headers included in
+/// multiple translation units)
+pub fn deduplicate_artifacts(
+    potential_leaks: Vec<PotentialLeak>,
+    statistics: &mut ExtractionStatistics,
+) -> Vec<PotentialLeak> {
+    let mut known_artifacts = HashSet::new();
+    let (unique_leaks, duplicate_leaks): (Vec<_>, Vec<_>) =
+        potential_leaks.into_iter().partition(|leak| {
+            known_artifacts.insert((leak.bytes.clone(), leak.declaration_metadata.clone()))
+        });
+    statistics.record_all(DropReason::Duplicate, &duplicate_leaks);
+
+    unique_leaks
+}
+
+/// Adds UTF-16LE variants of narrow ASCII artifacts
+pub fn add_utf16_variants(potential_leaks: Vec<PotentialLeak>) -> Vec<PotentialLeak> {
+    let utf16_variants: Vec<PotentialLeak> = potential_leaks
+        .par_iter()
+        .filter_map(PotentialLeak::utf16_variant)
+        .collect();
+
+    potential_leaks.into_iter().chain(utf16_variants).collect()
+}
+
+/// Adds variants of string artifacts in the encodings they aren't represented
+/// with (i.e., UTF-8, UTF-16LE and UTF-32LE). Variants identical to existing
+/// artifacts are skipped.
+pub fn add_encoding_variants(potential_leaks: Vec<PotentialLeak>) -> Vec<PotentialLeak> {
+    let mut known_artifacts: HashSet<_> = potential_leaks
+        .iter()
+        .map(|leak| (leak.bytes.clone(), leak.declaration_metadata.clone()))
+        .collect();
+    let encoding_variants: Vec<PotentialLeak> = potential_leaks
+        .par_iter()
+        .flat_map_iter(PotentialLeak::encoding_variants)
+        .collect();
+    let encoding_variants: Vec<PotentialLeak> = encoding_variants
+        .into_iter()
+        .filter(|leak| {
+            known_artifacts.insert((leak.bytes.clone(), leak.declaration_metadata.clone()))
+        })
+        .collect();
+
+    potential_leaks
+        .into_iter()
+        .chain(encoding_variants)
+        .collect()
+}
+
+/// Removes artifacts declared in suppressed files
+pub fn filter_suppressed_artifacts_by_origin(
+    potential_leaks: Vec<PotentialLeak>,
+    suppressions: &Option<Suppressions>,
+    statistics: &mut ExtractionStatistics,
+) -> Vec<PotentialLeak> {
+    if let Some(suppressions) = suppressions {
+        let (unsuppressed_leaks, suppressed_leaks): (Vec<_>, Vec<_>) = potential_leaks
+            .into_par_iter()
+            .partition(|leak| !suppressions.is_file_suppressed(&leak.declaration_metadata.file));
+        statistics.record_all(DropReason::SuppressedFile, &suppressed_leaks);
+
+        unsuppressed_leaks
+    } else {
+        potential_leaks
+    }
+}
+
+/// Removes artifacts whose value is suppressed
+pub fn filter_suppressed_artifacts_by_value(
+    potential_leaks: Vec<PotentialLeak>,
+    suppressions: &Option<Suppressions>,
+    statistics: &mut ExtractionStatistics,
+) -> Vec<PotentialLeak> {
+    if let Some(suppressions) = suppressions {
+        let (unsuppressed_leaks, suppressed_leaks): (Vec<_>, Vec<_>) = potential_leaks
+            .into_par_iter()
+            .partition(|leak| !suppressions.is_artifact_suppressed(&leak.data));
+        statistics.record_all(DropReason::SuppressedValue, &suppressed_leaks);
+
+        unsuppressed_leaks
+    } else {
+        potential_leaks
+    }
+}
+
+/// Removes artifacts whose type is suppressed, either everywhere or in the file
+/// declaring them
+pub fn filter_suppressed_artifacts_by_type(
+    potential_leaks: Vec<PotentialLeak>,
+    suppressions: &Option<Suppressions>,
+    statistics: &mut ExtractionStatistics,
+) -> Vec<PotentialLeak> {
+    if let Some(suppressions) = suppressions {
+        let (unsuppressed_leaks, suppressed_leaks): (Vec<_>, Vec<_>) =
+            potential_leaks.into_par_iter().partition(|leak| {
+                !suppressions.is_type_suppressed(leak.data_type, &leak.declaration_metadata.file)
+            });
+        statistics.record_all(DropReason::SuppressedType, &suppressed_leaks);
+
+        unsuppressed_leaks
+    } else {
+        potential_leaks
+    }
+}
+
+/// Size of the chunks of binary data scanned in parallel
+const SCAN_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Maximum depth of nested containers unpacked when looking for leaks
+pub(crate) const MAX_CONTAINER_DEPTH: usize = 4;
+
+/// Looks for the given potential leaks in a binary file and returns the
+/// confirmed leaks. Files contained in archives (e.g., members of static
+/// libraries, or files of ZIP and tar archives) are always scanned
+/// individually, and if `unpack_containers` is set, so are the files contained
+/// in supported containers (e.g., installers). Leaks found in contained files
+/// are attributed to these files (e.g., `libfoo.a!/foo.o`). Each architecture
+/// of universal (fat) Mach-O binaries is scanned individually, and leaks are
+/// labeled with the architecture they're found in.
+/// Artifacts that only partially leaked are reported if
+/// `minimum_partial_leak_size` is set: their prefixes, and their suffixes if
+/// `match_suffixes` is set, of at least the given size are looked for.
+pub fn find_leaks_in_binary_file<PotentialLeakCollection, SortedConfirmedLeak>(
+    binary_file_path: &Path,
+    leak_desc: PotentialLeakCollection,
+    aligned_wide_strings: bool,
+    minimum_partial_leak_size: Option<usize>,
+    match_suffixes: bool,
+    unpack_containers: bool,
+) -> Result<BTreeSet<SortedConfirmedLeak>>
+where
+    PotentialLeakCollection: IntoParallelIterator<Item = PotentialLeak>,
+    SortedConfirmedLeak: From<ConfirmedLeak> + Ord + Eq + Send,
+{
+    find_leaks_in_binary_files(
+        &[binary_file_path.to_path_buf()],
+        leak_desc,
+        aligned_wide_strings,
+        minimum_partial_leak_size,
+        match_suffixes,
+        unpack_containers,
+    )
+}
+
+/// Looks for the given potential leaks in multiple binary files (e.g., the
+/// content of a build's output directory), scanned in parallel, and returns
+/// the confirmed leaks found in all of them. Leaks are attributed to the file
+/// they're found in, the same way `find_leaks_in_binary_file` does.
+pub fn find_leaks_in_binary_files<PotentialLeakCollection, SortedConfirmedLeak>(
+    binary_file_paths: &[PathBuf],
+    leak_desc: PotentialLeakCollection,
+    aligned_wide_strings: bool,
+    minimum_partial_leak_size: Option<usize>,
+    match_suffixes: bool,
+    unpack_containers: bool,
+) -> Result<BTreeSet<SortedConfirmedLeak>>
+where
+    PotentialLeakCollection: IntoParallelIterator<Item = PotentialLeak>,
+    SortedConfirmedLeak: From<ConfirmedLeak> + Ord + Eq + Send,
+{
+    let matcher = LeakMatcher::new(
+        Arc::new(leak_desc.into_par_iter().collect()),
+        aligned_wide_strings,
+        minimum_partial_leak_size,
+        match_suffixes,
+    )?;
+
+    find_leaks_in_binary_files_with_matcher(binary_file_paths, &matcher, unpack_containers)
+}
+
+/// Looks for leaks in multiple binary files the same way
+/// `find_leaks_in_binary_files` does, with an existing matcher
+pub fn find_leaks_in_binary_files_with_matcher<SortedConfirmedLeak>(
+    binary_file_paths: &[PathBuf],
+    matcher: &LeakMatcher,
+    unpack_containers: bool,
+) -> Result<BTreeSet<SortedConfirmedLeak>>
+where
+    SortedConfirmedLeak: From<ConfirmedLeak> + Ord + Eq + Send,
+{
+    binary_file_paths
+        .par_iter()
+        .map(
+            |binary_file_path| -> Result<BTreeSet<SortedConfirmedLeak>> {
+                let scan_start = Instant::now();
+                let leaks = matcher.find_leaks_in_file(binary_file_path, unpack_containers)?;
+                emit_diagnostic(&DiagnosticEvent::BinaryScanned {
+                    file: binary_file_path,
+                    findings: leaks.len(),
+                    duration_ms: scan_start.elapsed().as_millis(),
+                });
+
+                Ok(leaks)
+            },
+        )
+        .try_reduce(BTreeSet::new, |mut accum, other| {
+            accum.extend(other);
+            Ok(accum)
+        })
+}
+
+/// Looks for leaks in the given data and in the files it contains, if it's an
+/// archive or, if `unpack_containers` is set, a supported container
+fn find_leaks_in_container<SortedConfirmedLeak>(
+    matcher: &LeakMatcher,
+    file_path: PathBuf,
+    data: &[u8],
+    unpack_containers: bool,
+    depth: usize,
+) -> Result<BTreeSet<SortedConfirmedLeak>>
+where
+    SortedConfirmedLeak: From<ConfirmedLeak> + Ord + Eq + Send,
+{
+    // Each architecture of universal binaries is scanned separately, so that
+    // leaks are located within the slice they're found in
+    let architecture_slices = parse_architecture_slices(data)
+        .with_context(|| format!("Failed to parse '{}'", file_path.display()))?;
+    if let Some(architecture_slices) = architecture_slices {
+        let file_path = Arc::new(file_path);
+        return Ok(architecture_slices
+            .iter()
+            .flat_map(|slice| {
+                matcher.find_leaks(
+                    file_path.clone(),
+                    &data[slice.file_range.clone()],
+                    &[],
+                    Some(slice),
+                )
+            })
+            .collect());
+    }
+
+    let contained_files = if depth < MAX_CONTAINER_DEPTH {
+        let contained_files = if unpack_containers {
+            unpack_container(data)
+        } else {
+            unpack_archive(data)
+        };
+        let contained_files = contained_files
+            .with_context(|| format!("Failed to unpack '{}'", file_path.display()))?;
+        if unpack_containers && contained_files.is_none() && is_inno_setup_installer(data) {
+            log::warn!(
+                "'{}' is an Inno Setup installer, which cannot be unpacked: its compressed \
+                 payload is scanned as is",
+                file_path.display()
+            );
+        }
+        contained_files.unwrap_or_default()
+    } else {
+        vec![]
+    };
+
+    // Leaks found in files stored as is are only attributed to these files
+    let excluded_ranges: Vec<_> = contained_files
+        .iter()
+        .filter_map(|file| file.source_range.clone())
+        .collect();
+    let mut confirmed_leaks =
+        matcher.find_leaks(Arc::new(file_path.clone()), data, &excluded_ranges, None);
+    for contained_file in contained_files {
+        let contained_file_path =
+            PathBuf::from(format!("{}!/{}", file_path.display(), contained_file.name));
+        confirmed_leaks.extend(find_leaks_in_container(
+            matcher,
+            contained_file_path,
+            &contained_file.data,
+            unpack_containers,
+            depth + 1,
+        )?);
+    }
+
+    Ok(confirmed_leaks)
+}
+
+/// Matches potential leaks' byte patterns against binary data.
+/// The automaton is built once, so that the same set of artifacts can be
+/// looked for in many binaries (e.g., concurrently) without rebuilding it.
+pub struct LeakMatcher {
+    potential_leaks: Arc<Vec<PotentialLeak>>,
+    automaton: AhoCorasick,
+    /// Index of the potential leak each pattern comes from
+    pattern_to_leak: Vec<usize>,
+    /// Index of the first pattern matching the leaks' suffixes (patterns
+    /// matching their prefixes come before)
+    first_suffix_pattern: usize,
+    aligned_wide_strings: bool,
+    minimum_partial_leak_size: Option<usize>,
+    /// Artifacts contained in other artifacts, whose matches inside their
+    /// containers' matches are dropped, if set
+    nested_artifacts: Option<NestedArtifacts>,
+}
+
+impl LeakMatcher {
+    /// Builds a matcher for the given potential leaks. Artifacts that only
+    /// partially leaked are matched the same way `find_leaks_in_binary_file`
+    /// matches them.
+    pub fn new(
+        potential_leaks: Arc<Vec<PotentialLeak>>,
+        aligned_wide_strings: bool,
+        minimum_partial_leak_size: Option<usize>,
+        match_suffixes: bool,
+    ) -> Result<Self> {
+        // Empty patterns would match everywhere
+        let potential_leaks = if potential_leaks.iter().any(|leak| leak.bytes.is_empty()) {
+            Arc::new(
+                potential_leaks
+                    .iter()
+                    .filter(|leak| !leak.bytes.is_empty())
+                    .cloned()
+                    .collect(),
+            )
+        } else {
+            potential_leaks
+        };
+        // Build an automaton that matches all the potential leaks at once.
+        // Patterns are the leaks' bytes, followed by the leaks' prefixes (and
+        // suffixes) used to detect partial leaks if requested.
+        let mut patterns: Vec<&[u8]> = potential_leaks
+            .iter()
+            .map(|leak| leak.bytes.as_slice())
+            .collect();
+        let mut pattern_to_leak: Vec<usize> = (0..potential_leaks.len()).collect();
+        let minimum_partial_leak_size = minimum_partial_leak_size.map(|size| size.max(1));
+        if let Some(minimum_size) = minimum_partial_leak_size {
+            for (leak_index, leak) in potential_leaks.iter().enumerate() {
+                if leak.bytes.len() > minimum_size {
+                    patterns.push(&leak.bytes[..minimum_size]);
+                    pattern_to_leak.push(leak_index);
+                }
+            }
+        }
+        let first_suffix_pattern = patterns.len();
+        match minimum_partial_leak_size {
+            Some(minimum_size) if match_suffixes => {
+                for (leak_index, leak) in potential_leaks.iter().enumerate() {
+                    if leak.bytes.len() > minimum_size {
+                        patterns.push(&leak.bytes[leak.bytes.len() - minimum_size..]);
+                        pattern_to_leak.push(leak_index);
+                    }
+                }
+            }
+            _ => {}
+        }
+        let automaton = AhoCorasick::new(&patterns)?;
+
+        Ok(Self {
+            potential_leaks,
+            automaton,
+            pattern_to_leak,
+            first_suffix_pattern,
+            aligned_wide_strings,
+            minimum_partial_leak_size,
+            nested_artifacts: None,
+        })
+    }
+
+    /// Only reports artifacts contained in other artifacts (e.g., `"abc"` in
+    /// `"xabcx"`) where they aren't part of a match of their containers, so
+    /// that the same bytes aren't reported twice
+    pub fn deduplicate_nested_artifacts(mut self) -> Result<Self> {
+        let nested_artifacts = NestedArtifacts::new(&self.potential_leaks)?;
+        log::debug!(
+            "{} artifact(s) contained in other artifacts",
+            nested_artifacts.nested_artifact_count()
+        );
+        self.nested_artifacts = Some(nested_artifacts);
+
+        Ok(self)
+    }
+
+    /// Looks for leaks in a binary file, and in the files it contains the same
+    /// way `find_leaks_in_binary_file` does
+    pub fn find_leaks_in_file<SortedConfirmedLeak>(
+        &self,
+        binary_file_path: &Path,
+        unpack_containers: bool,
+    ) -> Result<BTreeSet<SortedConfirmedLeak>>
+    where
+        SortedConfirmedLeak: From<ConfirmedLeak> + Ord + Eq + Send,
+    {
+        // Read binary file's content
+        let mut bin_file = File::open(binary_file_path)
+            .with_context(|| format!("Failed to open '{}'", binary_file_path.display()))?;
+        let mut bin_data = vec![];
+        bin_file.read_to_end(&mut bin_data)?;
+
+        let binary_file_path = binary_file_path.canonicalize()?;
+        find_leaks_in_container(self, binary_file_path, &bin_data, unpack_containers, 0)
+    }
+
+    /// Looks for leaks in `bin_data`, ignoring matches that start in
+    /// `excluded_ranges`. If `bin_data` is a slice of a universal binary,
+    /// offsets are given relative to the universal binary.
+    fn find_leaks<SortedConfirmedLeak>(
+        &self,
+        binary_file_path: Arc<PathBuf>,
+        bin_data: &[u8],
+        excluded_ranges: &[Range<usize>],
+        architecture_slice: Option<&ArchitectureSlice>,
+    ) -> BTreeSet<SortedConfirmedLeak>
+    where
+        SortedConfirmedLeak: From<ConfirmedLeak> + Ord + Eq + Send,
+    {
+        // Parse the binary's layout to give context to offsets, if its format
+        // is supported
+        let binary_layout = BinaryLayout::parse(bin_data).unwrap_or_else(|err| {
+            log::warn!(
+                "Failed to parse '{}', treating it as a raw file: {}",
+                binary_file_path.display(),
+                err
+            );
+            None
+        });
+
+        emit_progress_event(&ProgressEvent::ScanStarted {
+            file: &binary_file_path,
+            bytes: bin_data.len(),
+            artifacts: self.potential_leaks.len(),
+        });
+        let slice_start = architecture_slice.map_or(0, |slice| slice.file_range.start);
+        let bytes_scanned = AtomicUsize::new(0);
+        let findings = AtomicUsize::new(0);
+
+        // Go through the binary file in parallel, chunk by chunk. Chunks
+        // overlap so that leaks crossing chunk boundaries can be matched.
+        let chunk_overlap = self.automaton.max_pattern_len().saturating_sub(1);
+        let confirmed_leaks = bin_data
+            .par_chunks(SCAN_CHUNK_SIZE)
+            .enumerate()
+            // Find actual leaks
+            .map(|(chunk_index, chunk)| {
+                let mut confirmed_leaks = vec![];
+                // Skip the rest of the file if interrupted
+                if is_cancelled() {
+                    return confirmed_leaks;
+                }
+                let chunk_start = chunk_index * SCAN_CHUNK_SIZE;
+                let chunk_end = chunk_start + chunk.len();
+                let search_input = Input::new(bin_data)
+                    .range(chunk_start..std::cmp::min(chunk_end + chunk_overlap, bin_data.len()));
+
+                for pattern_match in self.automaton.find_overlapping_iter(search_input) {
+                    let i = pattern_match.start();
+                    // Matches starting in the overlap belong to the next chunk
+                    if i >= chunk_end || excluded_ranges.iter().any(|range| range.contains(&i)) {
+                        continue;
+                    }
+                    let pattern_index = pattern_match.pattern().as_usize();
+                    let leak = &self.potential_leaks[self.pattern_to_leak[pattern_index]];
+                    let (i, partial_size) = if pattern_index < self.potential_leaks.len() {
+                        // Bytes match, the leak is confirmed
+                        (i, None)
+                    } else {
+                        // Only the beginning (or the end) of the data matches,
+                        // the leak is partial (full matches are reported
+                        // through the leak's own pattern)
+                        let (start, matched_size) = if pattern_index < self.first_suffix_pattern
+                        {
+                            (i, matched_prefix_size(&bin_data[i..], leak))
+                        } else {
+                            let end = pattern_match.end();
+                            let matched_size = matched_suffix_size(&bin_data[..end], leak);
+                            (end - matched_size, matched_size)
+                        };
+                        match self.minimum_partial_leak_size {
+                            Some(minimum_size)
+                                if matched_size >= minimum_size
+                                    && matched_size < leak.bytes.len() =>
+                            {
+                                (start, Some(matched_size))
+                            }
+                            _ => continue,
+                        }
+                    };
+                    // Only match wide strings on code unit boundaries if requested
+                    if self.aligned_wide_strings && i % leak.code_unit_size != 0 {
+                        continue;
+                    }
+                    let region_location = binary_layout
+                        .as_ref()
+                        .and_then(|layout| layout.locate(i as u64));
+                    let matched_size = partial_size.unwrap_or(leak.bytes.len());
+                    let confidence = match_confidence(&MatchFeatures {
+                        matched_size,
+                        code_unit_size: leak.code_unit_size,
+                        terminated: partial_size.is_none()
+                            && is_null_terminated(
+                                &bin_data[i + matched_size..],
+                                leak.code_unit_size,
+                            ),
+                        partial: partial_size.is_some(),
+                        region: region_location
+                            .as_ref()
+                            .map(|location| location.region.as_str()),
+                    });
+                    confirmed_leaks.push((
+                        self.pattern_to_leak[pattern_index],
+                        ConfirmedLeak {
+                            data_type: leak.data_type,
+                            data: leak.data.clone(),
+                            location: information_leak::LeakLocation {
+                                source: leak.declaration_metadata.clone(),
+                                binary: BinaryLocation {
+                                    file: binary_file_path.clone(),
+                                    offset: (slice_start + i) as u64,
+                                    region: region_location
+                                        .as_ref()
+                                        .map(|location| location.region.clone()),
+                                    virtual_address: region_location
+                                        .as_ref()
+                                        .and_then(|location| location.virtual_address),
+                                    architecture: architecture_slice
+                                        .map(|slice| slice.architecture.clone()),
+                                    debug_info: matches!(region_location, Some(location) if location.debug_info),
+                                },
+                            },
+                            size: matched_size,
+                            partial_size,
+                            severity: None,
+                            confidence: Some(confidence),
+                            origin: None,
+                            visibility: None,
+                            infrastructure: None,
+                            context: None,
+                            pass: None,
+                        },
+                    ));
+                }
+
+                if progress_events_enabled() {
+                    emit_progress_event(&ProgressEvent::ScanProgress {
+                        file: &binary_file_path,
+                        bytes_scanned: bytes_scanned.fetch_add(chunk.len(), Ordering::Relaxed)
+                            + chunk.len(),
+                        bytes: bin_data.len(),
+                        findings: findings.fetch_add(confirmed_leaks.len(), Ordering::Relaxed)
+                            + confirmed_leaks.len(),
+                    });
+                }
+
+                confirmed_leaks
+            })
+            .reduce(Vec::new, |mut accum, other| {
+                accum.extend(other);
+                accum
+            });
+
+        // Drop matches of nested artifacts that are part of their containers'
+        // matches if requested
+        let confirmed_leaks = match self.nested_artifacts {
+            Some(ref nested_artifacts) => {
+                // Note: Partial matches don't cover their artifacts' bytes
+                let (full_match_indices, full_matches): (Vec<usize>, Vec<(usize, usize)>) =
+                    confirmed_leaks
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, (_, leak))| leak.partial_size.is_none())
+                        .map(|(i, (leak_index, leak))| {
+                            (i, (*leak_index, leak.location.binary.offset as usize))
+                        })
+                        .unzip();
+                let redundant_matches: HashSet<usize> = nested_artifacts
+                    .redundant_matches(&full_matches)
+                    .into_iter()
+                    .map(|i| full_match_indices[i])
+                    .collect();
+                confirmed_leaks
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(i, _)| !redundant_matches.contains(i))
+                    .map(|(_, confirmed_leak)| confirmed_leak)
+                    .collect()
+            }
+            None => confirmed_leaks,
+        };
+
+        // Matches of patterns found in many places are less likely to be
+        // actual leaks
+        let mut match_counts = vec![0_usize; self.potential_leaks.len()];
+        for (leak_index, _) in &confirmed_leaks {
+            match_counts[*leak_index] += 1;
+        }
+        confirmed_leaks
+            .into_iter()
+            .map(|(leak_index, mut confirmed_leak)| {
+                confirmed_leak.confidence = confirmed_leak.confidence.map(|confidence| {
+                    adjust_confidence_to_match_count(confidence, match_counts[leak_index])
+                });
+                SortedConfirmedLeak::from(confirmed_leak)
+            })
+            .collect()
+    }
+}
+
+/// Returns `true` if `bin_data` starts with a null code unit of the given size
+fn is_null_terminated(bin_data: &[u8], code_unit_size: usize) -> bool {
+    matches!(bin_data.get(..code_unit_size), Some(code_unit) if code_unit.iter().all(|byte| *byte == 0))
+}
+
+/// Returns the number of leading bytes of `leak` found at the beginning of
+/// `bin_data`, rounded down to the leak's code unit size
+fn matched_prefix_size(bin_data: &[u8], leak: &PotentialLeak) -> usize {
+    let matched_size = bin_data
+        .iter()
+        .zip(leak.bytes.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    matched_size - matched_size % leak.code_unit_size
+}
+
+/// Returns the number of trailing bytes of `leak` found at the end of
+/// `bin_data`, rounded down to the leak's code unit size
+fn matched_suffix_size(bin_data: &[u8], leak: &PotentialLeak) -> usize {
+    let matched_size = bin_data
+        .iter()
+        .rev()
+        .zip(leak.bytes.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    matched_size - matched_size % leak.code_unit_size
+}
+
+/// Removes the artifacts that can already be found in the reference binary.
+/// Only artifacts found in full are pruned: an artifact that only partially
+/// leaked in the reference binary may fully leak in the scanned binary.
+pub fn prune_leaks_present_in_reference(
+    reference_binary_file_path: &Path,
+    potential_leaks: Vec<PotentialLeak>,
+    aligned_wide_strings: bool,
+) -> Result<Vec<PotentialLeak>> {
+    if !reference_binary_file_path.is_file() {
+        return Err(anyhow!(
+            "'{}' is not a valid file path.",
+            reference_binary_file_path.display()
+        ));
+    }
+
+    let reference_leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> = find_leaks_in_binary_file(
+        reference_binary_file_path,
+        potential_leaks.clone(),
+        aligned_wide_strings,
+        None,
+        false,
+        false,
+    )?;
+    // Note: Artifacts are identified by their value and declaration, so that
+    // all the encodings of an artifact are pruned at once
+    let known_artifacts: HashSet<_> = reference_leaks
+        .iter()
+        .map(|leak| (leak.data.clone(), leak.location.source.clone()))
+        .collect();
+
+    Ok(potential_leaks
+        .into_iter()
+        .filter(|leak| {
+            !known_artifacts.contains(&(leak.data.clone(), leak.declaration_metadata.clone()))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use crate::compilation_database::{CompilationDatabase, FileListDatabase};
+    use crate::information_leak::LeakedDataType;
+
+    use super::*;
+
+    use serial_test::serial;
+
+    const FILE_LIST_PROJ_PATH: &str = "tests/data/main/file_list_proj";
+    const TEMPLATES_PROJ_PATH: &str = "tests/data/templates";
+    const FIELDS_PROJ_PATH: &str = "tests/data/fields";
+    const COMMENTS_PROJ_PATH: &str = "tests/data/comments";
+    const TYPEDEFS_PROJ_PATH: &str = "tests/data/typedefs";
+    const ATTRIBUTES_PROJ_PATH: &str = "tests/data/attributes";
+    const ENUMS_PROJ_PATH: &str = "tests/data/enums";
+    const FUNCTIONS_PROJ_PATH: &str = "tests/data/functions";
+
+    #[test]
+    #[serial]
+    fn extract_artifacts_from_source_files_file_list() {
+        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(FILE_LIST_PROJ_PATH);
+        let file_list_db = FileListDatabase::new(
+            &[root_dir_path.join("main.cc")],
+            vec![
+                "-DDEF_TEST".to_string(),
+                format!("-I{}", FILE_LIST_PROJ_PATH),
+            ],
+        );
+        let potential_leaks = extract_artifacts_from_source_files(
+            file_list_db
+                .get_all_compile_commands()
+                .expect("get_all_compile_commands failed"),
+            &ExtractionOptions {
+                use_file_path_from_arguments: file_list_db.is_file_path_in_arguments(),
+                minimum_leak_size: 0,
+                ..Default::default()
+            },
+            None,
+            None,
+            None,
+            &mut ExtractionStatistics::default(),
+        )
+        .expect("extract_artifacts_from_source_files failed");
+
+        let expected_string_literals = vec![
+            "included_string_literal",
+            "c_string",
+            "utf8_string",
+            "wide_string",
+            "utf16_string",
+            "utf32_string",
+            "raw_string",
+            "raw_utf8_string",
+            "wide_raw_string",
+            "raw_utf16_string",
+            "raw_utf32_string",
+            "def_test",
+            "concatenated_string",
+            r#"multiline\nstring"#,
+            r#"'\"\n\t\a\b|\220|\220|\351\246\231|\351\246\231|\360\237\230\202"#,
+            "MyStruct",
+            "",
+            "MyClass",
+            "",
+            r#"%s\n"#,
+            "preprocessor_string_literal",
+            r#"%s\n"#,
+            "preprocessor_string_literal",
+            r#"%s\n"#,
+        ];
+
+        // Check extracted string literals
+        assert!(potential_leaks.iter().enumerate().all(|(i, leak)| {
+            println!("{:?}", leak.data);
+            *leak.data == expected_string_literals[i]
+        }));
+        assert_eq!(expected_string_literals.len(), potential_leaks.len());
+    }
+
+    #[test]
+    #[serial]
+    fn extract_artifacts_from_source_files_templates() {
+        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(TEMPLATES_PROJ_PATH);
+        let file_list_db = FileListDatabase::new(&[root_dir_path.join("templates.cc")], vec![]);
+        let potential_leaks = extract_artifacts_from_source_files(
+            file_list_db
+                .get_all_compile_commands()
+                .expect("get_all_compile_commands failed"),
+            &ExtractionOptions {
+                use_file_path_from_arguments: file_list_db.is_file_path_in_arguments(),
+                extractor_names: ExtractorSelection {
+                    ignore_string_literals: true,
+                    ..Default::default()
+                }
+                .extractor_names(),
+                minimum_leak_size: 0,
+                ..Default::default()
+            },
+            None,
+            None,
+            None,
+            &mut ExtractionStatistics::default(),
+        )
+        .expect("extract_artifacts_from_source_files failed");
+
+        let template_names: Vec<&str> = potential_leaks
+            .iter()
+            .filter(|leak| leak.data_type == LeakedDataType::TemplateName)
+            .map(|leak| leak.data.as_str())
+            .collect();
+        assert_eq!(
+            template_names,
+            [
+                "MyTemplate",
+                "MyTemplate<char>",
+                "Derived",
+                "ns::MyTemplate<int>",
+                "MyTemplate<int>"
+            ]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn extract_artifacts_from_source_files_field_names() {
+        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(FIELDS_PROJ_PATH);
+        let file_list_db = FileListDatabase::new(&[root_dir_path.join("fields.cc")], vec![]);
+        let potential_leaks = extract_artifacts_from_source_files(
+            file_list_db
+                .get_all_compile_commands()
+                .expect("get_all_compile_commands failed"),
+            &ExtractionOptions {
+                use_file_path_from_arguments: file_list_db.is_file_path_in_arguments(),
+                extractor_names: ExtractorSelection {
+                    ignore_string_literals: true,
+                    ignore_struct_names: true,
+                    ignore_union_names: true,
+                    ignore_typedef_names: true,
+                    ignore_enum_names: true,
+                    include_field_names: true,
+                    ..Default::default()
+                }
+                .extractor_names(),
+                ..Default::default()
+            },
+            None,
+            None,
+            None,
+            &mut ExtractionStatistics::default(),
+        )
+        .expect("extract_artifacts_from_source_files failed");
+
+        // Note: Unnamed bit-fields have no name to leak
+        let field_names: Vec<&str> = potential_leaks
+            .iter()
+            .filter(|leak| leak.data_type == LeakedDataType::FieldName)
+            .map(|leak| leak.data.as_str())
+            .collect();
+        assert_eq!(
+            field_names,
+            vec!["user_name", "api_token", "session_id", "credentials"]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn extract_artifacts_from_source_files_union_and_typedef_names() {
+        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(TYPEDEFS_PROJ_PATH);
+        let file_list_db = FileListDatabase::new(&[root_dir_path.join("typedefs.cc")], vec![]);
+        let potential_leaks = extract_artifacts_from_source_files(
+            file_list_db
+                .get_all_compile_commands()
+                .expect("get_all_compile_commands failed"),
+            &ExtractionOptions {
+                use_file_path_from_arguments: file_list_db.is_file_path_in_arguments(),
+                extractor_names: ExtractorSelection {
+                    ignore_string_literals: true,
+                    ignore_struct_names: true,
+                    ignore_enum_names: true,
+                    ..Default::default()
+                }
+                .extractor_names(),
+                ..Default::default()
+            },
+            None,
+            None,
+            None,
+            &mut ExtractionStatistics::default(),
+        )
+        .expect("extract_artifacts_from_source_files failed");
+
+        let names: Vec<(LeakedDataType, &str)> = potential_leaks
+            .iter()
+            .map(|leak| (leak.data_type, leak.data.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                (LeakedDataType::UnionName, "Payload"),
+                (LeakedDataType::TypedefName, "AnonymousPoint"),
+                (LeakedDataType::TypedefName, "SessionHandle"),
+                (LeakedDataType::TypedefName, "PayloadPointer"),
+            ]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn extract_artifacts_from_source_files_attributes() {
+        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(ATTRIBUTES_PROJ_PATH);
+        let file_list_db = FileListDatabase::new(&[root_dir_path.join("attributes.cc")], vec![]);
+        let potential_leaks = extract_artifacts_from_source_files(
+            file_list_db
+                .get_all_compile_commands()
+                .expect("get_all_compile_commands failed"),
+            &ExtractionOptions {
+                use_file_path_from_arguments: file_list_db.is_file_path_in_arguments(),
+                extractor_names: vec!["string_literals".to_string()],
+                ..Default::default()
+            },
+            None,
+            None,
+            None,
+            &mut ExtractionStatistics::default(),
+        )
+        .expect("extract_artifacts_from_source_files failed");
+
+        let string_literals: Vec<(&str, u64)> = potential_leaks
+            .iter()
+            .map(|leak| (leak.data.as_str(), leak.declaration_metadata.line))
+            .collect();
+        assert_eq!(
+            string_literals,
+            vec![
+                ("session tokens must be checked", 1),
+                ("use open_session instead", 3),
+                ("internal_build_tag", 5),
+            ]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn extract_artifacts_from_source_files_enum_names() {
+        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(ENUMS_PROJ_PATH);
+        let file_list_db = FileListDatabase::new(&[root_dir_path.join("enums.cc")], vec![]);
+        let potential_leaks = extract_artifacts_from_source_files(
+            file_list_db
+                .get_all_compile_commands()
+                .expect("get_all_compile_commands failed"),
+            &ExtractionOptions {
+                use_file_path_from_arguments: file_list_db.is_file_path_in_arguments(),
+                extractor_names: vec!["enum_names".to_string()],
+                minimum_leak_size: 0,
+                ..Default::default()
+            },
+            None,
+            None,
+            None,
+            &mut ExtractionStatistics::default(),
+        )
+        .expect("extract_artifacts_from_source_files failed");
+
+        let names: Vec<(LeakedDataType, &str, u64)> = potential_leaks
+            .iter()
+            .map(|leak| {
+                (
+                    leak.data_type,
+                    leak.data.as_str(),
+                    leak.declaration_metadata.line,
+                )
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                (LeakedDataType::EnumName, "Color", 1),
+                (LeakedDataType::EnumeratorName, "kRed", 1),
+                (LeakedDataType::EnumeratorName, "kGreen", 1),
+                (LeakedDataType::EnumName, "ConnectionState", 3),
+                (LeakedDataType::EnumeratorName, "Disconnected", 4),
+                (LeakedDataType::EnumeratorName, "Connected", 5),
+            ]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn extract_artifacts_from_source_files_function_names() {
+        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(FUNCTIONS_PROJ_PATH);
+        let file_list_db = FileListDatabase::new(&[root_dir_path.join("functions.cc")], vec![]);
+        let potential_leaks = extract_artifacts_from_source_files(
+            file_list_db
+                .get_all_compile_commands()
+                .expect("get_all_compile_commands failed"),
+            &ExtractionOptions {
+                use_file_path_from_arguments: file_list_db.is_file_path_in_arguments(),
+                extractor_names: ExtractorSelection {
+                    ignore_string_literals: true,
+                    ignore_struct_names: true,
+                    ignore_union_names: true,
+                    ignore_typedef_names: true,
+                    ignore_enum_names: true,
+                    include_function_names: true,
+                    ..Default::default()
+                }
+                .extractor_names(),
+                minimum_leak_size: 0,
+                ..Default::default()
+            },
+            None,
+            None,
+            None,
+            &mut ExtractionStatistics::default(),
+        )
+        .expect("extract_artifacts_from_source_files failed");
+
+        let names: Vec<(LeakedDataType, &str)> = potential_leaks
+            .iter()
+            .map(|leak| (leak.data_type, leak.data.as_str()))
+            .collect();
+        // Note: Parameter types aren't part of the extracted names
+        assert_eq!(
+            names,
+            vec![
+                (LeakedDataType::FunctionName, "compute_checksum"),
+                (LeakedDataType::FunctionName, "send_heartbeat"),
+                (LeakedDataType::MethodName, "authenticate"),
+                (LeakedDataType::FunctionName, "main"),
+            ]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn extract_artifacts_from_source_files_comments() {
+        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(COMMENTS_PROJ_PATH);
+        let file_list_db = FileListDatabase::new(&[root_dir_path.join("comments.cc")], vec![]);
+        let potential_leaks = extract_artifacts_from_source_files(
+            file_list_db
+                .get_all_compile_commands()
+                .expect("get_all_compile_commands failed"),
+            &ExtractionOptions {
+                use_file_path_from_arguments: file_list_db.is_file_path_in_arguments(),
+                extractor_names: vec!["comments".to_string()],
+                ..Default::default()
+            },
+            None,
+            None,
+            None,
+            &mut ExtractionStatistics::default(),
+        )
+        .expect("extract_artifacts_from_source_files failed");
+
+        let comments: Vec<(&str, u64)> = potential_leaks
+            .iter()
+            .filter(|leak| leak.data_type == LeakedDataType::Comment)
+            .map(|leak| (leak.data.as_str(), leak.declaration_metadata.line))
+            .collect();
+        assert_eq!(
+            comments,
+            vec![
+                ("Copyright (c) 2023 ACME Corp.", 2),
+                ("@author Alex Doe <alex.doe@acme.example>", 3),
+                ("Alex Doe", 3),
+                ("alex.doe@acme.example", 3),
+                ("TODO: remove the debug backdoor", 6),
+                // Comments of included files
+                ("FIXME: rotate the staging credentials", 1),
+            ]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn extract_artifacts_from_source_files_keep_going() {
+        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(FILE_LIST_PROJ_PATH);
+        let compile_commands = || {
+            vec![compilation_database::CompileCommand {
+                filename: root_dir_path.join("missing.cc"),
+                arguments: Arc::new(vec![]),
+            }]
+        };
+        let extractor_names = ExtractorSelection::default().extractor_names();
+
+        // Fails by default
+        assert!(extract_artifacts_from_source_files(
+            compile_commands(),
+            &ExtractionOptions {
+                extractor_names: extractor_names.clone(),
+                ..Default::default()
+            },
+            None,
+            None,
+            None,
+            &mut ExtractionStatistics::default(),
+        )
+        .is_err());
+
+        let mut statistics = ExtractionStatistics::default();
+        let potential_leaks = extract_artifacts_from_source_files(
+            compile_commands(),
+            &ExtractionOptions {
+                extractor_names: extractor_names.clone(),
+                keep_going: true,
+                ..Default::default()
+            },
+            None,
+            None,
+            None,
+            &mut statistics,
+        )
+        .expect("extract_artifacts_from_source_files failed");
+        assert!(potential_leaks.is_empty());
+        let failed_translation_units = statistics.failed_translation_units();
+        assert_eq!(failed_translation_units.len(), 1);
+        assert_eq!(
+            failed_translation_units[0].file_path,
+            root_dir_path.join("missing.cc")
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn extract_artifacts_with_minimum_leak_size() {
+        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(FILE_LIST_PROJ_PATH);
+        let file_list_db = FileListDatabase::new(
+            &[root_dir_path.join("main.cc")],
+            vec![
+                "-DDEF_TEST".to_string(),
+                format!("-I{}", FILE_LIST_PROJ_PATH),
+            ],
+        );
+        let potential_leaks = extract_artifacts_from_source_files(
+            file_list_db
+                .get_all_compile_commands()
+                .expect("get_all_compile_commands failed"),
+            &ExtractionOptions {
+                use_file_path_from_arguments: file_list_db.is_file_path_in_arguments(),
+                ..Default::default()
+            },
+            None,
+            None,
+            None,
+            &mut ExtractionStatistics::default(),
+        )
+        .expect("extract_artifacts_from_source_files failed");
+
+        // r#""%s\n""# should be removed
+        let expected_string_literals = vec![
+            // main.cc
+            "included_string_literal",
+            "c_string",
+            "utf8_string",
+            "wide_string",
+            "utf16_string",
+            "utf32_string",
+            "raw_string",
+            "raw_utf8_string",
+            "wide_raw_string",
+            "raw_utf16_string",
+            "raw_utf32_string",
+            "def_test",
+            "concatenated_string",
+            r#"multiline\nstring"#,
+            r#"'\"\n\t\a\b|\220|\220|\351\246\231|\351\246\231|\360\237\230\202"#,
+            "MyStruct",
+            "MyClass",
+            "preprocessor_string_literal",
+            r#"%s\n"#,
+            "preprocessor_string_literal",
+        ];
+
+        // Check extracted string literals
+        assert!(potential_leaks.iter().enumerate().all(|(i, leak)| {
+            println!("{:?}", leak.data);
+            *leak.data == expected_string_literals[i]
+        }));
+        assert_eq!(expected_string_literals.len(), potential_leaks.len());
+    }
+
+    #[test]
+    fn prioritize_compile_commands_with_cache() {
+        let tmp_dir = tempfile::tempdir().expect("Failed to create directory");
+        let cache = AnalysisCache::new(tmp_dir.path()).expect("Failed to create cache");
+        let compile_commands: CompileCommands = ["few.c", "unknown.c", "many.c"]
+            .into_iter()
+            .map(|file_name| compilation_database::CompileCommand {
+                filename: tmp_dir.path().join(file_name),
+                arguments: Arc::new(vec![]),
+            })
+            .collect();
+        cache
+            .record_artifact_count(&tmp_dir.path().join("few.c"), 1)
+            .unwrap();
+        cache
+            .record_artifact_count(&tmp_dir.path().join("many.c"), 10)
+            .unwrap();
+
+        let file_names: Vec<_> = prioritize_compile_commands(compile_commands, Some(&cache))
+            .into_iter()
+            .map(|compile_cmd| compile_cmd.filename.file_name().unwrap().to_owned())
+            .collect();
+        assert_eq!(file_names, ["many.c", "few.c", "unknown.c"]);
+    }
+
+    #[test]
+    fn find_leaks_in_binary_file_wasm() {
+        // Module with a single data section, containing an active segment
+        // loaded at address 0x10
+        let mut bin_file = tempfile::NamedTempFile::new().expect("Failed to create file");
+        bin_file
+            .write_all(b"\0asm\x01\0\0\0\x0b\x0c\x01\x00\x41\x10\x0b\x06secret")
+            .expect("Failed to write file");
+        let potential_leaks = vec![PotentialLeak {
+            data_type: information_leak::LeakedDataType::StringLiteral,
+            data: Arc::new("secret".to_string()),
+            bytes: b"secret".to_vec(),
+            code_unit_size: 1,
+            declaration_metadata: Arc::new(information_leak::SourceLocation {
+                file: PathBuf::from("file.cc"),
+                line: 1,
+            }),
+        }];
+
+        let confirmed_leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> =
+            find_leaks_in_binary_file(bin_file.path(), potential_leaks, false, None, false, false)
+                .expect("find_leaks_in_binary_file failed");
+        assert_eq!(confirmed_leaks.len(), 1);
+        let location = &confirmed_leaks.iter().next().unwrap().location.binary;
+        assert_eq!(location.offset, 16);
+        assert_eq!(
+            location.region.as_deref().map(String::as_str),
+            Some("data segment 0")
+        );
+        assert_eq!(location.virtual_address, Some(0x10));
+    }
+
+    #[test]
+    fn find_leaks_in_binary_file_aligned_wide_strings() {
+        // UTF-16LE "wide" at an even offset, then at an odd offset
+        let mut bin_file = tempfile::NamedTempFile::new().expect("Failed to create file");
+        bin_file
+            .write_all(b"\0\0w\0i\0d\0e\0\0\0\0w\0i\0d\0e\0\0")
+            .expect("Failed to write file");
+        let potential_leaks = || {
+            vec![PotentialLeak {
+                data_type: information_leak::LeakedDataType::StringLiteral,
+                data: Arc::new("wide".to_string()),
+                bytes: b"w\0i\0d\0e\0".to_vec(),
+                code_unit_size: 2,
+                declaration_metadata: Arc::new(information_leak::SourceLocation {
+                    file: PathBuf::from("file.cc"),
+                    line: 1,
+                }),
+            }]
+        };
+
+        // Matching at every offset finds both occurrences
+        let confirmed_leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> = find_leaks_in_binary_file(
+            bin_file.path(),
+            potential_leaks(),
+            false,
+            None,
+            false,
+            false,
+        )
+        .expect("find_leaks_in_binary_file failed");
+        let offsets: Vec<u64> = confirmed_leaks
+            .iter()
+            .map(|leak| leak.location.binary.offset)
+            .collect();
+        assert_eq!(offsets, vec![2, 13]);
+        // Only the first occurrence is followed by a null terminator
+        let confidences: Vec<Option<u8>> =
+            confirmed_leaks.iter().map(|leak| leak.confidence).collect();
+        assert_eq!(confidences, vec![Some(41), Some(26)]);
+
+        // Matching on code unit boundaries only finds the aligned occurrence
+        let confirmed_leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> =
+            find_leaks_in_binary_file(bin_file.path(), potential_leaks(), true, None, false, false)
+                .expect("find_leaks_in_binary_file failed");
+        let offsets: Vec<u64> = confirmed_leaks
+            .iter()
+            .map(|leak| leak.location.binary.offset)
+            .collect();
+        assert_eq!(offsets, vec![2]);
+    }
+
+    #[test]
+    fn find_leaks_in_binary_file_across_chunks() {
+        // Leak crossing the boundary between the first two chunks
+        let mut bin_data = vec![0; SCAN_CHUNK_SIZE * 2];
+        bin_data[SCAN_CHUNK_SIZE - 3..SCAN_CHUNK_SIZE + 3].copy_from_slice(b"secret");
+        let mut bin_file = tempfile::NamedTempFile::new().expect("Failed to create file");
+        bin_file.write_all(&bin_data).expect("Failed to write file");
+        // Same value declared at two different locations
+        let potential_leaks = [1, 2]
+            .into_iter()
+            .map(|line| PotentialLeak {
+                data_type: information_leak::LeakedDataType::StringLiteral,
+                data: Arc::new("secret".to_string()),
+                bytes: b"secret".to_vec(),
+                code_unit_size: 1,
+                declaration_metadata: Arc::new(information_leak::SourceLocation {
+                    file: PathBuf::from("file.cc"),
+                    line,
+                }),
+            })
+            .collect::<Vec<_>>();
+
+        let confirmed_leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> =
+            find_leaks_in_binary_file(bin_file.path(), potential_leaks, false, None, false, false)
+                .expect("find_leaks_in_binary_file failed");
+        let results: Vec<(u64, u64)> = confirmed_leaks
+            .iter()
+            .map(|leak| (leak.location.source.line, leak.location.binary.offset))
+            .collect();
+        let offset = (SCAN_CHUNK_SIZE - 3) as u64;
+        assert_eq!(results, vec![(1, offset), (2, offset)]);
+    }
+
+    #[test]
+    fn find_leaks_in_binary_file_static_library() {
+        let mut bin_data = b"!<arch>\n".to_vec();
+        bin_data.extend(format!("{:<48}{:<10}`\n", "leak.o/", 8).as_bytes());
+        bin_data.extend(b"\0secret\0");
+        let mut bin_file = tempfile::NamedTempFile::new().expect("Failed to create file");
+        bin_file.write_all(&bin_data).expect("Failed to write file");
+        let potential_leaks = vec![PotentialLeak {
+            data_type: information_leak::LeakedDataType::StringLiteral,
+            data: Arc::new("secret".to_string()),
+            bytes: b"secret".to_vec(),
+            code_unit_size: 1,
+            declaration_metadata: Arc::new(information_leak::SourceLocation {
+                file: PathBuf::from("file.cc"),
+                line: 1,
+            }),
+        }];
+
+        // Members are scanned individually, even if containers aren't
+        // unpacked
+        let confirmed_leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> =
+            find_leaks_in_binary_file(bin_file.path(), potential_leaks, false, None, false, false)
+                .expect("find_leaks_in_binary_file failed");
+        let locations: Vec<(String, u64)> = confirmed_leaks
+            .iter()
+            .map(|leak| {
+                (
+                    leak.location.binary.file.display().to_string(),
+                    leak.location.binary.offset,
+                )
+            })
+            .collect();
+        let member_path = format!(
+            "{}!/leak.o",
+            bin_file.path().canonicalize().unwrap().display()
+        );
+        assert_eq!(locations, vec![(member_path, 1)]);
+    }
+
+    #[test]
+    fn find_leaks_in_binary_files_directory() {
+        let tmp_dir = tempfile::tempdir().expect("Failed to create directory");
+        let binary_file_paths = vec![tmp_dir.path().join("a.dll"), tmp_dir.path().join("b.dll")];
+        std::fs::write(&binary_file_paths[0], b"\0secret\0").expect("Failed to write file");
+        std::fs::write(&binary_file_paths[1], b"\0\0\0secret\0").expect("Failed to write file");
+        let potential_leaks = vec![PotentialLeak {
+            data_type: information_leak::LeakedDataType::StringLiteral,
+            data: Arc::new("secret".to_string()),
+            bytes: b"secret".to_vec(),
+            code_unit_size: 1,
+            declaration_metadata: Arc::new(information_leak::SourceLocation {
+                file: PathBuf::from("file.cc"),
+                line: 1,
+            }),
+        }];
+
+        // Leaks are attributed to the file they're found in
+        let confirmed_leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> =
+            find_leaks_in_binary_files(
+                &binary_file_paths,
+                potential_leaks,
+                false,
+                None,
+                false,
+                false,
+            )
+            .expect("find_leaks_in_binary_files failed");
+        let locations: Vec<(PathBuf, u64)> = confirmed_leaks
+            .iter()
+            .map(|leak| {
+                (
+                    leak.location.binary.file.as_ref().clone(),
+                    leak.location.binary.offset,
+                )
+            })
+            .collect();
+        assert_eq!(
+            locations,
+            vec![
+                (binary_file_paths[0].canonicalize().unwrap(), 1),
+                (binary_file_paths[1].canonicalize().unwrap(), 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn leak_matcher_shared_between_files() {
+        let tmp_dir = tempfile::tempdir().expect("Failed to create directory");
+        let binary_file_paths = vec![tmp_dir.path().join("v1.bin"), tmp_dir.path().join("v2.bin")];
+        std::fs::write(&binary_file_paths[0], b"\0secret\0").expect("Failed to write file");
+        std::fs::write(&binary_file_paths[1], b"\0\0other\0").expect("Failed to write file");
+        let potential_leaks = ["secret", "other", ""]
+            .into_iter()
+            .map(|value| PotentialLeak {
+                data_type: information_leak::LeakedDataType::StringLiteral,
+                data: Arc::new(value.to_string()),
+                bytes: value.as_bytes().to_vec(),
+                code_unit_size: 1,
+                declaration_metadata: Arc::new(information_leak::SourceLocation {
+                    file: PathBuf::from("file.cc"),
+                    line: 1,
+                }),
+            })
+            .collect();
+
+        // Empty artifacts are ignored, and the same automaton is used for
+        // both files
+        let matcher = LeakMatcher::new(Arc::new(potential_leaks), false, None, false)
+            .expect("LeakMatcher::new failed");
+        let results: Vec<Vec<(String, u64)>> = binary_file_paths
+            .par_iter()
+            .map(|binary_file_path| {
+                let confirmed_leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> = matcher
+                    .find_leaks_in_file(binary_file_path, false)
+                    .expect("find_leaks_in_file failed");
+                confirmed_leaks
+                    .iter()
+                    .map(|leak| (leak.data.to_string(), leak.location.binary.offset))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                vec![("secret".to_string(), 1)],
+                vec![("other".to_string(), 2)]
+            ]
+        );
+    }
+
+    #[test]
+    fn find_leaks_in_binary_file_universal_binary() {
+        // Fat header and two slices (x86_64 then arm64), containing the same
+        // value
+        let mut bin_data = b"\xca\xfe\xba\xbe\0\0\0\x02".to_vec();
+        bin_data.extend(b"\x01\0\0\x07\0\0\0\0\0\0\0\x30\0\0\0\x08\0\0\0\0");
+        bin_data.extend(b"\x01\0\0\x0c\0\0\0\0\0\0\0\x38\0\0\0\x08\0\0\0\0");
+        bin_data.resize(0x30, 0);
+        bin_data.extend(b"\0secret\0\0\0secret");
+        let mut bin_file = tempfile::NamedTempFile::new().expect("Failed to create file");
+        bin_file.write_all(&bin_data).expect("Failed to write file");
+        let potential_leaks = vec![PotentialLeak {
+            data_type: information_leak::LeakedDataType::StringLiteral,
+            data: Arc::new("secret".to_string()),
+            bytes: b"secret".to_vec(),
+            code_unit_size: 1,
+            declaration_metadata: Arc::new(information_leak::SourceLocation {
+                file: PathBuf::from("file.cc"),
+                line: 1,
+            }),
+        }];
+
+        // Offsets are relative to the universal binary
+        let confirmed_leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> =
+            find_leaks_in_binary_file(bin_file.path(), potential_leaks, false, None, false, false)
+                .expect("find_leaks_in_binary_file failed");
+        let locations: Vec<(u64, Option<&str>)> = confirmed_leaks
+            .iter()
+            .map(|leak| {
+                (
+                    leak.location.binary.offset,
+                    leak.location
+                        .binary
+                        .architecture
+                        .as_deref()
+                        .map(String::as_str),
+                )
+            })
+            .collect();
+        assert_eq!(
+            locations,
+            vec![(0x31, Some("x86_64")), (0x3a, Some("arm64"))]
+        );
+    }
+
+    #[test]
+    fn prune_leaks_present_in_reference_binary() {
+        let mut reference_bin_file = tempfile::NamedTempFile::new().expect("Failed to create file");
+        reference_bin_file
+            .write_all(b"\0old_value\0")
+            .expect("Failed to write file");
+        let potential_leaks = ["old_value", "new_value"]
+            .into_iter()
+            .map(|value| PotentialLeak {
+                data_type: information_leak::LeakedDataType::StringLiteral,
+                data: Arc::new(value.to_string()),
+                bytes: value.as_bytes().to_vec(),
+                code_unit_size: 1,
+                declaration_metadata: Arc::new(information_leak::SourceLocation {
+                    file: PathBuf::from("file.cc"),
+                    line: 1,
+                }),
+            })
+            .collect();
+
+        let potential_leaks =
+            prune_leaks_present_in_reference(reference_bin_file.path(), potential_leaks, false)
+                .expect("prune_leaks_present_in_reference failed");
+        assert_eq!(potential_leaks.len(), 1);
+        assert_eq!(*potential_leaks[0].data, "new_value");
+    }
+
+    #[test]
+    fn prune_leaks_present_in_reference_binary_partial_leaks() {
+        // Truncated copy of the artifact in the reference binary only
+        let mut reference_bin_file = tempfile::NamedTempFile::new().expect("Failed to create file");
+        reference_bin_file
+            .write_all(b"\0secret_\0")
+            .expect("Failed to write file");
+        let mut bin_file = tempfile::NamedTempFile::new().expect("Failed to create file");
+        bin_file
+            .write_all(b"\0secret_value\0")
+            .expect("Failed to write file");
+        let potential_leaks = vec![PotentialLeak {
+            data_type: information_leak::LeakedDataType::StringLiteral,
+            data: Arc::new("secret_value".to_string()),
+            bytes: b"secret_value".to_vec(),
+            code_unit_size: 1,
+            declaration_metadata: Arc::new(information_leak::SourceLocation {
+                file: PathBuf::from("file.cc"),
+                line: 1,
+            }),
+        }];
+
+        let potential_leaks =
+            prune_leaks_present_in_reference(reference_bin_file.path(), potential_leaks, false)
+                .expect("prune_leaks_present_in_reference failed");
+        assert_eq!(potential_leaks.len(), 1);
+
+        let leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> = find_leaks_in_binary_file(
+            bin_file.path(),
+            potential_leaks,
+            false,
+            Some(4),
+            false,
+            false,
+        )
+        .expect("find_leaks_in_binary_file failed");
+        assert_eq!(leaks.len(), 1);
+        assert!(leaks.iter().all(|leak| leak.partial_size.is_none()));
+    }
+
+    #[test]
+    fn find_leaks_in_binary_file_partial_leaks() {
+        // Truncated copy of "secret_value", then the full value
+        let mut bin_file = tempfile::NamedTempFile::new().expect("Failed to create file");
+        bin_file
+            .write_all(b"\0secret_\0secret_value\0")
+            .expect("Failed to write file");
+        let potential_leaks = || {
+            vec![PotentialLeak {
+                data_type: information_leak::LeakedDataType::StringLiteral,
+                data: Arc::new("secret_value".to_string()),
+                bytes: b"secret_value".to_vec(),
+                code_unit_size: 1,
+                declaration_metadata: Arc::new(information_leak::SourceLocation {
+                    file: PathBuf::from("file.cc"),
+                    line: 1,
+                }),
+            }]
+        };
+
+        // Partial leaks are ignored by default
+        let confirmed_leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> = find_leaks_in_binary_file(
+            bin_file.path(),
+            potential_leaks(),
+            false,
+            None,
+            false,
+            false,
+        )
+        .expect("find_leaks_in_binary_file failed");
+        let results: Vec<(u64, Option<usize>)> = confirmed_leaks
+            .iter()
+            .map(|leak| (leak.location.binary.offset, leak.partial_size))
+            .collect();
+        assert_eq!(results, vec![(9, None)]);
+
+        // Partial leaks are reported when big enough
+        let confirmed_leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> = find_leaks_in_binary_file(
+            bin_file.path(),
+            potential_leaks(),
+            false,
+            Some(4),
+            false,
+            false,
+        )
+        .expect("find_leaks_in_binary_file failed");
+        let results: Vec<(u64, Option<usize>)> = confirmed_leaks
+            .iter()
+            .map(|leak| (leak.location.binary.offset, leak.partial_size))
+            .collect();
+        assert_eq!(results, vec![(1, Some(7)), (9, None)]);
+
+        // Partial leaks smaller than the threshold are ignored
+        let confirmed_leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> = find_leaks_in_binary_file(
+            bin_file.path(),
+            potential_leaks(),
+            false,
+            Some(8),
+            false,
+            false,
+        )
+        .expect("find_leaks_in_binary_file failed");
+        assert_eq!(confirmed_leaks.len(), 1);
+    }
+
+    #[test]
+    fn find_leaks_in_binary_file_substrings() {
+        // Folded suffix of "secret_value", truncated prefix, then the full value
+        let mut bin_file = tempfile::NamedTempFile::new().expect("Failed to create file");
+        bin_file
+            .write_all(b"\0t_value\0secret_\0secret_value\0")
+            .expect("Failed to write file");
+        let potential_leaks = || {
+            vec![PotentialLeak {
+                data_type: information_leak::LeakedDataType::StringLiteral,
+                data: Arc::new("secret_value".to_string()),
+                bytes: b"secret_value".to_vec(),
+                code_unit_size: 1,
+                declaration_metadata: Arc::new(information_leak::SourceLocation {
+                    file: PathBuf::from("file.cc"),
+                    line: 1,
+                }),
+            }]
+        };
+
+        // Suffixes are only matched if requested
+        let confirmed_leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> = find_leaks_in_binary_file(
+            bin_file.path(),
+            potential_leaks(),
+            false,
+            Some(4),
+            false,
+            false,
+        )
+        .expect("find_leaks_in_binary_file failed");
+        let results: Vec<(u64, Option<usize>)> = confirmed_leaks
+            .iter()
+            .map(|leak| (leak.location.binary.offset, leak.partial_size))
+            .collect();
+        assert_eq!(results, vec![(9, Some(7)), (17, None)]);
+
+        let confirmed_leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> = find_leaks_in_binary_file(
+            bin_file.path(),
+            potential_leaks(),
+            false,
+            Some(4),
+            true,
+            false,
+        )
+        .expect("find_leaks_in_binary_file failed");
+        let results: Vec<(u64, Option<usize>)> = confirmed_leaks
+            .iter()
+            .map(|leak| (leak.location.binary.offset, leak.partial_size))
+            .collect();
+        assert_eq!(results, vec![(1, Some(7)), (9, Some(7)), (17, None)]);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    #[serial]
+    fn find_leaks_in_binary_file_exe() {
+        // Gather potential leaks
+        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(FILE_LIST_PROJ_PATH);
+        let file_list_db = FileListDatabase::new(
+            &[root_dir_path.join("main.cc")],
+            vec![
+                "-DDEF_TEST".to_string(),
+                format!("-I{}", FILE_LIST_PROJ_PATH),
+            ],
+        );
+        let potential_leaks = extract_artifacts_from_source_files(
+            file_list_db
+                .get_all_compile_commands()
+                .expect("get_all_compile_commands failed"),
+            &ExtractionOptions {
+                use_file_path_from_arguments: file_list_db.is_file_path_in_arguments(),
+                minimum_leak_size: 0,
+                ..Default::default()
+            },
+            None,
+            None,
+            None,
+            &mut ExtractionStatistics::default(),
+        )
+        .expect("extract_artifacts_from_source_files failed");
+
+        // Look for leaks present in the compiled binary
+        let bin_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join(FILE_LIST_PROJ_PATH)
+            .join("a.exe");
+
+        let confirmed_leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> =
+            find_leaks_in_binary_file(&bin_path, potential_leaks, false, None, false, false)
+                .expect("find_leaks_in_binary_file failed");
+
+        let expected_string_literals = vec![
+            // main.cc
+            "included_string_literal",
+            "MyStruct",
+            "MyStruct",
+            "MyStruct",
+            "MyClass",
+            "MyClass",
+            "MyClass",
+            "preprocessor_string_literal",
+            "preprocessor_string_literal",
+            r#"%s\n"#,
+        ];
+
+        // Check extracted string literals
+        assert!(confirmed_leaks.iter().enumerate().all(|(i, leak)| {
+            println!("{:?}", leak.data);
+            *leak.data == expected_string_literals[i]
+        }));
+        assert_eq!(confirmed_leaks.len(), expected_string_literals.len());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[serial]
+    fn find_leaks_in_binary_file_elf() {
+        // Gather potential leaks
+        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(FILE_LIST_PROJ_PATH);
+        let file_list_db = FileListDatabase::new(
+            &[root_dir_path.join("main.cc")],
+            vec!["-DDEF_TEST".to_string()],
+        );
+        let potential_leaks = extract_artifacts_from_source_files(
+            file_list_db
+                .get_all_compile_commands()
+                .expect("get_all_compile_commands failed"),
+            &ExtractionOptions {
+                use_file_path_from_arguments: file_list_db.is_file_path_in_arguments(),
+                minimum_leak_size: 0,
+                ..Default::default()
+            },
+            None,
+            None,
+            None,
+            &mut ExtractionStatistics::default(),
+        )
+        .expect("extract_artifacts_from_source_files failed");
+
+        // Look for leaks present in the compiled binary
+        let bin_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join(FILE_LIST_PROJ_PATH)
+            .join("a.out");
+
+        let confirmed_leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> =
+            find_leaks_in_binary_file(&bin_path, potential_leaks, false, None, false, false)
+                .expect("find_leaks_in_binary_file failed");
+
+        let expected_string_literals = vec![
+            // main.cc
+            "included_string_literal",
+            "included_string_literal",
+            "MyStruct",
+            "MyStruct",
+            "MyStruct",
+            "MyStruct",
+            "MyStruct",
+            "MyStruct",
+            "MyStruct",
+            "MyStruct",
+            "MyStruct",
+            "MyStruct",
+            "MyStruct",
+            "MyStruct",
+            "MyClass",
+            "MyClass",
+            "MyClass",
+            "MyClass",
+            "MyClass",
+            "MyClass",
+            "MyClass",
+            "MyClass",
+            "MyClass",
+            "MyClass",
+            "MyClass",
+            "preprocessor_string_literal",
+            r#"%s\n"#,
+            "preprocessor_string_literal",
+        ];
+
+        // Check extracted string literals
+        assert!(confirmed_leaks.iter().enumerate().all(|(i, leak)| {
+            println!("{:?}", leak.data);
+            *leak.data == expected_string_literals[i]
+        }));
+        assert_eq!(confirmed_leaks.len(), expected_string_literals.len());
+    }
+}
@@ -1,651 +1,1173 @@
+mod aggregate;
+mod attestation;
 mod cli;
-mod compilation_database;
-mod information_leak;
-mod reporting;
-mod suppressions;
+mod diff;
+mod doctor;
+mod grep;
+mod patterns;
+mod watch;
 
 use std::{
-    collections::{BTreeSet, HashMap},
+    collections::BTreeSet,
+    fmt,
     fs::File,
-    io::Read,
+    io::{BufWriter, Write},
+    ops::Deref,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
     vec,
 };
 
 use anyhow::{anyhow, Context, Result};
-use clang::{Clang, Entity, EntityKind, Index};
-use rayon::prelude::*;
 use structopt::StructOpt;
 
-use compilation_database::CompileCommands;
-use information_leak::{BinaryLocation, ConfirmedLeak};
-use reporting::dump_confirmed_leaks;
-use suppressions::Suppressions;
-
-use crate::{
-    cli::CpplumberOptions,
-    compilation_database::{generate_compilation_database, ProjectConfiguration},
+use cpplumber::{
+    add_encoding_variants, add_utf16_variants,
+    artifacts_file::{export_artifacts, import_artifacts, merge_artifacts_files},
+    baseline::{filter_baseline_leaks, Baseline},
+    binary_files::collect_binary_files,
+    cancellation::{install_cancellation_handler, is_cancelled, INTERRUPTED_EXIT_CODE},
+    compilation_database::locate_cmake_compile_commands,
+    confidence::filter_confirmed_leaks_by_confidence,
+    config::{generate_custom_artifacts, parse_configuration_file, Configuration},
+    debug_info::{classify_leak_visibility, Visibility},
+    diagnostics::{init_logger, LogFormat},
+    encoding_coverage::analyze_encoding_coverage,
+    entropy::{filter_low_entropy_artifacts, EntropyFilter},
+    extra_artifacts::parse_extra_artifacts_file,
+    find_leaks_in_binary_files, find_leaks_in_binary_files_with_matcher,
     information_leak::{
-        ConfirmedLeakWithUniqueLocation, ConfirmedLeakWithUniqueValue, PotentialLeak,
+        ConfirmedLeakWithUniqueLocation, ExtractorSelection, PotentialLeak, SourceLocation,
+    },
+    infrastructure::{classify_confirmed_leak_infrastructure, InfrastructureClassifier},
+    isolation::{serve_parse_request, WorkerCommand},
+    match_context::attach_match_context,
+    overlaps::remove_overlapping_leaks,
+    path_mapping::{normalize_confirmed_leak_paths, PathNormalizer},
+    pattern_set::generate_pattern_set,
+    policy::count_failing_leaks,
+    progress::{emit_progress_event, enable_progress_events, ProgressEvent},
+    provenance::{classify_confirmed_leak_origins, ProvenanceClassifier},
+    prune_leaks_present_in_reference,
+    reporting::{
+        dump_confirmed_leaks, write_leak_summary, FileDigest, HostEnvironment, JsonSink,
+        OutputSink, OutputSinkRegistry, ReportMetadata, ScanOutcome, TextSink, TomlSink, YamlSink,
+        PARTIAL_REPORT_NOTICE,
     },
-    suppressions::parse_suppressions_file,
+    resources::extract_artifacts_from_resource_files,
+    scan_passes::{merge_pass_leaks, validate_scan_passes, ScanPass},
+    severity::{classify_confirmed_leaks, SeverityClassifier},
+    statistics::{ExtractionStatistics, PoolingStatistics},
+    strings_prefilter::{collect_searchable_data, prune_potential_leaks},
+    suppressions::{generate_suppressions, parse_suppressions_file, Suppressions},
+    LeakMatcher, ProjectSource, Scanner,
 };
 
-fn main() -> Result<()> {
-    // Default to 'info' if 'RUST_LOG' is not set
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+use aggregate::run_aggregate_scan;
+use attestation::write_attestation;
+use cli::{Command, CpplumberOptions};
+use diff::run_binary_diff;
+use doctor::run_doctor;
+use grep::grep_binary_file;
+use patterns::preview_patterns;
+use watch::run_watch_mode;
+
+/// Number of source files listed in the summary of large text reports
+const SUMMARIZED_FILE_COUNT: usize = 50;
 
+fn main() -> Result<()> {
     // Parse command-line options
-    let options = CpplumberOptions::from_args();
+    let mut options = CpplumberOptions::from_args();
+    init_logger(options.log_format);
+    // Stop gracefully when interrupted, instead of losing all results
+    install_cancellation_handler()?;
+
+    let result = if options.watch && options.command.is_none() {
+        // Interrupting is the way to stop watching
+        run_watch_mode(&mut options)
+    } else {
+        run(&options)
+    };
+    if is_cancelled() && !options.watch {
+        if let Err(e) = result {
+            log::error!("{:#}", e);
+        }
+        log::warn!("Scan interrupted, results are partial");
+        std::process::exit(INTERRUPTED_EXIT_CODE);
+    }
+    // Use the requested exit code when found leaks fail the run, other errors
+    // keep the default one
+    if let Err(ref e) = result {
+        if e.downcast_ref::<LeaksDetected>().is_some() {
+            log::error!("{}", e);
+            std::process::exit(options.error_exit_code);
+        }
+    }
+
+    result
+}
+
+/// Error returned when found leaks fail the run
+#[derive(Debug)]
+pub struct LeaksDetected(pub String);
+
+impl fmt::Display for LeaksDetected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for LeaksDetected {}
+
+fn run(options: &CpplumberOptions) -> Result<()> {
+    match options.command {
+        Some(Command::Grep(ref grep_options)) => return grep_binary_file(grep_options),
+        Some(Command::Patterns(ref patterns_options)) => return preview_patterns(patterns_options),
+        Some(Command::MergeArtifacts(ref merge_options)) => {
+            return merge_artifacts_files(&merge_options.artifacts_files, &merge_options.output)
+        }
+        Some(Command::Aggregate(ref aggregate_options)) => {
+            return run_aggregate_scan(aggregate_options)
+        }
+        Some(Command::Doctor(ref doctor_options)) => return run_doctor(doctor_options),
+        Some(Command::ParseWorker) => {
+            return serve_parse_request(std::io::stdin().lock(), std::io::stdout().lock())
+        }
+        // Note: Diffs need the project's artifacts
+        Some(Command::Diff(_)) | None => {}
+    }
     let minimum_leak_size = options.minimum_leak_size.unwrap_or(4);
 
     // Initial checks before starting work
-    if !options.binary_file_path.is_file() {
+    if options.skip_scan
+        && options.export_artifacts.is_none()
+        && options.cache_directory.is_none()
+        && options.emit_patterns.is_none()
+    {
         return Err(anyhow!(
-            "'{}' is not a valid file path.",
-            options.binary_file_path.display()
+            "Extracted artifacts would be lost, use --cache-dir, --export-artifacts or \
+             --emit-patterns with --skip-scan"
         ));
     }
+    if options.export_artifacts.is_none()
+        && !options.skip_scan
+        && !matches!(options.command, Some(Command::Diff(_)))
+    {
+        check_binary_file_path(options)?;
+    }
+    if let Some(root) = options
+        .system_include_roots
+        .iter()
+        .find(|root| !root.is_dir())
+    {
+        return Err(anyhow!("'{}' is not a valid directory.", root.display()));
+    }
 
-    // Parse the suppression list if used
-    let suppressions = if let Some(ref suppressions_list) = options.suppressions_list {
-        log::info!("Parsing suppressions file...");
+    // Report progress on a side channel if requested
+    if let Some(ref progress_file_path) = options.progress_json {
+        let writer: Box<dyn Write + Send> =
+            if progress_file_path == Path::new("-") {
+                Box::new(std::io::stderr())
+            } else {
+                Box::new(File::create(progress_file_path).with_context(|| {
+                    format!("Failed to create '{}'", progress_file_path.display())
+                })?)
+            };
+        enable_progress_events(writer);
+    }
+
+    let reporting = load_reporting_context(options)?;
+    let suppressions = load_suppressions(options)?;
+    let scanner = project_scanner(options, suppressions, minimum_leak_size)?;
+
+    // Keep track of artifacts dropped during extraction
+    let mut statistics = ExtractionStatistics::default();
+    let potential_leaks = collect_artifacts(
+        options,
+        &scanner,
+        reporting.config.as_ref(),
+        minimum_leak_size,
+        &mut statistics,
+    )?;
+    let potential_leaks = prepare_artifacts(options, &scanner, potential_leaks, &mut statistics)?;
+
+    // Export the final byte patterns if requested
+    if let Some(ref patterns_file_path) = options.emit_patterns {
+        log::info!("Exporting byte patterns...");
+        write_pattern_set(
+            patterns_file_path,
+            &potential_leaks,
+            &PathNormalizer::new(options.path_prefix_map.clone()),
+        )
+        .with_context(|| "Failed to export byte patterns")?;
+    }
+
+    // Stop here if artifacts are only exported
+    if let Some(ref export_file_path) = options.export_artifacts {
+        log::info!("Exporting artifacts...");
+        return export_artifacts(export_file_path, &potential_leaks)
+            .with_context(|| "Failed to export artifacts");
+    }
+    // Stop here if artifacts are only extracted (e.g., to populate the cache)
+    if options.skip_scan {
+        log::info!("Skipping scan...");
+        return Ok(());
+    }
+    // Compare two binaries instead of scanning one if requested
+    if let Some(Command::Diff(ref diff_options)) = options.command {
+        return run_binary_diff(options, diff_options, potential_leaks);
+    }
+    let binary_file_path = check_binary_file_path(options)?;
+    let binary_file_paths = binary_files_to_scan(options)?;
+
+    // Analyze the encodings string artifacts are found with if requested
+    if let Some(ref coverage_file_path) = options.encoding_coverage {
+        log::info!("Analyzing the encodings of string artifacts...");
+        write_encoding_coverage(
+            coverage_file_path,
+            binary_file_path,
+            &potential_leaks,
+            &PathNormalizer::new(options.path_prefix_map.clone()),
+        )
+        .with_context(|| "Failed to write encoding coverage report")?;
+    }
+
+    let potential_leaks = prune_artifacts(options, &binary_file_paths, potential_leaks)?;
+    log::info!("Looking for leaks in '{}'...", binary_file_path.display());
+    log::debug!("{:#?}", potential_leaks);
+    report_leaks(
+        &binary_file_paths,
+        options,
+        &reporting,
+        potential_leaks,
+        statistics.skipped_translation_units() > 0
+            || !statistics.failed_translation_units().is_empty(),
+    )
+}
+
+/// Parses the configuration file if used, and builds the output sinks and the
+/// classifiers reports rely on
+fn load_reporting_context(options: &CpplumberOptions) -> Result<ReportingContext> {
+    let mut output_sinks = OutputSinkRegistry::default();
+    // Embed the environment and configuration into structured reports, so
+    // that they can be reproduced
+    let metadata = capture_report_metadata(options)?;
+    output_sinks.register("json", Box::new(JsonSink::with_metadata(metadata.clone())));
+    output_sinks.register("yaml", Box::new(YamlSink::with_metadata(metadata.clone())));
+    output_sinks.register("toml", Box::new(TomlSink::with_metadata(metadata)));
+    if let Some(group_by) = options.group_by {
+        output_sinks.register("text", Box::new(TextSink::grouped_by(group_by)));
+    }
+    output_sinks.get(options.output_format())?;
+
+    let config = if let Some(ref config_file_path) = options.config {
+        log::info!("Parsing configuration file...");
         Some(
-            parse_suppressions_file(suppressions_list)
-                .with_context(|| "Failed to parse suppressions list")?,
+            parse_configuration_file(config_file_path)
+                .with_context(|| "Failed to parse configuration file")?,
         )
     } else {
         None
     };
+    // Build the classifiers early, so that invalid rules are reported
+    // before the (potentially long) extraction phase
+    if let Some(ref config) = config {
+        validate_scan_passes(&config.passes).with_context(|| "Invalid scan passes")?;
+    }
+    let severity_classifier =
+        SeverityClassifier::new(config.as_ref().and_then(|config| config.severity.as_ref()))
+            .with_context(|| "Invalid severity configuration")?;
+    let provenance_classifier = ProvenanceClassifier::new(
+        config
+            .as_ref()
+            .and_then(|config| config.provenance.as_ref()),
+    )
+    .with_context(|| "Invalid provenance configuration")?;
 
-    log::info!("Gathering source files...");
-    // Extract project configuration from the CLI
-    let project_config = if let Some(ref project_file_path) = options.project_file_path {
-        ProjectConfiguration::CompilationDatabase { project_file_path }
-    } else {
-        ProjectConfiguration::Manual {
-            source_path_globs: &options.source_path_globs,
-            include_directories: &options.include_directories,
-            compile_definitions: &options.compile_definitions,
+    Ok(ReportingContext {
+        config,
+        severity_classifier,
+        provenance_classifier,
+        output_sinks,
+    })
+}
+
+/// Parses the suppression list if used
+fn load_suppressions(options: &CpplumberOptions) -> Result<Option<Suppressions>> {
+    let suppressions_list = match options.suppressions_list {
+        Some(ref suppressions_list) => suppressions_list,
+        None => return Ok(None),
+    };
+    log::info!("Parsing suppressions file...");
+    let mut suppressions = parse_suppressions_file(suppressions_list)
+        .with_context(|| "Failed to parse suppressions list")?;
+    if let Some(ref project_root) = options.project_root {
+        if !project_root.is_dir() {
+            return Err(anyhow!(
+                "'{}' is not a valid directory.",
+                project_root.display()
+            ));
         }
+        suppressions.set_project_root(project_root);
+    }
+
+    Ok(Some(suppressions))
+}
+
+/// Gathers the artifacts to look for: the ones extracted from the project's
+/// source files, and the ones imported, provided or generated from the
+/// configuration
+fn collect_artifacts(
+    options: &CpplumberOptions,
+    scanner: &Scanner,
+    config: Option<&Configuration>,
+    minimum_leak_size: usize,
+    statistics: &mut ExtractionStatistics,
+) -> Result<Vec<PotentialLeak>> {
+    let mut potential_leaks = if options.skip_extraction {
+        if options.import_artifacts.is_empty() && options.extra_artifacts.is_empty() {
+            return Err(anyhow!(
+                "No artifacts to look for, use --import-artifacts or --extra-artifacts with \
+                 --skip-extraction"
+            ));
+        }
+        log::info!("Skipping extraction...");
+        vec![]
+    } else {
+        log::info!("Gathering source files...");
+        scanner.extract_source_artifacts(statistics)?
     };
-    // Parse project file or process glob expressions
-    let compilation_db = generate_compilation_database(project_config)?;
-
-    log::info!("Filtering suppressed files...");
-    // Filter suppressed files from the list, to avoid parsing files we're not
-    // interested in
-    let compile_commands =
-        filter_suppressed_files(compilation_db.get_all_compile_commands()?, &suppressions);
-
-    log::info!("Extracting artifacts from source files...");
-    // Parse source files and extract information that could leak
-    let potential_leaks = extract_artifacts_from_source_files(
-        compile_commands,
-        compilation_db.is_file_path_in_arguments(),
-        !options.report_system_headers,
-        options.ignore_string_literals,
-        options.ignore_struct_names,
-        minimum_leak_size,
-    )?;
+    // Add artifacts extracted by previous runs
+    for artifacts_file_path in &options.import_artifacts {
+        potential_leaks.extend(import_artifacts(artifacts_file_path)?);
+    }
+    // Add user-provided artifacts (e.g., known secrets)
+    for extra_artifacts_file_path in &options.extra_artifacts {
+        potential_leaks.extend(
+            parse_extra_artifacts_file(extra_artifacts_file_path)
+                .with_context(|| "Failed to parse extra artifacts file")?,
+        );
+    }
+    // Add user-defined artifacts and resource files' content if needed
+    if let Some(config) = config {
+        potential_leaks.extend(
+            generate_custom_artifacts(config)
+                .with_context(|| "Failed to generate custom artifacts")?,
+        );
+        let resource_path_globs: Vec<String> = config
+            .resources
+            .iter()
+            .map(|glob_expression| config.resolve_glob_expression(glob_expression))
+            .collect();
+        potential_leaks.extend(
+            extract_artifacts_from_resource_files(
+                &resource_path_globs,
+                minimum_leak_size,
+                statistics,
+            )
+            .with_context(|| "Failed to extract artifacts from resource files")?,
+        );
+    }
+
+    Ok(potential_leaks)
+}
 
+/// Filters the gathered artifacts, and adds the variants looked for in the
+/// binary
+fn prepare_artifacts(
+    options: &CpplumberOptions,
+    scanner: &Scanner,
+    potential_leaks: Vec<PotentialLeak>,
+    statistics: &mut ExtractionStatistics,
+) -> Result<Vec<PotentialLeak>> {
+    // Remove artifacts extracted multiple times (e.g., from headers included
+    // in multiple translation units), as well as suppressed ones
     log::info!("Filtering suppressed artifacts...");
-    // Filter suppressed artifacts by source location if needed
-    // Note: We need to do this "again" because artifacts from suppressed
-    // headers might have been included during the parsing of other files
-    let potential_leaks = filter_suppressed_artifacts_by_origin(potential_leaks, &suppressions);
-    // Filter suppressed artifacts by value if needed
-    let potential_leaks = filter_suppressed_artifacts_by_value(potential_leaks, &suppressions);
+    let potential_leaks = scanner.filter_artifacts(potential_leaks, statistics);
+    // Filter artifacts that don't look random enough if requested
+    let entropy_filter = if options.only_high_entropy {
+        Some(EntropyFilter::high_entropy())
+    } else {
+        options.min_entropy.map(EntropyFilter::new)
+    };
+    let potential_leaks = if let Some(ref entropy_filter) = entropy_filter {
+        filter_low_entropy_artifacts(potential_leaks, entropy_filter, statistics)
+    } else {
+        potential_leaks
+    };
+    statistics.log_summary(options.verbose);
+    scanner.check_artifact_count(potential_leaks.len())?;
 
-    log::info!(
-        "Looking for leaks in '{}'...",
-        options.binary_file_path.display()
-    );
-    log::debug!("{:#?}", potential_leaks);
-    if options.ignore_multiple_locations {
-        // Find leaks and deduplicate based on their value
-        let leaks: BTreeSet<ConfirmedLeakWithUniqueValue> =
-            find_leaks_in_binary_file(&options.binary_file_path, potential_leaks)?;
-        log::debug!("Done!");
-
-        if leaks.is_empty() {
-            // Nothing leaked, alright!
-            Ok(())
-        } else {
-            // Print the result to stdout
-            dump_confirmed_leaks(std::io::stdout(), leaks, options.json_output)?;
+    // Add UTF-16LE variants of narrow artifacts or variants of strings in
+    // other encodings if requested
+    Ok(scanner.add_artifact_variants(potential_leaks))
+}
+
+/// Returns the binary files to scan: the given binary, or the files it
+/// contains if it's a directory
+fn binary_files_to_scan(options: &CpplumberOptions) -> Result<Vec<PathBuf>> {
+    let binary_file_path = check_binary_file_path(options)?;
+    let binary_file_paths = if binary_file_path.is_dir() {
+        let binary_file_paths = collect_binary_files(binary_file_path, &options.binary_globs)?;
+        if binary_file_paths.is_empty() {
+            return Err(anyhow!(
+                "No files to scan in '{}' (check the --bin-glob expressions)",
+                binary_file_path.display()
+            ));
+        }
+        log::info!(
+            "{} file(s) to scan in '{}'",
+            binary_file_paths.len(),
+            binary_file_path.display()
+        );
+        binary_file_paths
+    } else {
+        vec![binary_file_path.to_path_buf()]
+    };
+    // Warn about binaries built for another target than the expected one, as
+    // patterns wouldn't be encoded correctly
+    if let Some(profile) = options.profile {
+        for binary_file_path in &binary_file_paths {
+            if let Some(mismatch) = profile.check_binary(&std::fs::read(binary_file_path)?) {
+                log::warn!(
+                    "'{}' doesn't match the target profile ({}), check --profile",
+                    binary_file_path.display(),
+                    mismatch
+                );
+            }
+        }
+    }
 
-            // Return an error to indicate that leaks were found (useful for automation)
-            Err(anyhow!("Leaks detected!"))
+    Ok(binary_file_paths)
+}
+
+/// Drops the artifacts that cannot be found in the binaries' strings, or that
+/// are present in the reference binary, if requested
+fn prune_artifacts(
+    options: &CpplumberOptions,
+    binary_file_paths: &[PathBuf],
+    potential_leaks: Vec<PotentialLeak>,
+) -> Result<Vec<PotentialLeak>> {
+    // Prune artifacts that cannot be found in the binary's strings if requested
+    let potential_leaks = if options.strings_prefilter {
+        log::info!("Pruning artifacts absent from the binary's strings...");
+        let mut searchable_data = vec![];
+        for binary_file_path in binary_file_paths {
+            collect_searchable_data(&std::fs::read(binary_file_path)?, &mut searchable_data);
         }
+        prune_potential_leaks(&searchable_data, potential_leaks)
     } else {
-        // Find leaks and deduplicate based on their location (source + binary)
-        let leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> =
-            find_leaks_in_binary_file(&options.binary_file_path, potential_leaks)?;
-        log::debug!("Done!");
+        potential_leaks
+    };
+    // Only look for artifacts that aren't present in the reference binary if
+    // requested
+    if let Some(ref reference_binary_file_path) = options.reference_binary_file_path {
+        log::info!(
+            "Pruning artifacts present in '{}'...",
+            reference_binary_file_path.display()
+        );
+        prune_leaks_present_in_reference(
+            reference_binary_file_path,
+            potential_leaks,
+            options.aligned_wide_strings,
+        )
+    } else {
+        Ok(potential_leaks)
+    }
+}
 
-        if leaks.is_empty() {
-            // Nothing leaked, alright!
-            Ok(())
+/// Builds the scanner extracting the project's artifacts, as configured by the
+/// command-line options
+fn project_scanner(
+    options: &CpplumberOptions,
+    suppressions: Option<Suppressions>,
+    minimum_leak_size: usize,
+) -> Result<Scanner> {
+    // Extract project configuration from the CLI
+    let entry_point_globs: Vec<String> = options
+        .entry_points
+        .iter()
+        .map(|entry_point| glob::Pattern::escape(&entry_point.to_string_lossy()))
+        .collect();
+    let cmake_compile_commands_path = options
+        .cmake_build_directory
+        .as_deref()
+        .map(locate_cmake_compile_commands)
+        .transpose()?;
+    let project_file_path = options
+        .project_file_path
+        .clone()
+        .or(cmake_compile_commands_path);
+    let project = if let Some(project_file_path) = project_file_path {
+        if options.source_path_globs.is_empty() {
+            ProjectSource::CompilationDatabase(project_file_path)
         } else {
-            // Print the result to stdout
-            dump_confirmed_leaks(std::io::stdout(), leaks, options.json_output)?;
+            // Some sources (e.g., generated ones) may be missing from the
+            // database
+            ProjectSource::Combined {
+                project_file_path,
+                source_path_globs: options.source_path_globs.clone(),
+                include_directories: options.include_directories.clone(),
+                compile_definitions: options.compile_definitions.clone(),
+            }
+        }
+    } else {
+        ProjectSource::Manual {
+            // Entry points are the source files if none is given
+            source_path_globs: if options.source_path_globs.is_empty() {
+                entry_point_globs
+            } else {
+                options.source_path_globs.clone()
+            },
+            include_directories: options.include_directories.clone(),
+            compile_definitions: options.compile_definitions.clone(),
+        }
+    };
 
-            // Return an error to indicate that leaks were found (useful for automation)
-            Err(anyhow!("Leaks detected!"))
+    let mut scanner = Scanner::new(project)
+        .minimum_leak_size(minimum_leak_size)
+        .report_system_headers(options.report_system_headers)
+        .system_include_roots(options.system_include_roots.clone())
+        .extractor_selection(ExtractorSelection {
+            ignore_string_literals: options.ignore_string_literals,
+            ignore_struct_names: options.ignore_struct_names,
+            ignore_union_names: options.ignore_union_names,
+            ignore_typedef_names: options.ignore_typedef_names,
+            ignore_enum_names: options.ignore_enum_names,
+            include_function_names: options.include_function_names,
+            include_field_names: options.include_field_names,
+            include_comments: options.include_comments,
+            include_macro_values: options.include_macro_values,
+        })
+        .utf16_variants(options.utf16_variants)
+        .all_encodings(options.all_encodings)
+        .reuse_cache_only(options.reuse_cache_only)
+        .resume(options.resume)
+        .keep_going(options.keep_going)
+        .extra_args(options.extra_args.clone())
+        .remove_args(options.remove_args.clone())
+        .entry_points(options.entry_points.clone())
+        .only_files(options.only_files.clone(), options.exclude_files.clone())
+        .minimum_file_count(options.min_files)
+        .minimum_artifact_count(options.min_artifacts)
+        .detect_embedded_files(options.detect_embedded_files)
+        .detect_com_guids(options.detect_com_guids)
+        .detect_source_paths(options.detect_source_paths);
+    if let Some(suppressions) = suppressions {
+        scanner = scanner.suppressions(suppressions);
+    }
+    if !options.extractors.is_empty() {
+        scanner = scanner.extractors(options.extractors.clone());
+    }
+    if let Some(wide_char_mode) = options.wide_char_mode {
+        scanner = scanner.wide_char_mode(wide_char_mode);
+    }
+    if let Some(profile) = options.profile {
+        scanner = scanner.target_profile(profile);
+    }
+    if let Some(ref directory) = options.cache_directory {
+        scanner = scanner.cache_directory(directory);
+    }
+    if let Some(ref checkpoint_path) = options.checkpoint {
+        scanner = scanner.checkpoint(checkpoint_path);
+    }
+    if let Some(shard) = options.shard {
+        scanner = scanner.shard(shard);
+    }
+    if let Some(minutes) = options.time_budget {
+        scanner = scanner.time_budget(Duration::from_secs(minutes * 60));
+    }
+    if let Some(ref binary_file_path) = options.binary_file_path {
+        scanner = scanner.expected_binary(binary_file_path);
+    }
+    // Have worker processes started from this executable parse source files
+    // if requested
+    // Note: Parsing can only be interrupted in worker processes
+    if options.isolate_parsing || options.parse_timeout.is_some() {
+        // Workers log on the same stream, so they must use the same format
+        let mut arguments = vec![];
+        if options.log_format == LogFormat::Json {
+            arguments.extend(["--log-format".to_string(), "json".to_string()]);
         }
+        arguments.push("parse-worker".to_string());
+        scanner = scanner.parsing_worker(WorkerCommand {
+            program: std::env::current_exe()?,
+            arguments,
+            timeout: options.parse_timeout.map(Duration::from_secs),
+        });
     }
+
+    Ok(scanner)
 }
 
-fn gather_entities_by_kind<'tu>(
-    root_entity: Entity<'tu>,
-    entity_kind_filter: &[EntityKind],
-    ignore_system_headers: bool,
-) -> Vec<Entity<'tu>> {
-    gather_entities_by_kind_rec(root_entity, entity_kind_filter, ignore_system_headers)
+/// Creates the file the full listing of leaks is written into when there are
+/// too many to print them
+fn create_findings_file(options: &CpplumberOptions) -> Result<(File, PathBuf)> {
+    if let Some(ref findings_file_path) = options.findings_file {
+        let findings_file = File::create(findings_file_path)
+            .with_context(|| format!("Failed to create '{}'", findings_file_path.display()))?;
+        Ok((findings_file, findings_file_path.clone()))
+    } else {
+        let findings_file = tempfile::Builder::new()
+            .prefix("cpplumber-findings-")
+            .suffix(".txt")
+            .tempfile()
+            .with_context(|| "Failed to create findings file")?;
+        // Note: The file must outlive the process
+        Ok(findings_file.keep()?)
+    }
 }
 
-fn gather_entities_by_kind_rec<'tu>(
-    root_entity: Entity<'tu>,
-    entity_kind_filter: &[EntityKind],
-    ignore_system_headers: bool,
-) -> Vec<Entity<'tu>> {
-    let mut entities = vec![];
+/// Captures the environment and the effective configuration of the scan
+fn capture_report_metadata(options: &CpplumberOptions) -> Result<ReportMetadata> {
+    let path_normalizer = PathNormalizer::new(options.path_prefix_map.clone());
+    let file_digest = |file_path: &Path| FileDigest::new(file_path, &path_normalizer);
+    // Note: Configuration and suppressions files are validated later on
+    let config = options
+        .config
+        .as_deref()
+        .and_then(|config_file_path| std::fs::read(config_file_path).ok())
+        .and_then(|config_data| serde_yaml::from_slice(&config_data).ok());
 
-    let root_entity_kind = root_entity.get_kind();
-    // Check the if entity's kind is one we're looking for
-    if entity_kind_filter
-        .iter()
-        .any(|elem| elem == &root_entity_kind)
-    {
-        entities.push(root_entity);
-    }
+    Ok(ReportMetadata {
+        cpplumber_version: env!("CARGO_PKG_VERSION").into(),
+        libclang_version: clang::get_version(),
+        host: HostEnvironment::current(),
+        options: normalized_options(options, &path_normalizer)?,
+        config_file: options
+            .config
+            .as_deref()
+            .and_then(|file_path| file_digest(file_path).ok()),
+        config,
+        suppressions_file: options
+            .suppressions_list
+            .as_deref()
+            .and_then(|file_path| file_digest(file_path).ok()),
+    })
+}
+
+/// Options holding paths (or glob expressions matching paths), which are
+/// normalized when captured into reports
+const PATH_OPTIONS: &[&str] = &[
+    "binary_file_path",
+    "binary_globs",
+    "reference_binary_file_path",
+    "include_directories",
+    "only_files",
+    "exclude_files",
+    "entry_points",
+    "project_file_path",
+    "cmake_build_directory",
+    "config",
+    "suppressions_list",
+    "project_root",
+    "system_include_roots",
+    "baseline",
+    "write_baseline",
+    "generate_suppressions",
+    "encoding_coverage",
+    "emit_patterns",
+    "progress_json",
+    "report_file",
+    "export_artifacts",
+    "cache_directory",
+    "checkpoint",
+    "import_artifacts",
+    "extra_artifacts",
+    "attestation",
+    "attestation_key",
+    "findings_file",
+    "source_path_globs",
+];
 
-    for child in root_entity.get_children() {
-        // Ignore entity if requested
-        if ignore_system_headers && child.is_in_system_header() {
-            continue;
+/// Serializes the command-line options with their paths normalized by
+/// `path_normalizer`, so that reports don't depend on the machine they're
+/// generated on. Relative paths are kept relative.
+fn normalized_options(
+    options: &CpplumberOptions,
+    path_normalizer: &PathNormalizer,
+) -> Result<serde_json::Value> {
+    let normalize = |value: &mut serde_json::Value| {
+        if let serde_json::Value::String(path) = value {
+            *path = path_normalizer
+                .normalize(Path::new(path))
+                .display()
+                .to_string();
         }
+    };
 
-        let entities_sub =
-            gather_entities_by_kind_rec(child, entity_kind_filter, ignore_system_headers);
-        entities.extend(entities_sub);
+    let mut options = serde_json::to_value(options)?;
+    for option_name in PATH_OPTIONS {
+        match options.get_mut(*option_name) {
+            Some(serde_json::Value::Array(paths)) => paths.iter_mut().for_each(normalize),
+            Some(path) => normalize(path),
+            None => {}
+        }
     }
 
-    entities
+    Ok(options)
 }
 
-fn filter_suppressed_files(
-    compile_cmds: CompileCommands,
-    suppressions: &Option<Suppressions>,
-) -> CompileCommands {
-    if let Some(suppressions) = suppressions {
-        compile_cmds
-            .into_par_iter()
-            .filter(|compile_cmd| {
-                if let Some(file_path) = compile_cmd.filename.to_str() {
-                    !suppressions
-                        .files
-                        .par_iter()
-                        .any(|pattern| pattern.matches(file_path))
-                } else {
-                    true
-                }
-            })
-            .collect()
-    } else {
-        compile_cmds
-    }
+/// Writes a JSON report of the given leaks, with full values unless
+/// redaction is enabled
+fn write_json_report(
+    report_file_path: &Path,
+    leaks: &BTreeSet<ConfirmedLeakWithUniqueLocation>,
+    json_sink: &dyn OutputSink,
+    outcome: &ScanOutcome,
+    options: &CpplumberOptions,
+) -> Result<()> {
+    let report_file = File::create(report_file_path)
+        .with_context(|| format!("Failed to create '{}'", report_file_path.display()))?;
+
+    dump_confirmed_leaks(
+        BufWriter::new(report_file),
+        leaks,
+        json_sink,
+        outcome,
+        options.redact,
+        None,
+        options.ignore_multiple_locations,
+    )
 }
 
-fn extract_artifacts_from_source_files(
-    compile_commands: CompileCommands,
-    use_file_path_from_arguments: bool,
-    ignore_system_headers: bool,
-    ignore_string_literals: bool,
-    ignore_struct_names: bool,
-    minimum_leak_size: usize,
-) -> Result<Vec<PotentialLeak>> {
-    // Prepare the clang index
-    let clang = Clang::new().map_err(|e| anyhow!(e))?;
-    let index = Index::new(&clang, false, false);
-
-    compile_commands
-        .into_iter()
-        // Populate indexes by parsing source files in parallel
-        .try_fold(
-            Vec::new(),
-            |mut accum, compile_cmd| -> Result<Vec<PotentialLeak>> {
-                // Note: For some reason, having the file path in `arguments` when
-                // passing the file path explicitly to libclang make the parser fail.
-                // So we explicitely avoid doing so.
-                let file_path = if use_file_path_from_arguments {
-                    PathBuf::default()
-                } else {
-                    compile_cmd.filename
-                };
-                let translation_unit = index
-                    .parser(&file_path)
-                    .arguments(&compile_cmd.arguments)
-                    .parse()
-                    .with_context(|| {
-                        format!("Failed to parse source file '{}'", file_path.display())
-                    })?;
-
-                // Setup filter
-                let mut entity_kind_filter = vec![];
-                if !ignore_string_literals {
-                    entity_kind_filter.push(EntityKind::StringLiteral);
-                }
-                if !ignore_struct_names {
-                    entity_kind_filter.push(EntityKind::StructDecl);
-                    entity_kind_filter.push(EntityKind::ClassDecl);
-                }
+/// Writes the byte patterns generated for the given artifacts into
+/// `patterns_file_path`
+fn write_pattern_set(
+    patterns_file_path: &Path,
+    potential_leaks: &[PotentialLeak],
+    path_normalizer: &PathNormalizer,
+) -> Result<()> {
+    let mut pattern_set = generate_pattern_set(potential_leaks);
+    for pattern in &mut pattern_set.patterns {
+        pattern.source = Arc::new(SourceLocation {
+            file: path_normalizer.normalize(&pattern.source.file),
+            line: pattern.source.line,
+        });
+    }
 
-                // Gather entities
-                let string_literals = gather_entities_by_kind(
-                    translation_unit.get_entity(),
-                    &entity_kind_filter,
-                    ignore_system_headers,
-                );
+    let patterns_file = File::create(patterns_file_path)
+        .with_context(|| format!("Failed to create '{}'", patterns_file_path.display()))?;
+    Ok(serde_json::to_writer(
+        BufWriter::new(patterns_file),
+        &pattern_set,
+    )?)
+}
 
-                accum.extend(string_literals.into_iter().filter_map(|literal| {
-                    let leak_res: Result<PotentialLeak> = literal.try_into();
-                    if let Ok(potential_leak) = leak_res {
-                        if potential_leak.bytes.len() >= minimum_leak_size {
-                            Some(potential_leak)
-                        } else {
-                            // Value is too small, ignore it
-                            None
-                        }
-                    } else {
-                        // Log failure and discard element
-                        log::warn!(
-                            "Failed to process entity '{:?}': {}",
-                            literal,
-                            leak_res.unwrap_err()
-                        );
-                        None
-                    }
-                }));
-
-                Ok(accum)
-            },
-        )
+/// Writes a JSON report of the encodings string artifacts are found with in
+/// the binary
+fn write_encoding_coverage(
+    coverage_file_path: &Path,
+    binary_file_path: &Path,
+    potential_leaks: &[PotentialLeak],
+    path_normalizer: &PathNormalizer,
+) -> Result<()> {
+    let bin_data = std::fs::read(binary_file_path)?;
+    let mut report = analyze_encoding_coverage(&bin_data, potential_leaks)?;
+    for artifact in &mut report.artifacts {
+        artifact.declaration = Arc::new(SourceLocation {
+            file: path_normalizer.normalize(&artifact.declaration.file),
+            line: artifact.declaration.line,
+        });
+    }
+    log::info!(
+        "String artifacts found as narrow: {}, wide: {}, both: {}, neither: {} ({} only found with the encoding they're not declared with)",
+        report.summary.narrow,
+        report.summary.wide,
+        report.summary.both,
+        report.summary.neither,
+        report.summary.mismatched
+    );
+
+    let coverage_file = File::create(coverage_file_path)
+        .with_context(|| format!("Failed to create '{}'", coverage_file_path.display()))?;
+    Ok(serde_json::to_writer(
+        BufWriter::new(coverage_file),
+        &report,
+    )?)
 }
 
-fn filter_suppressed_artifacts_by_origin(
-    potential_leaks: Vec<PotentialLeak>,
-    suppressions: &Option<Suppressions>,
-) -> Vec<PotentialLeak> {
-    if let Some(suppressions) = suppressions {
-        potential_leaks
-            .into_par_iter()
-            .filter(|leak| {
-                let file_path = &leak.declaration_metadata.file;
-                if let Some(file_path) = file_path.as_os_str().to_str() {
-                    !suppressions
-                        .files
-                        .par_iter()
-                        .any(|pattern| pattern.matches(file_path))
-                } else {
-                    true
-                }
-            })
-            .collect()
-    } else {
-        potential_leaks
+/// Returns the path of the binary file to scan, after making sure it's valid
+fn check_binary_file_path(options: &CpplumberOptions) -> Result<&Path> {
+    // Note: The binary's path is required when no subcommand is used
+    let binary_file_path = options
+        .binary_file_path
+        .as_deref()
+        .ok_or_else(|| anyhow!("No binary file specified."))?;
+    if binary_file_path.is_dir() {
+        // These features describe a single binary
+        if options.encoding_coverage.is_some() || options.attestation.is_some() {
+            return Err(anyhow!(
+                "--encoding-coverage and --attestation cannot be used when --bin is a directory"
+            ));
+        }
+    } else if !binary_file_path.is_file() {
+        return Err(anyhow!(
+            "'{}' is not a valid file path.",
+            binary_file_path.display()
+        ));
+    } else if !options.binary_globs.is_empty() {
+        return Err(anyhow!(
+            "--bin-glob can only be used when --bin is a directory"
+        ));
     }
+
+    Ok(binary_file_path)
 }
 
-fn filter_suppressed_artifacts_by_value(
+/// Looks for leaks once per scan pass, with the pass' settings enabled on top
+/// of the command line's, and merges the results
+fn run_scan_passes(
+    binary_file_paths: &[PathBuf],
+    options: &CpplumberOptions,
+    passes: &[ScanPass],
     potential_leaks: Vec<PotentialLeak>,
-    suppressions: &Option<Suppressions>,
-) -> Vec<PotentialLeak> {
-    if let Some(suppressions) = suppressions {
-        potential_leaks
-            .into_par_iter()
-            .filter(|leak| !suppressions.artifacts.contains(&leak.data))
-            .collect()
-    } else {
-        potential_leaks
+) -> Result<BTreeSet<ConfirmedLeakWithUniqueLocation>> {
+    let (minimum_partial_leak_size, match_suffixes) = options.partial_matching();
+    let mut leaks = BTreeSet::new();
+    for pass in passes {
+        if is_cancelled() {
+            break;
+        }
+        log::info!("Running scan pass '{}'...", pass.name);
+        // Note: Variants requested on the command line have already been added
+        let pass_leaks = potential_leaks.clone();
+        let pass_leaks = if pass.utf16_variants && !options.utf16_variants {
+            add_utf16_variants(pass_leaks)
+        } else {
+            pass_leaks
+        };
+        let pass_leaks = if pass.all_encodings && !options.all_encodings {
+            add_encoding_variants(pass_leaks)
+        } else {
+            pass_leaks
+        };
+        let pass_leaks = find_leaks_in_binary_files(
+            binary_file_paths,
+            pass_leaks,
+            pass.aligned_wide_strings || options.aligned_wide_strings,
+            pass.partial_leaks.or(minimum_partial_leak_size),
+            match_suffixes,
+            pass.unpack_containers || options.unpack_containers,
+        )?;
+        let new_leak_count = merge_pass_leaks(&mut leaks, pass_leaks, &Arc::new(pass.name.clone()));
+        log::info!(
+            "Scan pass '{}' found {} new leak(s)",
+            pass.name,
+            new_leak_count
+        );
     }
-}
 
-fn find_leaks_in_binary_file<PotentialLeakCollection, SortedConfirmedLeak>(
-    binary_file_path: &Path,
-    leak_desc: PotentialLeakCollection,
-) -> Result<BTreeSet<SortedConfirmedLeak>>
-where
-    PotentialLeakCollection: IntoParallelIterator<Item = PotentialLeak>,
-    SortedConfirmedLeak: From<ConfirmedLeak> + Ord + Eq + Send,
-{
-    // Read binary file's content
-    let mut bin_file = File::open(binary_file_path)?;
-    let mut bin_data = vec![];
-    bin_file.read_to_end(&mut bin_data)?;
-
-    // Build a map that allows to lookup "leaks' first byte -> leaks"
-    let byte_to_leaks = leak_desc
-        .into_par_iter()
-        .fold(
-            HashMap::new,
-            |mut accum: HashMap<u8, Vec<PotentialLeak>>, potential_leak| {
-                if let Some(key) = potential_leak.bytes.first() {
-                    if let Some(value) = accum.get_mut(key) {
-                        value.push(potential_leak);
-                    } else {
-                        accum.insert(*key, vec![potential_leak]);
-                    }
-                }
+    Ok(leaks)
+}
 
-                accum
-            },
-        )
-        // Reduce intermediate maps into one
-        .reduce(HashMap::new, |mut accum, other| {
-            for (other_key, mut other_value) in other {
-                if let Some(value) = accum.get_mut(&other_key) {
-                    value.append(&mut other_value);
-                } else {
-                    accum.insert(other_key, other_value);
-                }
-            }
-            accum
-        });
+/// Looks for the given artifacts in the binary files, and classifies the
+/// leaks found
+fn find_confirmed_leaks(
+    binary_file_paths: &[PathBuf],
+    options: &CpplumberOptions,
+    reporting: &ReportingContext,
+    potential_leaks: Vec<PotentialLeak>,
+) -> Result<BTreeSet<ConfirmedLeakWithUniqueLocation>> {
+    let leaks = scan_binary_files(
+        binary_file_paths,
+        options,
+        reporting.config.as_ref(),
+        potential_leaks,
+    )?;
 
-    // Go through the binary file byte by byte and try to match leaks that start
-    // with each byte
-    let shared_binary_file_path = Arc::new(binary_file_path.to_path_buf().canonicalize()?);
-    let confirmed_leaks = bin_data
-        .par_iter()
-        .enumerate()
-        // Find actual leaks
-        .map(|(i, byte_value)| {
-            let mut confirmed_leaks = BTreeSet::new();
-            if let Some(potential_leaks) = byte_to_leaks.get(byte_value) {
-                // Go through each candidate
-                for leak in potential_leaks {
-                    // Check bounds
-                    if i + leak.bytes.len() <= bin_data.len() {
-                        let byte_slice = &bin_data[i..i + leak.bytes.len()];
-                        if byte_slice == leak.bytes {
-                            // Bytes match, the leak is confirmed
-                            confirmed_leaks.insert(SortedConfirmedLeak::from(ConfirmedLeak {
-                                data_type: leak.data_type,
-                                data: leak.data.clone(),
-                                location: information_leak::LeakLocation {
-                                    source: leak.declaration_metadata.clone(),
-                                    binary: BinaryLocation {
-                                        file: shared_binary_file_path.clone(),
-                                        offset: i as u64,
-                                    },
-                                },
-                            }));
-                        }
-                    }
-                }
-            }
+    classify_leaks(options, reporting, leaks)
+}
 
-            confirmed_leaks
-        })
-        .reduce(BTreeSet::new, |mut accum, other| {
-            accum.extend(other);
-            accum
-        });
+/// Looks for the given artifacts in the binary files, running the
+/// configuration's scan passes if any
+fn scan_binary_files(
+    binary_file_paths: &[PathBuf],
+    options: &CpplumberOptions,
+    config: Option<&Configuration>,
+    potential_leaks: Vec<PotentialLeak>,
+) -> Result<BTreeSet<ConfirmedLeakWithUniqueLocation>> {
+    // Find leaks and deduplicate based on their location (source + binary).
+    // Note: Deduplicating based on values is up to the output sinks
+    let passes = config
+        .map(|config| config.passes.as_slice())
+        .unwrap_or_default();
+    let leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> = if passes.is_empty() {
+        let (minimum_partial_leak_size, match_suffixes) = options.partial_matching();
+        let matcher = LeakMatcher::new(
+            Arc::new(potential_leaks),
+            options.aligned_wide_strings,
+            minimum_partial_leak_size,
+            match_suffixes,
+        )?;
+        // Don't report artifacts a second time as part of the artifacts
+        // containing them if requested
+        let matcher = if options.dedup_nested_artifacts {
+            matcher.deduplicate_nested_artifacts()?
+        } else {
+            matcher
+        };
+        find_leaks_in_binary_files_with_matcher(
+            binary_file_paths,
+            &matcher,
+            options.unpack_containers,
+        )?
+    } else {
+        run_scan_passes(binary_file_paths, options, passes, potential_leaks)?
+    };
+    log::debug!("Done!");
+    // Account for each range of bytes only once if requested
+    let leaks = if options.overlap_free {
+        let leak_count = leaks.len();
+        let leaks = remove_overlapping_leaks(leaks);
+        log::info!("{} overlapping leak(s) dropped", leak_count - leaks.len());
+        leaks
+    } else {
+        leaks
+    };
 
-    Ok(confirmed_leaks)
+    Ok(leaks)
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::compilation_database::{CompilationDatabase, FileListDatabase};
+/// Classifies the leaks found in the binary files, and drops the ones that
+/// shouldn't be reported
+fn classify_leaks(
+    options: &CpplumberOptions,
+    reporting: &ReportingContext,
+    leaks: BTreeSet<ConfirmedLeakWithUniqueLocation>,
+) -> Result<BTreeSet<ConfirmedLeakWithUniqueLocation>> {
+    let ReportingContext {
+        severity_classifier,
+        provenance_classifier,
+        ..
+    } = reporting;
 
-    use super::*;
+    // Note: Origins must be known to classify severities
+    let leaks = classify_confirmed_leak_origins(leaks, provenance_classifier);
+    let leaks = classify_confirmed_leaks(leaks, severity_classifier, options.min_severity);
+    // Tag strings revealing infrastructure (e.g., registry paths), so that
+    // they can be triaged first
+    let leaks = classify_confirmed_leak_infrastructure(leaks, &InfrastructureClassifier::new()?);
+    // Drop likely coincidental matches if requested
+    let leaks = if let Some(min_confidence) = options.min_confidence {
+        filter_confirmed_leaks_by_confidence(leaks, min_confidence)
+    } else {
+        leaks
+    };
+    // Tell apart leaks that stripping the binary would remove if requested
+    let leaks = if options.debug_info {
+        let leaks = classify_leak_visibility(leaks);
+        log::info!(
+            "{} leak(s) only found in debug information (removed when stripping the binary)",
+            leaks
+                .iter()
+                .filter(|leak| leak.visibility == Some(Visibility::DebugOnly))
+                .count()
+        );
+        leaks
+    } else {
+        leaks
+    };
+    // Show the bytes surrounding matches if requested
+    let leaks = if let Some(context_bytes) = options.context_bytes {
+        attach_match_context(leaks, context_bytes)
+    } else {
+        leaks
+    };
+
+    Ok(leaks)
+}
 
-    use serial_test::serial;
+/// Configuration used to classify and report leaks
+struct ReportingContext {
+    config: Option<Configuration>,
+    severity_classifier: SeverityClassifier,
+    provenance_classifier: ProvenanceClassifier,
+    output_sinks: OutputSinkRegistry,
+}
 
-    const FILE_LIST_PROJ_PATH: &str = "tests/data/main/file_list_proj";
+fn report_leaks(
+    binary_file_paths: &[PathBuf],
+    options: &CpplumberOptions,
+    reporting: &ReportingContext,
+    potential_leaks: Vec<PotentialLeak>,
+    incomplete_extraction: bool,
+) -> Result<()> {
+    let ReportingContext {
+        config,
+        output_sinks,
+        ..
+    } = reporting;
+    let baseline = options
+        .baseline
+        .as_deref()
+        .map(Baseline::load)
+        .transpose()?;
 
-    #[test]
-    #[serial]
-    fn extract_artifacts_from_source_files_file_list() {
-        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(FILE_LIST_PROJ_PATH);
-        let file_list_db = FileListDatabase::new(
-            &[root_dir_path.join("main.cc")],
-            vec![
-                "-DDEF_TEST".to_string(),
-                format!("-I{}", FILE_LIST_PROJ_PATH),
-            ],
+    let leaks = find_confirmed_leaks(binary_file_paths, options, reporting, potential_leaks)?;
+    // Bootstrap a suppressions file from the leaks if requested.
+    // Note: Paths aren't normalized yet, so that generated file patterns
+    // match artifacts' declarations.
+    if let Some(ref suppressions_file_path) = options.generate_suppressions {
+        let suppressions_file = File::create(suppressions_file_path)
+            .with_context(|| format!("Failed to create '{}'", suppressions_file_path.display()))?;
+        generate_suppressions(BufWriter::new(suppressions_file), &leaks)
+            .with_context(|| "Failed to generate suppressions")?;
+        log::info!(
+            "{} leak(s) written to suppressions file '{}'",
+            leaks.len(),
+            suppressions_file_path.display()
         );
-        let potential_leaks = extract_artifacts_from_source_files(
-            file_list_db
-                .get_all_compile_commands()
-                .expect("get_all_compile_commands failed"),
-            file_list_db.is_file_path_in_arguments(),
-            true,
-            false,
-            false,
-            0,
+        return Ok(());
+    }
+    // Make reports independent from where the project is built
+    let leaks = normalize_confirmed_leak_paths(
+        leaks,
+        &PathNormalizer::new(options.path_prefix_map.clone()),
+    );
+    // Results are partial if some source files have been skipped or if the
+    // scan has been interrupted
+    let partial = incomplete_extraction || is_cancelled();
+    PoolingStatistics::from_confirmed_leaks(leaks.iter().map(Deref::deref))
+        .log_summary(options.verbose);
+    emit_progress_event(&ProgressEvent::Finished {
+        findings: leaks.len(),
+    });
+
+    // Record all the leaks as known if requested
+    if let Some(ref baseline_file_path) = options.write_baseline {
+        write_json_report(
+            baseline_file_path,
+            &leaks,
+            output_sinks.get("json")?,
+            &ScanOutcome {
+                partial,
+                ..Default::default()
+            },
+            options,
         )
-        .expect("extract_artifacts_from_source_files failed");
-
-        let expected_string_literals = vec![
-            "included_string_literal",
-            "c_string",
-            "utf8_string",
-            "wide_string",
-            "utf16_string",
-            "utf32_string",
-            "raw_string",
-            "raw_utf8_string",
-            "wide_raw_string",
-            "raw_utf16_string",
-            "raw_utf32_string",
-            "def_test",
-            "concatenated_string",
-            r#"multiline\nstring"#,
-            r#"'\"\n\t\a\b|\220|\220|\351\246\231|\351\246\231|\360\237\230\202"#,
-            "MyStruct",
-            "",
-            "MyClass",
-            "",
-            r#"%s\n"#,
-            "preprocessor_string_literal",
-            r#"%s\n"#,
-            "preprocessor_string_literal",
-            r#"%s\n"#,
-        ];
-
-        // Check extracted string literals
-        assert!(potential_leaks.iter().enumerate().all(|(i, leak)| {
-            println!("{:?}", leak.data);
-            *leak.data == expected_string_literals[i]
-        }));
-        assert_eq!(expected_string_literals.len(), potential_leaks.len());
-    }
-
-    #[test]
-    #[serial]
-    fn extract_artifacts_with_minimum_leak_size() {
-        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(FILE_LIST_PROJ_PATH);
-        let file_list_db = FileListDatabase::new(
-            &[root_dir_path.join("main.cc")],
-            vec![
-                "-DDEF_TEST".to_string(),
-                format!("-I{}", FILE_LIST_PROJ_PATH),
-            ],
+        .with_context(|| "Failed to write baseline")?;
+        log::info!(
+            "{} leak(s) written to baseline '{}'",
+            leaks.len(),
+            baseline_file_path.display()
         );
-        let potential_leaks = extract_artifacts_from_source_files(
-            file_list_db
-                .get_all_compile_commands()
-                .expect("get_all_compile_commands failed"),
-            file_list_db.is_file_path_in_arguments(),
-            true,
-            false,
-            false,
-            4,
+        return Ok(());
+    }
+    // Only keep new leaks if a baseline is used
+    let leaks = if let Some(ref baseline) = baseline {
+        filter_baseline_leaks(leaks, baseline)
+    } else {
+        leaks
+    };
+
+    // Attest that the scan took place if requested
+    if let Some(ref attestation_file_path) = options.attestation {
+        // Note: Attestations are only written for single binaries (checked
+        // beforehand)
+        write_attestation(
+            attestation_file_path,
+            &binary_file_paths[0],
+            options,
+            &leaks,
+            partial,
         )
-        .expect("extract_artifacts_from_source_files failed");
-
-        // r#""%s\n""# should be removed
-        let expected_string_literals = vec![
-            // main.cc
-            "included_string_literal",
-            "c_string",
-            "utf8_string",
-            "wide_string",
-            "utf16_string",
-            "utf32_string",
-            "raw_string",
-            "raw_utf8_string",
-            "wide_raw_string",
-            "raw_utf16_string",
-            "raw_utf32_string",
-            "def_test",
-            "concatenated_string",
-            r#"multiline\nstring"#,
-            r#"'\"\n\t\a\b|\220|\220|\351\246\231|\351\246\231|\360\237\230\202"#,
-            "MyStruct",
-            "MyClass",
-            "preprocessor_string_literal",
-            r#"%s\n"#,
-            "preprocessor_string_literal",
-        ];
-
-        // Check extracted string literals
-        assert!(potential_leaks.iter().enumerate().all(|(i, leak)| {
-            println!("{:?}", leak.data);
-            *leak.data == expected_string_literals[i]
-        }));
-        assert_eq!(expected_string_literals.len(), potential_leaks.len());
-    }
-
-    #[cfg(windows)]
-    #[test]
-    #[serial]
-    fn find_leaks_in_binary_file_exe() {
-        // Gather potential leaks
-        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(FILE_LIST_PROJ_PATH);
-        let file_list_db = FileListDatabase::new(
-            &[root_dir_path.join("main.cc")],
-            vec![
-                "-DDEF_TEST".to_string(),
-                format!("-I{}", FILE_LIST_PROJ_PATH),
-            ],
-        );
-        let potential_leaks = extract_artifacts_from_source_files(
-            file_list_db
-                .get_all_compile_commands()
-                .expect("get_all_compile_commands failed"),
-            file_list_db.is_file_path_in_arguments(),
-            true,
-            false,
-            false,
-            0,
+        .with_context(|| "Failed to write attestation")?;
+    }
+
+    // Evaluate the policy's gates if one is defined, and record the failed
+    // ones into reports
+    let outcome = ScanOutcome {
+        partial,
+        policy_violations: config
+            .as_ref()
+            .and_then(|config| config.policy.as_ref())
+            .map(|policy| policy.evaluate(&leaks)),
+    };
+
+    // Write the full report into a file if requested
+    if let Some(ref report_file_path) = options.report_file {
+        write_json_report(
+            report_file_path,
+            &leaks,
+            output_sinks.get("json")?,
+            &outcome,
+            options,
         )
-        .expect("extract_artifacts_from_source_files failed");
-
-        // Look for leaks present in the compiled binary
-        let bin_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-            .join(FILE_LIST_PROJ_PATH)
-            .join("a.exe");
-
-        let confirmed_leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> =
-            find_leaks_in_binary_file(&bin_path, potential_leaks)
-                .expect("find_leaks_in_binary_file failed");
-
-        let expected_string_literals = vec![
-            // main.cc
-            "included_string_literal",
-            "MyStruct",
-            "MyStruct",
-            "MyStruct",
-            "MyClass",
-            "MyClass",
-            "MyClass",
-            "preprocessor_string_literal",
-            "preprocessor_string_literal",
-            r#"%s\n"#,
-        ];
-
-        // Check extracted string literals
-        assert!(confirmed_leaks.iter().enumerate().all(|(i, leak)| {
-            println!("{:?}", leak.data);
-            *leak.data == expected_string_literals[i]
-        }));
-        assert_eq!(confirmed_leaks.len(), expected_string_literals.len());
-    }
-
-    #[cfg(unix)]
-    #[test]
-    #[serial]
-    fn find_leaks_in_binary_file_elf() {
-        // Gather potential leaks
-        let root_dir_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(FILE_LIST_PROJ_PATH);
-        let file_list_db = FileListDatabase::new(
-            &[root_dir_path.join("main.cc")],
-            vec!["-DDEF_TEST".to_string()],
-        );
-        let potential_leaks = extract_artifacts_from_source_files(
-            file_list_db
-                .get_all_compile_commands()
-                .expect("get_all_compile_commands failed"),
-            file_list_db.is_file_path_in_arguments(),
-            true,
-            false,
-            false,
-            0,
+        .with_context(|| "Failed to write report file")?;
+    }
+
+    if leaks.is_empty() {
+        // Nothing leaked, alright!
+        return Ok(());
+    }
+    // Print the result to stdout, only summarizing large text reports to
+    // avoid flooding terminals and logs
+    if options.output_format() == "text"
+        && options.max_text_findings != 0
+        && leaks.len() > options.max_text_findings
+    {
+        let (findings_file, findings_file_path) = create_findings_file(options)?;
+        dump_confirmed_leaks(
+            BufWriter::new(findings_file),
+            &leaks,
+            output_sinks.get("text")?,
+            &outcome,
+            options.redact,
+            options.truncated_value_size,
+            options.ignore_multiple_locations,
         )
-        .expect("extract_artifacts_from_source_files failed");
-
-        // Look for leaks present in the compiled binary
-        let bin_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-            .join(FILE_LIST_PROJ_PATH)
-            .join("a.out");
-
-        let confirmed_leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> =
-            find_leaks_in_binary_file(&bin_path, potential_leaks)
-                .expect("find_leaks_in_binary_file failed");
-
-        let expected_string_literals = vec![
-            // main.cc
-            "included_string_literal",
-            "included_string_literal",
-            "MyStruct",
-            "MyStruct",
-            "MyStruct",
-            "MyStruct",
-            "MyStruct",
-            "MyStruct",
-            "MyStruct",
-            "MyStruct",
-            "MyStruct",
-            "MyStruct",
-            "MyStruct",
-            "MyStruct",
-            "MyClass",
-            "MyClass",
-            "MyClass",
-            "MyClass",
-            "MyClass",
-            "MyClass",
-            "MyClass",
-            "MyClass",
-            "MyClass",
-            "MyClass",
-            "MyClass",
-            "preprocessor_string_literal",
-            r#"%s\n"#,
-            "preprocessor_string_literal",
-        ];
-
-        // Check extracted string literals
-        assert!(confirmed_leaks.iter().enumerate().all(|(i, leak)| {
-            println!("{:?}", leak.data);
-            *leak.data == expected_string_literals[i]
-        }));
-        assert_eq!(confirmed_leaks.len(), expected_string_literals.len());
+        .with_context(|| "Failed to write findings file")?;
+        if outcome.partial {
+            println!("{}", PARTIAL_REPORT_NOTICE);
+        }
+        write_leak_summary(std::io::stdout(), &leaks, SUMMARIZED_FILE_COUNT)?;
+        println!("Full listing written to '{}'", findings_file_path.display());
+    } else {
+        dump_confirmed_leaks(
+            std::io::stdout(),
+            &leaks,
+            output_sinks.get(options.output_format())?,
+            &outcome,
+            options.redact,
+            options.truncated_value_size,
+            options.ignore_multiple_locations,
+        )?;
+    }
+
+    // Return an error to indicate that leaks were found or that the policy
+    // has been violated (useful for automation)
+    let failure = match outcome.policy_violations {
+        None => match count_failing_leaks(&leaks, &options.fail_on) {
+            0 => None,
+            _ if options.fail_on.is_empty() => Some("Leaks detected!".to_string()),
+            failing_leak_count => Some(format!(
+                "Leaks detected! ({} matching --fail-on)",
+                failing_leak_count
+            )),
+        },
+        Some(policy_violations) if policy_violations.is_empty() => None,
+        Some(policy_violations) => {
+            for violation in &policy_violations {
+                if options.warn_only {
+                    log::warn!("Policy violation: {}", violation);
+                } else {
+                    log::error!("Policy violation: {}", violation);
+                }
+            }
+            Some(format!(
+                "Policy violated ({} gate(s) failed)!",
+                policy_violations.len()
+            ))
+        }
+    };
+    match failure {
+        Some(message) if options.warn_only => {
+            log::warn!("{} (ignored because of --warn-only)", message);
+            Ok(())
+        }
+        Some(message) => Err(LeaksDetected(message).into()),
+        None => Ok(()),
     }
 }
@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+
+/// Sub-project of an aggregate scan
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestProject {
+    /// Name used to identify the project in reports
+    pub name: String,
+    /// Path to the project's compilation database
+    pub compile_commands: PathBuf,
+    /// Binaries built from the project
+    pub binaries: Vec<PathBuf>,
+    /// Path to the project's suppressions file
+    pub suppressions: Option<PathBuf>,
+}
+
+/// Lists the sub-projects of a monorepo, to scan them as a single job
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub projects: Vec<ManifestProject>,
+}
+
+/// Parses a manifest file. Relative paths are resolved against the directory
+/// containing the manifest.
+pub fn parse_manifest_file(manifest_file_path: &Path) -> Result<Manifest> {
+    // Read file
+    let mut manifest_data = vec![];
+    let mut manifest_file = File::open(manifest_file_path)
+        .with_context(|| format!("Failed to open manifest '{}'", manifest_file_path.display()))?;
+    manifest_file.read_to_end(&mut manifest_data)?;
+
+    // Parse YAML content
+    let mut manifest: Manifest = serde_yaml::from_slice(&manifest_data)
+        .with_context(|| format!("Malformed manifest '{}'", manifest_file_path.display()))?;
+
+    let mut names = HashSet::new();
+    if let Some(project) = manifest
+        .projects
+        .iter()
+        .find(|project| !names.insert(project.name.as_str()))
+    {
+        return Err(anyhow!(
+            "Project '{}' is listed more than once in the manifest",
+            project.name
+        ));
+    }
+
+    let base_directory = manifest_file_path.parent().unwrap_or_else(|| Path::new(""));
+    for project in &mut manifest.projects {
+        project.compile_commands = base_directory.join(&project.compile_commands);
+        for binary in &mut project.binaries {
+            *binary = base_directory.join(&binary);
+        }
+        if let Some(ref mut suppressions) = project.suppressions {
+            *suppressions = base_directory.join(&suppressions);
+        }
+    }
+
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST_PATH: &str = "tests/data/manifest/manifest.yml";
+
+    #[test]
+    fn parse_manifest_file_relative_paths() {
+        let file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(MANIFEST_PATH);
+        let base_directory = file_path.parent().unwrap();
+        let manifest = parse_manifest_file(&file_path).expect("Failed parsing manifest");
+
+        assert_eq!(
+            manifest,
+            Manifest {
+                projects: vec![
+                    ManifestProject {
+                        name: "engine".to_string(),
+                        compile_commands: base_directory.join("engine/compile_commands.json"),
+                        binaries: vec![
+                            base_directory.join("engine/engine.dll"),
+                            PathBuf::from("/opt/engine/engine_tool.exe")
+                        ],
+                        suppressions: Some(base_directory.join("engine/suppressions.yml")),
+                    },
+                    ManifestProject {
+                        name: "launcher".to_string(),
+                        compile_commands: base_directory.join("launcher/compile_commands.json"),
+                        binaries: vec![base_directory.join("launcher/launcher.exe")],
+                        suppressions: None,
+                    },
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn parse_manifest_file_duplicate_names() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let manifest_path = tmp_dir.path().join("manifest.yml");
+        std::fs::write(
+            &manifest_path,
+            "projects:\n\
+             - {name: a, compile_commands: a.json, binaries: [a.exe]}\n\
+             - {name: a, compile_commands: b.json, binaries: [b.exe]}\n",
+        )
+        .unwrap();
+
+        assert!(parse_manifest_file(&manifest_path).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+//! Binary content surrounding confirmed leaks, so that reviewers can judge
+//! whether a match is a real string or a coincidental byte sequence.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::information_leak::ConfirmedLeak;
+
+/// Bytes surrounding a match (including the match itself)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchContext {
+    /// Offset of the first byte of the context in the binary
+    pub offset: u64,
+    /// Hex dump of the context's bytes
+    pub hex: String,
+    /// Printable rendering of the context's bytes, non-printable bytes being
+    /// replaced with '.'
+    pub text: String,
+}
+
+impl MatchContext {
+    /// Extracts the `context_size` bytes surrounding the `size` bytes located
+    /// at `offset` in `data`, on each side
+    pub fn new(data: &[u8], offset: u64, size: usize, context_size: usize) -> Option<Self> {
+        let match_start = usize::try_from(offset).ok()?;
+        let match_end = match_start.checked_add(size)?;
+        if match_end > data.len() {
+            return None;
+        }
+        let start = match_start.saturating_sub(context_size);
+        let end = match_end.saturating_add(context_size).min(data.len());
+        let bytes = &data[start..end];
+
+        Some(Self {
+            offset: start as u64,
+            hex: bytes
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<Vec<_>>()
+                .join(" "),
+            text: bytes
+                .iter()
+                .map(|&byte| {
+                    if byte.is_ascii_graphic() || byte == b' ' {
+                        byte as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Attaches `context_size` bytes of surrounding binary content to each
+/// confirmed leak. Leaks whose binary cannot be read (e.g., files extracted
+/// from containers) are left without context.
+pub fn attach_match_context<SortedConfirmedLeak>(
+    confirmed_leaks: BTreeSet<SortedConfirmedLeak>,
+    context_size: usize,
+) -> BTreeSet<SortedConfirmedLeak>
+where
+    SortedConfirmedLeak: Into<ConfirmedLeak> + From<ConfirmedLeak> + Ord,
+{
+    // Group leaks per binary, so that binaries are loaded one at a time
+    let mut leaks_per_binary: HashMap<Arc<PathBuf>, Vec<ConfirmedLeak>> = HashMap::new();
+    for leak in confirmed_leaks.into_iter().map(Into::into) {
+        leaks_per_binary
+            .entry(leak.location.binary.file.clone())
+            .or_default()
+            .push(leak);
+    }
+
+    leaks_per_binary
+        .into_iter()
+        .flat_map(|(binary_file_path, leaks)| {
+            let binary_data = match std::fs::read(binary_file_path.as_ref()) {
+                Ok(binary_data) => binary_data,
+                Err(err) => {
+                    log::debug!(
+                        "Cannot read '{}', leaks won't have context: {}",
+                        binary_file_path.display(),
+                        err
+                    );
+                    vec![]
+                }
+            };
+
+            leaks.into_iter().map(move |mut leak| {
+                leak.context = MatchContext::new(
+                    &binary_data,
+                    leak.location.binary.offset,
+                    leak.size,
+                    context_size,
+                )
+                .map(Arc::new);
+                SortedConfirmedLeak::from(leak)
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_match_context() {
+        let data = b"\x00\x01key=secret\x00\xff";
+        let context = MatchContext::new(data, 6, 6, 4).expect("Missing context");
+        assert_eq!(
+            context,
+            MatchContext {
+                offset: 2,
+                hex: "6b 65 79 3d 73 65 63 72 65 74 00 ff".into(),
+                text: "key=secret..".into(),
+            }
+        );
+
+        // Context is clamped to the binary's bounds
+        let context = MatchContext::new(data, 0, 2, 4).expect("Missing context");
+        assert_eq!(context.offset, 0);
+        assert_eq!(context.text, "..key=");
+
+        // Matches outside of the binary (e.g., in another file) have no context
+        assert_eq!(MatchContext::new(data, 12, 4, 4), None);
+    }
+}
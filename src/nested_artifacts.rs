@@ -0,0 +1,129 @@
+//! Detection of artifacts whose byte patterns are contained in other
+//! artifacts' (e.g., `"abc"` in `"xabcx"`), which would otherwise be reported
+//! a second time wherever the containing artifact leaks.
+
+use std::collections::HashSet;
+
+use aho_corasick::AhoCorasick;
+use anyhow::Result;
+use rayon::prelude::*;
+
+use crate::information_leak::PotentialLeak;
+
+/// Links each artifact to the artifacts containing it
+pub struct NestedArtifacts {
+    /// For each artifact, the indices of the artifacts containing it, along
+    /// with the offset of its bytes in theirs
+    containers: Vec<Vec<(usize, usize)>>,
+}
+
+impl NestedArtifacts {
+    /// Finds the artifacts whose bytes are a strict substring of another
+    /// artifact's bytes. Artifacts with identical bytes (e.g., a value
+    /// declared at multiple locations) aren't linked.
+    pub fn new(potential_leaks: &[PotentialLeak]) -> Result<Self> {
+        let automaton = AhoCorasick::new(potential_leaks.iter().map(|leak| &leak.bytes))?;
+        let links: Vec<(usize, usize, usize)> = potential_leaks
+            .par_iter()
+            .enumerate()
+            .flat_map_iter(|(container_index, container)| {
+                automaton
+                    .find_overlapping_iter(&container.bytes)
+                    .filter(|pattern_match| pattern_match.len() < container.bytes.len())
+                    .map(move |pattern_match| {
+                        (
+                            pattern_match.pattern().as_usize(),
+                            container_index,
+                            pattern_match.start(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let mut containers = vec![vec![]; potential_leaks.len()];
+        for (nested_index, container_index, offset) in links {
+            containers[nested_index].push((container_index, offset));
+        }
+
+        Ok(Self { containers })
+    }
+
+    /// Returns the number of artifacts contained in another artifact
+    pub fn nested_artifact_count(&self) -> usize {
+        self.containers
+            .iter()
+            .filter(|containers| !containers.is_empty())
+            .count()
+    }
+
+    /// Returns the indices of the matches (given as artifact index and
+    /// offset) that are part of a match of a containing artifact, at the same
+    /// offset in the binary
+    pub fn redundant_matches(&self, matches: &[(usize, usize)]) -> HashSet<usize> {
+        let matched: HashSet<(usize, usize)> = matches.iter().copied().collect();
+
+        matches
+            .iter()
+            .enumerate()
+            .filter(|(_, (leak_index, offset))| {
+                self.containers[*leak_index]
+                    .iter()
+                    .any(|&(container_index, nested_offset)| {
+                        *offset >= nested_offset
+                            && matched.contains(&(container_index, offset - nested_offset))
+                    })
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::information_leak::{LeakedDataType, SourceLocation};
+
+    fn potential_leak(data: &str, line: u64) -> PotentialLeak {
+        PotentialLeak {
+            data_type: LeakedDataType::StringLiteral,
+            data: Arc::new(data.to_string()),
+            bytes: data.as_bytes().to_vec(),
+            code_unit_size: 1,
+            declaration_metadata: Arc::new(SourceLocation {
+                file: PathBuf::from("main.cc"),
+                line,
+            }),
+        }
+    }
+
+    #[test]
+    fn nested_artifacts_redundant_matches() {
+        let nested_artifacts = NestedArtifacts::new(&[
+            potential_leak("xabcx", 1),
+            potential_leak("abc", 2),
+            // Same value declared twice
+            potential_leak("xabcx", 3),
+            potential_leak("unrelated", 4),
+        ])
+        .expect("NestedArtifacts::new failed");
+        assert_eq!(nested_artifacts.nested_artifact_count(), 1);
+
+        let matches = [
+            (0, 0x100),
+            (2, 0x100),
+            // Inside the containing artifacts
+            (1, 0x101),
+            // On its own
+            (1, 0x200),
+            (3, 0x300),
+        ];
+        assert_eq!(
+            nested_artifacts.redundant_matches(&matches),
+            [2].into_iter().collect()
+        );
+    }
+}
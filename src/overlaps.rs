@@ -0,0 +1,99 @@
+//! Overlap-free accounting of matches, for binaries in which many related
+//! artifacts share bytes (e.g., string literals sharing prefixes).
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::information_leak::ConfirmedLeak;
+
+/// Only keeps the best (i.e., longest) match for each range of bytes of a
+/// binary, dropping the matches that overlap it.
+/// Matches of the same bytes (e.g., a value declared at multiple locations)
+/// are all kept.
+pub fn remove_overlapping_leaks<SortedConfirmedLeak>(
+    confirmed_leaks: BTreeSet<SortedConfirmedLeak>,
+) -> BTreeSet<SortedConfirmedLeak>
+where
+    SortedConfirmedLeak: Into<ConfirmedLeak> + From<ConfirmedLeak> + Ord,
+{
+    let mut confirmed_leaks: Vec<ConfirmedLeak> =
+        confirmed_leaks.into_iter().map(Into::into).collect();
+    // Longest matches first, then by location
+    confirmed_leaks.sort_by(|leak, other| {
+        other
+            .size
+            .cmp(&leak.size)
+            .then_with(|| leak.location.cmp(&other.location))
+    });
+
+    // Disjoint ranges of bytes kept so far, per binary (start -> end)
+    let mut kept_ranges: HashMap<Arc<PathBuf>, BTreeMap<u64, u64>> = HashMap::new();
+    confirmed_leaks
+        .into_iter()
+        .filter(|leak| {
+            let start = leak.location.binary.offset;
+            let end = start + leak.size as u64;
+            let ranges = kept_ranges
+                .entry(leak.location.binary.file.clone())
+                .or_default();
+            // As kept ranges are disjoint, only the last one starting before
+            // the end of this match can overlap it
+            match ranges.range(..end).next_back() {
+                Some((&kept_start, &kept_end)) if kept_start == start && kept_end == end => true,
+                Some((_, &kept_end)) if kept_end > start => false,
+                _ => {
+                    ranges.insert(start, end);
+                    true
+                }
+            }
+        })
+        .map(SortedConfirmedLeak::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::information_leak::{
+        BinaryLocation, ConfirmedLeakWithUniqueLocation, LeakedDataType, SourceLocation,
+    };
+
+    fn confirmed_leak(data: &str, line: u64, offset: u64) -> ConfirmedLeakWithUniqueLocation {
+        ConfirmedLeak::builder(
+            LeakedDataType::StringLiteral,
+            Arc::new(data.to_string()),
+            Arc::new(SourceLocation {
+                file: PathBuf::from("main.cc"),
+                line,
+            }),
+            BinaryLocation::new(Arc::new(PathBuf::from("a.out")), offset),
+        )
+        .build()
+        .into()
+    }
+
+    #[test]
+    fn remove_overlapping_leaks_keeps_longest() {
+        let leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> = [
+            // Literals sharing a prefix
+            confirmed_leak("https://", 1, 0x100),
+            confirmed_leak("https://example.com", 2, 0x100),
+            // Suffix of the longest literal
+            confirmed_leak("example.com", 3, 0x108),
+            // Same value declared twice
+            confirmed_leak("token", 4, 0x200),
+            confirmed_leak("token", 5, 0x200),
+            // Overlaps the end of the previous value
+            confirmed_leak("en_id", 6, 0x203),
+        ]
+        .into_iter()
+        .collect();
+
+        let kept_lines: Vec<u64> = remove_overlapping_leaks(leaks)
+            .iter()
+            .map(|leak| leak.location.source.line)
+            .collect();
+        assert_eq!(kept_lines, vec![2, 4, 5]);
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use crate::information_leak::{ConfirmedLeak, SourceLocation};
+
+/// Replaces a machine-specific path prefix (e.g., a build directory) with
+/// another one in reports. Parsed from "<old>=<new>".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PathPrefixMapping {
+    pub old: String,
+    pub new: String,
+}
+
+impl FromStr for PathPrefixMapping {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (old, new) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Invalid path prefix mapping '{}' (expected old=new)", s))?;
+
+        Ok(Self {
+            old: normalize_separators(old),
+            new: new.to_string(),
+        })
+    }
+}
+
+/// Normalizes the paths written into reports, so that reports generated from
+/// the same inputs on different machines are identical
+#[derive(Default)]
+pub struct PathNormalizer {
+    mappings: Vec<PathPrefixMapping>,
+}
+
+impl PathNormalizer {
+    pub fn new(mappings: Vec<PathPrefixMapping>) -> Self {
+        Self { mappings }
+    }
+
+    /// Uses forward slashes as separators and applies the first matching
+    /// prefix mapping, trying the last specified mappings first
+    pub fn normalize(&self, path: &Path) -> PathBuf {
+        let path = normalize_separators(&path.to_string_lossy());
+        let mapped_path = self.mappings.iter().rev().find_map(|mapping| {
+            path.strip_prefix(&mapping.old)
+                .map(|suffix| format!("{}{}", mapping.new, suffix))
+        });
+
+        PathBuf::from(mapped_path.unwrap_or(path))
+    }
+}
+
+fn normalize_separators(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+/// Normalizes the source and binary paths of the given leaks
+pub fn normalize_confirmed_leak_paths<SortedConfirmedLeak>(
+    confirmed_leaks: BTreeSet<SortedConfirmedLeak>,
+    normalizer: &PathNormalizer,
+) -> BTreeSet<SortedConfirmedLeak>
+where
+    SortedConfirmedLeak: Into<ConfirmedLeak> + From<ConfirmedLeak> + Ord,
+{
+    // Keep source locations and binary paths shared between leaks
+    let mut source_locations: BTreeMap<Arc<SourceLocation>, Arc<SourceLocation>> = BTreeMap::new();
+    let mut binary_paths: BTreeMap<Arc<PathBuf>, Arc<PathBuf>> = BTreeMap::new();
+
+    confirmed_leaks
+        .into_iter()
+        .map(Into::into)
+        .map(|mut leak: ConfirmedLeak| {
+            leak.location.source = source_locations
+                .entry(leak.location.source.clone())
+                .or_insert_with_key(|location| {
+                    Arc::new(SourceLocation {
+                        file: normalizer.normalize(&location.file),
+                        line: location.line,
+                    })
+                })
+                .clone();
+            leak.location.binary.file = binary_paths
+                .entry(leak.location.binary.file.clone())
+                .or_insert_with_key(|path| Arc::new(normalizer.normalize(path)))
+                .clone();
+
+            SortedConfirmedLeak::from(leak)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalizer(mappings: &[&str]) -> PathNormalizer {
+        PathNormalizer::new(
+            mappings
+                .iter()
+                .map(|mapping| mapping.parse().unwrap())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn parse_path_prefix_mapping() {
+        assert_eq!(
+            "C:\\build=/src".parse::<PathPrefixMapping>().unwrap(),
+            PathPrefixMapping {
+                old: "C:/build".to_string(),
+                new: "/src".to_string()
+            }
+        );
+        assert!("/build".parse::<PathPrefixMapping>().is_err());
+    }
+
+    #[test]
+    fn normalize_paths() {
+        let normalizer = normalizer(&["/home/ci=.", "/home/ci/project=project"]);
+
+        assert_eq!(
+            normalizer.normalize(Path::new("/home/ci/project/main.c")),
+            PathBuf::from("project/main.c")
+        );
+        assert_eq!(
+            normalizer.normalize(Path::new("/home/ci/other/main.c")),
+            PathBuf::from("./other/main.c")
+        );
+        assert_eq!(
+            normalizer.normalize(Path::new("src\\main.c")),
+            PathBuf::from("src/main.c")
+        );
+    }
+}
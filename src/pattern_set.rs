@@ -0,0 +1,108 @@
+//! Export of the byte patterns looked for in binaries, so that they can be fed
+//! to scanners that can't run cpplumber (e.g., network DLP, artifact
+//! repositories' scanners).
+
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::information_leak::{LeakedDataType, PotentialLeak, SourceLocation};
+
+const PATTERN_SET_FORMAT_VERSION: u32 = 1;
+
+/// Encoding of a byte pattern, deduced from the size of its code units
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternEncoding {
+    /// Single-byte code units (e.g., ASCII or UTF-8 strings, raw bytes)
+    Narrow,
+    Utf16le,
+    Utf32le,
+}
+
+impl PatternEncoding {
+    fn from_code_unit_size(code_unit_size: usize) -> Self {
+        match code_unit_size {
+            2 => PatternEncoding::Utf16le,
+            4 => PatternEncoding::Utf32le,
+            _ => PatternEncoding::Narrow,
+        }
+    }
+}
+
+/// Byte pattern, along with the artifact it's been generated from
+#[derive(Debug, Serialize)]
+pub struct ExportedPattern {
+    /// Bytes to look for, as a hex string
+    pub pattern: String,
+    pub encoding: PatternEncoding,
+    pub data_type: LeakedDataType,
+    /// Artifact, as represented in the source code
+    pub data: Arc<String>,
+    /// Where the artifact is declared in the source code
+    pub source: Arc<SourceLocation>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PatternSet {
+    pub format: u32,
+    pub patterns: Vec<ExportedPattern>,
+}
+
+/// Generates the set of byte patterns looked for when scanning binaries for
+/// the given artifacts
+pub fn generate_pattern_set(potential_leaks: &[PotentialLeak]) -> PatternSet {
+    let patterns = potential_leaks
+        .iter()
+        .filter(|leak| !leak.bytes.is_empty())
+        .map(|leak| ExportedPattern {
+            pattern: leak
+                .bytes
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect(),
+            encoding: PatternEncoding::from_code_unit_size(leak.code_unit_size),
+            data_type: leak.data_type,
+            data: leak.data.clone(),
+            source: leak.declaration_metadata.clone(),
+        })
+        .collect();
+
+    PatternSet {
+        format: PATTERN_SET_FORMAT_VERSION,
+        patterns,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn generate_pattern_set_with_encodings() {
+        let narrow_leak = PotentialLeak::from_string_literal(
+            "\"key\"",
+            SourceLocation {
+                file: PathBuf::from("main.cc"),
+                line: 3,
+            },
+            None,
+        )
+        .unwrap();
+        let wide_leak = narrow_leak.utf16_variant().unwrap();
+
+        let pattern_set = generate_pattern_set(&[narrow_leak, wide_leak]);
+        let pattern_set = serde_json::to_value(&pattern_set).unwrap();
+        assert_eq!(pattern_set["format"], PATTERN_SET_FORMAT_VERSION);
+        assert_eq!(pattern_set["patterns"][0]["pattern"], "6b6579");
+        assert_eq!(pattern_set["patterns"][0]["encoding"], "narrow");
+        assert_eq!(pattern_set["patterns"][0]["data"], "key");
+        assert_eq!(pattern_set["patterns"][0]["data_type"], "StringLiteral");
+        assert_eq!(pattern_set["patterns"][0]["source"]["file"], "main.cc");
+        assert_eq!(pattern_set["patterns"][0]["source"]["line"], 3);
+        assert_eq!(pattern_set["patterns"][1]["pattern"], "6b0065007900");
+        assert_eq!(pattern_set["patterns"][1]["encoding"], "utf16le");
+    }
+}
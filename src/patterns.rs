@@ -0,0 +1,187 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use cpplumber::information_leak::{PotentialLeak, SourceLocation, WideCharMode};
+use cpplumber::target_profile::TargetProfile;
+
+use crate::cli::PatternsOptions;
+
+/// Number of bytes displayed per line in hex dumps
+const HEX_DUMP_LINE_SIZE: usize = 16;
+
+/// Prints the byte patterns generated for user-provided string literals, with
+/// the same encoding settings as the ones used when scanning binaries
+pub fn preview_patterns(options: &PatternsOptions) -> Result<()> {
+    write_patterns(&mut std::io::stdout(), options)
+}
+
+fn write_patterns<W: Write>(writer: &mut W, options: &PatternsOptions) -> Result<()> {
+    for literal in &options.literals {
+        let potential_leak = parse_literal(
+            literal,
+            options
+                .wide_char_mode
+                .or_else(|| options.profile.map(TargetProfile::wide_char_mode)),
+        )?;
+
+        let mut patterns = vec![("pattern".to_string(), potential_leak.bytes.clone())];
+        if options.utf16_variants {
+            if let Some(variant) = potential_leak.utf16_variant() {
+                patterns.push(("UTF-16LE variant".to_string(), variant.bytes));
+            }
+        }
+        if options.all_encodings {
+            for variant in potential_leak.encoding_variants() {
+                patterns.push((
+                    format!("{}-byte code unit variant", variant.code_unit_size),
+                    variant.bytes,
+                ));
+            }
+        }
+        if let Some(minimum_size) = options.minimum_partial_leak_size {
+            // Same prefixes as the ones looked for when scanning
+            let minimum_size = minimum_size.max(1);
+            if potential_leak.bytes.len() > minimum_size {
+                patterns.push((
+                    format!("partial leak prefix ({} bytes)", minimum_size),
+                    potential_leak.bytes[..minimum_size].to_vec(),
+                ));
+            }
+        }
+
+        writeln!(
+            writer,
+            "{} ({}-byte code units)",
+            literal, potential_leak.code_unit_size
+        )?;
+        for (description, bytes) in patterns {
+            writeln!(writer, "  {}, {} bytes:", description, bytes.len())?;
+            write_hex_dump(writer, &bytes)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses a string literal as written in the source code. Values without
+/// quotes are treated as ordinary string literals.
+fn parse_literal(literal: &str, wide_char_mode: Option<WideCharMode>) -> Result<PotentialLeak> {
+    let literal = if literal.ends_with('"') {
+        literal.to_string()
+    } else {
+        format!("\"{}\"", literal)
+    };
+
+    PotentialLeak::from_string_literal(
+        &literal,
+        SourceLocation {
+            file: PathBuf::default(),
+            line: 0,
+        },
+        wide_char_mode,
+    )
+    .with_context(|| format!("Invalid string literal: {}", literal))
+}
+
+/// Writes the given bytes as a hex dump, with their ASCII representation
+fn write_hex_dump<W: Write>(writer: &mut W, bytes: &[u8]) -> Result<()> {
+    for (line_index, line) in bytes.chunks(HEX_DUMP_LINE_SIZE).enumerate() {
+        let hex: Vec<String> = line.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let ascii: String = line
+            .iter()
+            .map(|byte| {
+                if byte.is_ascii_graphic() || *byte == b' ' {
+                    *byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        writeln!(
+            writer,
+            "    {:08x}  {:<width$}  |{}|",
+            line_index * HEX_DUMP_LINE_SIZE,
+            hex.join(" "),
+            ascii,
+            width = HEX_DUMP_LINE_SIZE * 3 - 1
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use structopt::StructOpt;
+
+    use super::*;
+
+    fn preview(arguments: &[&str]) -> String {
+        let options = PatternsOptions::from_iter(
+            std::iter::once("patterns").chain(arguments.iter().copied()),
+        );
+        let mut output = vec![];
+        write_patterns(&mut output, &options).expect("write_patterns failed");
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn preview_narrow_literal() {
+        assert_eq!(
+            preview(&["secret\\n"]),
+            concat!(
+                "secret\\n (1-byte code units)\n",
+                "  pattern, 7 bytes:\n",
+                "    00000000  73 65 63 72 65 74 0a                             |secret.|\n",
+            )
+        );
+    }
+
+    #[test]
+    fn preview_variants_and_prefixes() {
+        let output = preview(&["--utf16-variants", "--partial-leaks", "4", "\"key\""]);
+        assert_eq!(
+            output,
+            concat!(
+                "\"key\" (1-byte code units)\n",
+                "  pattern, 3 bytes:\n",
+                "    00000000  6b 65 79                                         |key|\n",
+                "  UTF-16LE variant, 6 bytes:\n",
+                "    00000000  6b 00 65 00 79 00                                |k.e.y.|\n",
+            )
+        );
+    }
+
+    #[test]
+    fn preview_utf16_literal() {
+        let output = preview(&["u\"k\""]);
+        assert!(output.contains("(2-byte code units)"));
+        assert!(output.contains("6b 00"));
+    }
+
+    #[test]
+    fn preview_wide_literal_in_all_encodings() {
+        let output = preview(&["--wide-char-mode", "windows", "--all-encodings", "L\"k\""]);
+        assert_eq!(
+            output,
+            concat!(
+                "L\"k\" (2-byte code units)\n",
+                "  pattern, 2 bytes:\n",
+                "    00000000  6b 00                                            |k.|\n",
+                "  1-byte code unit variant, 1 bytes:\n",
+                "    00000000  6b                                               |k|\n",
+                "  4-byte code unit variant, 4 bytes:\n",
+                "    00000000  6b 00 00 00                                      |k...|\n",
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_literal() {
+        let options = PatternsOptions::from_iter(["patterns", "x\"y\""]);
+        assert!(write_patterns(&mut vec![], &options).is_err());
+    }
+}
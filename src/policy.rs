@@ -0,0 +1,424 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use serde::de::value::{Error as DeserializerError, StrDeserializer};
+use serde::{Deserialize, Serialize};
+
+use crate::information_leak::{ConfirmedLeak, LeakedDataType};
+use crate::provenance::Origin;
+use crate::reporting::display_leaked_data_type;
+use crate::severity::Severity;
+
+/// Machine-checkable gates evaluated against the confirmed leaks. When a
+/// policy is defined, it replaces the default behavior of failing as soon as
+/// a leak is found.
+#[derive(Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct Policy {
+    /// Maximum number of leaks allowed
+    pub max_total: Option<usize>,
+    /// Maximum number of leaks allowed per type of data
+    #[serde(default)]
+    pub max_per_type: BTreeMap<LeakedDataType, usize>,
+    /// Types of data that must not leak at all
+    #[serde(default)]
+    pub forbidden_types: BTreeSet<LeakedDataType>,
+    /// Maximum number of leaks allowed per severity (e.g., `critical: 0`).
+    /// Leaks that haven't been classified aren't counted.
+    #[serde(default)]
+    pub max_per_severity: BTreeMap<Severity, usize>,
+    /// Set if leaks from third-party code aren't counted by the gates
+    #[serde(default)]
+    pub ignore_third_party: bool,
+}
+
+/// Gate of a `Policy` that failed
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "gate", rename_all = "snake_case")]
+pub enum PolicyViolation {
+    MaxTotal {
+        max: usize,
+        found: usize,
+    },
+    MaxPerType {
+        data_type: LeakedDataType,
+        max: usize,
+        found: usize,
+    },
+    ForbiddenType {
+        data_type: LeakedDataType,
+        found: usize,
+    },
+    MaxPerSeverity {
+        severity: Severity,
+        max: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyViolation::MaxTotal { max, found } => {
+                write!(f, "{} leak(s) found, at most {} allowed", found, max)
+            }
+            PolicyViolation::MaxPerType {
+                data_type,
+                max,
+                found,
+            } => write!(
+                f,
+                "{} leak(s) of type '{}' found, at most {} allowed",
+                found,
+                display_leaked_data_type(*data_type),
+                max
+            ),
+            PolicyViolation::ForbiddenType { data_type, found } => write!(
+                f,
+                "{} leak(s) of forbidden type '{}' found",
+                found,
+                display_leaked_data_type(*data_type)
+            ),
+            PolicyViolation::MaxPerSeverity {
+                severity,
+                max,
+                found,
+            } => write!(
+                f,
+                "{} leak(s) of severity '{}' found, at most {} allowed",
+                found, severity, max
+            ),
+        }
+    }
+}
+
+impl Policy {
+    /// Evaluates the policy's gates and returns the ones that failed
+    pub fn evaluate<SortedConfirmedLeak>(
+        &self,
+        confirmed_leaks: &BTreeSet<SortedConfirmedLeak>,
+    ) -> Vec<PolicyViolation>
+    where
+        SortedConfirmedLeak: Deref<Target = ConfirmedLeak>,
+    {
+        let counted_leaks: Vec<&ConfirmedLeak> = confirmed_leaks
+            .iter()
+            .map(Deref::deref)
+            .filter(|leak| !(self.ignore_third_party && leak.origin == Some(Origin::ThirdParty)))
+            .collect();
+        let leak_count_per_type = counted_leaks
+            .iter()
+            .fold(BTreeMap::new(), |mut accum, leak| {
+                *accum.entry(leak.data_type).or_insert(0) += 1;
+                accum
+            });
+        let leak_count_per_severity = counted_leaks.iter().filter_map(|leak| leak.severity).fold(
+            BTreeMap::new(),
+            |mut accum, severity| {
+                *accum.entry(severity).or_insert(0) += 1;
+                accum
+            },
+        );
+
+        let mut violations = vec![];
+        if let Some(max_total) = self.max_total {
+            if counted_leaks.len() > max_total {
+                violations.push(PolicyViolation::MaxTotal {
+                    max: max_total,
+                    found: counted_leaks.len(),
+                });
+            }
+        }
+        for (data_type, max) in &self.max_per_type {
+            let found = leak_count_per_type.get(data_type).copied().unwrap_or(0);
+            if found > *max {
+                violations.push(PolicyViolation::MaxPerType {
+                    data_type: *data_type,
+                    max: *max,
+                    found,
+                });
+            }
+        }
+        for data_type in &self.forbidden_types {
+            let found = leak_count_per_type.get(data_type).copied().unwrap_or(0);
+            if found > 0 {
+                violations.push(PolicyViolation::ForbiddenType {
+                    data_type: *data_type,
+                    found,
+                });
+            }
+        }
+        for (severity, max) in &self.max_per_severity {
+            let found = leak_count_per_severity.get(severity).copied().unwrap_or(0);
+            if found > *max {
+                violations.push(PolicyViolation::MaxPerSeverity {
+                    severity: *severity,
+                    max: *max,
+                    found,
+                });
+            }
+        }
+
+        violations
+    }
+}
+
+/// Prefix of the criteria selecting leaks by confidence (e.g., "confidence:70")
+const CONFIDENCE_CRITERION_PREFIX: &str = "confidence:";
+
+/// Criterion selecting the leaks that fail the run when no policy is defined.
+/// Parsed from a severity (e.g., "high", selecting leaks at least that
+/// severe), a type of data (e.g., "string_literal"), an origin (e.g.,
+/// "first_party") or a minimum confidence score (e.g., "confidence:70").
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum FailOn {
+    Severity(Severity),
+    DataType(LeakedDataType),
+    Origin(Origin),
+    Confidence(u8),
+}
+
+impl FromStr for FailOn {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Ok(severity) = s.parse() {
+            return Ok(FailOn::Severity(severity));
+        }
+        if let Ok(origin) = Origin::deserialize(StrDeserializer::<DeserializerError>::new(s)) {
+            return Ok(FailOn::Origin(origin));
+        }
+        if let Some(confidence) = s.strip_prefix(CONFIDENCE_CRITERION_PREFIX) {
+            return confidence
+                .parse()
+                .map(FailOn::Confidence)
+                .map_err(|_| anyhow!("Invalid confidence score '{}'", confidence));
+        }
+
+        LeakedDataType::deserialize(StrDeserializer::<DeserializerError>::new(s))
+            .map(FailOn::DataType)
+            .map_err(|_| {
+                anyhow!(
+                    "'{}' is neither a severity, a type of data, an origin nor a confidence criterion",
+                    s
+                )
+            })
+    }
+}
+
+impl FailOn {
+    /// Indicates if the given leak is selected by the criterion
+    pub fn matches(&self, leak: &ConfirmedLeak) -> bool {
+        match self {
+            FailOn::Severity(minimum_severity) => {
+                matches!(leak.severity, Some(severity) if severity >= *minimum_severity)
+            }
+            FailOn::DataType(data_type) => leak.data_type == *data_type,
+            FailOn::Origin(origin) => leak.origin == Some(*origin),
+            FailOn::Confidence(minimum_confidence) => {
+                matches!(leak.confidence, Some(confidence) if confidence >= *minimum_confidence)
+            }
+        }
+    }
+}
+
+/// Returns the number of leaks that fail the run, i.e., the leaks matching
+/// any of the given criteria, or all the leaks if there's none
+pub fn count_failing_leaks<SortedConfirmedLeak>(
+    confirmed_leaks: &BTreeSet<SortedConfirmedLeak>,
+    fail_on: &[FailOn],
+) -> usize
+where
+    SortedConfirmedLeak: Deref<Target = ConfirmedLeak>,
+{
+    if fail_on.is_empty() {
+        return confirmed_leaks.len();
+    }
+
+    confirmed_leaks
+        .iter()
+        .filter(|leak| fail_on.iter().any(|criterion| criterion.matches(leak)))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, sync::Arc};
+
+    use crate::information_leak::{
+        BinaryLocation, ConfirmedLeakWithUniqueLocation, SourceLocation,
+    };
+
+    use super::*;
+
+    fn confirmed_leak(data_type: LeakedDataType, offset: u64) -> ConfirmedLeakWithUniqueLocation {
+        ConfirmedLeak::builder(
+            data_type,
+            Arc::new("leak".to_string()),
+            Arc::new(SourceLocation {
+                file: PathBuf::from("file.cc"),
+                line: 1,
+            }),
+            BinaryLocation::new(Arc::new(PathBuf::from("a.out")), offset),
+        )
+        .build()
+        .into()
+    }
+
+    #[test]
+    fn parse_fail_on_criteria() {
+        assert_eq!(
+            "high".parse::<FailOn>().unwrap(),
+            FailOn::Severity(Severity::High)
+        );
+        assert_eq!(
+            "string_literal".parse::<FailOn>().unwrap(),
+            FailOn::DataType(LeakedDataType::StringLiteral)
+        );
+        assert_eq!(
+            "confidence:70".parse::<FailOn>().unwrap(),
+            FailOn::Confidence(70)
+        );
+        assert_eq!(
+            "third_party".parse::<FailOn>().unwrap(),
+            FailOn::Origin(Origin::ThirdParty)
+        );
+        assert!("confidence:high".parse::<FailOn>().is_err());
+        assert!("unknown".parse::<FailOn>().is_err());
+    }
+
+    #[test]
+    fn count_leaks_failing_the_run() {
+        let classified_leak = |data_type, offset, severity| {
+            let mut leak = ConfirmedLeak::clone(&confirmed_leak(data_type, offset));
+            leak.severity = Some(severity);
+            ConfirmedLeakWithUniqueLocation::from(leak)
+        };
+        let leaks = BTreeSet::from([
+            classified_leak(LeakedDataType::StringLiteral, 0, Severity::High),
+            classified_leak(LeakedDataType::StructName, 1, Severity::Low),
+        ]);
+
+        assert_eq!(count_failing_leaks(&leaks, &[]), 2);
+        assert_eq!(
+            count_failing_leaks(&leaks, &[FailOn::Severity(Severity::Medium)]),
+            1
+        );
+        assert_eq!(
+            count_failing_leaks(
+                &leaks,
+                &[
+                    FailOn::Severity(Severity::Critical),
+                    FailOn::DataType(LeakedDataType::StructName)
+                ]
+            ),
+            1
+        );
+    }
+
+    #[test]
+    fn evaluate_empty_policy() {
+        let leaks = BTreeSet::from([confirmed_leak(LeakedDataType::StringLiteral, 0)]);
+        assert!(Policy::default().evaluate(&leaks).is_empty());
+    }
+
+    #[test]
+    fn evaluate_policy_ignoring_third_party() {
+        let leak_with_origin = |offset, origin| {
+            let mut leak =
+                ConfirmedLeak::clone(&confirmed_leak(LeakedDataType::StringLiteral, offset));
+            leak.origin = Some(origin);
+            ConfirmedLeakWithUniqueLocation::from(leak)
+        };
+        let leaks = BTreeSet::from([
+            leak_with_origin(0, Origin::FirstParty),
+            leak_with_origin(1, Origin::ThirdParty),
+        ]);
+        let policy = Policy {
+            max_total: Some(1),
+            ignore_third_party: true,
+            ..Default::default()
+        };
+
+        assert!(policy.evaluate(&leaks).is_empty());
+        assert_eq!(
+            count_failing_leaks(&leaks, &[FailOn::Origin(Origin::FirstParty)]),
+            1
+        );
+    }
+
+    #[test]
+    fn evaluate_policy_gates() {
+        let policy: Policy = serde_yaml::from_str(
+            r#"
+max_total: 2
+max_per_type:
+  string_literal: 1
+  class_name: 1
+forbidden_types: [struct_name]
+"#,
+        )
+        .expect("Failed to parse policy");
+        let leaks = BTreeSet::from([
+            confirmed_leak(LeakedDataType::StringLiteral, 0),
+            confirmed_leak(LeakedDataType::StringLiteral, 1),
+            confirmed_leak(LeakedDataType::StructName, 2),
+        ]);
+
+        assert_eq!(
+            policy.evaluate(&leaks),
+            vec![
+                PolicyViolation::MaxTotal { max: 2, found: 3 },
+                PolicyViolation::MaxPerType {
+                    data_type: LeakedDataType::StringLiteral,
+                    max: 1,
+                    found: 2
+                },
+                PolicyViolation::ForbiddenType {
+                    data_type: LeakedDataType::StructName,
+                    found: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn evaluate_policy_severity_gates() {
+        let policy: Policy = serde_yaml::from_str(
+            r#"
+max_per_severity:
+  critical: 0
+  high: 1
+"#,
+        )
+        .expect("Failed to parse policy");
+        let leak_with_severity = |offset, severity| {
+            let mut leak =
+                ConfirmedLeak::clone(&confirmed_leak(LeakedDataType::StringLiteral, offset));
+            leak.severity = severity;
+            ConfirmedLeakWithUniqueLocation::from(leak)
+        };
+        let leaks = BTreeSet::from([
+            leak_with_severity(0, Some(Severity::Critical)),
+            leak_with_severity(1, Some(Severity::High)),
+            leak_with_severity(2, None),
+        ]);
+
+        let violations = policy.evaluate(&leaks);
+        assert_eq!(
+            violations,
+            vec![PolicyViolation::MaxPerSeverity {
+                severity: Severity::Critical,
+                max: 0,
+                found: 1
+            }]
+        );
+        assert_eq!(
+            serde_json::to_value(&violations[0]).unwrap(),
+            serde_json::json!({"gate": "max_per_severity", "severity": "critical", "max": 0, "found": 1})
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Machine-readable progress events, written as JSON lines on a side channel
+//! (e.g., so that CI dashboards can display the status of long scans).
+
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use serde::Serialize;
+
+/// Progress of a scan
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent<'a> {
+    /// Extraction of artifacts from the given number of source files started
+    ExtractionStarted { files: usize },
+    /// A source file has been parsed, or its artifacts have been loaded from
+    /// the cache
+    FileParsed {
+        file: &'a Path,
+        files_parsed: usize,
+        files: usize,
+        /// Number of artifacts extracted so far
+        artifacts: usize,
+    },
+    /// Looking for artifacts in a binary file started
+    ScanStarted {
+        file: &'a Path,
+        bytes: usize,
+        artifacts: usize,
+    },
+    /// Part of a binary file has been scanned
+    ScanProgress {
+        file: &'a Path,
+        bytes_scanned: usize,
+        bytes: usize,
+        /// Number of leaks found in the file so far
+        findings: usize,
+    },
+    /// The scan is over
+    Finished { findings: usize },
+}
+
+#[derive(Serialize)]
+struct TimedProgressEvent<'e, 'a> {
+    /// Time elapsed since progress events have been enabled
+    elapsed_ms: u128,
+    #[serde(flatten)]
+    event: &'e ProgressEvent<'a>,
+}
+
+struct ProgressChannel {
+    writer: Box<dyn Write + Send>,
+    start: Instant,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static CHANNEL: Mutex<Option<ProgressChannel>> = Mutex::new(None);
+
+/// Writes progress events into `writer` from now on
+pub fn enable_progress_events(writer: Box<dyn Write + Send>) {
+    if let Ok(mut channel) = CHANNEL.lock() {
+        *channel = Some(ProgressChannel {
+            writer,
+            start: Instant::now(),
+        });
+        ENABLED.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Returns `true` if progress events are written somewhere, so that callers
+/// can avoid computing them otherwise
+pub fn progress_events_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Writes the given event as a JSON line, if progress events are enabled.
+/// Progress is best-effort: failures to write events are only logged.
+pub fn emit_progress_event(event: &ProgressEvent) {
+    if !progress_events_enabled() {
+        return;
+    }
+    let mut channel = match CHANNEL.lock() {
+        Ok(channel) => channel,
+        Err(_) => return,
+    };
+    if let Some(ref mut channel) = *channel {
+        let timed_event = TimedProgressEvent {
+            elapsed_ms: channel.start.elapsed().as_millis(),
+            event,
+        };
+        let result = serde_json::to_writer(&mut channel.writer, &timed_event)
+            .map_err(std::io::Error::from)
+            .and_then(|_| writeln!(channel.writer))
+            .and_then(|_| channel.writer.flush());
+        if let Err(err) = result {
+            log::debug!("Failed to write progress event: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    /// Writer whose content can be read while it's owned by the channel
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn emit_json_lines() {
+        let buffer = SharedBuffer::default();
+        enable_progress_events(Box::new(buffer.clone()));
+        assert!(progress_events_enabled());
+
+        emit_progress_event(&ProgressEvent::FileParsed {
+            file: Path::new("main.c"),
+            files_parsed: 1,
+            files: 2,
+            artifacts: 3,
+        });
+
+        // Note: Other tests may emit events concurrently
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let event: serde_json::Value = output
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .find(|event| event["event"] == "file_parsed" && event["file"] == "main.c")
+            .expect("Event not written");
+        assert_eq!(event["files_parsed"], 1);
+        assert_eq!(event["files"], 2);
+        assert_eq!(event["artifacts"], 3);
+        assert!(event["elapsed_ms"].is_u64());
+    }
+}
@@ -0,0 +1,220 @@
+//! Classification of artifacts as first-party or third-party code, based on
+//! the paths of the files declaring them.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Component, Path};
+
+use anyhow::{anyhow, Result};
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+
+use crate::information_leak::ConfirmedLeak;
+
+/// Paths of third-party code used when none are configured: vendored
+/// dependencies, CMake's `FetchContent`, Conan and vcpkg caches
+const DEFAULT_THIRD_PARTY_PATHS: [&str; 8] = [
+    "vendor",
+    "third_party",
+    "thirdparty",
+    "3rdparty",
+    "_deps",
+    ".conan/data",
+    ".conan2/p",
+    "vcpkg_installed",
+];
+
+/// Origin of the code declaring an artifact
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Origin {
+    FirstParty,
+    ThirdParty,
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Origin::FirstParty => "first-party",
+            Origin::ThirdParty => "third-party",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// `provenance` section of the configuration file
+#[derive(Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct ProvenanceConfiguration {
+    /// Paths of third-party code, replacing the default ones. Relative paths
+    /// (e.g., "vendor/") match anywhere in a file's path, absolute paths
+    /// match the beginning of a file's path, and glob expressions match whole
+    /// paths.
+    pub third_party: Option<Vec<String>>,
+}
+
+enum PathMatcher {
+    /// Sequence of path components, matched anywhere in paths
+    Components(Vec<String>),
+    /// Absolute path, matched at the beginning of paths
+    Prefix(String),
+    Glob(Pattern),
+}
+
+/// Determines the origin of artifacts from the paths of their declarations
+pub struct ProvenanceClassifier {
+    third_party_paths: Vec<PathMatcher>,
+}
+
+impl ProvenanceClassifier {
+    /// Creates a classifier with the configured third-party paths, or with
+    /// the default ones if none are configured
+    pub fn new(config: Option<&ProvenanceConfiguration>) -> Result<Self> {
+        let third_party_paths = match config.and_then(|config| config.third_party.as_ref()) {
+            Some(paths) => paths
+                .iter()
+                .map(|path| parse_path_matcher(path))
+                .collect::<Result<_>>()?,
+            None => DEFAULT_THIRD_PARTY_PATHS
+                .iter()
+                .map(|path| parse_path_matcher(path))
+                .collect::<Result<_>>()?,
+        };
+
+        Ok(Self { third_party_paths })
+    }
+
+    /// Returns the origin of artifacts declared in the given file
+    pub fn classify(&self, file_path: &Path) -> Origin {
+        let file_path_str = file_path.to_string_lossy();
+        let components = normal_components(file_path);
+        let is_third_party = self.third_party_paths.iter().any(|matcher| match matcher {
+            PathMatcher::Components(sequence) => components
+                .windows(sequence.len())
+                .any(|window| window == sequence.as_slice()),
+            PathMatcher::Prefix(prefix) => file_path.starts_with(prefix),
+            PathMatcher::Glob(pattern) => pattern.matches(&file_path_str),
+        });
+
+        if is_third_party {
+            Origin::ThirdParty
+        } else {
+            Origin::FirstParty
+        }
+    }
+}
+
+fn parse_path_matcher(path: &str) -> Result<PathMatcher> {
+    if path.contains(['*', '?', '[']) {
+        return Pattern::new(path)
+            .map(PathMatcher::Glob)
+            .map_err(|_| anyhow!("'{}' is not a valid glob expression", path));
+    }
+    if Path::new(path).has_root() {
+        return Ok(PathMatcher::Prefix(path.to_string()));
+    }
+
+    let components = normal_components(Path::new(path));
+    if components.is_empty() {
+        Err(anyhow!("'{}' is not a valid third-party path", path))
+    } else {
+        Ok(PathMatcher::Components(components))
+    }
+}
+
+/// Returns the names of the directories and file of the given path
+fn normal_components(path: &Path) -> Vec<String> {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Assigns an origin to each confirmed leak, based on where its data is
+/// declared
+pub fn classify_confirmed_leak_origins<SortedConfirmedLeak>(
+    confirmed_leaks: BTreeSet<SortedConfirmedLeak>,
+    classifier: &ProvenanceClassifier,
+) -> BTreeSet<SortedConfirmedLeak>
+where
+    SortedConfirmedLeak: Into<ConfirmedLeak> + From<ConfirmedLeak> + Ord,
+{
+    confirmed_leaks
+        .into_iter()
+        .map(Into::into)
+        .map(|mut leak: ConfirmedLeak| {
+            leak.origin = Some(classifier.classify(&leak.location.source.file));
+            SortedConfirmedLeak::from(leak)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_default_paths() {
+        let classifier = ProvenanceClassifier::new(None).unwrap();
+
+        assert_eq!(
+            classifier.classify(Path::new("/src/project/vendor/zlib/inflate.c")),
+            Origin::ThirdParty
+        );
+        assert_eq!(
+            classifier.classify(Path::new(
+                "/home/ci/.conan/data/boost/1.80.0/_/_/package/include/boost/any.hpp"
+            )),
+            Origin::ThirdParty
+        );
+        assert_eq!(
+            classifier.classify(Path::new("/src/project/src/vendors.cc")),
+            Origin::FirstParty
+        );
+    }
+
+    #[test]
+    fn classify_configured_paths() {
+        let config: ProvenanceConfiguration = serde_yaml::from_str(
+            r#"
+third_party:
+  - libs/external/
+  - /opt/sdk
+  - "*/generated/*.pb.cc"
+"#,
+        )
+        .unwrap();
+        let classifier = ProvenanceClassifier::new(Some(&config)).unwrap();
+
+        assert_eq!(
+            classifier.classify(Path::new("/src/libs/external/json.hpp")),
+            Origin::ThirdParty
+        );
+        assert_eq!(
+            classifier.classify(Path::new("/opt/sdk/include/sdk.h")),
+            Origin::ThirdParty
+        );
+        assert_eq!(
+            classifier.classify(Path::new("/src/generated/messages.pb.cc")),
+            Origin::ThirdParty
+        );
+        // Configured paths replace the default ones
+        assert_eq!(
+            classifier.classify(Path::new("/src/vendor/zlib/inflate.c")),
+            Origin::FirstParty
+        );
+        assert_eq!(
+            classifier.classify(Path::new("/opt/sdkx/sdk.h")),
+            Origin::FirstParty
+        );
+    }
+
+    #[test]
+    fn invalid_third_party_path() {
+        let config = ProvenanceConfiguration {
+            third_party: Some(vec!["[".to_string()]),
+        };
+        assert!(ProvenanceClassifier::new(Some(&config)).is_err());
+    }
+}
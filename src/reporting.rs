@@ -1,90 +1,1485 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ffi::OsStr;
+use std::io::Write;
+use std::ops::Deref;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
 
-use anyhow::Result;
-use serde::Serialize;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
-use crate::information_leak::{ConfirmedLeak, LeakedDataType};
+use crate::baseline::leak_fingerprint;
+use crate::debug_info::Visibility;
+use crate::information_leak::{
+    BinaryLocation, ConfirmedLeak, ConfirmedLeakWithUniqueLocation, LeakedDataType,
+};
+use crate::path_mapping::PathNormalizer;
+use crate::policy::PolicyViolation;
+use crate::provenance::Origin;
+use crate::statistics::{PoolingStatistics, PoolingSummary};
 
 const PKG_VERSION: &str = env!("CARGO_PKG_VERSION");
-const REPORT_FORMAT_VERSION: u32 = 1;
+const REPORT_FORMAT_VERSION: u32 = 3;
+/// Size of the offset ranges findings are bucketed into when computing their
+/// fingerprints
+const FINDING_OFFSET_BUCKET_SIZE: u64 = 0x1000;
+/// Number of characters kept at each end of redacted values
+const REDACTION_VISIBLE_CHARS: usize = 2;
+/// Number of hex digits of the SHA-256 digest kept in redacted values
+const REDACTION_DIGEST_SIZE: usize = 16;
+/// Prefix of the leaks listed under a group in text reports
+const TEXT_GROUP_INDENTATION: &str = "    ";
+/// Line written at the top of text reports when the scan hasn't completed
+pub const PARTIAL_REPORT_NOTICE: &str =
+    "Partial results: the scan hasn't completed, some leaks may be missing";
+
+/// Output format used when none is specified
+pub const DEFAULT_OUTPUT_FORMAT: &str = "text";
+
+/// Content of a report, passed to `OutputSink`s
+pub struct Report<'l> {
+    /// Confirmed leaks, one per location
+    pub leaks: &'l [ConfirmedLeak],
+    /// Stable identifiers of the leaks (see `leak_fingerprint`), computed
+    /// before values are redacted
+    pub fingerprints: Vec<String>,
+    /// Identifiers of the leaks unique within the report (see
+    /// `finding_fingerprints`), computed before values are redacted
+    pub finding_fingerprints: Vec<String>,
+    /// Index of the leaked values, listing the leaks' indices in `leaks` for
+    /// each distinct value. Values are told apart before being redacted or
+    /// truncated.
+    pub unique_values: Vec<UniqueValue>,
+    /// Number of distinct binary locations the value of each leak has been
+    /// found at
+    pub occurrence_counts: Vec<usize>,
+    /// Number of source declarations that collapsed onto the same binary
+    /// offsets
+    pub string_pooling: PoolingSummary,
+    /// Set if the scan has been interrupted before completion
+    pub partial: bool,
+    /// Gates of the policy that failed, if a policy is defined
+    pub policy_violations: Option<&'l [PolicyViolation]>,
+    /// Set if leaked values should only be displayed once, for sinks that
+    /// cannot provide both views
+    pub ignore_multiple_locations: bool,
+}
+
+/// Outcome of the scan the reported leaks have been found by
+#[derive(Debug, Default)]
+pub struct ScanOutcome {
+    /// Set if the scan has been interrupted before completion
+    pub partial: bool,
+    /// Gates of the policy that failed, if a policy is defined
+    pub policy_violations: Option<Vec<PolicyViolation>>,
+}
+
+/// Leaked value, along with the leaks it's been found in
+#[derive(Debug, Serialize)]
+pub struct UniqueValue {
+    /// Value, as displayed in the report (i.e., redacted or truncated if
+    /// requested)
+    pub value: Arc<String>,
+    /// Indices of the leaks in the report's leaks
+    pub leaks: Vec<usize>,
+}
+
+/// Environment and effective configuration of the scan that produced a
+/// report, so that the report can be reproduced and audited
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportMetadata {
+    pub cpplumber_version: String,
+    pub libclang_version: String,
+    pub host: HostEnvironment,
+    /// Command-line options
+    pub options: serde_json::Value,
+    pub config_file: Option<FileDigest>,
+    /// Values of the configuration file
+    pub config: Option<serde_json::Value>,
+    pub suppressions_file: Option<FileDigest>,
+}
+
+/// Platform cpplumber runs on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostEnvironment {
+    pub os: String,
+    pub arch: String,
+}
+
+impl HostEnvironment {
+    /// Returns the platform of the current process
+    pub fn current() -> Self {
+        Self {
+            os: std::env::consts::OS.into(),
+            arch: std::env::consts::ARCH.into(),
+        }
+    }
+}
+
+/// Identifies the exact content of an input file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileDigest {
+    pub path: String,
+    pub sha256: String,
+}
+
+impl FileDigest {
+    /// Computes the SHA-256 digest of the given file. Its path is normalized
+    /// with `path_normalizer`.
+    pub fn new(file_path: &Path, path_normalizer: &PathNormalizer) -> Result<Self> {
+        let file_data = std::fs::read(file_path)
+            .with_context(|| format!("Failed to read '{}'", file_path.display()))?;
+
+        Ok(Self {
+            path: path_normalizer
+                .normalize(&file_path.canonicalize()?)
+                .display()
+                .to_string(),
+            sha256: to_hex_string(&Sha256::digest(file_data)),
+        })
+    }
+}
+
+/// Returns the lowercase hexadecimal representation of the given bytes (e.g.,
+/// of a digest)
+pub fn to_hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Destination of the confirmed leaks (i.e., an output format)
+pub trait OutputSink: Send + Sync {
+    /// Writes the given report into `writer`
+    fn write_report(&self, writer: &mut dyn Write, report: &Report) -> Result<()>;
+}
+
+/// Collection of `OutputSink`s, keyed by format name
+pub struct OutputSinkRegistry {
+    sinks: BTreeMap<String, Box<dyn OutputSink>>,
+}
+
+impl OutputSinkRegistry {
+    /// Creates a registry without any sink
+    pub fn new() -> Self {
+        Self {
+            sinks: BTreeMap::new(),
+        }
+    }
+
+    /// Registers a sink for the given format, replacing any sink previously
+    /// registered for it
+    pub fn register(&mut self, format: &str, sink: Box<dyn OutputSink>) {
+        self.sinks.insert(format.to_string(), sink);
+    }
+
+    /// Returns the sink registered for the given format
+    pub fn get(&self, format: &str) -> Result<&dyn OutputSink> {
+        self.sinks
+            .get(format)
+            .map(|sink| sink.as_ref())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unknown output format '{}' (available formats: {})",
+                    format,
+                    self.sinks.keys().cloned().collect::<Vec<_>>().join(", ")
+                )
+            })
+    }
+}
+
+impl Default for OutputSinkRegistry {
+    /// Creates a registry containing the built-in sinks
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register("text", Box::new(TextSink::default()));
+        registry.register("json", Box::new(JsonSink::default()));
+        registry.register("yaml", Box::new(YamlSink::default()));
+        registry.register("toml", Box::new(TomlSink::default()));
+
+        registry
+    }
+}
+
+/// Writes a report of the given leaks and of the scan's `outcome` with
+/// `output_sink`.
+/// Leaked values are redacted if `redact` is set, or truncated to
+/// `truncated_value_size` characters if set (e.g., to keep secrets out of
+/// build logs).
+pub fn dump_confirmed_leaks<W, SortedConfirmedLeak>(
+    mut writer: W,
+    confirmed_leaks: &BTreeSet<SortedConfirmedLeak>,
+    output_sink: &dyn OutputSink,
+    outcome: &ScanOutcome,
+    redact: bool,
+    truncated_value_size: Option<usize>,
+    ignore_multiple_locations: bool,
+) -> Result<()>
+where
+    W: std::io::Write,
+    SortedConfirmedLeak: Deref<Target = ConfirmedLeak>,
+{
+    let mut confirmed_leaks: Vec<ConfirmedLeak> = confirmed_leaks
+        .iter()
+        .map(|leak| ConfirmedLeak::clone(leak))
+        .collect();
+    let fingerprints: Vec<String> = confirmed_leaks.iter().map(leak_fingerprint).collect();
+    let finding_fingerprints = finding_fingerprints(&confirmed_leaks, &fingerprints);
+    // Note: Leaks are indexed by their full values, as distinct values may
+    // look the same once redacted or truncated
+    let value_index =
+        confirmed_leaks
+            .iter()
+            .enumerate()
+            .fold(BTreeMap::new(), |mut accum, (i, leak)| {
+                accum
+                    .entry(leak.data.clone())
+                    .or_insert_with(Vec::new)
+                    .push(i);
+                accum
+            });
+    let mut occurrence_counts = vec![0; confirmed_leaks.len()];
+    for indices in value_index.values() {
+        let occurrence_count = indices
+            .iter()
+            .map(|&i| {
+                let location = &confirmed_leaks[i].location.binary;
+                (&location.file, location.offset)
+            })
+            .collect::<BTreeSet<_>>()
+            .len();
+        for &i in indices {
+            occurrence_counts[i] = occurrence_count;
+        }
+    }
+
+    for leak in &mut confirmed_leaks {
+        if redact {
+            leak.data = Arc::new(redact_value(&leak.data));
+        } else if let Some(truncated_value_size) = truncated_value_size {
+            leak.data = Arc::new(truncate_value(&leak.data, truncated_value_size));
+        }
+        // Note: The surrounding bytes contain the full value
+        if redact || truncated_value_size.is_some() {
+            leak.context = None;
+        }
+    }
+
+    let unique_values = value_index
+        .into_values()
+        .map(|leaks| UniqueValue {
+            value: confirmed_leaks[leaks[0]].data.clone(),
+            leaks,
+        })
+        .collect();
+
+    let string_pooling = PoolingStatistics::from_confirmed_leaks(&confirmed_leaks).summary();
+
+    output_sink.write_report(
+        &mut writer,
+        &Report {
+            leaks: &confirmed_leaks,
+            fingerprints,
+            finding_fingerprints,
+            unique_values,
+            occurrence_counts,
+            string_pooling,
+            partial: outcome.partial,
+            policy_violations: outcome.policy_violations.as_deref(),
+            ignore_multiple_locations,
+        },
+    )
+}
+
+/// Returns an identifier for each of the given leaks, unique within the
+/// report and stable across builds, so that issue trackers can deduplicate
+/// findings: the digest of the leak's fingerprint (see `leak_fingerprint`),
+/// binary file name and offset bucket. Unlike fingerprints, which are shared
+/// by all the locations of a value, finding fingerprints tell locations apart.
+/// Offsets are taken relative to the first location the value has been found
+/// at in the binary before being bucketed, so that findings keep their
+/// identifier when unrelated code moves them around. Locations falling into
+/// the same bucket are told apart by their rank.
+fn finding_fingerprints(leaks: &[ConfirmedLeak], fingerprints: &[String]) -> Vec<String> {
+    let finding_key = |i: usize| -> (&str, Option<&OsStr>) {
+        (&fingerprints[i], leaks[i].location.binary.file.file_name())
+    };
+
+    let mut first_offsets: HashMap<_, u64> = HashMap::new();
+    for (i, leak) in leaks.iter().enumerate() {
+        let first_offset = first_offsets
+            .entry(finding_key(i))
+            .or_insert(leak.location.binary.offset);
+        *first_offset = (*first_offset).min(leak.location.binary.offset);
+    }
+
+    let mut leak_indices: Vec<usize> = (0..leaks.len()).collect();
+    leak_indices.sort_by_key(|&i| {
+        let location = &leaks[i].location;
+        (location.binary.offset, location.source.line)
+    });
+    let mut bucket_ranks: HashMap<_, u64> = HashMap::new();
+    let mut finding_fingerprints = vec![String::new(); leaks.len()];
+    for i in leak_indices {
+        let key = finding_key(i);
+        let bucket =
+            (leaks[i].location.binary.offset - first_offsets[&key]) / FINDING_OFFSET_BUCKET_SIZE;
+        let rank = bucket_ranks.entry((key, bucket)).or_default();
+
+        let mut hasher = Sha256::new();
+        hasher.update(key.0.as_bytes());
+        hasher.update([0]);
+        if let Some(file_name) = key.1 {
+            hasher.update(file_name.to_string_lossy().as_bytes());
+        }
+        hasher.update([0]);
+        hasher.update(bucket.to_le_bytes());
+        hasher.update(rank.to_le_bytes());
+        finding_fingerprints[i] = format!("{:x}", hasher.finalize());
+
+        *rank += 1;
+    }
+
+    finding_fingerprints
+}
+
+/// Returns the identifier of the rule that reports leaks of the given type
+/// (e.g., `cpplumber/string-literal`)
+pub fn leak_rule_id(data_type: LeakedDataType) -> String {
+    format!(
+        "cpplumber/{}",
+        display_leaked_data_type(data_type)
+            .to_lowercase()
+            .replace(' ', "-")
+    )
+}
 
 #[derive(Serialize)]
-struct JsonReport<SortedConfirmedLeak: Into<ConfirmedLeak> + Ord + Eq + Serialize> {
+struct JsonReport<'l> {
     version: ReportVersion,
-    leaks: BTreeSet<SortedConfirmedLeak>,
+    /// Set if the scan has been interrupted before completion
+    partial: bool,
+    /// Leaks, one per location
+    leaks: Vec<JsonLeak<'l>>,
+    /// Indices of the leaks in `leaks`, for each leaked value
+    unique_values: &'l [UniqueValue],
+    /// Number of source declarations that collapsed onto the same binary
+    /// offsets
+    string_pooling: &'l PoolingSummary,
+    /// Gates of the policy that failed, if a policy is defined
+    #[serde(skip_serializing_if = "Option::is_none")]
+    policy_violations: Option<&'l [PolicyViolation]>,
+    /// Environment and configuration of the scan
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<&'l ReportMetadata>,
 }
 
 #[derive(Serialize)]
+struct JsonLeak<'l> {
+    #[serde(flatten)]
+    leak: &'l ConfirmedLeak,
+    /// Identifier of the rule reporting the leak, derived from its type
+    rule_id: String,
+    /// Identifier of the leaked value and its declaration, shared by all its
+    /// locations and used to match leaks against baselines
+    fingerprint: &'l str,
+    /// Identifier of this location of the leak, unique within the report
+    finding_fingerprint: &'l str,
+    /// Number of distinct binary locations the leaked value has been found at
+    count: usize,
+}
+
+#[derive(Serialize, Deserialize)]
 struct ReportVersion {
     executable: String,
     format: u32,
 }
 
-pub fn dump_confirmed_leaks<W, SortedConfirmedLeak>(
-    writer: W,
-    confirmed_leaks: BTreeSet<SortedConfirmedLeak>,
-    json: bool,
-) -> Result<()>
-where
-    W: std::io::Write,
-    SortedConfirmedLeak: Into<ConfirmedLeak> + Ord + Eq + Serialize,
-{
-    if json {
-        dump_confirmed_leaks_as_json(writer, confirmed_leaks)
-    } else {
-        dump_confirmed_leaks_as_text(writer, confirmed_leaks)
+/// Report loaded from a JSON document written by `JsonSink`, so that it can
+/// be post-processed and written again with `dump_confirmed_leaks`
+#[derive(Debug, Deserialize)]
+pub struct LoadedReport {
+    /// Set if the scan has been interrupted before completion
+    pub partial: bool,
+    /// Leaks, one per location
+    pub leaks: Vec<ConfirmedLeak>,
+    /// Environment and configuration of the scan, if embedded
+    pub metadata: Option<ReportMetadata>,
+}
+
+impl LoadedReport {
+    /// Returns the leaks, deduplicated and sorted like the leaks of a scan
+    pub fn confirmed_leaks(&self) -> BTreeSet<ConfirmedLeakWithUniqueLocation> {
+        self.leaks.iter().cloned().map(Into::into).collect()
     }
 }
 
-fn dump_confirmed_leaks_as_json<W, SortedConfirmedLeak>(
-    writer: W,
-    confirmed_leaks: BTreeSet<SortedConfirmedLeak>,
-) -> Result<()>
-where
-    W: std::io::Write,
-    SortedConfirmedLeak: Into<ConfirmedLeak> + Ord + Eq + Serialize,
-{
-    let report = JsonReport {
-        version: ReportVersion {
-            executable: PKG_VERSION.into(),
-            format: REPORT_FORMAT_VERSION,
-        },
-        leaks: confirmed_leaks,
+#[derive(Deserialize)]
+struct LoadedReportVersion {
+    version: ReportVersion,
+}
+
+/// Loads a report written by `JsonSink`. Reports written in a newer format
+/// are rejected.
+pub fn load_json_report<R: std::io::Read>(mut reader: R) -> Result<LoadedReport> {
+    let mut report = String::new();
+    reader.read_to_string(&mut report)?;
+
+    let LoadedReportVersion { version } =
+        serde_json::from_str(&report).context("Malformed report")?;
+    if version.format > REPORT_FORMAT_VERSION {
+        return Err(anyhow!(
+            "Report written by cpplumber {} in an unsupported format (version {})",
+            version.executable,
+            version.format
+        ));
+    }
+
+    serde_json::from_str(&report).context("Malformed report")
+}
+
+/// Writes reports as JSON documents
+#[derive(Default)]
+pub struct JsonSink {
+    metadata: Option<ReportMetadata>,
+}
+
+impl JsonSink {
+    /// Creates a sink that embeds the given metadata into reports
+    pub fn with_metadata(metadata: ReportMetadata) -> Self {
+        Self {
+            metadata: Some(metadata),
+        }
+    }
+}
+
+impl<'l> JsonReport<'l> {
+    fn new(report: &'l Report, metadata: Option<&'l ReportMetadata>) -> Self {
+        // Note: Both views are always provided, so `ignore_multiple_locations`
+        // is ignored
+        Self {
+            version: ReportVersion {
+                executable: PKG_VERSION.into(),
+                format: REPORT_FORMAT_VERSION,
+            },
+            partial: report.partial,
+            leaks: report
+                .leaks
+                .iter()
+                .zip(&report.fingerprints)
+                .zip(&report.finding_fingerprints)
+                .zip(&report.occurrence_counts)
+                .map(
+                    |(((leak, fingerprint), finding_fingerprint), &count)| JsonLeak {
+                        leak,
+                        rule_id: leak_rule_id(leak.data_type),
+                        fingerprint,
+                        finding_fingerprint,
+                        count,
+                    },
+                )
+                .collect(),
+            unique_values: &report.unique_values,
+            string_pooling: &report.string_pooling,
+            policy_violations: report.policy_violations,
+            metadata,
+        }
+    }
+}
+
+impl OutputSink for JsonSink {
+    fn write_report(&self, writer: &mut dyn Write, report: &Report) -> Result<()> {
+        let report = JsonReport::new(report, self.metadata.as_ref());
+
+        Ok(serde_json::to_writer(writer, &report)?)
+    }
+}
+
+/// Writes reports as YAML documents, structured like JSON reports
+#[derive(Default)]
+pub struct YamlSink {
+    metadata: Option<ReportMetadata>,
+}
+
+impl YamlSink {
+    /// Creates a sink that embeds the given metadata into reports
+    pub fn with_metadata(metadata: ReportMetadata) -> Self {
+        Self {
+            metadata: Some(metadata),
+        }
+    }
+}
+
+impl OutputSink for YamlSink {
+    fn write_report(&self, writer: &mut dyn Write, report: &Report) -> Result<()> {
+        let report = JsonReport::new(report, self.metadata.as_ref());
+
+        Ok(serde_yaml::to_writer(writer, &report)?)
+    }
+}
+
+/// Writes reports as TOML documents, structured like JSON reports
+#[derive(Default)]
+pub struct TomlSink {
+    metadata: Option<ReportMetadata>,
+}
+
+impl TomlSink {
+    /// Creates a sink that embeds the given metadata into reports
+    pub fn with_metadata(metadata: ReportMetadata) -> Self {
+        Self {
+            metadata: Some(metadata),
+        }
+    }
+}
+
+impl OutputSink for TomlSink {
+    fn write_report(&self, writer: &mut dyn Write, report: &Report) -> Result<()> {
+        let report = serde_json::to_value(JsonReport::new(report, self.metadata.as_ref()))?;
+        // Note: TOML has no null values, and converting the report first makes
+        // sure that tables are written after plain values, as TOML requires
+        let report = toml::Value::try_from(remove_null_values(report))?;
+
+        Ok(writer.write_all(toml::to_string(&report)?.as_bytes())?)
+    }
+}
+
+/// Removes null values from objects and arrays, recursively
+fn remove_null_values(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(object) => object
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| (key, remove_null_values(value)))
+            .collect(),
+        serde_json::Value::Array(array) => array
+            .into_iter()
+            .filter(|value| !value.is_null())
+            .map(remove_null_values)
+            .collect(),
+        value => value,
+    }
+}
+
+/// Criterion by which leaks are grouped in text reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeakGrouping {
+    /// Source file the leaked data is declared in
+    Source,
+    /// Binary file the leaked data has been found in
+    Binary,
+    /// Leaked value
+    Value,
+}
+
+impl FromStr for LeakGrouping {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "source" => Ok(LeakGrouping::Source),
+            "binary" => Ok(LeakGrouping::Binary),
+            "value" => Ok(LeakGrouping::Value),
+            _ => Err(anyhow!(
+                "Invalid grouping '{}' (expected source, binary or value)",
+                s
+            )),
+        }
+    }
+}
+
+/// Writes reports as human-readable text, one leak per line, optionally
+/// grouped (e.g., by source file)
+#[derive(Default)]
+pub struct TextSink {
+    group_by: Option<LeakGrouping>,
+}
+
+impl TextSink {
+    /// Creates a sink that lists leaks under a header for each group, along
+    /// with the number of leaks in the group
+    pub fn grouped_by(group_by: LeakGrouping) -> Self {
+        Self {
+            group_by: Some(group_by),
+        }
+    }
+}
+
+impl OutputSink for TextSink {
+    fn write_report(&self, writer: &mut dyn Write, report: &Report) -> Result<()> {
+        if report.partial {
+            writeln!(writer, "{}", PARTIAL_REPORT_NOTICE)?;
+        }
+        let leak_indices: Vec<usize> = if report.ignore_multiple_locations {
+            // Only keep the first location of each value
+            report
+                .unique_values
+                .iter()
+                .map(|unique_value| unique_value.leaks[0])
+                .collect()
+        } else {
+            (0..report.leaks.len()).collect()
+        };
+
+        let group_by = match self.group_by {
+            Some(group_by) => group_by,
+            None => {
+                for i in leak_indices {
+                    write_text_leak(writer, report, i, "")?;
+                }
+                return Ok(());
+            }
+        };
+
+        // Index of each leak's value, so that distinct values displayed the
+        // same way (e.g., once truncated) are grouped separately
+        let mut value_indices = vec![0; report.leaks.len()];
+        for (value_index, unique_value) in report.unique_values.iter().enumerate() {
+            for &i in &unique_value.leaks {
+                value_indices[i] = value_index;
+            }
+        }
+
+        // Note: Leaks keep their order within groups
+        let mut groups: BTreeMap<(String, usize), Vec<usize>> = BTreeMap::new();
+        for i in leak_indices {
+            let leak = &report.leaks[i];
+            let key = match group_by {
+                LeakGrouping::Source => (leak.location.source.file.display().to_string(), 0),
+                LeakGrouping::Binary => (leak.location.binary.file.display().to_string(), 0),
+                LeakGrouping::Value => (format!("\"{}\"", leak.data), value_indices[i]),
+            };
+            groups.entry(key).or_default().push(i);
+        }
+        for ((key, _), leak_indices) in groups {
+            writeln!(writer, "{} ({} leak(s)):", key, leak_indices.len())?;
+            for i in leak_indices {
+                write_text_leak(writer, report, i, TEXT_GROUP_INDENTATION)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the leak at index `leak_index` as a line of text, prefixed with
+/// `indentation`
+fn write_text_leak(
+    writer: &mut dyn Write,
+    report: &Report,
+    leak_index: usize,
+    indentation: &str,
+) -> Result<()> {
+    let leak = &report.leaks[leak_index];
+    let mut leak_description = if let Some(partial_size) = leak.partial_size {
+        format!("partially leaked ({} bytes)", partial_size)
+    } else {
+        "leaked".to_string()
     };
+    if report.ignore_multiple_locations {
+        let occurrence_count = report.occurrence_counts[leak_index];
+        if occurrence_count > 1 {
+            leak_description = format!("{} {} times, first", leak_description, occurrence_count);
+        }
+    }
+    writeln!(
+        writer,
+        "{}\"{}\" ({}{}{}{}{}{}{}) {} at offset 0x{:x}{} in \"{}\" [declared at {}:{}]",
+        indentation,
+        leak.data,
+        display_leaked_data_type(leak.data_type),
+        // Note: Only third-party code is highlighted
+        match leak.origin {
+            Some(Origin::ThirdParty) => ", third-party",
+            _ => "",
+        },
+        // Note: Only leaks that stripping would remove are highlighted
+        match leak.visibility {
+            Some(Visibility::DebugOnly) => ", debug-only",
+            _ => "",
+        },
+        leak.infrastructure
+            .map(|infrastructure| format!(", {}", infrastructure))
+            .unwrap_or_default(),
+        leak.severity
+            .map(|severity| format!(", {} severity", severity))
+            .unwrap_or_default(),
+        leak.confidence
+            .map(|confidence| format!(", confidence {}", confidence))
+            .unwrap_or_default(),
+        leak.pass
+            .as_ref()
+            .map(|pass| format!(", pass '{}'", pass))
+            .unwrap_or_default(),
+        leak_description,
+        leak.location.binary.offset,
+        display_binary_context(&leak.location.binary),
+        leak.location.binary.file.display(),
+        leak.location.source.file.display(),
+        leak.location.source.line,
+    )?;
+    if let Some(ref context) = leak.context {
+        writeln!(
+            writer,
+            "{}    context at 0x{:x}: {} |{}|",
+            indentation, context.offset, context.hex, context.text
+        )?;
+    }
 
-    Ok(serde_json::to_writer(writer, &report)?)
+    Ok(())
 }
 
-fn dump_confirmed_leaks_as_text<W, SortedConfirmedLeak>(
+/// Writes the number of leaks declared in each source file (for the
+/// `max_file_count` files with the most leaks), instead of listing every leak
+pub fn write_leak_summary<W, SortedConfirmedLeak>(
     mut writer: W,
-    confirmed_leaks: BTreeSet<SortedConfirmedLeak>,
+    confirmed_leaks: &BTreeSet<SortedConfirmedLeak>,
+    max_file_count: usize,
 ) -> Result<()>
 where
     W: std::io::Write,
-    SortedConfirmedLeak: Into<ConfirmedLeak> + Ord + Eq + Serialize,
+    SortedConfirmedLeak: Deref<Target = ConfirmedLeak>,
 {
+    let mut leak_counts: BTreeMap<&Path, usize> = BTreeMap::new();
     for leak in confirmed_leaks {
-        let leak: ConfirmedLeak = leak.into();
+        *leak_counts
+            .entry(leak.location.source.file.as_path())
+            .or_default() += 1;
+    }
+    // Files with the most leaks first
+    let mut leak_counts: Vec<(&Path, usize)> = leak_counts.into_iter().collect();
+    leak_counts.sort_by(|(_, count), (_, other_count)| other_count.cmp(count));
+
+    writeln!(
+        writer,
+        "{} leak(s) found in {} source file(s):",
+        confirmed_leaks.len(),
+        leak_counts.len()
+    )?;
+    for (file_path, leak_count) in leak_counts.iter().take(max_file_count) {
+        writeln!(writer, "  {}: {} leak(s)", file_path.display(), leak_count)?;
+    }
+    if leak_counts.len() > max_file_count {
         writeln!(
-            &mut writer,
-            "\"{}\" ({}) leaked at offset 0x{:x} in \"{}\" [declared at {}:{}]",
-            leak.data,
-            display_leaked_data_type(leak.data_type),
-            leak.location.binary.offset,
-            leak.location.binary.file.display(),
-            leak.location.source.file.display(),
-            leak.location.source.line,
+            writer,
+            "  ... and {} other file(s)",
+            leak_counts.len() - max_file_count
         )?;
     }
 
     Ok(())
 }
 
+/// Keeps the first `size` characters of a leaked value, followed by an
+/// ellipsis if the value is longer (e.g., "my_s…")
+pub fn truncate_value(value: &str, size: usize) -> String {
+    if value.chars().count() <= size {
+        return value.to_string();
+    }
+    let first_chars: String = value.chars().take(size).collect();
+
+    format!("{}…", first_chars)
+}
+
+/// Replaces a leaked value with a digest of the value, along with its first
+/// and last few characters (e.g., "se…et [sha256:2bb80d537b1da3e3]"), so that
+/// reports don't leak the values themselves
+pub fn redact_value(value: &str) -> String {
+    let digest = format!("{:x}", Sha256::digest(value.as_bytes()));
+    let digest = &digest[..REDACTION_DIGEST_SIZE];
+
+    let char_count = value.chars().count();
+    // Don't reveal anything but the digest for short values, of which the
+    // visible characters would give away too large a share
+    if char_count < 4 * REDACTION_VISIBLE_CHARS {
+        return format!("[sha256:{}]", digest);
+    }
+    let first_chars: String = value.chars().take(REDACTION_VISIBLE_CHARS).collect();
+    let last_chars: String = value
+        .chars()
+        .skip(char_count - REDACTION_VISIBLE_CHARS)
+        .collect();
+
+    format!("{}…{} [sha256:{}]", first_chars, last_chars, digest)
+}
+
+/// Describes where leaked data is located in the binary's layout (e.g.,
+/// " (arm64, section "__cstring", address 0x100003f80)"), if known
+pub fn display_binary_context(location: &BinaryLocation) -> String {
+    let context: Vec<String> = [
+        location
+            .architecture
+            .as_ref()
+            .map(|architecture| architecture.to_string()),
+        location.region.as_ref().map(|region| region.to_string()),
+        location
+            .virtual_address
+            .map(|address| format!("address 0x{:x}", address)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    if context.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", context.join(", "))
+    }
+}
+
 /// Returns a text representation of `LeakedDataType`
-fn display_leaked_data_type(data_type: LeakedDataType) -> String {
+pub fn display_leaked_data_type(data_type: LeakedDataType) -> String {
     match data_type {
         LeakedDataType::StringLiteral => "string literal".to_string(),
+        LeakedDataType::MacroValue => "macro value".to_string(),
         LeakedDataType::StructName => "struct name".to_string(),
         LeakedDataType::ClassName => "class name".to_string(),
+        LeakedDataType::TemplateName => "template name".to_string(),
+        LeakedDataType::UnionName => "union name".to_string(),
+        LeakedDataType::TypedefName => "typedef name".to_string(),
+        LeakedDataType::EnumName => "enum name".to_string(),
+        LeakedDataType::EnumeratorName => "enumerator name".to_string(),
+        LeakedDataType::FunctionName => "function name".to_string(),
+        LeakedDataType::MethodName => "method name".to_string(),
+        LeakedDataType::FieldName => "field name".to_string(),
+        LeakedDataType::CustomArtifact => "custom artifact".to_string(),
+        LeakedDataType::ResourceString => "resource string".to_string(),
+        LeakedDataType::EmbeddedFile => "embedded file".to_string(),
+        LeakedDataType::SerializationSchema => "serialization schema".to_string(),
+        LeakedDataType::Guid => "GUID".to_string(),
+        LeakedDataType::ComInterfaceName => "COM interface name".to_string(),
+        LeakedDataType::SourcePath => "source path".to_string(),
+        LeakedDataType::UserProvided => "user-provided data".to_string(),
+        LeakedDataType::Comment => "comment".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, sync::Arc};
+
+    use crate::information_leak::{
+        BinaryLocation, ConfirmedLeakWithUniqueLocation, LeakLocation, SourceLocation,
+    };
+
+    use crate::severity::{classify_confirmed_leaks, Severity, SeverityClassifier};
+
+    use super::*;
+
+    /// Sink that only writes the number of leaks
+    struct CountSink;
+
+    impl OutputSink for CountSink {
+        fn write_report(&self, writer: &mut dyn Write, report: &Report) -> Result<()> {
+            Ok(write!(writer, "{}", report.leaks.len())?)
+        }
+    }
+
+    fn confirmed_leaks() -> BTreeSet<ConfirmedLeakWithUniqueLocation> {
+        confirmed_leaks_at(&[16])
+    }
+
+    fn confirmed_leaks_at(offsets: &[u64]) -> BTreeSet<ConfirmedLeakWithUniqueLocation> {
+        offsets
+            .iter()
+            .map(|offset| {
+                ConfirmedLeak {
+                    data_type: LeakedDataType::StringLiteral,
+                    data: Arc::new("leak".to_string()),
+                    location: LeakLocation {
+                        source: Arc::new(SourceLocation {
+                            file: PathBuf::from("file.cc"),
+                            line: 1,
+                        }),
+                        binary: BinaryLocation {
+                            file: Arc::new(PathBuf::from("a.out")),
+                            offset: *offset,
+                            region: None,
+                            virtual_address: None,
+                            architecture: None,
+                            debug_info: false,
+                        },
+                    },
+                    size: 4,
+                    partial_size: None,
+                    severity: None,
+                    confidence: None,
+                    origin: None,
+                    visibility: None,
+                    infrastructure: None,
+                    context: None,
+                    pass: None,
+                }
+                .into()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dump_confirmed_leaks_as_json_with_unique_values() {
+        let registry = OutputSinkRegistry::default();
+        let mut output = vec![];
+        dump_confirmed_leaks(
+            &mut output,
+            &confirmed_leaks_at(&[16, 32]),
+            registry.get("json").expect("Missing JSON sink"),
+            &ScanOutcome::default(),
+            false,
+            None,
+            // Both views are always available in JSON reports
+            true,
+        )
+        .expect("dump_confirmed_leaks failed");
+
+        let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(report["leaks"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            report["unique_values"],
+            serde_json::json!([{"value": "leak", "leaks": [0, 1]}])
+        );
+        assert_eq!(report["leaks"][0]["count"], 2);
+    }
+
+    #[test]
+    fn dump_confirmed_leaks_as_json_with_finding_fingerprints() {
+        let finding_fingerprints = |offsets: &[u64]| -> Vec<serde_json::Value> {
+            let mut output = vec![];
+            dump_confirmed_leaks(
+                &mut output,
+                &confirmed_leaks_at(offsets),
+                &JsonSink::default(),
+                &ScanOutcome::default(),
+                false,
+                None,
+                false,
+            )
+            .expect("dump_confirmed_leaks failed");
+
+            let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+            assert_eq!(report["leaks"][0]["rule_id"], "cpplumber/string-literal");
+            report["leaks"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|leak| leak["finding_fingerprint"].clone())
+                .collect()
+        };
+
+        let fingerprints = finding_fingerprints(&[16, 32, 0x2010]);
+        // Unique within the report
+        assert_ne!(fingerprints[0], fingerprints[1]);
+        assert_ne!(fingerprints[0], fingerprints[2]);
+        assert_ne!(fingerprints[1], fingerprints[2]);
+        // Stable when the leaks are moved around
+        assert_eq!(fingerprints, finding_fingerprints(&[0x110, 0x120, 0x2110]));
+        assert_ne!(fingerprints, finding_fingerprints(&[16, 32, 0x4010]));
+    }
+
+    #[test]
+    fn dump_confirmed_leaks_as_json_with_policy_violations() {
+        let policy_violations = |outcome: &ScanOutcome| -> serde_json::Value {
+            let mut output = vec![];
+            dump_confirmed_leaks(
+                &mut output,
+                &confirmed_leaks(),
+                &JsonSink::default(),
+                outcome,
+                false,
+                None,
+                false,
+            )
+            .expect("dump_confirmed_leaks failed");
+
+            let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+            report["policy_violations"].clone()
+        };
+
+        assert!(policy_violations(&ScanOutcome::default()).is_null());
+        assert_eq!(
+            policy_violations(&ScanOutcome {
+                partial: false,
+                policy_violations: Some(vec![PolicyViolation::MaxTotal { max: 0, found: 1 }]),
+            }),
+            serde_json::json!([{"gate": "max_total", "max": 0, "found": 1}])
+        );
+    }
+
+    #[test]
+    fn dump_confirmed_leaks_as_json_with_metadata() {
+        let config_file_path =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/config/policy.yml");
+        let config_file = FileDigest::new(&config_file_path, &PathNormalizer::new(vec![]))
+            .expect("Failed to hash configuration file");
+        assert_eq!(config_file.sha256.len(), 64);
+        let metadata = ReportMetadata {
+            cpplumber_version: PKG_VERSION.into(),
+            libclang_version: "clang version 14.0.0".into(),
+            host: HostEnvironment::current(),
+            options: serde_json::json!({"minimum_leak_size": 4}),
+            config_file: Some(config_file),
+            config: Some(serde_json::json!({"artifacts": [{"value": "secret"}]})),
+            suppressions_file: None,
+        };
+
+        let mut output = vec![];
+        dump_confirmed_leaks(
+            &mut output,
+            &confirmed_leaks(),
+            &JsonSink::with_metadata(metadata),
+            &ScanOutcome::default(),
+            false,
+            None,
+            false,
+        )
+        .expect("dump_confirmed_leaks failed");
+
+        let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(report["metadata"]["options"]["minimum_leak_size"], 4);
+        assert_eq!(report["metadata"]["host"]["os"], std::env::consts::OS);
+        assert_eq!(
+            report["metadata"]["config"]["artifacts"][0]["value"],
+            "secret"
+        );
+        assert!(report["metadata"]["suppressions_file"].is_null());
+
+        // Metadata is omitted if not provided
+        let mut output = vec![];
+        dump_confirmed_leaks(
+            &mut output,
+            &confirmed_leaks(),
+            &JsonSink::default(),
+            &ScanOutcome::default(),
+            false,
+            None,
+            false,
+        )
+        .expect("dump_confirmed_leaks failed");
+        let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert!(report.get("metadata").is_none());
+    }
+
+    #[test]
+    fn write_leak_summary_per_file() {
+        let leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> =
+            [("b.cc", 1), ("a.cc", 2), ("b.cc", 3), ("c.cc", 4)]
+                .iter()
+                .map(|(file, offset)| {
+                    let mut leak: ConfirmedLeak = confirmed_leaks_at(&[*offset])
+                        .into_iter()
+                        .next()
+                        .unwrap()
+                        .into();
+                    leak.location.source = Arc::new(SourceLocation {
+                        file: PathBuf::from(file),
+                        line: *offset,
+                    });
+                    leak.into()
+                })
+                .collect();
+
+        let mut output = vec![];
+        write_leak_summary(&mut output, &leaks, 2).expect("write_leak_summary failed");
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "4 leak(s) found in 3 source file(s):\n  b.cc: 2 leak(s)\n  a.cc: 1 leak(s)\n  \
+             ... and 1 other file(s)\n"
+        );
+    }
+
+    #[test]
+    fn dump_confirmed_leaks_as_text_ignoring_multiple_locations() {
+        let registry = OutputSinkRegistry::default();
+        let mut output = vec![];
+        dump_confirmed_leaks(
+            &mut output,
+            &confirmed_leaks_at(&[16, 32]),
+            registry.get("text").expect("Missing text sink"),
+            &ScanOutcome::default(),
+            false,
+            None,
+            true,
+        )
+        .expect("dump_confirmed_leaks failed");
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\"leak\" (string literal) leaked 2 times, first at offset 0x10 in \"a.out\" [declared at file.cc:1]\n"
+        );
+    }
+
+    #[test]
+    fn dump_confirmed_leaks_as_text() {
+        let registry = OutputSinkRegistry::default();
+        let mut output = vec![];
+        dump_confirmed_leaks(
+            &mut output,
+            &confirmed_leaks(),
+            registry.get("text").expect("Missing text sink"),
+            &ScanOutcome::default(),
+            false,
+            None,
+            false,
+        )
+        .expect("dump_confirmed_leaks failed");
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\"leak\" (string literal) leaked at offset 0x10 in \"a.out\" [declared at file.cc:1]\n"
+        );
+    }
+
+    #[test]
+    fn dump_confirmed_leaks_as_text_partial() {
+        let mut output = vec![];
+        dump_confirmed_leaks(
+            &mut output,
+            &confirmed_leaks(),
+            &TextSink::grouped_by(LeakGrouping::Binary),
+            &ScanOutcome {
+                partial: true,
+                ..Default::default()
+            },
+            false,
+            None,
+            false,
+        )
+        .expect("dump_confirmed_leaks failed");
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!(
+                "{}\n\
+                a.out (1 leak(s)):\n\
+                \x20   \"leak\" (string literal) leaked at offset 0x10 in \"a.out\" [declared at file.cc:1]\n",
+                PARTIAL_REPORT_NOTICE
+            )
+        );
+    }
+
+    #[test]
+    fn dump_confirmed_leaks_as_text_grouped() {
+        let mut confirmed_leaks = confirmed_leaks_at(&[16, 32]);
+        let mut other_leak = ConfirmedLeak::clone(confirmed_leaks.iter().next().unwrap());
+        other_leak.data = Arc::new("other leak".to_string());
+        other_leak.location.source = Arc::new(SourceLocation {
+            file: PathBuf::from("other_file.cc"),
+            line: 2,
+        });
+        confirmed_leaks.insert(other_leak.into());
+
+        let mut output = vec![];
+        dump_confirmed_leaks(
+            &mut output,
+            &confirmed_leaks,
+            &TextSink::grouped_by(LeakGrouping::Source),
+            &ScanOutcome::default(),
+            false,
+            None,
+            false,
+        )
+        .expect("dump_confirmed_leaks failed");
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "file.cc (2 leak(s)):\n\
+            \x20   \"leak\" (string literal) leaked at offset 0x10 in \"a.out\" [declared at file.cc:1]\n\
+            \x20   \"leak\" (string literal) leaked at offset 0x20 in \"a.out\" [declared at file.cc:1]\n\
+            other_file.cc (1 leak(s)):\n\
+            \x20   \"other leak\" (string literal) leaked at offset 0x10 in \"a.out\" [declared at other_file.cc:2]\n"
+        );
+
+        let mut output = vec![];
+        dump_confirmed_leaks(
+            &mut output,
+            &confirmed_leaks,
+            &TextSink::grouped_by(LeakGrouping::Binary),
+            &ScanOutcome::default(),
+            false,
+            None,
+            false,
+        )
+        .expect("dump_confirmed_leaks failed");
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .starts_with("a.out (3 leak(s)):\n"));
+
+        assert_eq!(
+            "value".parse::<LeakGrouping>().unwrap(),
+            LeakGrouping::Value
+        );
+        assert!("file".parse::<LeakGrouping>().is_err());
+    }
+
+    #[test]
+    fn dump_classified_confirmed_leaks_as_text() {
+        let registry = OutputSinkRegistry::default();
+        let classifier = SeverityClassifier::new(None).unwrap();
+        let mut output = vec![];
+        dump_confirmed_leaks(
+            &mut output,
+            &classify_confirmed_leaks(confirmed_leaks(), &classifier, None),
+            registry.get("text").expect("Missing text sink"),
+            &ScanOutcome::default(),
+            false,
+            None,
+            false,
+        )
+        .expect("dump_confirmed_leaks failed");
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\"leak\" (string literal, info severity) leaked at offset 0x10 in \"a.out\" [declared at file.cc:1]\n"
+        );
+    }
+
+    #[test]
+    fn dump_confirmed_leaks_as_yaml_and_toml() {
+        let registry = OutputSinkRegistry::default();
+        let mut output = vec![];
+        dump_confirmed_leaks(
+            &mut output,
+            &confirmed_leaks_at(&[16, 32]),
+            registry.get("yaml").expect("Missing YAML sink"),
+            &ScanOutcome::default(),
+            false,
+            None,
+            false,
+        )
+        .expect("dump_confirmed_leaks failed");
+        let report: serde_yaml::Value = serde_yaml::from_slice(&output).unwrap();
+        assert_eq!(report["leaks"][1]["data"], "leak");
+        assert_eq!(report["leaks"][1]["location"]["binary"]["offset"], 32);
+
+        let metadata = ReportMetadata {
+            cpplumber_version: PKG_VERSION.into(),
+            libclang_version: "clang version 14.0.0".into(),
+            host: HostEnvironment::current(),
+            // Null values cannot be represented in TOML
+            options: serde_json::json!({"minimum_leak_size": 4, "config": null}),
+            config_file: None,
+            config: None,
+            suppressions_file: None,
+        };
+        let mut output = vec![];
+        dump_confirmed_leaks(
+            &mut output,
+            &confirmed_leaks_at(&[16, 32]),
+            &TomlSink::with_metadata(metadata),
+            &ScanOutcome::default(),
+            false,
+            None,
+            false,
+        )
+        .expect("dump_confirmed_leaks failed");
+        let report: toml::Value = toml::from_slice(&output).unwrap();
+        assert_eq!(report["leaks"][1]["data"].as_str(), Some("leak"));
+        assert_eq!(
+            report["unique_values"][0]["leaks"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            report["metadata"]["options"]["minimum_leak_size"].as_integer(),
+            Some(4)
+        );
+        assert!(report["metadata"]["options"].get("config").is_none());
+    }
+
+    #[test]
+    fn load_and_dump_json_report() {
+        let mut binary_location = BinaryLocation::new(Arc::new(PathBuf::from("a.out")), 16);
+        binary_location.virtual_address = Some(0x1010);
+        let leak = ConfirmedLeak::builder(
+            LeakedDataType::StringLiteral,
+            Arc::new("leak".to_string()),
+            Arc::new(SourceLocation {
+                file: PathBuf::from("file.cc"),
+                line: 1,
+            }),
+            binary_location,
+        )
+        .severity(Severity::High)
+        .confidence(80)
+        .pass(Arc::new("wide".to_string()))
+        .build();
+        let confirmed_leaks: BTreeSet<ConfirmedLeakWithUniqueLocation> =
+            [leak.clone().into()].into_iter().collect();
+
+        let mut output = vec![];
+        dump_confirmed_leaks(
+            &mut output,
+            &confirmed_leaks,
+            &JsonSink::default(),
+            &ScanOutcome {
+                partial: true,
+                ..Default::default()
+            },
+            false,
+            None,
+            false,
+        )
+        .expect("dump_confirmed_leaks failed");
+        let report = load_json_report(output.as_slice()).expect("load_json_report failed");
+        assert!(report.partial);
+        assert!(report.metadata.is_none());
+        // Note: Match sizes aren't part of reports
+        assert_eq!(report.leaks, vec![ConfirmedLeak { size: 0, ..leak }]);
+
+        // Loaded reports can be written in other formats
+        let registry = OutputSinkRegistry::default();
+        let mut output = vec![];
+        dump_confirmed_leaks(
+            &mut output,
+            &report.confirmed_leaks(),
+            registry.get("text").expect("Missing text sink"),
+            &ScanOutcome {
+                partial: report.partial,
+                ..Default::default()
+            },
+            false,
+            None,
+            false,
+        )
+        .expect("dump_confirmed_leaks failed");
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!(
+                "{}\n\"leak\" (string literal, high severity, confidence 80, pass 'wide') leaked at offset 0x10 (address 0x1010) in \"a.out\" [declared at file.cc:1]\n",
+                PARTIAL_REPORT_NOTICE
+            )
+        );
+
+        // Reports written in newer formats are rejected
+        let report = format!(
+            r#"{{"version": {{"executable": "99.0.0", "format": {}}}, "partial": false, "leaks": []}}"#,
+            REPORT_FORMAT_VERSION + 1
+        );
+        assert!(load_json_report(report.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn dump_redacted_confirmed_leaks() {
+        let registry = OutputSinkRegistry::default();
+        let mut output = vec![];
+        dump_confirmed_leaks(
+            &mut output,
+            &confirmed_leaks(),
+            registry.get("text").expect("Missing text sink"),
+            &ScanOutcome::default(),
+            true,
+            None,
+            false,
+        )
+        .expect("dump_confirmed_leaks failed");
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(!output.contains("\"leak\""));
+        assert!(output.starts_with("\"[sha256:"));
+        assert!(output.contains("leaked at offset 0x10 in \"a.out\" [declared at file.cc:1]"));
+    }
+
+    #[test]
+    fn redact_values() {
+        let redacted = redact_value("my_secret_value");
+        assert!(redacted.starts_with("my…ue [sha256:"));
+        assert!(!redacted.contains("secret"));
+        // Redaction is deterministic
+        assert_eq!(redacted, redact_value("my_secret_value"));
+        assert_ne!(redacted, redact_value("my_other_value"));
+
+        assert!(!redact_value("abcd").contains("ab"));
+        // Short values are entirely masked
+        assert!(redact_value("secret7").starts_with("[sha256:"));
+        assert!(redact_value("secret08").starts_with("se…08 [sha256:"));
+    }
+
+    #[test]
+    fn truncate_values() {
+        assert_eq!(truncate_value("my_secret_value", 4), "my_s…");
+        assert_eq!(truncate_value("leak", 4), "leak");
+        assert_eq!(truncate_value("clé_secrète", 3), "clé…");
+    }
+
+    #[test]
+    fn dump_truncated_confirmed_leaks() {
+        let registry = OutputSinkRegistry::default();
+        let mut output = vec![];
+        dump_confirmed_leaks(
+            &mut output,
+            &confirmed_leaks(),
+            registry.get("text").expect("Missing text sink"),
+            &ScanOutcome::default(),
+            false,
+            Some(2),
+            false,
+        )
+        .expect("dump_confirmed_leaks failed");
+
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .starts_with("\"le…\" (string literal) leaked"));
+    }
+
+    #[test]
+    fn dump_truncated_confirmed_leaks_with_common_prefix() {
+        let mut confirmed_leaks = confirmed_leaks_at(&[16]);
+        let mut other_leak = ConfirmedLeak::clone(confirmed_leaks.iter().next().unwrap());
+        other_leak.data = Arc::new("leaked".to_string());
+        other_leak.location.binary.offset = 32;
+        confirmed_leaks.insert(other_leak.into());
+
+        let dump = |output_sink: &dyn OutputSink, ignore_multiple_locations: bool| {
+            let mut output = vec![];
+            dump_confirmed_leaks(
+                &mut output,
+                &confirmed_leaks,
+                output_sink,
+                &ScanOutcome::default(),
+                false,
+                Some(2),
+                ignore_multiple_locations,
+            )
+            .expect("dump_confirmed_leaks failed");
+            String::from_utf8(output).unwrap()
+        };
+
+        // Values look the same once truncated, but are still distinct
+        let output = dump(&TextSink::default(), true);
+        assert_eq!(output.lines().count(), 2);
+        assert!(!output.contains("times"));
+        assert_eq!(
+            dump(&TextSink::grouped_by(LeakGrouping::Value), false),
+            "\"le…\" (1 leak(s)):\n\
+            \x20   \"le…\" (string literal) leaked at offset 0x10 in \"a.out\" [declared at file.cc:1]\n\
+            \"le…\" (1 leak(s)):\n\
+            \x20   \"le…\" (string literal) leaked at offset 0x20 in \"a.out\" [declared at file.cc:1]\n"
+        );
+        let report: serde_json::Value =
+            serde_json::from_str(&dump(&JsonSink::default(), false)).unwrap();
+        assert_eq!(report["unique_values"].as_array().unwrap().len(), 2);
+        assert_eq!(report["leaks"][0]["count"], 1);
+    }
+
+    #[test]
+    fn register_custom_sink() {
+        let mut registry = OutputSinkRegistry::default();
+        assert!(registry.get("count").is_err());
+
+        registry.register("count", Box::new(CountSink));
+        let mut output = vec![];
+        dump_confirmed_leaks(
+            &mut output,
+            &confirmed_leaks(),
+            registry.get("count").expect("Missing custom sink"),
+            &ScanOutcome::default(),
+            false,
+            None,
+            false,
+        )
+        .expect("dump_confirmed_leaks failed");
+
+        assert_eq!(output, b"1");
     }
 }
@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use glob::glob;
+
+use crate::information_leak::{LeakedDataType, PotentialLeak, SourceLocation};
+use crate::statistics::{DropReason, ExtractionStatistics};
+
+/// Supported resource file formats
+enum ResourceFormat {
+    Json,
+    Yaml,
+    Ini,
+}
+
+impl ResourceFormat {
+    /// Deduces the format of a resource file from its extension
+    fn from_path(file_path: &Path) -> Option<Self> {
+        let extension = file_path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "json" => Some(Self::Json),
+            "yml" | "yaml" => Some(Self::Yaml),
+            "ini" => Some(Self::Ini),
+            _ => None,
+        }
+    }
+}
+
+/// Extracts string values from the resource files (i.e., JSON, YAML and INI
+/// files) matching the given glob expressions.
+pub fn extract_artifacts_from_resource_files(
+    resource_path_globs: &[String],
+    minimum_leak_size: usize,
+    statistics: &mut ExtractionStatistics,
+) -> Result<Vec<PotentialLeak>> {
+    let mut potential_leaks = vec![];
+    for glob_expression in resource_path_globs {
+        let paths = glob(glob_expression)
+            .map_err(|_| anyhow!("'{}' is not a valid glob expression", glob_expression))?;
+        for path in paths {
+            let path = path?;
+            if let Some(format) = ResourceFormat::from_path(&path) {
+                let (leaks, small_leaks): (Vec<_>, Vec<_>) =
+                    extract_artifacts_from_resource_file(&path, format)
+                        .with_context(|| format!("Failed to parse '{}'", path.display()))?
+                        .into_iter()
+                        .partition(|leak| leak.bytes.len() >= minimum_leak_size);
+                statistics.record_all(DropReason::TooSmall, &small_leaks);
+                potential_leaks.extend(leaks);
+            } else {
+                log::warn!(
+                    "'{}' is not a supported resource file, ignoring it",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    Ok(potential_leaks)
+}
+
+fn extract_artifacts_from_resource_file(
+    file_path: &Path,
+    format: ResourceFormat,
+) -> Result<Vec<PotentialLeak>> {
+    let file_content = fs::read_to_string(file_path)?;
+    let file_path = file_path.canonicalize()?;
+
+    // Gather string values along with their line number when known
+    let values: Vec<(String, Option<usize>)> = match format {
+        ResourceFormat::Json => {
+            let mut values = vec![];
+            collect_json_strings(&serde_json::from_str(&file_content)?, &mut values);
+            values.into_iter().map(|value| (value, None)).collect()
+        }
+        ResourceFormat::Yaml => {
+            let mut values = vec![];
+            collect_yaml_strings(&serde_yaml::from_str(&file_content)?, &mut values);
+            values.into_iter().map(|value| (value, None)).collect()
+        }
+        ResourceFormat::Ini => parse_ini_values(&file_content)
+            .into_iter()
+            .map(|(value, line)| (value, Some(line)))
+            .collect(),
+    };
+
+    let mut locator = ScalarLocator::new(&file_content);
+    Ok(values
+        .into_iter()
+        .map(|(value, line)| {
+            // Look the value up in the file if the parser doesn't give us its
+            // location
+            let line = line.unwrap_or_else(|| locator.next_line_number(&value));
+            PotentialLeak {
+                data_type: LeakedDataType::ResourceString,
+                bytes: value.as_bytes().to_vec(),
+                code_unit_size: 1,
+                data: Arc::new(value),
+                declaration_metadata: Arc::new(SourceLocation {
+                    file: file_path.clone(),
+                    line: line as u64,
+                }),
+            }
+        })
+        .collect())
+}
+
+fn collect_json_strings(value: &serde_json::Value, accum: &mut Vec<String>) {
+    match value {
+        serde_json::Value::String(string) => accum.push(string.clone()),
+        serde_json::Value::Array(array) => {
+            array
+                .iter()
+                .for_each(|value| collect_json_strings(value, accum));
+        }
+        serde_json::Value::Object(object) => {
+            object
+                .values()
+                .for_each(|value| collect_json_strings(value, accum));
+        }
+        _ => {}
+    }
+}
+
+fn collect_yaml_strings(value: &serde_yaml::Value, accum: &mut Vec<String>) {
+    match value {
+        serde_yaml::Value::String(string) => accum.push(string.clone()),
+        serde_yaml::Value::Sequence(sequence) => {
+            sequence
+                .iter()
+                .for_each(|value| collect_yaml_strings(value, accum));
+        }
+        serde_yaml::Value::Mapping(mapping) => {
+            mapping
+                .values()
+                .for_each(|value| collect_yaml_strings(value, accum));
+        }
+        serde_yaml::Value::Tagged(tagged) => collect_yaml_strings(&tagged.value, accum),
+        _ => {}
+    }
+}
+
+/// Returns the non-empty values of all the `key=value` entries found in the
+/// given INI content, with their (1-based) line numbers.
+fn parse_ini_values(ini_content: &str) -> Vec<(String, usize)> {
+    ini_content
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let line = line.trim();
+            // Skip comments and section headers
+            if line.starts_with(';') || line.starts_with('#') || line.starts_with('[') {
+                return None;
+            }
+
+            let (_, value) = line.split_once('=')?;
+            let value = value.trim();
+            // Remove optional quotes
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+            if value.is_empty() {
+                None
+            } else {
+                Some((value.to_string(), i + 1))
+            }
+        })
+        .collect()
+}
+
+/// Locates scalar values in JSON or YAML content, for parsers that don't
+/// record where values are declared. A value that is looked up several times
+/// is located at its next occurrence each time, so that repeated values are
+/// reported at their own lines.
+pub(crate) struct ScalarLocator<'a> {
+    content: &'a str,
+    /// Position to start searching values from
+    start_position: usize,
+    /// Position to resume the search of each value from
+    next_positions: HashMap<String, usize>,
+}
+
+impl<'a> ScalarLocator<'a> {
+    pub(crate) fn new(content: &'a str) -> Self {
+        Self::starting_at(content, 0)
+    }
+
+    /// Creates a locator that ignores the values declared before
+    /// `start_position` in `content`
+    pub(crate) fn starting_at(content: &'a str, start_position: usize) -> Self {
+        Self {
+            content,
+            start_position,
+            next_positions: HashMap::new(),
+        }
+    }
+
+    /// Returns the (1-based) line number of the next occurrence of `value` as
+    /// a whole scalar (i.e., not as a part of another value nor as a mapping
+    /// key), or 0 if it cannot be found.
+    pub(crate) fn next_line_number(&mut self, value: &str) -> usize {
+        if value.is_empty() {
+            return 0;
+        }
+
+        let mut search_position = self
+            .next_positions
+            .get(value)
+            .copied()
+            .unwrap_or(self.start_position);
+        while let Some(offset) = self.content[search_position..].find(value) {
+            let position = search_position + offset;
+            let end = position + value.len();
+            if self.is_scalar(position, end) {
+                self.next_positions.insert(value.to_string(), end);
+                return self.content[..position].matches('\n').count() + 1;
+            }
+            search_position = position + value.chars().next().map_or(1, char::len_utf8);
+        }
+
+        // Stop looking for this value
+        self.next_positions
+            .insert(value.to_string(), self.content.len());
+        0
+    }
+
+    /// Indicates if `content[start..end]` is a whole scalar value
+    fn is_scalar(&self, start: usize, end: usize) -> bool {
+        let before = &self.content[..start];
+        let after = &self.content[end..];
+        // Strip matching quotes
+        let (before, after) = match before.chars().last() {
+            Some(quote @ ('"' | '\'')) => match after.strip_prefix(quote) {
+                Some(after) => (&before[..before.len() - 1], after),
+                None => return false,
+            },
+            _ => (before, after),
+        };
+
+        let preceded_by_separator = match before.trim_end_matches([' ', '\t']).chars().last() {
+            None => true,
+            Some(previous) => matches!(previous, '\n' | '-' | ':' | '[' | '{' | ','),
+        };
+        let followed_by_separator = match after.chars().next() {
+            None => true,
+            Some(next) => matches!(next, '\r' | '\n' | ' ' | '\t' | ',' | ']' | '}' | '#'),
+        };
+        // Keys are followed by a colon
+        let is_key = after.trim_start_matches([' ', '\t']).starts_with(':');
+
+        preceded_by_separator && followed_by_separator && !is_key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESOURCES_PATH: &str = "tests/data/resources";
+
+    fn extract_values(file_name: &str) -> Vec<(String, u64)> {
+        let glob_expression = format!(
+            "{}/{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            RESOURCES_PATH,
+            file_name
+        );
+        let mut values: Vec<(String, u64)> = extract_artifacts_from_resource_files(
+            &[glob_expression],
+            4,
+            &mut ExtractionStatistics::default(),
+        )
+        .expect("extract_artifacts_from_resource_files failed")
+        .into_iter()
+        .map(|leak| (leak.data.to_string(), leak.declaration_metadata.line))
+        .collect();
+        // JSON objects' fields aren't ordered
+        values.sort_by_key(|(_, line)| *line);
+
+        values
+    }
+
+    #[test]
+    fn extract_artifacts_from_json_file() {
+        assert_eq!(
+            extract_values("strings.json"),
+            vec![
+                ("https://internal.corp.example".to_string(), 2),
+                ("admin_password".to_string(), 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn extract_artifacts_from_yaml_file() {
+        assert_eq!(
+            extract_values("strings.yml"),
+            vec![
+                ("https://internal.corp.example".to_string(), 1),
+                ("admin_password".to_string(), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn extract_artifacts_from_ini_file() {
+        assert_eq!(
+            extract_values("strings.ini"),
+            vec![
+                ("https://internal.corp.example".to_string(), 3),
+                ("admin_password".to_string(), 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn locate_repeated_scalars() {
+        let content =
+            "password: password\nusers:\n  - \"admin\"\n  - password_backup\n  - password\n";
+        let mut locator = ScalarLocator::new(content);
+        assert_eq!(locator.next_line_number("password"), 1);
+        assert_eq!(locator.next_line_number("password"), 5);
+        assert_eq!(locator.next_line_number("password"), 0);
+        assert_eq!(locator.next_line_number("admin"), 3);
+        assert_eq!(locator.next_line_number("users"), 0);
+
+        let content = "{\n  \"key\": \"value\",\n  \"other\": [\"value\"]\n}";
+        let mut locator = ScalarLocator::new(content);
+        assert_eq!(locator.next_line_number("key"), 0);
+        assert_eq!(locator.next_line_number("value"), 2);
+        assert_eq!(locator.next_line_number("value"), 3);
+    }
+
+    #[test]
+    fn extract_artifacts_from_unsupported_file() {
+        assert!(extract_values("*.txt").is_empty());
+    }
+}
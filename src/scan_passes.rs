@@ -0,0 +1,148 @@
+//! Multi-pass scans, which layer expensive detection modes (e.g., encoding
+//! variants, partial leaks) onto a cheap baseline scan, in a single run.
+//! Passes are declared in the configuration file:
+//!
+//! ```yaml
+//! passes:
+//!   - name: exact
+//!   - name: wide
+//!     all_encodings: true
+//!   - name: partial
+//!     partial_leaks: 8
+//! ```
+
+use std::collections::{BTreeSet, HashSet};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+
+use crate::information_leak::ConfirmedLeak;
+
+/// Detection settings of a scan pass. Settings are enabled on top of the
+/// ones given on the command line.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScanPass {
+    /// Name used to label the leaks found by the pass
+    pub name: String,
+    /// Also look for UTF-16LE versions of narrow ASCII artifacts
+    #[serde(default)]
+    pub utf16_variants: bool,
+    /// Also look for string artifacts in all the encodings they may be stored
+    /// with
+    #[serde(default)]
+    pub all_encodings: bool,
+    /// Only match wide strings at offsets aligned on their code unit size
+    #[serde(default)]
+    pub aligned_wide_strings: bool,
+    /// Also report artifacts that only partially leaked, as long as at least
+    /// the given number of bytes is found
+    pub partial_leaks: Option<usize>,
+    /// Also look for leaks in the files contained in installers and archives
+    #[serde(default)]
+    pub unpack_containers: bool,
+}
+
+/// Makes sure passes can be told apart in reports
+pub fn validate_scan_passes(passes: &[ScanPass]) -> Result<()> {
+    let mut pass_names = HashSet::new();
+    for pass in passes {
+        if pass.name.is_empty() {
+            return Err(anyhow!("Scan passes must have a name"));
+        }
+        if !pass_names.insert(pass.name.as_str()) {
+            return Err(anyhow!("Scan pass '{}' is declared twice", pass.name));
+        }
+    }
+
+    Ok(())
+}
+
+/// Merges the leaks found by a pass into the leaks found by the previous
+/// passes, labeling them with the pass' name. Leaks already found by a
+/// previous pass keep their label. Returns the number of new leaks.
+pub fn merge_pass_leaks<SortedConfirmedLeak>(
+    merged_leaks: &mut BTreeSet<SortedConfirmedLeak>,
+    pass_leaks: BTreeSet<SortedConfirmedLeak>,
+    pass_name: &Arc<String>,
+) -> usize
+where
+    SortedConfirmedLeak: Into<ConfirmedLeak> + From<ConfirmedLeak> + Ord,
+{
+    let leak_count = merged_leaks.len();
+    for leak in pass_leaks {
+        let mut leak: ConfirmedLeak = leak.into();
+        leak.pass = Some(pass_name.clone());
+        // Note: Existing leaks aren't replaced
+        merged_leaks.insert(leak.into());
+    }
+
+    merged_leaks.len() - leak_count
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::information_leak::{
+        BinaryLocation, ConfirmedLeakWithUniqueLocation, LeakedDataType, SourceLocation,
+    };
+
+    fn confirmed_leak(offset: u64) -> ConfirmedLeakWithUniqueLocation {
+        ConfirmedLeak::builder(
+            LeakedDataType::StringLiteral,
+            Arc::new("secret".to_string()),
+            Arc::new(SourceLocation {
+                file: PathBuf::from("main.cc"),
+                line: 1,
+            }),
+            BinaryLocation::new(Arc::new(PathBuf::from("a.out")), offset),
+        )
+        .build()
+        .into()
+    }
+
+    #[test]
+    fn merge_leaks_of_passes() {
+        let mut leaks = BTreeSet::new();
+        let exact_pass = Arc::new("exact".to_string());
+        let wide_pass = Arc::new("wide".to_string());
+
+        let new_leak_count = merge_pass_leaks(
+            &mut leaks,
+            BTreeSet::from([confirmed_leak(0x10)]),
+            &exact_pass,
+        );
+        assert_eq!(new_leak_count, 1);
+        let new_leak_count = merge_pass_leaks(
+            &mut leaks,
+            BTreeSet::from([confirmed_leak(0x10), confirmed_leak(0x40)]),
+            &wide_pass,
+        );
+        assert_eq!(new_leak_count, 1);
+
+        let passes: Vec<(u64, &str)> = leaks
+            .iter()
+            .map(|leak| {
+                (
+                    leak.location.binary.offset,
+                    leak.pass.as_ref().unwrap().as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(passes, vec![(0x10, "exact"), (0x40, "wide")]);
+    }
+
+    #[test]
+    fn validate_pass_names() {
+        let pass = |name: &str| ScanPass {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        assert!(validate_scan_passes(&[pass("exact"), pass("wide")]).is_ok());
+        assert!(validate_scan_passes(&[pass("exact"), pass("exact")]).is_err());
+        assert!(validate_scan_passes(&[pass("")]).is_err());
+    }
+}
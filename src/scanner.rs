@@ -0,0 +1,911 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context, Result};
+use rayon::prelude::*;
+
+use crate::analysis_cache::AnalysisCache;
+use crate::checkpoint::Checkpoint;
+use crate::com_guids::extract_com_guids;
+use crate::compilation_database::{
+    adjust_compile_arguments, filter_compile_commands_by_path, generate_compilation_database,
+    implied_target_names, is_target_binary, newer_source_file, select_entry_points, select_shard,
+    CompileCommands, ProjectConfiguration, Shard,
+};
+use crate::embedded_files::extract_embedded_files;
+use crate::information_leak::{
+    ConfirmedLeakWithUniqueLocation, ExtractorSelection, PotentialLeak, WideCharMode,
+};
+use crate::isolation::WorkerCommand;
+use crate::source_paths::generate_source_path_artifacts;
+use crate::statistics::ExtractionStatistics;
+use crate::suppressions::Suppressions;
+use crate::target_profile::{retain_mangling_scheme, TargetProfile};
+use crate::{
+    add_encoding_variants, add_utf16_variants, deduplicate_artifacts,
+    extract_artifacts_from_source_files, filter_suppressed_artifacts_by_origin,
+    filter_suppressed_artifacts_by_type, filter_suppressed_artifacts_by_value,
+    filter_suppressed_files, prioritize_compile_commands, ExtractionOptions, LeakMatcher,
+};
+
+/// Describes where the source files to analyze come from
+#[derive(Debug, Clone)]
+pub enum ProjectSource {
+    /// JSON compilation database (i.e., `compile_commands.json` file), or
+    /// Visual Studio project (`.vcxproj`) or solution (`.sln`)
+    CompilationDatabase(PathBuf),
+    /// List of source files, compiled with the given arguments
+    Manual {
+        /// Paths of the source files (can be glob expressions)
+        source_path_globs: Vec<String>,
+        /// Additional include directories
+        include_directories: Vec<String>,
+        /// Additional preprocessor definitions
+        compile_definitions: Vec<String>,
+    },
+    /// JSON compilation database, along with source files that aren't part of
+    /// it (e.g., generated sources), compiled with the given arguments
+    Combined {
+        /// Path to the `compile_commands.json` file (or Visual Studio project)
+        project_file_path: PathBuf,
+        /// Paths of the additional source files (can be glob expressions)
+        source_path_globs: Vec<String>,
+        /// Include directories used for the additional source files
+        include_directories: Vec<String>,
+        /// Preprocessor definitions used for the additional source files
+        compile_definitions: Vec<String>,
+    },
+}
+
+/// Extracts artifacts from a C/C++ project and looks for them in binary files.
+/// Options follow the defaults of the command-line tool.
+pub struct Scanner {
+    project: ProjectSource,
+    suppressions: Option<Suppressions>,
+    minimum_leak_size: usize,
+    report_system_headers: bool,
+    system_include_roots: Vec<PathBuf>,
+    extractor_selection: ExtractorSelection,
+    extractors: Option<Vec<String>>,
+    utf16_variants: bool,
+    all_encodings: bool,
+    wide_char_mode: Option<WideCharMode>,
+    target_profile: Option<TargetProfile>,
+    aligned_wide_strings: bool,
+    minimum_partial_leak_size: Option<usize>,
+    match_suffixes: bool,
+    cache_directory: Option<PathBuf>,
+    reuse_cache_only: bool,
+    checkpoint_path: Option<PathBuf>,
+    resume: bool,
+    parsing_worker: Option<WorkerCommand>,
+    keep_going: bool,
+    extra_args: Vec<String>,
+    remove_args: Vec<String>,
+    entry_points: Vec<PathBuf>,
+    only_files: Vec<String>,
+    exclude_files: Vec<String>,
+    shard: Option<Shard>,
+    minimum_file_count: usize,
+    minimum_artifact_count: usize,
+    expected_binary: Option<PathBuf>,
+    detect_embedded_files: bool,
+    detect_com_guids: bool,
+    detect_source_paths: bool,
+    time_budget: Option<Duration>,
+    unpack_containers: bool,
+    dedup_nested_artifacts: bool,
+}
+
+impl Scanner {
+    /// Creates a scanner for the given project
+    pub fn new(project: ProjectSource) -> Self {
+        Self {
+            project,
+            suppressions: None,
+            minimum_leak_size: 4,
+            report_system_headers: false,
+            system_include_roots: vec![],
+            extractor_selection: ExtractorSelection::default(),
+            extractors: None,
+            utf16_variants: false,
+            all_encodings: false,
+            wide_char_mode: None,
+            target_profile: None,
+            aligned_wide_strings: false,
+            minimum_partial_leak_size: None,
+            match_suffixes: false,
+            cache_directory: None,
+            reuse_cache_only: false,
+            checkpoint_path: None,
+            resume: false,
+            parsing_worker: None,
+            keep_going: false,
+            extra_args: vec![],
+            remove_args: vec![],
+            entry_points: vec![],
+            only_files: vec![],
+            exclude_files: vec![],
+            shard: None,
+            minimum_file_count: 1,
+            minimum_artifact_count: 1,
+            expected_binary: None,
+            detect_embedded_files: false,
+            detect_com_guids: false,
+            detect_source_paths: false,
+            time_budget: None,
+            unpack_containers: false,
+            dedup_nested_artifacts: false,
+        }
+    }
+
+    /// Ignores the files and artifacts matched by the given suppressions
+    pub fn suppressions(mut self, suppressions: Suppressions) -> Self {
+        self.suppressions = Some(suppressions);
+        self
+    }
+
+    /// Sets the minimum size in bytes, for a leak to be reported
+    pub fn minimum_leak_size(mut self, minimum_leak_size: usize) -> Self {
+        self.minimum_leak_size = minimum_leak_size;
+        self
+    }
+
+    /// Reports leaks for data declared in system headers
+    pub fn report_system_headers(mut self, report_system_headers: bool) -> Self {
+        self.report_system_headers = report_system_headers;
+        self
+    }
+
+    /// Treats the headers located under the given directories (e.g., SDKs
+    /// installed inside the project) as system headers
+    pub fn system_include_roots(mut self, system_include_roots: Vec<PathBuf>) -> Self {
+        self.system_include_roots = system_include_roots;
+        self
+    }
+
+    /// Ignores leaks of string literals
+    pub fn ignore_string_literals(mut self, ignore_string_literals: bool) -> Self {
+        self.extractor_selection.ignore_string_literals = ignore_string_literals;
+        self
+    }
+
+    /// Ignores leaks of struct and class names
+    pub fn ignore_struct_names(mut self, ignore_struct_names: bool) -> Self {
+        self.extractor_selection.ignore_struct_names = ignore_struct_names;
+        self
+    }
+
+    /// Ignores leaks of union names
+    pub fn ignore_union_names(mut self, ignore_union_names: bool) -> Self {
+        self.extractor_selection.ignore_union_names = ignore_union_names;
+        self
+    }
+
+    /// Ignores leaks of type names introduced with `typedef` or `using`
+    pub fn ignore_typedef_names(mut self, ignore_typedef_names: bool) -> Self {
+        self.extractor_selection.ignore_typedef_names = ignore_typedef_names;
+        self
+    }
+
+    /// Ignores leaks of enum and enumerator names
+    pub fn ignore_enum_names(mut self, ignore_enum_names: bool) -> Self {
+        self.extractor_selection.ignore_enum_names = ignore_enum_names;
+        self
+    }
+
+    /// Reports leaks of function and method names
+    pub fn include_function_names(mut self, include_function_names: bool) -> Self {
+        self.extractor_selection.include_function_names = include_function_names;
+        self
+    }
+
+    /// Reports leaks of struct and class field names
+    pub fn include_field_names(mut self, include_field_names: bool) -> Self {
+        self.extractor_selection.include_field_names = include_field_names;
+        self
+    }
+
+    /// Reports leaks of comments' content (e.g., `TODO` notes or authors)
+    pub fn include_comments(mut self, include_comments: bool) -> Self {
+        self.extractor_selection.include_comments = include_comments;
+        self
+    }
+
+    /// Reports leaks of the values of macros defined as string literals (e.g.,
+    /// `#define API_KEY "..."`)
+    pub fn include_macro_values(mut self, include_macro_values: bool) -> Self {
+        self.extractor_selection.include_macro_values = include_macro_values;
+        self
+    }
+
+    /// Selects the categories of artifacts to extract at once, instead of
+    /// through the `ignore_*` and `include_*` options
+    pub fn extractor_selection(mut self, extractor_selection: ExtractorSelection) -> Self {
+        self.extractor_selection = extractor_selection;
+        self
+    }
+
+    /// Extracts artifacts with the given built-in extractors (e.g.,
+    /// `string_literals`, `type_names`) instead of the ones enabled by the
+    /// `ignore_*` and `include_*` options
+    pub fn extractors(mut self, extractors: Vec<String>) -> Self {
+        self.extractors = Some(extractors);
+        self
+    }
+
+    /// Also looks for UTF-16LE versions of narrow ASCII artifacts
+    pub fn utf16_variants(mut self, utf16_variants: bool) -> Self {
+        self.utf16_variants = utf16_variants;
+        self
+    }
+
+    /// Also looks for string artifacts in all the encodings they may be
+    /// represented with (i.e., UTF-8, UTF-16LE and UTF-32LE)
+    pub fn all_encodings(mut self, all_encodings: bool) -> Self {
+        self.all_encodings = all_encodings;
+        self
+    }
+
+    /// Encodes wide strings as done by compilers targeting the given platform,
+    /// instead of the current one
+    pub fn wide_char_mode(mut self, wide_char_mode: WideCharMode) -> Self {
+        self.wide_char_mode = Some(wide_char_mode);
+        self
+    }
+
+    /// Only looks for artifacts as the given target would encode them (e.g.,
+    /// its wide strings and mangled type names), unless the encoding of wide
+    /// strings is set explicitly
+    pub fn target_profile(mut self, target_profile: TargetProfile) -> Self {
+        self.target_profile = Some(target_profile);
+        self
+    }
+
+    /// Only matches wide strings at offsets aligned on their code unit size
+    pub fn aligned_wide_strings(mut self, aligned_wide_strings: bool) -> Self {
+        self.aligned_wide_strings = aligned_wide_strings;
+        self
+    }
+
+    /// Also reports artifacts that only partially leaked, as long as at least
+    /// `minimum_size` bytes are found
+    pub fn partial_leaks(mut self, minimum_size: usize) -> Self {
+        self.minimum_partial_leak_size = Some(minimum_size);
+        self
+    }
+
+    /// Also reports artifacts whose beginning or end partially leaked (e.g.,
+    /// strings folded or truncated by compilers), as long as at least
+    /// `minimum_size` bytes are found
+    pub fn match_substrings(mut self, minimum_size: usize) -> Self {
+        self.minimum_partial_leak_size = Some(minimum_size);
+        self.match_suffixes = true;
+        self
+    }
+
+    /// Caches the artifacts extracted from each translation unit in the given
+    /// directory, so that unchanged files aren't parsed again by later scans
+    pub fn cache_directory<P: Into<PathBuf>>(mut self, directory: P) -> Self {
+        self.cache_directory = Some(directory.into());
+        self
+    }
+
+    /// Only uses the artifacts stored in the cache directory, without parsing
+    /// any source file: extraction fails if some translation units don't have
+    /// an up-to-date cache entry
+    pub fn reuse_cache_only(mut self, reuse_cache_only: bool) -> Self {
+        self.reuse_cache_only = reuse_cache_only;
+        self
+    }
+
+    /// Records the artifacts extracted from each translation unit into the
+    /// given checkpoint file, so that interrupted extractions can be resumed
+    pub fn checkpoint<P: Into<PathBuf>>(mut self, checkpoint_path: P) -> Self {
+        self.checkpoint_path = Some(checkpoint_path.into());
+        self
+    }
+
+    /// Resumes the extraction recorded into the checkpoint file, instead of
+    /// parsing every translation unit again
+    pub fn resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    /// Parses each translation unit in a separate worker process started with
+    /// the given command, so that crashes of libclang only skip the
+    /// translation unit being parsed
+    pub fn parsing_worker(mut self, parsing_worker: WorkerCommand) -> Self {
+        self.parsing_worker = Some(parsing_worker);
+        self
+    }
+
+    /// Skips the source files that fail to parse instead of failing
+    pub fn keep_going(mut self, keep_going: bool) -> Self {
+        self.keep_going = keep_going;
+        self
+    }
+
+    /// Appends the given arguments to every compile command
+    pub fn extra_args(mut self, extra_args: Vec<String>) -> Self {
+        self.extra_args = extra_args;
+        self
+    }
+
+    /// Removes the arguments matching the given glob expressions (e.g., "/MP"
+    /// or "-W*") from every compile command
+    pub fn remove_args(mut self, remove_args: Vec<String>) -> Self {
+        self.remove_args = remove_args;
+        self
+    }
+
+    /// Only parses the given translation units (and the files they include)
+    pub fn entry_points(mut self, entry_points: Vec<PathBuf>) -> Self {
+        self.entry_points = entry_points;
+        self
+    }
+
+    /// Only parses the source files matching one of the given glob
+    /// expressions, and none of the `exclude_files` ones. Expressions match
+    /// files' paths as written in the compilation database, or relative to the
+    /// current directory.
+    pub fn only_files(mut self, only_files: Vec<String>, exclude_files: Vec<String>) -> Self {
+        self.only_files = only_files;
+        self.exclude_files = exclude_files;
+        self
+    }
+
+    /// Only parses the source files that belong to the given shard, to split
+    /// the extraction across multiple jobs
+    pub fn shard(mut self, shard: Shard) -> Self {
+        self.shard = Some(shard);
+        self
+    }
+
+    /// Fails if fewer source files than this are left to parse (e.g., because
+    /// of a wrong compilation database). Use 0 to disable.
+    /// Note: Sharded projects are checked as a whole.
+    pub fn minimum_file_count(mut self, minimum_file_count: usize) -> Self {
+        self.minimum_file_count = minimum_file_count;
+        self
+    }
+
+    /// Fails if fewer artifacts than this are left to look for (e.g., because
+    /// everything has been suppressed). Use 0 to disable.
+    /// Note: Shards aren't checked, as some of them may legitimately be empty.
+    pub fn minimum_artifact_count(mut self, minimum_artifact_count: usize) -> Self {
+        self.minimum_artifact_count = minimum_artifact_count;
+        self
+    }
+
+    /// Warns if the given binary doesn't seem to be built from the project, or
+    /// is older than its source files
+    pub fn expected_binary<P: Into<PathBuf>>(mut self, binary_file_path: P) -> Self {
+        self.expected_binary = Some(binary_file_path.into());
+        self
+    }
+
+    /// Also looks for the content of the files embedded into source files
+    /// (e.g., with `#embed` or `.incbin`)
+    pub fn detect_embedded_files(mut self, detect_embedded_files: bool) -> Self {
+        self.detect_embedded_files = detect_embedded_files;
+        self
+    }
+
+    /// Also looks for the COM GUIDs and interfaces declared in source files
+    pub fn detect_com_guids(mut self, detect_com_guids: bool) -> Self {
+        self.detect_com_guids = detect_com_guids;
+        self
+    }
+
+    /// Also looks for the paths of source files, as `__FILE__` expands to
+    pub fn detect_source_paths(mut self, detect_source_paths: bool) -> Self {
+        self.detect_source_paths = detect_source_paths;
+        self
+    }
+
+    /// Stops parsing source files once the given duration has elapsed, parsing
+    /// the files most likely to contain leaks first. Skipped files are counted
+    /// in the returned `ExtractionStatistics`.
+    pub fn time_budget(mut self, time_budget: Duration) -> Self {
+        self.time_budget = Some(time_budget);
+        self
+    }
+
+    /// Also looks for leaks in the files contained in installers and archives
+    /// (e.g., NSIS installers, Electron asar archives)
+    pub fn unpack_containers(mut self, unpack_containers: bool) -> Self {
+        self.unpack_containers = unpack_containers;
+        self
+    }
+
+    /// Doesn't report artifacts contained in other artifacts (e.g., "abc" in
+    /// "xabcx") where they're only found as part of the artifacts containing
+    /// them
+    pub fn dedup_nested_artifacts(mut self, dedup_nested_artifacts: bool) -> Self {
+        self.dedup_nested_artifacts = dedup_nested_artifacts;
+        self
+    }
+
+    /// Parses the project's source files and returns the artifacts that may
+    /// leak, along with statistics on the artifacts dropped in the process
+    pub fn extract_artifacts(&self) -> Result<(Vec<PotentialLeak>, ExtractionStatistics)> {
+        let mut statistics = ExtractionStatistics::default();
+        let potential_leaks = self.extract_source_artifacts(&mut statistics)?;
+        let potential_leaks = self.filter_artifacts(potential_leaks, &mut statistics);
+        self.check_artifact_count(potential_leaks.len())?;
+        let potential_leaks = self.add_artifact_variants(potential_leaks);
+
+        Ok((potential_leaks, statistics))
+    }
+
+    /// Parses the project's source files and returns the artifacts extracted
+    /// from them, before they're deduplicated and filtered. Artifacts dropped
+    /// in the process are recorded into `statistics`.
+    pub fn extract_source_artifacts(
+        &self,
+        statistics: &mut ExtractionStatistics,
+    ) -> Result<Vec<PotentialLeak>> {
+        let deadline = self
+            .time_budget
+            .map(|time_budget| Instant::now() + time_budget);
+        log::info!("Filtering suppressed files...");
+        let (compile_commands, use_file_path_from_arguments) = self.project_compile_commands()?;
+        // Warn about binaries that don't seem to be built from the project, as
+        // scanning them would give misleadingly clean results
+        if let Some(binary_file_path) = self
+            .expected_binary
+            .as_deref()
+            .filter(|path| path.is_file())
+        {
+            check_binary_origin(binary_file_path, &compile_commands);
+        }
+
+        // Look for embedded files before source files are parsed, if requested
+        let embedded_files = if self.detect_embedded_files {
+            log::info!("Looking for embedded files...");
+            extract_embedded_files(&compile_commands)?
+        } else {
+            vec![]
+        };
+        // Look for COM GUIDs and interfaces, if requested
+        let com_artifacts = if self.detect_com_guids {
+            log::info!("Looking for COM GUIDs...");
+            extract_com_guids(&compile_commands)?
+        } else {
+            vec![]
+        };
+        // Generate the paths `__FILE__` may expand to, if requested
+        let source_paths = if self.detect_source_paths {
+            generate_source_path_artifacts(&compile_commands)
+        } else {
+            vec![]
+        };
+
+        log::info!("Extracting artifacts from source files...");
+        let cache = match self.cache_directory {
+            Some(ref directory) if self.reuse_cache_only => Some(
+                AnalysisCache::open_reuse_only(directory)
+                    .with_context(|| "Cannot reuse the analysis cache")?,
+            ),
+            Some(ref directory) => Some(AnalysisCache::new(directory)?),
+            None => None,
+        };
+        // Parse the files most likely to contain leaks first if time is
+        // limited
+        let compile_commands = if deadline.is_some() {
+            prioritize_compile_commands(compile_commands, cache.as_ref())
+        } else {
+            compile_commands
+        };
+        let mut checkpoint = match self.checkpoint_path {
+            Some(ref checkpoint_path) if self.resume => Some(Checkpoint::resume(checkpoint_path)?),
+            Some(ref checkpoint_path) => Some(Checkpoint::new(checkpoint_path)),
+            None => None,
+        };
+        // Explicitly selected extractors take precedence over the options
+        // enabling or disabling categories of artifacts
+        let extractor_names = self
+            .extractors
+            .clone()
+            .unwrap_or_else(|| self.extractor_selection.extractor_names());
+        let mut potential_leaks = extract_artifacts_from_source_files(
+            compile_commands,
+            &ExtractionOptions {
+                use_file_path_from_arguments,
+                ignore_system_headers: !self.report_system_headers,
+                system_include_roots: self.system_include_roots.clone(),
+                extractor_names,
+                minimum_leak_size: self.minimum_leak_size,
+                wide_char_mode: self
+                    .wide_char_mode
+                    .or_else(|| self.target_profile.map(TargetProfile::wide_char_mode)),
+                keep_going: self.keep_going,
+                deadline,
+            },
+            cache.as_ref(),
+            checkpoint.as_mut(),
+            self.parsing_worker.as_ref(),
+            statistics,
+        )?;
+        // Only look for type names mangled the way the target does if known
+        if let Some(target_profile) = self.target_profile {
+            potential_leaks =
+                retain_mangling_scheme(potential_leaks, target_profile.mangling_scheme());
+        }
+        potential_leaks.extend(embedded_files);
+        potential_leaks.extend(com_artifacts);
+        potential_leaks.extend(source_paths);
+
+        Ok(potential_leaks)
+    }
+
+    /// Returns the project's source files, along with the headers translation
+    /// units included the last time they were parsed (if a cache directory is
+    /// used), so that changes to the project can be detected
+    pub fn source_files(&self) -> Result<Vec<PathBuf>> {
+        let (compile_commands, _) = self.project_compile_commands()?;
+        let mut source_files: BTreeSet<PathBuf> = compile_commands
+            .into_iter()
+            .map(|compile_cmd| compile_cmd.filename)
+            .collect();
+        if let Some(ref directory) = self.cache_directory {
+            source_files.extend(AnalysisCache::new(directory)?.dependencies());
+        }
+
+        Ok(source_files.into_iter().collect())
+    }
+
+    /// Returns the compile commands of the translation units to parse, and
+    /// whether source files' paths are part of their arguments
+    fn project_compile_commands(&self) -> Result<(CompileCommands, bool)> {
+        let project_config = match self.project {
+            ProjectSource::CompilationDatabase(ref project_file_path) => {
+                ProjectConfiguration::CompilationDatabase { project_file_path }
+            }
+            ProjectSource::Manual {
+                ref source_path_globs,
+                ref include_directories,
+                ref compile_definitions,
+            } => ProjectConfiguration::Manual {
+                source_path_globs,
+                include_directories,
+                compile_definitions,
+            },
+            ProjectSource::Combined {
+                ref project_file_path,
+                ref source_path_globs,
+                ref include_directories,
+                ref compile_definitions,
+            } => ProjectConfiguration::Combined {
+                project_file_path,
+                source_path_globs,
+                include_directories,
+                compile_definitions,
+            },
+        };
+        // Parse project file or process glob expressions
+        let compilation_db = generate_compilation_database(project_config)?;
+
+        // Filter suppressed files from the list, to avoid parsing files we're
+        // not interested in
+        let compile_commands = filter_suppressed_files(
+            compilation_db.get_all_compile_commands()?,
+            &self.suppressions,
+        );
+        let compile_commands = self.select_compile_commands(compile_commands)?;
+
+        Ok((compile_commands, compilation_db.is_file_path_in_arguments()))
+    }
+
+    /// Adjusts the arguments of the compile commands and only keeps the ones
+    /// selected by the scanner's options (e.g., entry points or shard)
+    fn select_compile_commands(
+        &self,
+        compile_commands: CompileCommands,
+    ) -> Result<CompileCommands> {
+        let compile_commands = if self.extra_args.is_empty() && self.remove_args.is_empty() {
+            compile_commands
+        } else {
+            adjust_compile_arguments(compile_commands, &self.extra_args, &self.remove_args)?
+        };
+        // Only keep the requested translation units if needed
+        let compile_commands = if self.entry_points.is_empty() {
+            compile_commands
+        } else {
+            select_entry_points(compile_commands, &self.entry_points)?
+        };
+        // Scope the run to the requested files if needed
+        let compile_commands = if self.only_files.is_empty() && self.exclude_files.is_empty() {
+            compile_commands
+        } else {
+            filter_compile_commands_by_path(
+                compile_commands,
+                &self.only_files,
+                &self.exclude_files,
+                &std::env::current_dir()?,
+            )?
+        };
+        // Fail loudly instead of reporting a clean scan for misconfigured
+        // projects
+        // Note: This is checked before sharding, as shards may legitimately be
+        // empty (e.g., with more shards than source files)
+        if compile_commands.len() < self.minimum_file_count {
+            return Err(anyhow!(
+                "Only {} source file(s) to parse, expected at least {} (check the project \
+                 file, glob expressions and suppressions, or use --min-files)",
+                compile_commands.len(),
+                self.minimum_file_count
+            ));
+        }
+        // Only keep the requested shard if needed
+        if let Some(shard) = self.shard {
+            Ok(select_shard(compile_commands, shard))
+        } else {
+            Ok(compile_commands)
+        }
+    }
+
+    /// Removes the artifacts extracted multiple times (e.g., from headers
+    /// included in multiple translation units), as well as suppressed ones.
+    /// Dropped artifacts are recorded into `statistics`.
+    pub fn filter_artifacts(
+        &self,
+        potential_leaks: Vec<PotentialLeak>,
+        statistics: &mut ExtractionStatistics,
+    ) -> Vec<PotentialLeak> {
+        let potential_leaks = deduplicate_artifacts(potential_leaks, statistics);
+        // Note: Artifacts are filtered by origin "again" because artifacts from
+        // suppressed headers might have been included during the parsing of
+        // other files
+        let potential_leaks =
+            filter_suppressed_artifacts_by_origin(potential_leaks, &self.suppressions, statistics);
+        let potential_leaks =
+            filter_suppressed_artifacts_by_value(potential_leaks, &self.suppressions, statistics);
+
+        filter_suppressed_artifacts_by_type(potential_leaks, &self.suppressions, statistics)
+    }
+
+    /// Fails if fewer artifacts than the minimum are left to look for, as
+    /// scanning for them would give misleadingly clean results
+    pub fn check_artifact_count(&self, artifact_count: usize) -> Result<()> {
+        if self.shard.is_none() && artifact_count < self.minimum_artifact_count {
+            return Err(anyhow!(
+                "Only {} artifact(s) to look for, expected at least {} (check the extraction \
+                 settings and suppressions, or use --min-artifacts)",
+                artifact_count,
+                self.minimum_artifact_count
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Adds the variants of string artifacts in other encodings, if requested
+    pub fn add_artifact_variants(&self, potential_leaks: Vec<PotentialLeak>) -> Vec<PotentialLeak> {
+        let potential_leaks = if self.utf16_variants {
+            add_utf16_variants(potential_leaks)
+        } else {
+            potential_leaks
+        };
+        if self.all_encodings {
+            add_encoding_variants(potential_leaks)
+        } else {
+            potential_leaks
+        }
+    }
+
+    /// Looks for the given artifacts in a binary file
+    pub fn find_leaks(
+        &self,
+        binary_file_path: &Path,
+        potential_leaks: Vec<PotentialLeak>,
+    ) -> Result<BTreeSet<ConfirmedLeakWithUniqueLocation>> {
+        self.leak_matcher(Arc::new(potential_leaks))?
+            .find_leaks_in_file(binary_file_path, self.unpack_containers)
+    }
+
+    /// Looks for the given artifacts in multiple binary files, scanned in
+    /// parallel, and returns the leaks found in each of them. Artifacts are
+    /// shared between binaries, and their patterns are only compiled once.
+    pub fn find_leaks_in_binaries(
+        &self,
+        binary_file_paths: &[PathBuf],
+        potential_leaks: Arc<Vec<PotentialLeak>>,
+    ) -> Result<Vec<(PathBuf, BTreeSet<ConfirmedLeakWithUniqueLocation>)>> {
+        let matcher = self.leak_matcher(potential_leaks)?;
+
+        binary_file_paths
+            .par_iter()
+            .map(|binary_file_path| {
+                let confirmed_leaks =
+                    matcher.find_leaks_in_file(binary_file_path, self.unpack_containers)?;
+                Ok((binary_file_path.clone(), confirmed_leaks))
+            })
+            .collect()
+    }
+
+    /// Builds a matcher for the given artifacts, with the scanner's options
+    fn leak_matcher(&self, potential_leaks: Arc<Vec<PotentialLeak>>) -> Result<LeakMatcher> {
+        let matcher = LeakMatcher::new(
+            potential_leaks,
+            self.aligned_wide_strings,
+            self.minimum_partial_leak_size,
+            self.match_suffixes,
+        )?;
+        if self.dedup_nested_artifacts {
+            matcher.deduplicate_nested_artifacts()
+        } else {
+            Ok(matcher)
+        }
+    }
+
+    /// Extracts the project's artifacts and looks for them in a binary file
+    pub fn scan(
+        &self,
+        binary_file_path: &Path,
+    ) -> Result<BTreeSet<ConfirmedLeakWithUniqueLocation>> {
+        let (potential_leaks, _) = self.extract_artifacts()?;
+
+        self.find_leaks(binary_file_path, potential_leaks)
+    }
+}
+
+/// Warns if the binary doesn't correspond to any of the targets implied by
+/// the compile commands, or if it's older than some of the source files
+fn check_binary_origin(binary_file_path: &Path, compile_commands: &CompileCommands) {
+    let target_names = implied_target_names(compile_commands);
+    log::debug!("Targets built by the project: {:?}", target_names);
+    if !target_names.is_empty() && !is_target_binary(binary_file_path, &target_names) {
+        log::warn!(
+            "'{}' doesn't correspond to any of the project's targets ({}), make sure it's \
+             been built from this project",
+            binary_file_path.display(),
+            target_names.iter().cloned().collect::<Vec<_>>().join(", ")
+        );
+    }
+    if let Some(source_file_path) = newer_source_file(binary_file_path, compile_commands) {
+        log::warn!(
+            "'{}' is older than some of the project's source files (e.g., '{}'), it may be \
+             stale",
+            binary_file_path.display(),
+            source_file_path.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::compilation_database::CompileCommand;
+
+    #[test]
+    fn select_compile_commands_with_options() {
+        let compile_commands: CompileCommands = ["a.cc", "b.cc", "c.cc", "d_test.cc"]
+            .into_iter()
+            .map(|file_name| CompileCommand {
+                filename: PathBuf::from("/project/src").join(file_name),
+                arguments: Arc::new(vec!["-c".to_string(), "-W4".to_string()]),
+            })
+            .collect();
+        let scanner = Scanner::new(ProjectSource::CompilationDatabase(PathBuf::new()))
+            .extra_args(vec!["-DSCANNER".to_string()])
+            .remove_args(vec!["-W*".to_string()])
+            .only_files(
+                vec!["/project/src/*".to_string()],
+                vec!["*_test.cc".to_string()],
+            )
+            .shard("2/2".parse().unwrap());
+
+        let compile_commands = scanner
+            .select_compile_commands(compile_commands)
+            .expect("select_compile_commands failed");
+        assert_eq!(
+            compile_commands
+                .iter()
+                .map(|compile_cmd| compile_cmd.filename.as_path())
+                .collect::<Vec<_>>(),
+            vec![Path::new("/project/src/b.cc")]
+        );
+        assert_eq!(
+            *compile_commands[0].arguments,
+            vec!["-c".to_string(), "-DSCANNER".to_string()]
+        );
+    }
+
+    #[test]
+    fn check_minimum_file_count() {
+        let compile_commands = || -> CompileCommands {
+            ["a.cc", "b.cc"]
+                .into_iter()
+                .map(|file_name| CompileCommand {
+                    filename: PathBuf::from("/project/src").join(file_name),
+                    arguments: Arc::new(vec![]),
+                })
+                .collect()
+        };
+        let scanner = Scanner::new(ProjectSource::CompilationDatabase(PathBuf::new()));
+        assert_eq!(
+            scanner
+                .select_compile_commands(compile_commands())
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(scanner
+            .select_compile_commands(CompileCommands::new())
+            .is_err());
+
+        // Files excluded from the run don't count
+        let scanner = scanner
+            .only_files(vec![], vec!["*/b.cc".to_string()])
+            .minimum_file_count(2);
+        assert!(scanner.select_compile_commands(compile_commands()).is_err());
+        let scanner = scanner.minimum_file_count(0);
+        assert!(scanner
+            .select_compile_commands(CompileCommands::new())
+            .is_ok());
+
+        // Empty shards are fine as long as the project isn't empty
+        let scanner = Scanner::new(ProjectSource::CompilationDatabase(PathBuf::new()))
+            .shard("3/3".parse().unwrap());
+        assert!(scanner
+            .select_compile_commands(compile_commands())
+            .unwrap()
+            .is_empty());
+        assert!(scanner
+            .select_compile_commands(CompileCommands::new())
+            .is_err());
+    }
+
+    #[test]
+    fn check_minimum_artifact_count() {
+        let scanner = Scanner::new(ProjectSource::CompilationDatabase(PathBuf::new()));
+        assert!(scanner.check_artifact_count(1).is_ok());
+        assert!(scanner.check_artifact_count(0).is_err());
+
+        let scanner = scanner.minimum_artifact_count(3);
+        assert!(scanner.check_artifact_count(2).is_err());
+        assert!(scanner.check_artifact_count(3).is_ok());
+
+        // Shards may be empty
+        let scanner = scanner.shard("2/2".parse().unwrap());
+        assert!(scanner.check_artifact_count(0).is_ok());
+    }
+
+    #[test]
+    fn list_source_files() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let source_tree = tmp_dir.path().canonicalize().unwrap();
+        let source_path = source_tree.join("main.cc");
+        let header_path = source_tree.join("config.h");
+        std::fs::write(&source_path, "#include \"config.h\"").unwrap();
+        std::fs::write(&header_path, "").unwrap();
+        let cache_directory = source_tree.join("cache");
+        let scanner = Scanner::new(ProjectSource::Manual {
+            source_path_globs: vec![source_tree.join("*.cc").to_string_lossy().to_string()],
+            include_directories: vec![],
+            compile_definitions: vec![],
+        })
+        .cache_directory(&cache_directory);
+        assert_eq!(scanner.source_files().unwrap(), vec![source_path.clone()]);
+
+        // Headers are known once translation units have been parsed
+        AnalysisCache::new(&cache_directory)
+            .unwrap()
+            .store(
+                "key",
+                &[source_path.clone(), header_path.clone()],
+                Default::default(),
+            )
+            .unwrap();
+        assert_eq!(
+            scanner.source_files().unwrap(),
+            vec![header_path, source_path]
+        );
+    }
+}
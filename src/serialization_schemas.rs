@@ -0,0 +1,92 @@
+use std::path::Path;
+
+use crate::information_leak::{LeakedDataType, PotentialLeak};
+
+/// Suffixes of the source files generated by serialization frameworks (i.e.,
+/// protoc and flatc)
+const GENERATED_SCHEMA_FILE_SUFFIXES: &[&str] =
+    &[".pb.cc", ".pb.h", ".pb-c.c", ".pb-c.h", "_generated.h"];
+
+/// Returns `true` if the given file has been generated from a Protobuf or
+/// FlatBuffers schema
+pub fn is_generated_schema_file(file_path: &Path) -> bool {
+    file_path
+        .file_name()
+        .and_then(|file_name| file_name.to_str())
+        .map(|file_name| {
+            GENERATED_SCHEMA_FILE_SUFFIXES
+                .iter()
+                .any(|suffix| file_name.ends_with(suffix))
+        })
+        .unwrap_or(false)
+}
+
+/// Classifies artifacts declared in generated schema files (e.g., embedded
+/// descriptors, message and field names) as serialization schema leaks, since
+/// they reveal internal message layouts.
+pub fn classify_serialization_schema_artifact(potential_leak: &mut PotentialLeak) {
+    let is_code_artifact = matches!(
+        potential_leak.data_type,
+        LeakedDataType::StringLiteral
+            | LeakedDataType::StructName
+            | LeakedDataType::ClassName
+            | LeakedDataType::TemplateName
+            | LeakedDataType::UnionName
+            | LeakedDataType::TypedefName
+            | LeakedDataType::EnumName
+            | LeakedDataType::EnumeratorName
+            | LeakedDataType::FunctionName
+            | LeakedDataType::MethodName
+            | LeakedDataType::FieldName
+    );
+    if is_code_artifact && is_generated_schema_file(&potential_leak.declaration_metadata.file) {
+        potential_leak.data_type = LeakedDataType::SerializationSchema;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, sync::Arc};
+
+    use crate::information_leak::SourceLocation;
+
+    use super::*;
+
+    fn potential_leak(data_type: LeakedDataType, file: &str) -> PotentialLeak {
+        PotentialLeak {
+            data_type,
+            data: Arc::new("Message".to_string()),
+            bytes: b"Message".to_vec(),
+            code_unit_size: 1,
+            declaration_metadata: Arc::new(SourceLocation {
+                file: PathBuf::from(file),
+                line: 1,
+            }),
+        }
+    }
+
+    #[test]
+    fn generated_schema_files() {
+        assert!(is_generated_schema_file(Path::new("/src/messages.pb.cc")));
+        assert!(is_generated_schema_file(Path::new("messages.pb.h")));
+        assert!(is_generated_schema_file(Path::new("monster_generated.h")));
+        assert!(!is_generated_schema_file(Path::new("/src/pb.cc")));
+        assert!(!is_generated_schema_file(Path::new("main.cc")));
+    }
+
+    #[test]
+    fn classify_artifacts() {
+        let mut leak = potential_leak(LeakedDataType::ClassName, "messages.pb.h");
+        classify_serialization_schema_artifact(&mut leak);
+        assert_eq!(leak.data_type, LeakedDataType::SerializationSchema);
+
+        let mut leak = potential_leak(LeakedDataType::StringLiteral, "main.cc");
+        classify_serialization_schema_artifact(&mut leak);
+        assert_eq!(leak.data_type, LeakedDataType::StringLiteral);
+
+        // Artifacts that don't come from the code itself are left untouched
+        let mut leak = potential_leak(LeakedDataType::CustomArtifact, "messages.pb.cc");
+        classify_serialization_schema_artifact(&mut leak);
+        assert_eq!(leak.data_type, LeakedDataType::CustomArtifact);
+    }
+}
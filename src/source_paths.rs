@@ -0,0 +1,106 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::compilation_database::{CompileCommand, CompileCommands};
+use crate::information_leak::{LeakedDataType, PotentialLeak, SourceLocation};
+
+/// Generates the paths that `__FILE__` may expand to in the given translation
+/// units (e.g., in `assert` messages or logging macros), as potential leaks.
+///
+/// `__FILE__` expands to the path of the source file as passed to the
+/// compiler, so both the path from the compilation database and the paths
+/// found in the compile arguments are used.
+pub fn generate_source_path_artifacts(compile_commands: &CompileCommands) -> Vec<PotentialLeak> {
+    let mut seen_paths = BTreeSet::new();
+
+    compile_commands
+        .iter()
+        .flat_map(|compile_cmd| {
+            let declaration_metadata = Arc::new(SourceLocation {
+                file: compile_cmd.filename.clone(),
+                line: 0,
+            });
+
+            source_file_spellings(compile_cmd)
+                .into_iter()
+                .filter(|path| seen_paths.insert(path.clone()))
+                .map(move |path| PotentialLeak {
+                    data_type: LeakedDataType::SourcePath,
+                    bytes: path.as_bytes().to_vec(),
+                    data: Arc::new(path),
+                    code_unit_size: 1,
+                    declaration_metadata: declaration_metadata.clone(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Returns the different ways the source file is referred to in the given
+/// compile command
+fn source_file_spellings(compile_cmd: &CompileCommand) -> Vec<String> {
+    let file_name = compile_cmd.filename.file_name();
+    let mut spellings = vec![compile_cmd.filename.display().to_string()];
+    spellings.extend(
+        compile_cmd
+            .arguments
+            .iter()
+            .filter(|argument| !argument.starts_with('-'))
+            .filter(|argument| Path::new(argument.as_str()).file_name() == file_name)
+            .cloned(),
+    );
+    spellings.dedup();
+
+    spellings
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn generate_source_paths_from_compile_commands() {
+        let compile_commands = vec![
+            CompileCommand {
+                filename: PathBuf::from("/project/src/main.c"),
+                arguments: Arc::new(vec![
+                    "-DFILE=main.c".to_string(),
+                    "-c".to_string(),
+                    "../src/main.c".to_string(),
+                    "-o".to_string(),
+                    "main.o".to_string(),
+                ]),
+            },
+            CompileCommand {
+                filename: PathBuf::from("/project/src/util.c"),
+                arguments: Arc::new(vec!["/project/src/util.c".to_string()]),
+            },
+            // Same file, built with different settings
+            CompileCommand {
+                filename: PathBuf::from("/project/src/util.c"),
+                arguments: Arc::new(vec!["-DTEST".to_string()]),
+            },
+        ];
+
+        let potential_leaks = generate_source_path_artifacts(&compile_commands);
+        let paths: Vec<&str> = potential_leaks
+            .iter()
+            .map(|leak| leak.data.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "/project/src/main.c",
+                "../src/main.c",
+                "/project/src/util.c"
+            ]
+        );
+        assert!(potential_leaks
+            .iter()
+            .all(|leak| leak.data_type == LeakedDataType::SourcePath
+                && leak.bytes == leak.data.as_bytes()));
+    }
+}
@@ -0,0 +1,330 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::information_leak::{ConfirmedLeak, PotentialLeak, SourceLocation};
+
+/// Reasons for which artifacts can be dropped during the extraction phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropReason {
+    /// Artifact is smaller than the minimum leak size
+    TooSmall,
+    /// Artifact has already been extracted (e.g., from a header included by
+    /// multiple translation units)
+    Duplicate,
+    /// Artifact is declared in a suppressed file
+    SuppressedFile,
+    /// Artifact's value is suppressed
+    SuppressedValue,
+    /// Artifact's type is suppressed
+    SuppressedType,
+    /// Artifact's value doesn't look random enough
+    LowEntropy,
+}
+
+impl DropReason {
+    fn description(&self) -> &'static str {
+        match self {
+            DropReason::TooSmall => "too small",
+            DropReason::Duplicate => "duplicate",
+            DropReason::SuppressedFile => "suppressed file",
+            DropReason::SuppressedValue => "suppressed value",
+            DropReason::SuppressedType => "suppressed type",
+            DropReason::LowEntropy => "low entropy",
+        }
+    }
+}
+
+/// Artifact dropped during the extraction phase
+struct DroppedArtifact {
+    reason: DropReason,
+    data: Arc<String>,
+    declaration_metadata: Arc<SourceLocation>,
+}
+
+/// Translation unit that couldn't be parsed
+pub struct FailedTranslationUnit {
+    pub file_path: PathBuf,
+    /// Why parsing failed (e.g., crash, timeout, or error message)
+    pub reason: String,
+}
+
+/// Keeps track of the artifacts dropped during the extraction phase, so users
+/// can tell filtered artifacts apart from artifacts that were never extracted.
+#[derive(Default)]
+pub struct ExtractionStatistics {
+    dropped_artifacts: Vec<DroppedArtifact>,
+    skipped_translation_units: usize,
+    failed_translation_units: Vec<FailedTranslationUnit>,
+}
+
+impl ExtractionStatistics {
+    /// Records a single dropped artifact
+    pub fn record(&mut self, reason: DropReason, leak: &PotentialLeak) {
+        self.dropped_artifacts.push(DroppedArtifact {
+            reason,
+            data: leak.data.clone(),
+            declaration_metadata: leak.declaration_metadata.clone(),
+        });
+    }
+
+    /// Records a collection of dropped artifacts
+    pub fn record_all(&mut self, reason: DropReason, leaks: &[PotentialLeak]) {
+        for leak in leaks {
+            self.record(reason, leak);
+        }
+    }
+
+    /// Records a translation unit that hasn't been parsed because the time
+    /// budget has been exhausted
+    pub fn record_skipped_translation_unit(&mut self) {
+        self.skipped_translation_units += 1;
+    }
+
+    /// Returns the number of translation units that haven't been parsed
+    pub fn skipped_translation_units(&self) -> usize {
+        self.skipped_translation_units
+    }
+
+    /// Records a translation unit that has been skipped because its parsing
+    /// failed (e.g., crashed or timed out)
+    pub fn record_failed_translation_unit(&mut self, file_path: PathBuf, reason: String) {
+        self.failed_translation_units
+            .push(FailedTranslationUnit { file_path, reason });
+    }
+
+    /// Returns the translation units whose parsing failed
+    pub fn failed_translation_units(&self) -> &[FailedTranslationUnit] {
+        &self.failed_translation_units
+    }
+
+    /// Returns the number of artifacts dropped for the given reason
+    pub fn dropped_count(&self, reason: DropReason) -> usize {
+        self.dropped_artifacts
+            .iter()
+            .filter(|artifact| artifact.reason == reason)
+            .count()
+    }
+
+    /// Logs a summary of the dropped artifacts, and the list of dropped
+    /// artifacts if `verbose` is set.
+    pub fn log_summary(&self, verbose: bool) {
+        log::info!(
+            "Dropped {} artifact(s): {} too small, {} duplicate(s), {} in suppressed files, {} with suppressed values, {} with suppressed types, {} with low entropy",
+            self.dropped_artifacts.len(),
+            self.dropped_count(DropReason::TooSmall),
+            self.dropped_count(DropReason::Duplicate),
+            self.dropped_count(DropReason::SuppressedFile),
+            self.dropped_count(DropReason::SuppressedValue),
+            self.dropped_count(DropReason::SuppressedType),
+            self.dropped_count(DropReason::LowEntropy),
+        );
+
+        if self.skipped_translation_units > 0 {
+            log::warn!(
+                "Time budget exhausted, {} translation unit(s) haven't been parsed",
+                self.skipped_translation_units
+            );
+        }
+        if !self.failed_translation_units.is_empty() {
+            log::warn!(
+                "Failed to parse {} translation unit(s):",
+                self.failed_translation_units.len()
+            );
+            for failed_translation_unit in &self.failed_translation_units {
+                log::warn!(
+                    "  '{}': {}",
+                    failed_translation_unit.file_path.display(),
+                    failed_translation_unit.reason
+                );
+            }
+        }
+
+        if verbose {
+            for artifact in &self.dropped_artifacts {
+                log::info!(
+                    "Dropped \"{}\" ({}) [declared at {}:{}]",
+                    artifact.data,
+                    artifact.reason.description(),
+                    artifact.declaration_metadata.file.display(),
+                    artifact.declaration_metadata.line,
+                );
+            }
+        }
+    }
+}
+
+/// Binary offset at which multiple source declarations have been found (e.g.,
+/// identical string literals merged by the compiler)
+struct PooledOffset {
+    file: Arc<PathBuf>,
+    offset: u64,
+    declarations: BTreeSet<Arc<SourceLocation>>,
+}
+
+/// Keeps track of the distinct source declarations that collapsed onto the same
+/// binary offset (i.e., string pooling), which tells whether the compiler's
+/// pooling settings affect the leak surface.
+#[derive(Default)]
+pub struct PoolingStatistics {
+    pooled_offsets: Vec<PooledOffset>,
+}
+
+/// Summary of `PoolingStatistics`, included in reports
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct PoolingSummary {
+    /// Number of binary offsets matched by multiple source declarations
+    pub pooled_offsets: usize,
+    /// Number of source declarations found at these offsets
+    pub pooled_declarations: usize,
+}
+
+impl PoolingStatistics {
+    /// Groups the given confirmed leaks by binary offset and keeps track of
+    /// the offsets shared by distinct source declarations
+    pub fn from_confirmed_leaks<'l, I>(confirmed_leaks: I) -> Self
+    where
+        I: IntoIterator<Item = &'l ConfirmedLeak>,
+    {
+        let declarations_by_offset = confirmed_leaks.into_iter().fold(
+            BTreeMap::new(),
+            |mut accum: BTreeMap<(Arc<PathBuf>, u64), BTreeSet<Arc<SourceLocation>>>, leak| {
+                accum
+                    .entry((
+                        leak.location.binary.file.clone(),
+                        leak.location.binary.offset,
+                    ))
+                    .or_default()
+                    .insert(leak.location.source.clone());
+                accum
+            },
+        );
+
+        Self {
+            pooled_offsets: declarations_by_offset
+                .into_iter()
+                .filter(|(_, declarations)| declarations.len() > 1)
+                .map(|((file, offset), declarations)| PooledOffset {
+                    file,
+                    offset,
+                    declarations,
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the number of pooled offsets and of declarations found at these
+    /// offsets
+    pub fn summary(&self) -> PoolingSummary {
+        PoolingSummary {
+            pooled_offsets: self.pooled_offsets.len(),
+            pooled_declarations: self
+                .pooled_offsets
+                .iter()
+                .map(|pooled_offset| pooled_offset.declarations.len())
+                .sum(),
+        }
+    }
+
+    /// Logs a summary of the pooled offsets, and the declarations found at
+    /// each of them if `verbose` is set.
+    pub fn log_summary(&self, verbose: bool) {
+        let summary = self.summary();
+        log::info!(
+            "{} source declaration(s) collapsed onto {} binary offset(s)",
+            summary.pooled_declarations,
+            summary.pooled_offsets,
+        );
+
+        if verbose {
+            for pooled_offset in &self.pooled_offsets {
+                let declarations: Vec<String> = pooled_offset
+                    .declarations
+                    .iter()
+                    .map(|declaration| {
+                        format!("{}:{}", declaration.file.display(), declaration.line)
+                    })
+                    .collect();
+                log::info!(
+                    "Offset 0x{:x} in \"{}\" is shared by {}",
+                    pooled_offset.offset,
+                    pooled_offset.file.display(),
+                    declarations.join(", "),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::information_leak::{BinaryLocation, LeakedDataType};
+
+    use super::*;
+
+    fn potential_leak(data: &str) -> PotentialLeak {
+        PotentialLeak {
+            data_type: LeakedDataType::StringLiteral,
+            data: Arc::new(data.to_string()),
+            bytes: data.as_bytes().to_vec(),
+            code_unit_size: 1,
+            declaration_metadata: Arc::new(SourceLocation {
+                file: PathBuf::from("file.cc"),
+                line: 1,
+            }),
+        }
+    }
+
+    #[test]
+    fn dropped_count_per_reason() {
+        let mut statistics = ExtractionStatistics::default();
+        statistics.record(DropReason::TooSmall, &potential_leak("a"));
+        statistics.record_all(
+            DropReason::SuppressedValue,
+            &[potential_leak("value1"), potential_leak("value2")],
+        );
+
+        assert_eq!(statistics.dropped_count(DropReason::TooSmall), 1);
+        assert_eq!(statistics.dropped_count(DropReason::Duplicate), 0);
+        assert_eq!(statistics.dropped_count(DropReason::SuppressedFile), 0);
+        assert_eq!(statistics.dropped_count(DropReason::SuppressedValue), 2);
+        assert_eq!(statistics.dropped_count(DropReason::LowEntropy), 0);
+    }
+
+    fn confirmed_leak(data: &str, line: u64, offset: u64) -> ConfirmedLeak {
+        ConfirmedLeak::builder(
+            LeakedDataType::StringLiteral,
+            Arc::new(data.to_string()),
+            Arc::new(SourceLocation {
+                file: PathBuf::from("file.cc"),
+                line,
+            }),
+            BinaryLocation::new(Arc::new(PathBuf::from("a.out")), offset),
+        )
+        .build()
+    }
+
+    #[test]
+    fn pooling_statistics_from_confirmed_leaks() {
+        let confirmed_leaks = [
+            // Same literal declared three times and merged by the compiler
+            confirmed_leak("pooled", 1, 0x10),
+            confirmed_leak("pooled", 2, 0x10),
+            confirmed_leak("pooled", 3, 0x10),
+            // Same declaration found at multiple offsets
+            confirmed_leak("duplicated", 4, 0x20),
+            confirmed_leak("duplicated", 4, 0x30),
+        ];
+        let statistics = PoolingStatistics::from_confirmed_leaks(&confirmed_leaks);
+
+        assert_eq!(
+            statistics.summary(),
+            PoolingSummary {
+                pooled_offsets: 1,
+                pooled_declarations: 3,
+            }
+        );
+    }
+}
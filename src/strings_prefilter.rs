@@ -0,0 +1,192 @@
+use std::collections::HashSet;
+
+use rayon::prelude::*;
+
+use crate::{containers::unpack_container, information_leak::PotentialLeak, MAX_CONTAINER_DEPTH};
+
+/// Maximum length of the prefixes used to match potential leaks against the
+/// printable strings found in the binary
+const MAX_PREFIX_LENGTH: usize = 4;
+
+/// Removes potential leaks that cannot possibly be found in the given binary
+/// data, by looking at the printable string regions it contains (the same way
+/// `strings` would).
+/// Only potential leaks whose byte patterns are entirely made of printable
+/// characters can be pruned, others are always kept.
+pub fn prune_potential_leaks(
+    bin_data: &[u8],
+    potential_leaks: Vec<PotentialLeak>,
+) -> Vec<PotentialLeak> {
+    // Use the shortest prunable pattern to determine the prefix length, so
+    // that every prunable pattern can be looked up
+    let prefix_length = potential_leaks
+        .iter()
+        .filter(|leak| is_printable_pattern(&leak.bytes))
+        .map(|leak| leak.bytes.len())
+        .min()
+        .unwrap_or(MAX_PREFIX_LENGTH)
+        .min(MAX_PREFIX_LENGTH);
+    if prefix_length == 0 {
+        return potential_leaks;
+    }
+
+    // Gather all the prefixes that can be found in printable regions
+    let prefixes: HashSet<&[u8]> = extract_printable_regions(bin_data, prefix_length)
+        .into_par_iter()
+        .fold(HashSet::new, |mut accum, region| {
+            accum.extend(region.windows(prefix_length));
+            accum
+        })
+        .reduce(HashSet::new, |mut accum, other| {
+            accum.extend(other);
+            accum
+        });
+
+    potential_leaks
+        .into_par_iter()
+        .filter(|leak| {
+            !is_printable_pattern(&leak.bytes) || prefixes.contains(&leak.bytes[..prefix_length])
+        })
+        .collect()
+}
+
+/// Appends the given binary data to `searchable_data`, followed by the data of
+/// the files it contains (e.g., compressed members of archives, or payloads of
+/// installers), so that artifacts only present in these files aren't pruned.
+/// All supported containers are unpacked, as the scan may unpack them too.
+/// Note: Files are separated by null bytes, so that no string spans multiple
+/// files
+pub fn collect_searchable_data(bin_data: &[u8], searchable_data: &mut Vec<u8>) {
+    collect_searchable_data_recursive(bin_data, searchable_data, 0);
+}
+
+fn collect_searchable_data_recursive(bin_data: &[u8], searchable_data: &mut Vec<u8>, depth: usize) {
+    searchable_data.extend_from_slice(bin_data);
+    searchable_data.push(0);
+    if depth >= MAX_CONTAINER_DEPTH {
+        return;
+    }
+
+    match unpack_container(bin_data) {
+        Ok(Some(contained_files)) => {
+            for contained_file in contained_files {
+                // Files stored as is are already part of the container's data
+                if contained_file.source_range.is_none() {
+                    collect_searchable_data_recursive(
+                        &contained_file.data,
+                        searchable_data,
+                        depth + 1,
+                    );
+                }
+            }
+        }
+        Ok(None) => {}
+        // Note: The scan reports the error if it unpacks this container too
+        Err(err) => log::debug!("Failed to unpack container: {:#}", err),
+    }
+}
+
+/// Returns all the printable regions of at least `minimum_length` bytes found
+/// in `bin_data`.
+fn extract_printable_regions(bin_data: &[u8], minimum_length: usize) -> Vec<&[u8]> {
+    bin_data
+        .split(|byte| !is_printable(*byte))
+        .filter(|region| region.len() >= minimum_length)
+        .collect()
+}
+
+/// Indicates if the given byte pattern is non-empty and only contains
+/// printable characters.
+fn is_printable_pattern(bytes: &[u8]) -> bool {
+    !bytes.is_empty() && bytes.iter().all(|byte| is_printable(*byte))
+}
+
+/// Indicates if the given byte is considered printable (i.e., could be part of
+/// a string extracted by `strings`).
+fn is_printable(byte: u8) -> bool {
+    byte.is_ascii_graphic() || byte == b' ' || byte == b'\t'
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, sync::Arc};
+
+    use crate::{
+        containers::zip_archive,
+        information_leak::{LeakedDataType, SourceLocation},
+    };
+
+    use super::*;
+
+    fn potential_leak(bytes: &[u8]) -> PotentialLeak {
+        PotentialLeak {
+            data_type: LeakedDataType::StringLiteral,
+            data: Arc::new(String::from_utf8_lossy(bytes).to_string()),
+            bytes: bytes.to_vec(),
+            code_unit_size: 1,
+            declaration_metadata: Arc::new(SourceLocation {
+                file: PathBuf::from("file.cc"),
+                line: 1,
+            }),
+        }
+    }
+
+    #[test]
+    fn extract_printable_regions_min_length() {
+        let regions = extract_printable_regions(b"\0abc\x01abcdef\xffab\0", 4);
+        assert_eq!(regions, vec![b"abcdef".as_slice()]);
+    }
+
+    #[test]
+    fn prune_potential_leaks_printable() {
+        let bin_data = b"\0\0hello world\0\x90\x90secret_key\0";
+        let potential_leaks = prune_potential_leaks(
+            bin_data,
+            vec![
+                potential_leak(b"world"),
+                potential_leak(b"secret_key"),
+                potential_leak(b"not_present"),
+            ],
+        );
+
+        assert_eq!(potential_leaks.len(), 2);
+        assert_eq!(potential_leaks[0].bytes, b"world");
+        assert_eq!(potential_leaks[1].bytes, b"secret_key");
+    }
+
+    #[test]
+    fn prune_potential_leaks_non_printable_patterns_are_kept() {
+        let bin_data = b"\0\0hello world\0";
+        let potential_leaks = prune_potential_leaks(
+            bin_data,
+            vec![
+                potential_leak(b"w\0i\0d\0e\0"),
+                potential_leak(b"new\nline"),
+                potential_leak(b"absent"),
+            ],
+        );
+
+        assert_eq!(potential_leaks.len(), 2);
+        assert_eq!(potential_leaks[0].bytes, b"w\0i\0d\0e\0");
+        assert_eq!(potential_leaks[1].bytes, b"new\nline");
+    }
+
+    #[test]
+    fn prune_potential_leaks_compressed_zip_member() {
+        let archive = zip_archive(&[("bin/app.exe", b"\0\0secret_key\0", true)]);
+        let mut searchable_data = vec![];
+        collect_searchable_data(&archive, &mut searchable_data);
+        let potential_leaks = prune_potential_leaks(
+            &searchable_data,
+            vec![
+                potential_leak(b"secret_key"),
+                potential_leak(b"not_present"),
+            ],
+        );
+
+        assert_eq!(potential_leaks.len(), 1);
+        assert_eq!(potential_leaks[0].bytes, b"secret_key");
+        // The artifact is only found once the member is decompressed
+        assert!(prune_potential_leaks(&archive, vec![potential_leak(b"secret_key")]).is_empty());
+    }
+}
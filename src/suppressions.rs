@@ -1,20 +1,205 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fs::File;
-use std::io::Read;
-use std::path::Path;
+use std::io::{Read, Write};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use glob::Pattern;
 use serde::Deserialize;
 
+use crate::information_leak::{ConfirmedLeak, LeakedDataType};
+use crate::resources::ScalarLocator;
+
+/// Latest version of the suppressions file schema supported
+const LATEST_SCHEMA_VERSION: u32 = 2;
+
+/// How the artifact values of suppressions are compared with extracted values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactMatchMode {
+    /// Compare values exactly
+    #[default]
+    Raw,
+    /// Compare the content of values, ignoring the quotes and prefixes of
+    /// string literals (e.g., `U"value"` matches `value`)
+    Content,
+}
+
+/// Suppresses an artifact value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactSuppression {
+    pub value: String,
+    /// How `value` is compared with extracted values
+    pub artifact_match: ArtifactMatchMode,
+}
+
+impl ArtifactSuppression {
+    /// Indicates if the given artifact value is suppressed
+    fn matches(&self, data: &str) -> bool {
+        match self.artifact_match {
+            ArtifactMatchMode::Raw => self.value == data,
+            ArtifactMatchMode::Content => {
+                string_literal_content(&self.value) == string_literal_content(data)
+            }
+        }
+    }
+}
+
+/// Suppresses all the artifacts of a given type, optionally only in some
+/// files
+#[derive(Debug, PartialEq, Eq)]
+pub struct TypeSuppression {
+    pub data_type: LeakedDataType,
+    /// Files the type is suppressed in (everywhere if empty)
+    pub files: Vec<Pattern>,
+}
+
 pub struct Suppressions {
     pub files: Vec<Pattern>,
-    pub artifacts: Vec<String>,
+    pub artifacts: Vec<ArtifactSuppression>,
+    pub types: Vec<TypeSuppression>,
+    /// Directory file patterns can be relative to, so that suppressions
+    /// don't depend on where the project is checked out
+    pub project_root: Option<PathBuf>,
+}
+
+impl Suppressions {
+    /// Makes file patterns match paths relative to the given directory, in
+    /// addition to absolute paths
+    pub fn set_project_root(&mut self, project_root: &Path) {
+        self.project_root = Some(
+            project_root
+                .canonicalize()
+                .unwrap_or_else(|_| project_root.to_path_buf()),
+        );
+    }
+
+    /// Indicates if artifacts declared in the given file are suppressed
+    pub fn is_file_suppressed(&self, file_path: &Path) -> bool {
+        self.matches_file(&self.files, file_path)
+    }
+
+    /// Indicates if artifacts of the given type, declared in the given file,
+    /// are suppressed
+    pub fn is_type_suppressed(&self, data_type: LeakedDataType, file_path: &Path) -> bool {
+        self.types.iter().any(|type_suppression| {
+            type_suppression.data_type == data_type
+                && (type_suppression.files.is_empty()
+                    || self.matches_file(&type_suppression.files, file_path))
+        })
+    }
+
+    /// Indicates if the given file matches one of the patterns, either by its
+    /// absolute path or by its path relative to the project root
+    fn matches_file(&self, patterns: &[Pattern], file_path: &Path) -> bool {
+        let relative_file_path = self.relative_file_path(file_path);
+        if let Some(file_path) = file_path.to_str() {
+            patterns.iter().any(|pattern| {
+                pattern.matches(file_path)
+                    || matches!(relative_file_path, Some(ref relative) if pattern.matches(relative))
+            })
+        } else {
+            false
+        }
+    }
+
+    /// Returns the path of the given file relative to the project root, with
+    /// '/' as separator, if it's located under the root
+    fn relative_file_path(&self, file_path: &Path) -> Option<String> {
+        let relative_file_path = file_path.strip_prefix(self.project_root.as_ref()?).ok()?;
+        let components = relative_file_path
+            .components()
+            .map(|component| component.as_os_str().to_str())
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(components.join("/"))
+    }
+
+    /// Indicates if the given artifact value is suppressed
+    pub fn is_artifact_suppressed(&self, data: &str) -> bool {
+        self.artifacts.iter().any(|artifact| artifact.matches(data))
+    }
+}
+
+/// Returns the content of the given string literal (e.g., `value` for
+/// `u8"value"` or `R"x(value)x"`), or the value as is if it isn't a string
+/// literal
+fn string_literal_content(value: &str) -> &str {
+    let literal = ["u8", "u", "U", "L"]
+        .iter()
+        .find_map(|prefix| value.strip_prefix(prefix))
+        .unwrap_or(value);
+    let (raw, literal) = match literal.strip_prefix('R') {
+        Some(literal) => (true, literal),
+        None => (false, literal),
+    };
+    let content = match literal
+        .strip_prefix('"')
+        .and_then(|literal| literal.strip_suffix('"'))
+    {
+        Some(content) => content,
+        None => return value,
+    };
+    if !raw {
+        return content;
+    }
+
+    // Raw string literals are delimited with `delimiter(` and `)delimiter`
+    content
+        .split_once('(')
+        .and_then(|(delimiter, content)| {
+            content
+                .strip_suffix(delimiter)
+                .and_then(|content| content.strip_suffix(')'))
+        })
+        .unwrap_or(value)
+}
+
+/// Type suppression, either suppressing a type everywhere (e.g.,
+/// `struct_names`) or only in some files
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TypeSuppressionYaml {
+    Everywhere(String),
+    InFiles {
+        #[serde(rename = "type")]
+        data_type: String,
+        files: Vec<String>,
+    },
+}
+
+/// Artifact suppression, either a value compared with the file's default mode
+/// or a value along with its own mode
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ArtifactSuppressionYaml {
+    Value(String),
+    WithMode {
+        value: String,
+        #[serde(rename = "match")]
+        artifact_match: ArtifactMatchMode,
+    },
 }
 
 #[derive(Deserialize)]
 struct SuppressionsListYaml {
+    /// Version of the schema used (1 if unspecified)
+    version: Option<u32>,
     files: Option<Vec<String>>,
-    artifacts: Option<Vec<String>>,
+    artifacts: Option<Vec<ArtifactSuppressionYaml>>,
+    types: Option<Vec<TypeSuppressionYaml>>,
+    /// How artifact values are compared with extracted values, unless
+    /// specified for a given artifact (exactly by default)
+    #[serde(rename = "match")]
+    artifact_match: Option<ArtifactMatchMode>,
+    /// Directory file patterns can be relative to (relative to the
+    /// suppressions file's directory)
+    project_root: Option<PathBuf>,
+    /// Fields unknown to this version of cpplumber
+    #[serde(flatten)]
+    unknown_fields: BTreeMap<String, serde_yaml::Value>,
 }
 
 pub fn parse_suppressions_file(suppression_file_path: &Path) -> Result<Suppressions> {
@@ -23,33 +208,273 @@ pub fn parse_suppressions_file(suppression_file_path: &Path) -> Result<Suppressi
     let mut suppression_file = File::open(suppression_file_path)?;
     suppression_file.read_to_end(&mut suppression_data)?;
 
-    // Parse YAML content
-    let suppressions_yaml: SuppressionsListYaml = serde_yaml::from_slice(&suppression_data)?;
+    let (mut suppressions, warnings) = parse_suppressions(&suppression_data)?;
+    for warning in warnings {
+        log::warn!("{}: {}", suppression_file_path.display(), warning);
+    }
+    // File patterns are relative to the suppressions file's directory by
+    // default
+    let suppressions_directory = suppression_file_path.parent().unwrap_or(Path::new(""));
+    let project_root = match suppressions.project_root {
+        Some(ref project_root) => suppressions_directory.join(project_root),
+        None => suppressions_directory.to_path_buf(),
+    };
+    let project_root = if project_root.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        project_root
+    };
+    suppressions.set_project_root(&project_root);
 
-    // Compile glob patterns
-    let files = suppressions_yaml
-        .files
-        .unwrap_or_default()
-        .iter()
-        .map(|pattern| {
-            if let Ok(pattern) = Pattern::new(pattern) {
-                pattern
+    Ok(suppressions)
+}
+
+/// Parses the YAML content of a suppressions file. Returns the suppressions
+/// as well as warnings about the parts of the content that were ignored.
+fn parse_suppressions(suppression_data: &[u8]) -> Result<(Suppressions, Vec<String>)> {
+    // Parse YAML content
+    let suppressions_yaml: SuppressionsListYaml = serde_yaml::from_slice(suppression_data)
+        .map_err(|err| {
+            if let Some(location) = err.location() {
+                anyhow!(
+                    "Malformed suppressions file (line {}, column {}): {}",
+                    location.line(),
+                    location.column(),
+                    err
+                )
             } else {
-                log::warn!("Failed to compile '{}', ignoring ...", &pattern);
-                Pattern::default()
+                anyhow!("Malformed suppressions file: {}", err)
             }
+        })?;
+    let suppression_text = String::from_utf8_lossy(suppression_data);
+
+    let mut warnings = vec![];
+    let version = suppressions_yaml.version.unwrap_or(1);
+    if version > LATEST_SCHEMA_VERSION {
+        warnings.push(format!(
+            "Schema version {} is newer than the latest supported version ({}), some rules might be ignored",
+            version, LATEST_SCHEMA_VERSION
+        ));
+    }
+    // Unknown fields are silently ignored in version 1
+    if version >= 2 {
+        for field in suppressions_yaml.unknown_fields.keys() {
+            warnings.push(format!(
+                "Unknown field '{}' (line {}), ignoring it",
+                field,
+                find_key_line_number(&suppression_text, field)
+            ));
+        }
+    }
+
+    // Compile glob patterns, locating invalid ones in their section
+    let section_locator = |key: &str| {
+        ScalarLocator::starting_at(
+            &suppression_text,
+            find_key_position(&suppression_text, key).unwrap_or(0),
+        )
+    };
+    let mut compile_patterns = |patterns: &[String], locator: &mut ScalarLocator| -> Vec<Pattern> {
+        patterns
+            .iter()
+            .map(|pattern| {
+                if let Ok(pattern) = Pattern::new(pattern) {
+                    pattern
+                } else {
+                    warnings.push(format!(
+                        "Failed to compile '{}' (line {}), ignoring ...",
+                        &pattern,
+                        locator.next_line_number(pattern)
+                    ));
+                    Pattern::default()
+                }
+            })
+            .collect()
+    };
+    let files = compile_patterns(
+        &suppressions_yaml.files.unwrap_or_default(),
+        &mut section_locator("files"),
+    );
+    let mut types_locator = section_locator("types");
+    let mut types = vec![];
+    let mut unknown_types = vec![];
+    for type_suppression in suppressions_yaml.types.unwrap_or_default() {
+        let (type_name, files) = match type_suppression {
+            TypeSuppressionYaml::Everywhere(type_name) => (type_name, vec![]),
+            TypeSuppressionYaml::InFiles { data_type, files } => {
+                (data_type, compile_patterns(&files, &mut types_locator))
+            }
+        };
+        match parse_data_type_name(&type_name) {
+            Some(data_type) => types.push(TypeSuppression { data_type, files }),
+            None => unknown_types.push(type_name),
+        }
+    }
+    for type_name in unknown_types {
+        warnings.push(format!(
+            "Unknown type '{}' (line {}), ignoring it",
+            type_name,
+            types_locator.next_line_number(&type_name)
+        ));
+    }
+
+    let default_artifact_match = suppressions_yaml.artifact_match.unwrap_or_default();
+    let artifacts = suppressions_yaml
+        .artifacts
+        .unwrap_or_default()
+        .into_iter()
+        .map(|artifact| match artifact {
+            ArtifactSuppressionYaml::Value(value) => ArtifactSuppression {
+                value,
+                artifact_match: default_artifact_match,
+            },
+            ArtifactSuppressionYaml::WithMode {
+                value,
+                artifact_match,
+            } => ArtifactSuppression {
+                value,
+                artifact_match,
+            },
         })
         .collect();
 
-    Ok(Suppressions {
-        files,
-        artifacts: suppressions_yaml.artifacts.unwrap_or_default(),
-    })
+    Ok((
+        Suppressions {
+            files,
+            artifacts,
+            types,
+            project_root: suppressions_yaml.project_root,
+        },
+        warnings,
+    ))
+}
+
+/// Returns the type corresponding to the given name, which can be plural
+/// (e.g., `struct_names`) or singular (e.g., `struct_name`)
+fn parse_data_type_name(type_name: &str) -> Option<LeakedDataType> {
+    let parse = |name: &str| {
+        serde_yaml::from_value::<LeakedDataType>(serde_yaml::Value::String(name.to_string())).ok()
+    };
+
+    parse(type_name).or_else(|| type_name.strip_suffix('s').and_then(parse))
+}
+
+/// Writes a suppressions file suppressing the values of the given leaks,
+/// grouped by the source file declaring them. File patterns are written as
+/// comments, so that they can be enabled selectively when editing the file.
+pub fn generate_suppressions<W, SortedConfirmedLeak>(
+    mut writer: W,
+    confirmed_leaks: &BTreeSet<SortedConfirmedLeak>,
+) -> Result<()>
+where
+    W: Write,
+    SortedConfirmedLeak: Deref<Target = ConfirmedLeak>,
+{
+    // Leak count and values, for each source file
+    let mut values_per_file: BTreeMap<&PathBuf, (usize, Vec<&Arc<String>>)> = BTreeMap::new();
+    let mut known_values = HashSet::new();
+    for leak in confirmed_leaks {
+        let (leak_count, values) = values_per_file
+            .entry(&leak.location.source.file)
+            .or_default();
+        *leak_count += 1;
+        // Note: Values declared in multiple files are only listed once
+        if known_values.insert(&leak.data) {
+            values.push(&leak.data);
+        }
+    }
+
+    writeln!(
+        writer,
+        "# Suppressions generated from {} confirmed leak(s). Review the entries\n\
+         # below and remove the ones that must not be suppressed.\n\
+         version: {}\n",
+        confirmed_leaks.len(),
+        LATEST_SCHEMA_VERSION
+    )?;
+    writeln!(
+        writer,
+        "# Files to ignore (can include glob expressions). Uncomment entries to\n\
+         # suppress all the artifacts declared in a file, including future ones.\n\
+         files:"
+    )?;
+    for (file_path, (leak_count, _)) in &values_per_file {
+        let pattern = Pattern::escape(&file_path.to_string_lossy());
+        writeln!(
+            writer,
+            "  # - {}  # {} leak(s)",
+            serde_json::to_string(&pattern)?,
+            leak_count
+        )?;
+    }
+    writeln!(
+        writer,
+        "\n# Artifacts to ignore, grouped by the file declaring them\nartifacts:"
+    )?;
+    for (file_path, (_, values)) in &values_per_file {
+        if values.is_empty() {
+            continue;
+        }
+        writeln!(writer, "  # {}", file_path.display())?;
+        for value in values {
+            // Note: JSON strings are valid YAML scalars
+            writeln!(writer, "  - {}", serde_json::to_string(value.as_str())?)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes the leaks of suppressed artifacts (i.e., declared in suppressed
+/// files, or with suppressed values or types), so that suppressions can be
+/// applied again to leaks found without them
+pub fn filter_suppressed_confirmed_leaks<SortedConfirmedLeak>(
+    confirmed_leaks: BTreeSet<SortedConfirmedLeak>,
+    suppressions: &Suppressions,
+) -> BTreeSet<SortedConfirmedLeak>
+where
+    SortedConfirmedLeak: Deref<Target = ConfirmedLeak> + Ord,
+{
+    confirmed_leaks
+        .into_iter()
+        .filter(|leak| {
+            let file_path = &leak.location.source.file;
+            !suppressions.is_file_suppressed(file_path)
+                && !suppressions.is_artifact_suppressed(&leak.data)
+                && !suppressions.is_type_suppressed(leak.data_type, file_path)
+        })
+        .collect()
+}
+
+/// Returns the position of the top-level `key` in the given YAML content
+fn find_key_position(content: &str, key: &str) -> Option<usize> {
+    let mut line_position = 0;
+    for line in content.split_inclusive('\n') {
+        if line
+            .strip_prefix(key)
+            .is_some_and(|rest| rest.trim_start().starts_with(':'))
+        {
+            return Some(line_position);
+        }
+        line_position += line.len();
+    }
+
+    None
+}
+
+/// Returns the (1-based) line number of the top-level `key` in the given YAML
+/// content, or 0 if it cannot be found.
+fn find_key_line_number(content: &str, key: &str) -> usize {
+    find_key_position(content, key)
+        .map_or(0, |position| content[..position].matches('\n').count() + 1)
 }
 
 #[cfg(test)]
 mod tests {
-    use std::path::PathBuf;
+    use crate::information_leak::{
+        BinaryLocation, ConfirmedLeakWithUniqueLocation, LeakLocation, LeakedDataType,
+        SourceLocation,
+    };
 
     use super::*;
 
@@ -70,7 +495,279 @@ mod tests {
 
         // Artifacts
         assert_eq!(suppressions.artifacts.len(), 2);
-        assert_eq!(suppressions.artifacts[0], "c_string");
-        assert_eq!(suppressions.artifacts[1], "utf32_string");
+        assert_eq!(suppressions.artifacts[0].value, "c_string");
+        assert_eq!(suppressions.artifacts[1].value, "utf32_string");
+        assert_eq!(
+            suppressions.artifacts[0].artifact_match,
+            ArtifactMatchMode::Raw
+        );
+    }
+
+    #[test]
+    fn is_suppressed() {
+        let (suppressions, _) =
+            parse_suppressions(b"files: ['*/file2.cc']\nartifacts: [c_string]\n")
+                .expect("Parsing failed");
+
+        assert!(suppressions.is_file_suppressed(Path::new("/src/file2.cc")));
+        assert!(!suppressions.is_file_suppressed(Path::new("/src/file1.cc")));
+        assert!(suppressions.is_artifact_suppressed("c_string"));
+        assert!(!suppressions.is_artifact_suppressed("utf8_string"));
+    }
+
+    #[test]
+    fn is_artifact_suppressed_by_content() {
+        // Values are compared exactly by default
+        let (suppressions, _) =
+            parse_suppressions(b"artifacts: ['U\"utf32_string\"']\n").expect("Parsing failed");
+        assert!(!suppressions.is_artifact_suppressed("utf32_string"));
+        assert!(suppressions.is_artifact_suppressed("U\"utf32_string\""));
+
+        let (suppressions, _) = parse_suppressions(
+            b"version: 2\nmatch: content\nartifacts: ['U\"utf32_string\"', '\"c_string\"', 'R\"x(raw)x\"', plain]\n",
+        )
+        .expect("Parsing failed");
+        assert!(suppressions.is_artifact_suppressed("utf32_string"));
+        assert!(suppressions.is_artifact_suppressed("c_string"));
+        assert!(suppressions.is_artifact_suppressed("u8\"c_string\""));
+        assert!(suppressions.is_artifact_suppressed("raw"));
+        assert!(suppressions.is_artifact_suppressed("plain"));
+        assert!(!suppressions.is_artifact_suppressed("utf8_string"));
+
+        // Per-artifact modes
+        let (suppressions, _) = parse_suppressions(
+            b"version: 2\nartifacts:\n  - 'L\"exact\"'\n  - value: 'U\"content\"'\n    match: content\n",
+        )
+        .expect("Parsing failed");
+        assert!(!suppressions.is_artifact_suppressed("exact"));
+        assert!(suppressions.is_artifact_suppressed("L\"exact\""));
+        assert!(suppressions.is_artifact_suppressed("content"));
+        assert!(suppressions.is_artifact_suppressed("u8\"content\""));
+    }
+
+    #[test]
+    fn extract_string_literal_content() {
+        assert_eq!(string_literal_content("\"value\""), "value");
+        assert_eq!(string_literal_content("L\"value\""), "value");
+        assert_eq!(
+            string_literal_content("u8R\"delim(va\"lue)delim\""),
+            "va\"lue"
+        );
+        // Not string literals
+        assert_eq!(string_literal_content("value"), "value");
+        assert_eq!(string_literal_content("Unquoted\""), "Unquoted\"");
+        assert_eq!(
+            string_literal_content("R\"x(unterminated\""),
+            "R\"x(unterminated\""
+        );
+        assert_eq!(string_literal_content("\""), "\"");
+    }
+
+    #[test]
+    fn is_suppressed_relative_to_project_root() {
+        let (mut suppressions, _) = parse_suppressions(
+            b"version: 2\nproject_root: ..\nfiles: ['src/*.cc', 'third_party/**']\n",
+        )
+        .expect("Parsing failed");
+        assert_eq!(suppressions.project_root, Some(PathBuf::from("..")));
+
+        suppressions.set_project_root(Path::new("/home/user/project"));
+        assert!(suppressions.is_file_suppressed(Path::new("/home/user/project/src/main.cc")));
+        assert!(suppressions
+            .is_file_suppressed(Path::new("/home/user/project/third_party/zlib/inflate.c")));
+        assert!(!suppressions.is_file_suppressed(Path::new("/home/user/project/tools/main.cc")));
+        // Files outside of the root only match absolute patterns
+        assert!(!suppressions.is_file_suppressed(Path::new("/ci/project/src/main.cc")));
+
+        // Paths are relative to the suppressions file's directory by default
+        let file_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(FILE1_PATH);
+        let suppressions =
+            parse_suppressions_file(&file_path).expect("Failed parsing suppressions file");
+        assert_eq!(
+            suppressions.project_root,
+            file_path
+                .parent()
+                .and_then(|directory| directory.canonicalize().ok())
+        );
+    }
+
+    #[test]
+    fn is_type_suppressed_everywhere_and_in_files() {
+        let (mut suppressions, warnings) = parse_suppressions(
+            b"version: 2\n\
+              types:\n  \
+                - struct_names\n  \
+                - type: string_literal\n    files: ['tests/**']\n  \
+                - unknown_names\n",
+        )
+        .expect("Parsing failed");
+        assert_eq!(
+            warnings,
+            vec!["Unknown type 'unknown_names' (line 6), ignoring it".to_string()]
+        );
+        suppressions.set_project_root(Path::new("/home/user/project"));
+
+        assert!(suppressions.is_type_suppressed(
+            LeakedDataType::StructName,
+            Path::new("/home/user/project/a.cc")
+        ));
+        assert!(!suppressions.is_type_suppressed(
+            LeakedDataType::ClassName,
+            Path::new("/home/user/project/a.cc")
+        ));
+        assert!(suppressions.is_type_suppressed(
+            LeakedDataType::StringLiteral,
+            Path::new("/home/user/project/tests/main.cc")
+        ));
+        assert!(!suppressions.is_type_suppressed(
+            LeakedDataType::StringLiteral,
+            Path::new("/home/user/project/src/main.cc")
+        ));
+    }
+
+    fn artifact_values(suppressions: &Suppressions) -> Vec<&str> {
+        suppressions
+            .artifacts
+            .iter()
+            .map(|artifact| artifact.value.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn parse_suppressions_v1_unknown_fields() {
+        let (suppressions, warnings) =
+            parse_suppressions(b"artifacts: [value]\nunknown: true\n").expect("Parsing failed");
+
+        assert_eq!(artifact_values(&suppressions), vec!["value"]);
+        // Unknown fields are silently ignored
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn parse_suppressions_v2_unknown_fields() {
+        let (suppressions, warnings) =
+            parse_suppressions(b"version: 2\nartifacts: [value]\nunknown: true\n")
+                .expect("Parsing failed");
+
+        assert_eq!(artifact_values(&suppressions), vec!["value"]);
+        assert_eq!(
+            warnings,
+            vec!["Unknown field 'unknown' (line 3), ignoring it".to_string()]
+        );
+    }
+
+    #[test]
+    fn parse_suppressions_repeated_unknown_types() {
+        let (_, warnings) = parse_suppressions(
+            b"version: 2\nartifacts: [unknown]\ntypes:\n  - unknown\n  - unknown\n",
+        )
+        .expect("Parsing failed");
+
+        assert_eq!(
+            warnings,
+            vec![
+                "Unknown type 'unknown' (line 4), ignoring it".to_string(),
+                "Unknown type 'unknown' (line 5), ignoring it".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_suppressions_newer_version() {
+        let (_, warnings) = parse_suppressions(b"version: 3\n").expect("Parsing failed");
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn filter_suppressed_leaks() {
+        let confirmed_leak = |data: &str, file: &str, offset| {
+            ConfirmedLeakWithUniqueLocation::from(
+                ConfirmedLeak::builder(
+                    LeakedDataType::StringLiteral,
+                    Arc::new(data.to_string()),
+                    Arc::new(SourceLocation {
+                        file: PathBuf::from(file),
+                        line: 1,
+                    }),
+                    BinaryLocation::new(Arc::new(PathBuf::from("a.out")), offset),
+                )
+                .build(),
+            )
+        };
+        let leaks = BTreeSet::from([
+            confirmed_leak("\"kept\"", "/src/main.cc", 0),
+            confirmed_leak("\"secret\"", "/src/main.cc", 8),
+            confirmed_leak("\"vendored\"", "/third_party/lib.cc", 16),
+        ]);
+        let (suppressions, _) =
+            parse_suppressions(b"files: ['/third_party/*']\nartifacts: ['\"secret\"']\n")
+                .expect("Parsing failed");
+
+        let leaks = filter_suppressed_confirmed_leaks(leaks, &suppressions);
+        let values: Vec<&str> = leaks.iter().map(|leak| leak.data.as_str()).collect();
+        assert_eq!(values, vec!["\"kept\""]);
+    }
+
+    #[test]
+    fn generate_suppressions_from_leaks() {
+        let confirmed_leak = |data: &str, file: &str, offset| {
+            ConfirmedLeakWithUniqueLocation::from(ConfirmedLeak {
+                data_type: LeakedDataType::StringLiteral,
+                data: Arc::new(data.to_string()),
+                location: LeakLocation {
+                    source: Arc::new(SourceLocation {
+                        file: PathBuf::from(file),
+                        line: 1,
+                    }),
+                    binary: BinaryLocation {
+                        file: Arc::new(PathBuf::from("a.out")),
+                        offset,
+                        region: None,
+                        virtual_address: None,
+                        architecture: None,
+                        debug_info: false,
+                    },
+                },
+                size: data.len(),
+                partial_size: None,
+                severity: None,
+                confidence: None,
+                origin: None,
+                visibility: None,
+                infrastructure: None,
+                context: None,
+                pass: None,
+            })
+        };
+        let leaks = BTreeSet::from([
+            confirmed_leak("secret", "/src/[legacy].cc", 0),
+            confirmed_leak("key: \"value\"", "/src/[legacy].cc", 1),
+            confirmed_leak("secret", "/src/main.cc", 2),
+        ]);
+
+        let mut suppression_data = vec![];
+        generate_suppressions(&mut suppression_data, &leaks).expect("generate_suppressions failed");
+        let suppression_text = String::from_utf8(suppression_data.clone()).unwrap();
+        assert!(suppression_text.contains("  # - \"/src/[[]legacy[]].cc\"  # 2 leak(s)\n"));
+
+        let (suppressions, warnings) =
+            parse_suppressions(&suppression_data).expect("Parsing failed");
+        assert!(warnings.is_empty());
+        // File patterns must be enabled manually
+        assert!(suppressions.files.is_empty());
+        assert_eq!(
+            artifact_values(&suppressions),
+            vec!["secret", "key: \"value\""]
+        );
+    }
+
+    #[test]
+    fn parse_suppressions_malformed_entry() {
+        let err = parse_suppressions(b"version: 2\nartifacts:\n  - [nested]\n")
+            .err()
+            .expect("Parsing should fail");
+        assert!(err
+            .to_string()
+            .starts_with("Malformed suppressions file (line 3"));
     }
 }
@@ -0,0 +1,95 @@
+//! Detection of entities declared in system headers, either according to
+//! libclang (i.e., headers found through system include directories) or
+//! because they're located under user-defined system include roots (e.g.,
+//! SDKs installed inside the repository, generated directories).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use clang::Entity;
+
+/// Tells whether entities are declared in system headers. Meant to be used
+/// while processing a single translation unit.
+pub struct SystemHeaderFilter {
+    system_include_roots: Vec<PathBuf>,
+    /// Files already looked up, and whether they're under a system include
+    /// root
+    known_files: RefCell<HashMap<PathBuf, bool>>,
+}
+
+impl SystemHeaderFilter {
+    /// Creates a filter that treats files located under the given directories
+    /// as system headers, in addition to the ones libclang considers as such
+    pub fn new(system_include_roots: &[PathBuf]) -> Self {
+        Self {
+            system_include_roots: system_include_roots
+                .iter()
+                .map(|root| root.canonicalize().unwrap_or_else(|_| root.clone()))
+                .collect(),
+            known_files: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Returns `true` if the given entity is declared in a system header
+    pub fn is_in_system_header(&self, entity: &Entity) -> bool {
+        if entity.is_in_system_header() {
+            return true;
+        }
+        if self.system_include_roots.is_empty() {
+            return false;
+        }
+
+        match entity
+            .get_location()
+            .and_then(|location| location.get_file_location().file)
+        {
+            Some(file) => self.is_under_system_include_root(&file.get_path()),
+            None => false,
+        }
+    }
+
+    /// Returns `true` if the given file is located under one of the system
+    /// include roots
+    pub fn is_under_system_include_root(&self, file_path: &Path) -> bool {
+        if let Some(&known) = self.known_files.borrow().get(file_path) {
+            return known;
+        }
+
+        // Note: Paths reported by libclang aren't necessarily canonical
+        let canonical_file_path = file_path
+            .canonicalize()
+            .unwrap_or_else(|_| file_path.to_path_buf());
+        let is_under_root = self
+            .system_include_roots
+            .iter()
+            .any(|root| file_path.starts_with(root) || canonical_file_path.starts_with(root));
+        self.known_files
+            .borrow_mut()
+            .insert(file_path.to_path_buf(), is_under_root);
+
+        is_under_root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_system_include_roots() {
+        let filter = SystemHeaderFilter::new(&[
+            PathBuf::from("/src/project/sdk"),
+            PathBuf::from("/src/project/build/generated"),
+        ]);
+
+        assert!(filter.is_under_system_include_root(Path::new("/src/project/sdk/include/sdk.h")));
+        assert!(filter
+            .is_under_system_include_root(Path::new("/src/project/build/generated/messages.pb.h")));
+        assert!(!filter.is_under_system_include_root(Path::new("/src/project/src/main.cc")));
+        // Roots only match whole directory names
+        assert!(!filter.is_under_system_include_root(Path::new("/src/project/sdk2/sdk.h")));
+        // Decisions are remembered
+        assert!(filter.is_under_system_include_root(Path::new("/src/project/sdk/include/sdk.h")));
+    }
+}
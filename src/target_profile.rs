@@ -0,0 +1,190 @@
+//! Target profiles, bundling the settings that depend on the platform binaries
+//! are built for, so that binaries of multiple targets can be scanned from a
+//! single host (e.g., a Linux CI runner).
+//! Note: All the supported targets are little-endian, which is how patterns
+//! are encoded.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use object::{Architecture, BinaryFormat, Object};
+use serde::{Deserialize, Serialize};
+
+use crate::information_leak::{ManglingScheme, PotentialLeak, WideCharMode};
+
+/// Platform and architecture binaries are built for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TargetProfile {
+    WindowsX64,
+    WindowsArm64,
+    LinuxX64,
+    LinuxArm64,
+    MacosX64,
+    MacosArm64,
+}
+
+const PROFILE_NAMES: [(&str, TargetProfile); 6] = [
+    ("windows-x64", TargetProfile::WindowsX64),
+    ("windows-arm64", TargetProfile::WindowsArm64),
+    ("linux-x64", TargetProfile::LinuxX64),
+    ("linux-arm64", TargetProfile::LinuxArm64),
+    ("macos-x64", TargetProfile::MacosX64),
+    ("macos-arm64", TargetProfile::MacosArm64),
+];
+
+impl FromStr for TargetProfile {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        PROFILE_NAMES
+            .iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, profile)| *profile)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Invalid target profile '{}' (expected one of: {})",
+                    s,
+                    PROFILE_NAMES
+                        .iter()
+                        .map(|(name, _)| *name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })
+    }
+}
+
+impl fmt::Display for TargetProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = PROFILE_NAMES
+            .iter()
+            .find(|(_, profile)| profile == self)
+            .map(|(name, _)| *name)
+            .unwrap_or_default();
+        write!(f, "{}", name)
+    }
+}
+
+impl TargetProfile {
+    /// Returns how wide strings are encoded on the target
+    pub fn wide_char_mode(self) -> WideCharMode {
+        match self {
+            TargetProfile::WindowsX64 | TargetProfile::WindowsArm64 => WideCharMode::Windows,
+            _ => WideCharMode::Unix,
+        }
+    }
+
+    /// Returns the mangling scheme of the target's C++ ABI
+    pub fn mangling_scheme(self) -> ManglingScheme {
+        match self {
+            TargetProfile::WindowsX64 | TargetProfile::WindowsArm64 => ManglingScheme::Msvc,
+            _ => ManglingScheme::Itanium,
+        }
+    }
+
+    /// Returns the format of the target's executables and shared libraries
+    pub fn binary_format(self) -> BinaryFormat {
+        match self {
+            TargetProfile::WindowsX64 | TargetProfile::WindowsArm64 => BinaryFormat::Pe,
+            TargetProfile::LinuxX64 | TargetProfile::LinuxArm64 => BinaryFormat::Elf,
+            TargetProfile::MacosX64 | TargetProfile::MacosArm64 => BinaryFormat::MachO,
+        }
+    }
+
+    /// Returns the target's architecture
+    pub fn architecture(self) -> Architecture {
+        match self {
+            TargetProfile::WindowsX64 | TargetProfile::LinuxX64 | TargetProfile::MacosX64 => {
+                Architecture::X86_64
+            }
+            _ => Architecture::Aarch64,
+        }
+    }
+
+    /// Returns a description of how the given binary differs from the target,
+    /// if it does. Files whose format isn't supported (e.g., static libraries,
+    /// raw blobs) cannot be checked.
+    pub fn check_binary(self, data: &[u8]) -> Option<String> {
+        let object_file = object::File::parse(data).ok()?;
+        if object_file.format() != self.binary_format() {
+            Some(format!(
+                "{:?} binary, expected {:?} for {}",
+                object_file.format(),
+                self.binary_format(),
+                self
+            ))
+        } else if object_file.architecture() != self.architecture() {
+            Some(format!(
+                "{:?} binary, expected {:?} for {}",
+                object_file.architecture(),
+                self.architecture(),
+                self
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+/// Removes the mangled type names that don't follow the given mangling scheme,
+/// as they cannot appear in the target's binaries
+pub fn retain_mangling_scheme(
+    potential_leaks: Vec<PotentialLeak>,
+    mangling_scheme: ManglingScheme,
+) -> Vec<PotentialLeak> {
+    potential_leaks
+        .into_iter()
+        .filter(|leak| !matches!(leak.mangling_scheme(), Some(scheme) if scheme != mangling_scheme))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    const FILE_LIST_PROJ_PATH: &str = "tests/data/main/file_list_proj";
+
+    fn read_test_binary(file_name: &str) -> Vec<u8> {
+        let binary_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join(FILE_LIST_PROJ_PATH)
+            .join(file_name);
+        std::fs::read(binary_path).expect("Failed to read test binary")
+    }
+
+    #[test]
+    fn parse_target_profiles() {
+        let profile: TargetProfile = "windows-arm64".parse().unwrap();
+        assert_eq!(profile, TargetProfile::WindowsArm64);
+        assert_eq!(profile.to_string(), "windows-arm64");
+        assert_eq!(profile.wide_char_mode(), WideCharMode::Windows);
+        assert_eq!(profile.mangling_scheme(), ManglingScheme::Msvc);
+        assert_eq!(
+            "linux-arm64"
+                .parse::<TargetProfile>()
+                .unwrap()
+                .wide_char_mode(),
+            WideCharMode::Unix
+        );
+        assert!("windows-x86".parse::<TargetProfile>().is_err());
+    }
+
+    #[test]
+    fn check_binaries_against_profiles() {
+        let pe_binary = read_test_binary("a.exe");
+        assert_eq!(TargetProfile::WindowsX64.check_binary(&pe_binary), None);
+        assert_eq!(
+            TargetProfile::WindowsArm64.check_binary(&pe_binary),
+            Some("X86_64 binary, expected Aarch64 for windows-arm64".to_string())
+        );
+        assert_eq!(
+            TargetProfile::LinuxX64.check_binary(&pe_binary),
+            Some("Pe binary, expected Elf for linux-x64".to_string())
+        );
+        // Raw blobs cannot be checked
+        assert_eq!(TargetProfile::LinuxX64.check_binary(b"raw data"), None);
+    }
+}
@@ -0,0 +1,734 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use cpplumber::{
+    baseline::{filter_baseline_leaks, Baseline},
+    cancellation::is_cancelled,
+    compilation_database::locate_cmake_compile_commands,
+    information_leak::{ConfirmedLeakWithUniqueLocation, PotentialLeak},
+    leak_diff::{diff_confirmed_leaks, LeakDiff},
+    path_mapping::{normalize_confirmed_leak_paths, PathNormalizer},
+    reporting::{dump_confirmed_leaks, OutputSink, OutputSinkRegistry, ScanOutcome},
+    statistics::ExtractionStatistics,
+    suppressions::{filter_suppressed_confirmed_leaks, Suppressions},
+    Scanner,
+};
+
+use crate::cli::CpplumberOptions;
+use crate::{
+    binary_files_to_scan, classify_leaks, collect_artifacts, load_reporting_context,
+    load_suppressions, prepare_artifacts, project_scanner, prune_artifacts, scan_binary_files,
+    write_json_report, ReportingContext,
+};
+
+/// Time without changes after which changed files are considered written
+/// (e.g., by the linker)
+const SETTLE_DELAY: Duration = Duration::from_millis(500);
+/// Granularity of the waits, so that interruptions are handled quickly
+const SLEEP_STEP: Duration = Duration::from_millis(100);
+
+/// Changes to the watched files, ordered by the work they require
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Change {
+    /// The binary has been rebuilt and must be scanned again
+    Binary,
+    /// Source files or the artifacts' sources changed, artifacts must be
+    /// extracted again (only changed translation units are parsed) and the
+    /// binary scanned again
+    Sources,
+    /// The configuration or the suppressions changed, they must be loaded
+    /// again and applied to the leaks already found
+    Settings,
+}
+
+/// Scans the project, then keeps track of the changes made to its source
+/// files, settings and binary, and scans again when needed until interrupted.
+/// Only the leaks found or resolved since the previous scan are printed.
+/// Artifacts are cached between scans (in a temporary directory if no cache
+/// directory is given), so that only changed translation units are parsed
+/// again.
+pub fn run_watch_mode(options: &mut CpplumberOptions) -> Result<()> {
+    let _temporary_cache_directory = if options.cache_directory.is_none() {
+        let directory = tempfile::tempdir()?;
+        options.cache_directory = Some(directory.path().to_path_buf());
+        Some(directory)
+    } else {
+        None
+    };
+    let options = &*options;
+
+    let mut watcher = FileWatcher::new()?;
+    let mut session: Option<WatchSession> = None;
+    let mut findings = BTreeSet::new();
+    let mut changes = BTreeSet::new();
+    loop {
+        match update_findings(options, &mut session, &changes) {
+            Ok((new_findings, partial)) => {
+                let output_sinks = &session
+                    .as_ref()
+                    .expect("Watch session not loaded")
+                    .reporting
+                    .output_sinks;
+                let diff = diff_confirmed_leaks(std::mem::take(&mut findings), new_findings);
+                if let Err(err) = print_changes(options, output_sinks, &diff, partial) {
+                    log::error!("{:#}", err);
+                }
+                findings = diff.introduced.into_iter().chain(diff.persisting).collect();
+                // Keep the full report up to date if requested
+                if let Some(ref report_file_path) = options.report_file {
+                    let result = output_sinks.get("json").and_then(|json_sink| {
+                        let outcome = ScanOutcome {
+                            partial,
+                            ..Default::default()
+                        };
+                        write_json_report(report_file_path, &findings, json_sink, &outcome, options)
+                    });
+                    if let Err(err) = result {
+                        log::error!("Failed to write report file: {:#}", err);
+                    }
+                }
+            }
+            // Keep watching, the error may be fixed by the next change
+            Err(err) => log::error!("{:#}", err),
+        }
+
+        let watched_files = WatchedFiles::collect(options, session.as_ref());
+        watcher.update(&watched_files)?;
+        log::info!("Watching for changes (interrupt to stop)...");
+        match watcher.wait_for_changes(&watched_files) {
+            Some((new_changes, changed_file)) => {
+                log::info!("'{}' changed, updating findings...", changed_file.display());
+                changes = new_changes;
+            }
+            None => return Ok(()),
+        }
+    }
+}
+
+/// Takes the given changes into account, and returns the current findings
+/// along with whether they're partial
+fn update_findings<'o>(
+    options: &'o CpplumberOptions,
+    session: &mut Option<WatchSession<'o>>,
+    changes: &BTreeSet<Change>,
+) -> Result<(BTreeSet<ConfirmedLeakWithUniqueLocation>, bool)> {
+    let session = match session {
+        Some(session) => {
+            if changes.contains(&Change::Settings) {
+                session.reload_settings()?;
+            }
+            session
+        }
+        // Start over if the previous session couldn't be set up
+        None => session.insert(WatchSession::new(options)?),
+    };
+    // Note: Artifacts are also extracted again (and the binary scanned again)
+    // if the previous attempt failed
+    if changes.contains(&Change::Sources) || session.potential_leaks.is_none() {
+        session.extract_artifacts()?;
+    }
+    if changes.contains(&Change::Binary) || session.scanned_leaks.is_none() {
+        session.scan_binary()?;
+    }
+
+    Ok((session.findings()?, session.incomplete_extraction))
+}
+
+/// Settings, artifacts and leaks kept between scans
+struct WatchSession<'o> {
+    options: &'o CpplumberOptions,
+    reporting: ReportingContext,
+    suppressions: Option<Suppressions>,
+    /// Scanner extracting all the artifacts, including the suppressed ones,
+    /// so that suppressions can be changed without parsing source files again
+    scanner: Scanner,
+    /// Artifacts to look for in the binary, once extracted
+    potential_leaks: Option<Vec<PotentialLeak>>,
+    /// Leaks found in the binary, before suppressions and classification
+    scanned_leaks: Option<BTreeSet<ConfirmedLeakWithUniqueLocation>>,
+    /// Set if some translation units couldn't be parsed
+    incomplete_extraction: bool,
+}
+
+impl<'o> WatchSession<'o> {
+    /// Loads the configuration and the suppressions
+    fn new(options: &'o CpplumberOptions) -> Result<Self> {
+        let minimum_leak_size = options.minimum_leak_size.unwrap_or(4);
+        let reporting = load_reporting_context(options)?;
+        let suppressions = load_suppressions(options)?;
+        let scanner = project_scanner(options, None, minimum_leak_size)?;
+
+        Ok(Self {
+            options,
+            reporting,
+            suppressions,
+            scanner,
+            potential_leaks: None,
+            scanned_leaks: None,
+            incomplete_extraction: false,
+        })
+    }
+
+    /// Loads the configuration and the suppressions again. Leaks already found
+    /// are kept, unless the configuration changes the artifacts to look for or
+    /// the scan passes.
+    fn reload_settings(&mut self) -> Result<()> {
+        let reporting = load_reporting_context(self.options)?;
+        let suppressions = load_suppressions(self.options)?;
+        let same_scan_settings = match (&self.reporting.config, &reporting.config) {
+            (Some(config), Some(new_config)) => config.has_same_scan_settings(new_config),
+            (config, new_config) => config.is_none() && new_config.is_none(),
+        };
+        if !same_scan_settings {
+            // Note: Source files' artifacts are loaded from the analysis cache
+            log::info!("Scan settings changed, the binary will be scanned again");
+            self.potential_leaks = None;
+            self.scanned_leaks = None;
+        }
+        self.reporting = reporting;
+        self.suppressions = suppressions;
+
+        Ok(())
+    }
+
+    /// Extracts the artifacts to look for, through the analysis cache
+    fn extract_artifacts(&mut self) -> Result<()> {
+        self.potential_leaks = None;
+        self.scanned_leaks = None;
+        let mut statistics = ExtractionStatistics::default();
+        let potential_leaks = collect_artifacts(
+            self.options,
+            &self.scanner,
+            self.reporting.config.as_ref(),
+            self.options.minimum_leak_size.unwrap_or(4),
+            &mut statistics,
+        )?;
+        let potential_leaks = prepare_artifacts(
+            self.options,
+            &self.scanner,
+            potential_leaks,
+            &mut statistics,
+        )?;
+        self.potential_leaks = Some(potential_leaks);
+        self.incomplete_extraction = statistics.skipped_translation_units() > 0
+            || !statistics.failed_translation_units().is_empty();
+
+        Ok(())
+    }
+
+    /// Looks for the extracted artifacts in the binary
+    fn scan_binary(&mut self) -> Result<()> {
+        let options = self.options;
+        self.scanned_leaks = None;
+        let potential_leaks = self.potential_leaks.clone().unwrap_or_default();
+        let binary_file_paths = binary_files_to_scan(options)?;
+        let potential_leaks = prune_artifacts(options, &binary_file_paths, potential_leaks)?;
+        log::info!("Looking for leaks...");
+        self.scanned_leaks = Some(scan_binary_files(
+            &binary_file_paths,
+            options,
+            self.reporting.config.as_ref(),
+            potential_leaks,
+        )?);
+
+        Ok(())
+    }
+
+    /// Applies the suppressions and the configuration to the leaks found in
+    /// the binary, and returns the leaks to report
+    fn findings(&self) -> Result<BTreeSet<ConfirmedLeakWithUniqueLocation>> {
+        let options = self.options;
+        let leaks = self.scanned_leaks.clone().unwrap_or_default();
+        let leaks = match self.suppressions {
+            Some(ref suppressions) => filter_suppressed_confirmed_leaks(leaks, suppressions),
+            None => leaks,
+        };
+        let leaks = classify_leaks(options, &self.reporting, leaks)?;
+        let leaks = normalize_confirmed_leak_paths(
+            leaks,
+            &PathNormalizer::new(options.path_prefix_map.clone()),
+        );
+
+        // Only keep new leaks if a baseline is used
+        match options.baseline {
+            Some(ref baseline_file_path) => Ok(filter_baseline_leaks(
+                leaks,
+                &Baseline::load(baseline_file_path)?,
+            )),
+            None => Ok(leaks),
+        }
+    }
+}
+
+/// Prints the leaks found or resolved since the previous scan
+fn print_changes(
+    options: &CpplumberOptions,
+    output_sinks: &OutputSinkRegistry,
+    diff: &LeakDiff,
+    partial: bool,
+) -> Result<()> {
+    log::info!(
+        "{} new leak(s), {} resolved, {} unchanged",
+        diff.introduced.len(),
+        diff.removed.len(),
+        diff.persisting.len()
+    );
+
+    write_changes(
+        std::io::stdout(),
+        diff,
+        output_sinks.get(options.output_format())?,
+        partial,
+        options,
+    )
+}
+
+fn write_changes<W: Write>(
+    mut writer: W,
+    diff: &LeakDiff,
+    output_sink: &dyn OutputSink,
+    partial: bool,
+    options: &CpplumberOptions,
+) -> Result<()> {
+    for (name, leaks) in [("New", &diff.introduced), ("Resolved", &diff.removed)] {
+        if leaks.is_empty() {
+            continue;
+        }
+        writeln!(writer, "=== {} ({} leak(s)) ===", name, leaks.len())?;
+        dump_confirmed_leaks(
+            &mut writer,
+            leaks,
+            output_sink,
+            &ScanOutcome {
+                partial,
+                ..Default::default()
+            },
+            options.redact,
+            options.truncated_value_size,
+            options.ignore_multiple_locations,
+        )?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Files whose changes are watched, along with the changes they cause
+#[derive(Default)]
+struct WatchedFiles {
+    files: BTreeMap<PathBuf, Change>,
+    /// Directory containing the binaries to scan, if a directory is scanned
+    binary_directory: Option<PathBuf>,
+}
+
+impl WatchedFiles {
+    /// Lists the project's source files and settings files, as well as the
+    /// binary to scan
+    fn collect(options: &CpplumberOptions, session: Option<&WatchSession>) -> Self {
+        let mut watched_files = Self::default();
+        // Source files are only known once the project has been loaded
+        if let Some(session) = session.filter(|_| !options.skip_extraction) {
+            match session.scanner.source_files() {
+                Ok(source_files) => {
+                    for source_file in source_files {
+                        watched_files.add(&source_file, Change::Sources);
+                    }
+                }
+                Err(err) => log::warn!("Cannot list the project's source files: {:#}", err),
+            }
+        }
+
+        let cmake_compile_commands_path = options
+            .cmake_build_directory
+            .as_deref()
+            .and_then(|build_directory| locate_cmake_compile_commands(build_directory).ok());
+        for file_path in [&options.project_file_path, &cmake_compile_commands_path]
+            .into_iter()
+            .flatten()
+            .chain(&options.extra_artifacts)
+            .chain(&options.import_artifacts)
+        {
+            watched_files.add(file_path, Change::Sources);
+        }
+        for file_path in [&options.config, &options.suppressions_list]
+            .into_iter()
+            .flatten()
+        {
+            watched_files.add(file_path, Change::Settings);
+        }
+        if let Some(ref binary_file_path) = options.binary_file_path {
+            if binary_file_path.is_dir() {
+                watched_files.binary_directory = binary_file_path.canonicalize().ok();
+            } else {
+                watched_files.add(binary_file_path, Change::Binary);
+            }
+        }
+
+        watched_files
+    }
+
+    fn add(&mut self, file_path: &Path, change: Change) {
+        let file_path = absolute_path(file_path);
+        let file_change = self.files.entry(file_path).or_insert(change);
+        *file_change = std::cmp::max(*file_change, change);
+    }
+
+    /// Returns the change caused by the given file, if it's watched
+    fn change(&self, file_path: &Path) -> Option<Change> {
+        self.files.get(file_path).copied().or_else(|| {
+            self.binary_directory
+                .as_ref()
+                .filter(|directory| file_path.starts_with(directory))
+                .map(|_| Change::Binary)
+        })
+    }
+
+    /// Returns the directories to watch, and whether they must be watched
+    /// recursively
+    fn directories(&self) -> BTreeMap<PathBuf, bool> {
+        let mut directories: BTreeMap<PathBuf, bool> = self
+            .files
+            .keys()
+            .filter_map(|file_path| file_path.parent())
+            .filter(|directory| directory.is_dir())
+            .map(|directory| (directory.to_path_buf(), false))
+            .collect();
+        if let Some(ref binary_directory) = self.binary_directory {
+            directories.insert(binary_directory.clone(), true);
+        }
+
+        directories
+    }
+}
+
+/// Returns the absolute path of the given file, with its parent directory
+/// canonicalized so that it matches the paths of file system events (the file
+/// itself may not exist)
+fn absolute_path(file_path: &Path) -> PathBuf {
+    let canonical_directory = file_path
+        .parent()
+        .map(|directory| {
+            if directory.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                directory
+            }
+        })
+        .and_then(|directory| directory.canonicalize().ok());
+    match (canonical_directory, file_path.file_name()) {
+        (Some(directory), Some(file_name)) => directory.join(file_name),
+        _ => std::env::current_dir()
+            .map(|directory| directory.join(file_path))
+            .unwrap_or_else(|_| file_path.to_path_buf()),
+    }
+}
+
+/// Watches the directories containing the watched files rather than the files
+/// themselves, as editors and linkers often replace files instead of writing
+/// into them
+struct FileWatcher {
+    watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    /// Watched directories, and whether they're watched recursively
+    directories: BTreeMap<PathBuf, bool>,
+}
+
+impl FileWatcher {
+    fn new() -> Result<Self> {
+        let (sender, events) = mpsc::channel();
+
+        Ok(Self {
+            watcher: notify::recommended_watcher(sender)?,
+            events,
+            directories: BTreeMap::new(),
+        })
+    }
+
+    /// Watches the directories of the given files, and stops watching the
+    /// directories that don't contain any of them anymore
+    fn update(&mut self, watched_files: &WatchedFiles) -> Result<()> {
+        let directories = watched_files.directories();
+        for (directory, recursive) in &self.directories {
+            if directories.get(directory) != Some(recursive) {
+                // Note: Removed directories aren't watched anymore
+                let _ = self.watcher.unwatch(directory);
+            }
+        }
+        for (directory, recursive) in &directories {
+            if self.directories.get(directory) != Some(recursive) {
+                let recursive_mode = if *recursive {
+                    RecursiveMode::Recursive
+                } else {
+                    RecursiveMode::NonRecursive
+                };
+                self.watcher.watch(directory, recursive_mode)?;
+            }
+        }
+        log::debug!(
+            "Watching {} file(s) in {} directory(ies)",
+            watched_files.files.len(),
+            directories.len()
+        );
+        self.directories = directories;
+
+        Ok(())
+    }
+
+    /// Waits for changes to the watched files, and for them to settle.
+    /// Returns the changes along with one of the changed files, or `None` if
+    /// interrupted.
+    fn wait_for_changes(
+        &self,
+        watched_files: &WatchedFiles,
+    ) -> Option<(BTreeSet<Change>, PathBuf)> {
+        let mut changes: Option<(BTreeSet<Change>, PathBuf)> = None;
+        let mut last_change_time = Instant::now();
+        loop {
+            if is_cancelled() {
+                return None;
+            }
+            match self.events.recv_timeout(SLEEP_STEP) {
+                Ok(Ok(event)) => {
+                    for (change, file_path) in event_changes(&event, watched_files) {
+                        last_change_time = Instant::now();
+                        changes
+                            .get_or_insert_with(|| (BTreeSet::new(), file_path))
+                            .0
+                            .insert(change);
+                    }
+                }
+                Ok(Err(err)) => log::warn!("Failed to watch files: {}", err),
+                Err(RecvTimeoutError::Timeout) => {
+                    if changes.is_some() && last_change_time.elapsed() >= SETTLE_DELAY {
+                        return changes;
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => return None,
+            }
+        }
+    }
+}
+
+/// Returns the changes caused by the given file system event
+fn event_changes(event: &Event, watched_files: &WatchedFiles) -> Vec<(Change, PathBuf)> {
+    // Note: Accesses and metadata changes (e.g., permissions) don't change
+    // files' content
+    if matches!(
+        event.kind,
+        EventKind::Access(_) | EventKind::Modify(ModifyKind::Metadata(_))
+    ) {
+        return vec![];
+    }
+
+    event
+        .paths
+        .iter()
+        .filter_map(|file_path| {
+            watched_files
+                .change(file_path)
+                .map(|change| (change, file_path.clone()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use cpplumber::information_leak::{
+        BinaryLocation, ConfirmedLeak, LeakedDataType, SourceLocation,
+    };
+    use notify::event::{AccessKind, CreateKind, DataChange};
+    use structopt::StructOpt;
+
+    use super::*;
+
+    fn confirmed_leaks(data: &[&str]) -> BTreeSet<ConfirmedLeakWithUniqueLocation> {
+        data.iter()
+            .map(|data| {
+                ConfirmedLeak::builder(
+                    LeakedDataType::StringLiteral,
+                    Arc::new(data.to_string()),
+                    Arc::new(SourceLocation {
+                        file: PathBuf::from("main.cc"),
+                        line: 1,
+                    }),
+                    // Note: Leaks are deduplicated based on their location
+                    BinaryLocation::new(Arc::new(PathBuf::from("a.out")), data.len() as u64),
+                )
+                .build()
+                .into()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn classify_watched_file_changes() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let root = tmp_dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("bin")).unwrap();
+        let options = CpplumberOptions::from_iter([
+            "cpplumber",
+            "--bin",
+            root.join("bin").to_str().unwrap(),
+            "--suppressions-list",
+            root.join("suppressions.yml").to_str().unwrap(),
+            "--extra-artifacts",
+            root.join("src/secrets.txt").to_str().unwrap(),
+        ]);
+
+        let mut watched_files = WatchedFiles::collect(&options, None);
+        watched_files.add(&root.join("src/main.cc"), Change::Sources);
+        assert_eq!(
+            watched_files.change(&root.join("suppressions.yml")),
+            Some(Change::Settings)
+        );
+        assert_eq!(
+            watched_files.change(&root.join("src/main.cc")),
+            Some(Change::Sources)
+        );
+        assert_eq!(
+            watched_files.change(&root.join("bin/lib/app.so")),
+            Some(Change::Binary)
+        );
+        assert_eq!(watched_files.change(&root.join("src/notes.txt")), None);
+        assert_eq!(
+            watched_files.directories(),
+            BTreeMap::from([
+                (root.clone(), false),
+                (root.join("bin"), true),
+                (root.join("src"), false),
+            ])
+        );
+
+        // Only events changing files' content are taken into account
+        let event = |kind| Event::new(kind).add_path(root.join("src/main.cc"));
+        assert!(
+            event_changes(&event(EventKind::Access(AccessKind::Any)), &watched_files).is_empty()
+        );
+        assert_eq!(
+            event_changes(
+                &event(EventKind::Modify(ModifyKind::Data(DataChange::Any))),
+                &watched_files
+            ),
+            vec![(Change::Sources, root.join("src/main.cc"))]
+        );
+        assert_eq!(
+            event_changes(&event(EventKind::Create(CreateKind::File)), &watched_files).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn wait_for_file_changes() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let source_path = absolute_path(&tmp_dir.path().join("main.cc"));
+        std::fs::write(&source_path, "int main() {}").unwrap();
+        let mut watched_files = WatchedFiles::default();
+        watched_files.add(&source_path, Change::Sources);
+        let mut watcher = FileWatcher::new().unwrap();
+        watcher.update(&watched_files).unwrap();
+
+        // Files that aren't watched are ignored
+        std::fs::write(tmp_dir.path().join("notes.txt"), "").unwrap();
+        std::fs::write(&source_path, "int main() { return 1; }").unwrap();
+        assert_eq!(
+            watcher.wait_for_changes(&watched_files),
+            Some((BTreeSet::from([Change::Sources]), source_path))
+        );
+    }
+
+    #[test]
+    fn apply_changed_suppressions_without_scanning_again() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let root = tmp_dir.path().canonicalize().unwrap();
+        let binary_path = root.join("app.bin");
+        let extra_artifacts_path = root.join("secrets.txt");
+        let suppressions_path = root.join("suppressions.yml");
+        std::fs::write(&binary_path, b"\0first_secret\0second_secret\0").unwrap();
+        std::fs::write(&extra_artifacts_path, "first_secret\nsecond_secret\n").unwrap();
+        std::fs::write(&suppressions_path, "artifacts: []\n").unwrap();
+        let options = CpplumberOptions::from_iter([
+            "cpplumber",
+            "--skip-extraction",
+            "--extra-artifacts",
+            extra_artifacts_path.to_str().unwrap(),
+            "--suppressions-list",
+            suppressions_path.to_str().unwrap(),
+            "--bin",
+            binary_path.to_str().unwrap(),
+        ]);
+        let leak_values = |leaks: &BTreeSet<ConfirmedLeakWithUniqueLocation>| -> Vec<String> {
+            leaks.iter().map(|leak| leak.data.to_string()).collect()
+        };
+
+        let mut session = None;
+        let (findings, _) = update_findings(&options, &mut session, &BTreeSet::new()).unwrap();
+        assert_eq!(
+            leak_values(&findings),
+            vec!["first_secret", "second_secret"]
+        );
+
+        // Neither the artifacts nor the binary are read again
+        std::fs::remove_file(&extra_artifacts_path).unwrap();
+        std::fs::remove_file(&binary_path).unwrap();
+        std::fs::write(&suppressions_path, "artifacts: ['second_secret']\n").unwrap();
+        let (findings, _) =
+            update_findings(&options, &mut session, &BTreeSet::from([Change::Settings])).unwrap();
+        assert_eq!(leak_values(&findings), vec!["first_secret"]);
+
+        // Removed suppressions apply too
+        std::fs::write(&suppressions_path, "artifacts: []\n").unwrap();
+        let (findings, _) =
+            update_findings(&options, &mut session, &BTreeSet::from([Change::Settings])).unwrap();
+        assert_eq!(findings.len(), 2);
+    }
+
+    #[test]
+    fn write_new_and_resolved_leaks() {
+        let options = CpplumberOptions::from_iter(["cpplumber"]);
+        let output_sinks = OutputSinkRegistry::default();
+        let diff = diff_confirmed_leaks(
+            confirmed_leaks(&["fixed", "kept"]),
+            confirmed_leaks(&["kept", "new"]),
+        );
+
+        let mut output = vec![];
+        write_changes(
+            &mut output,
+            &diff,
+            output_sinks.get("text").unwrap(),
+            false,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "=== New (1 leak(s)) ===\n\
+             \"new\" (string literal) leaked at offset 0x3 in \"a.out\" [declared at main.cc:1]\n\
+             === Resolved (1 leak(s)) ===\n\
+             \"fixed\" (string literal) leaked at offset 0x5 in \"a.out\" [declared at main.cc:1]\n"
+        );
+
+        // Nothing is printed if nothing changed
+        let diff = diff_confirmed_leaks(confirmed_leaks(&["kept"]), confirmed_leaks(&["kept"]));
+        let mut output = vec![];
+        write_changes(
+            &mut output,
+            &diff,
+            output_sinks.get("text").unwrap(),
+            false,
+            &options,
+        )
+        .unwrap();
+        assert!(output.is_empty());
+    }
+}
@@ -0,0 +1,9 @@
+[[nodiscard("session tokens must be checked")]] int open_session();
+
+[[deprecated("use open_session instead")]] int legacy_login();
+
+__attribute__((annotate("internal_build_tag"))) int tagged_function();
+
+[[gnu::aligned(16)]] int aligned_value;
+
+int main() { return 0; }
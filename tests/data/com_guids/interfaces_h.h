@@ -0,0 +1,7 @@
+#pragma once
+
+MIDL_INTERFACE("A1B2C3D4-0000-1111-2222-333344445555")
+IInternalService : public IUnknown {
+public:
+    virtual HRESULT STDMETHODCALLTYPE Run() = 0;
+};
@@ -0,0 +1,10 @@
+#include <windows.h>
+#include "interfaces_h.h"
+
+DEFINE_GUID(CLSID_Internal, 0x6b29fc40, 0xca47, 0x1067, 0xb3, 0x1d, 0x00, 0xdd, 0x01, 0x06, 0x62, 0xda);
+
+int main() {
+    IInternalService* service = nullptr;
+    return CoCreateInstance(CLSID_Internal, nullptr, CLSCTX_INPROC_SERVER,
+                            __uuidof(IInternalService), (void**)&service);
+}
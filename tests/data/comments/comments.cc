@@ -0,0 +1,11 @@
+/*
+ * Copyright (c) 2023 ACME Corp.
+ * @author Alex Doe <alex.doe@acme.example>
+ */
+
+// TODO: remove the debug backdoor
+#include "credentials.h"
+
+int main() {
+  return 0;  // ok
+}
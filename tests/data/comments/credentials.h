@@ -0,0 +1,2 @@
+// FIXME: rotate the staging credentials
+int debug_level();
@@ -0,0 +1,2 @@
+SELECT id, name FROM secret_users;
+SELECT token FROM secret_sessions;
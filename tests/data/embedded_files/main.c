@@ -0,0 +1,10 @@
+static const unsigned char blob[] = {
+#embed "blob.bin"
+};
+
+#embed <icon.bin>
+
+#include "incbin.h"
+INCBIN(Blob, "blob.bin");
+
+int main() { return 0; }
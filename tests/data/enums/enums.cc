@@ -0,0 +1,11 @@
+enum Color { kRed, kGreen };
+
+enum class ConnectionState {
+  Disconnected,
+  Connected,
+};
+
+int main() {
+  ConnectionState state = ConnectionState::Disconnected;
+  return state == ConnectionState::Connected ? kRed : kGreen;
+}
@@ -0,0 +1,19 @@
+struct Credentials {
+  const char* user_name;
+  const char* api_token;
+  // Unnamed bit-field
+  unsigned int : 4;
+};
+
+class Session {
+ public:
+  int session_id;
+
+ private:
+  Credentials credentials;
+};
+
+int main() {
+  Session session;
+  return 0;
+}
@@ -0,0 +1,12 @@
+int compute_checksum(const char* data, int size);
+
+namespace net {
+void send_heartbeat() {}
+}  // namespace net
+
+class Session {
+ public:
+  bool authenticate(const char* token);
+};
+
+int main() { return 0; }
@@ -0,0 +1,22 @@
+namespace ns {
+
+template <typename T>
+class MyTemplate {
+    T value;
+};
+
+template <>
+class MyTemplate<char> {};
+
+}  // namespace ns
+
+struct Base {};
+
+template <typename T>
+struct Derived : Base {};
+
+ns::MyTemplate<int>* my_instance = nullptr;
+
+int main() {
+    return 0;
+}
@@ -0,0 +1,24 @@
+union Payload {
+  int integer;
+  float real;
+};
+
+typedef struct {
+  int x;
+  int y;
+} AnonymousPoint;
+
+typedef unsigned long SessionHandle;
+using PayloadPointer = Payload*;
+
+struct Packet {
+  // Anonymous unions have no name to leak
+  union {
+    int raw;
+  };
+};
+
+int main() {
+  Packet packet;
+  return 0;
+}
@@ -0,0 +1,3 @@
+#include "config.h"
+
+const char* config_path() { return "C:\\ProgramData\\app\\config.ini"; }
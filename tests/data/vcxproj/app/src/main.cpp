@@ -0,0 +1,8 @@
+#include <cstdio>
+
+#include "config.h"
+
+int main() {
+  std::printf("%s: %s\n", APP_NAME, config_path());
+  return 0;
+}